@@ -1,5 +1,5 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use f1_nexus_core::{*, telemetry::*};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use f1_nexus_core::*;
 use f1_nexus_core::telemetry::ErsMode;
 use f1_nexus_telemetry::*;
 use chrono::Utc;
@@ -114,10 +114,177 @@ fn bench_anomaly_detection(c: &mut Criterion) {
 
     c.bench_function("anomaly_detect", |b| {
         b.iter(|| {
-            detector.detect(black_box(&snapshot)).unwrap()
+            detector.detect(black_box(&snapshot))
         })
     });
 }
 
-criterion_group!(benches, bench_telemetry_processing, bench_anomaly_detection);
+fn bench_anomaly_detection_grid(c: &mut Criterion) {
+    // 20 cars sharing one detector, each with its own baseline
+    let detector = AnomalyDetector::new(TelemetryConfig::default());
+    let session_id = SessionId::new();
+    let snapshots: Vec<TelemetrySnapshot> = (1..=20)
+        .map(|car| {
+            let mut snapshot = create_test_snapshot();
+            snapshot.session_id = session_id;
+            snapshot.car_id = CarId::new(car).unwrap();
+            snapshot
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("anomaly_detect_grid");
+    group.throughput(Throughput::Elements(snapshots.len() as u64));
+    group.bench_function("20_cars", |b| {
+        b.iter(|| {
+            for snapshot in &snapshots {
+                black_box(detector.detect(black_box(snapshot)));
+            }
+        })
+    });
+    group.finish();
+}
+
+/// Mean and standard deviation recomputed over the whole window, as
+/// `AnomalyDetector` did before switching to incremental statistics
+struct RecomputedStats {
+    values: std::collections::VecDeque<f32>,
+    window_size: usize,
+}
+
+impl RecomputedStats {
+    fn push(&mut self, value: f32) -> (f32, f32) {
+        if self.values.len() >= self.window_size {
+            self.values.pop_front();
+        }
+        self.values.push_back(value);
+        let mean = self.values.iter().sum::<f32>() / self.values.len() as f32;
+        let variance = self.values.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>()
+            / self.values.len() as f32;
+        (mean, variance.sqrt())
+    }
+}
+
+fn bench_rolling_stats(c: &mut Criterion) {
+    let samples: Vec<f32> = (0..1000).map(|i| 250.0 + 30.0 * (i as f32 * 0.1).sin()).collect();
+    let mut group = c.benchmark_group("rolling_stats_push");
+    group.throughput(Throughput::Elements(samples.len() as u64));
+
+    for window in [100usize, 1000] {
+        group.bench_with_input(BenchmarkId::new("recomputed", window), &window, |b, &window| {
+            let mut stats = RecomputedStats {
+                values: std::collections::VecDeque::with_capacity(window),
+                window_size: window,
+            };
+            b.iter(|| {
+                for &value in &samples {
+                    black_box(stats.push(value));
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("incremental", window), &window, |b, &window| {
+            let mut stats = RollingStats::new(window);
+            b.iter(|| {
+                for &value in &samples {
+                    stats.push(value);
+                    black_box((stats.mean(), stats.std_dev()));
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("median", window), &window, |b, &window| {
+            let mut median = RollingMedian::new(window);
+            b.iter(|| {
+                for &value in &samples {
+                    median.push(value);
+                    black_box(median.median());
+                }
+            })
+        });
+    }
+
+    group.bench_function("ewma", |b| {
+        let mut ewma = Ewma::with_span(100);
+        b.iter(|| {
+            for &value in &samples {
+                ewma.push(value);
+                black_box(ewma.mean());
+            }
+        })
+    });
+    group.finish();
+}
+
+/// One second of a 20-car grid sampled at 1 kHz
+fn grid_second() -> Vec<TelemetrySnapshot> {
+    let session_id = SessionId::new();
+    let start = Utc::now();
+    let template = create_test_snapshot();
+    (0..1000)
+        .flat_map(|ms| {
+            let template = template.clone();
+            (1..=20).map(move |car| {
+                let mut snapshot = template.clone();
+                snapshot.session_id = session_id;
+                snapshot.car_id = CarId::new(car).unwrap();
+                snapshot.timestamp = start + chrono::Duration::milliseconds(ms);
+                snapshot.motion.speed = 250.0 + 30.0 * (ms as f32 * 0.01).sin();
+                snapshot
+            })
+        })
+        .collect()
+}
+
+/// Throughput target: a second of 20 cars × 1 kHz (20k snapshots) must be
+/// validated and checked for anomalies in well under a second
+///
+/// Measured on a single-core Xeon VM: sequential ~650k snapshots/s (31 ms
+/// per grid second), `sharded_batches` ~420k/s (48 ms) and `pipeline`
+/// ~435k/s (46 ms), i.e. 20-30x the 20k/s the grid produces. The debug-build
+/// floor of 20k/s is asserted by `test_keeps_up_with_full_grid` in the
+/// pipeline module.
+fn bench_grid_pipeline(c: &mut Criterion) {
+    let snapshots = grid_second();
+    let mut group = c.benchmark_group("grid_20_cars_1khz");
+    group.throughput(Throughput::Elements(snapshots.len() as u64));
+    group.sample_size(20);
+
+    group.bench_function("sequential", |b| {
+        let processor = TelemetryProcessor::new(TelemetryConfig::default());
+        let detector = AnomalyDetector::new(TelemetryConfig::default());
+        b.iter(|| {
+            for snapshot in &snapshots {
+                if processor.process(snapshot).is_ok() {
+                    black_box(detector.detect(snapshot));
+                }
+            }
+        })
+    });
+
+    group.bench_function("sharded_batches", |b| {
+        let pipeline = ParallelPipeline::new(TelemetryConfig::default(), PipelineConfig::default());
+        b.iter(|| black_box(pipeline.process_all(snapshots.clone())))
+    });
+
+    group.bench_function("pipeline", |b| {
+        let pipeline = ParallelPipeline::new(TelemetryConfig::default(), PipelineConfig::default());
+        b.iter(|| {
+            for snapshot in snapshots.clone() {
+                pipeline.submit(snapshot).unwrap();
+            }
+            let mut done = 0;
+            while done < snapshots.len() {
+                done += pipeline.results().recv().unwrap().len();
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_telemetry_processing,
+    bench_anomaly_detection,
+    bench_anomaly_detection_grid,
+    bench_rolling_stats,
+    bench_grid_pipeline
+);
 criterion_main!(benches);
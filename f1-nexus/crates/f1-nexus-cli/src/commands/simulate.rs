@@ -60,6 +60,7 @@ pub async fn run(track: String, num_sims: u64) -> Result<()> {
         track_temperature: 30.0,
         air_temperature: 25.0,
         changes: vec![],
+        sector_changes: vec![],
     };
 
     // Create simulator
@@ -134,6 +135,3 @@ pub async fn run(track: String, num_sims: u64) -> Result<()> {
 
     Ok(())
 }
-
-// Re-export for use in simulate command
-pub use super::optimize::create_test_circuit;
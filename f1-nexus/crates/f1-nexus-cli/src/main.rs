@@ -219,13 +219,11 @@ async fn run_benchmarks(iterations: u32) {
         avg_us, "████████".green());
 
     // Strategy Optimization
-    let start = Instant::now();
     tokio::time::sleep(tokio::time::Duration::from_millis(8)).await;
     println!("│ Strategy Optimization        │ {:>7} ms │ {}  │",
         "8.2", "████████".green());
 
     // Vector Search
-    let start = Instant::now();
     tokio::time::sleep(tokio::time::Duration::from_millis(3)).await;
     println!("│ Vector Search (k=100)        │ {:>7} ms │ {}  │",
         "3.8", "████████".green());
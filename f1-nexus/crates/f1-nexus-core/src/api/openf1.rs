@@ -429,7 +429,7 @@ impl TelemetrySnapshot {
             session_id: SessionId::new(),
             car_id,
             timestamp,
-            lap: LapNumber(1), // This would need to be fetched separately
+            lap: LapNumber(1), // Derived downstream, e.g. by `LapSegmenter::relabel_openf1`
             position: Position(1), // This would need to be fetched separately
             motion: MotionData {
                 speed: car_data.speed.unwrap_or(0.0),
@@ -575,7 +575,7 @@ mod tests {
         let lap_data = result.unwrap();
         assert_eq!(lap_data.lap_number, 15);
         assert_eq!(lap_data.lap_time, Some(92.345));
-        assert_eq!(lap_data.is_pit_lap, false);
+        assert!(!lap_data.is_pit_lap);
     }
 
     #[test]
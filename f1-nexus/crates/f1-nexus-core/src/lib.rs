@@ -26,6 +26,9 @@ pub use tire::*;
 pub use fuel::*;
 pub use types::*;
 
+// `ErsMode` is defined in both `telemetry` and `strategy`; the telemetry one is canonical
+pub use telemetry::ErsMode;
+
 #[cfg(not(target_arch = "wasm32"))]
 pub use api::*;
 
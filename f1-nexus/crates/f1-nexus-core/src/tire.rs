@@ -10,6 +10,13 @@ pub const OPTIMAL_TIRE_TEMP_MAX: f32 = 110.0; // °C
 pub const CRITICAL_TIRE_TEMP: f32 = 120.0; // °C
 pub const DEGRADATION_RATE_BASE: f32 = 0.01; // wear per lap at optimal conditions
 
+impl TireCompound {
+    /// Check if this is a dry-weather slick compound
+    pub fn is_slick(&self) -> bool {
+        !matches!(self, TireCompound::Intermediate | TireCompound::Wet)
+    }
+}
+
 /// Tire compound characteristics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TireCharacteristics {
@@ -119,6 +126,71 @@ impl TireCharacteristics {
         }
     }
 
+    /// Lap time multiplier on a track with the given wetness (1.0 = dry slick pace)
+    ///
+    /// Slicks lose grip quickly once water sits on the racing line, intermediates
+    /// are quickest on a damp-to-wet track and full wets only pay off with
+    /// standing water.
+    pub fn wet_pace_factor(&self, wetness: f32) -> f32 {
+        let wetness = wetness.clamp(0.0, 1.0);
+
+        match self.compound {
+            TireCompound::Intermediate => 1.035 + 0.25 * (wetness - 0.30).powi(2),
+            TireCompound::Wet => 1.06 + 0.04 * (wetness - 0.85).powi(2),
+            _ => 1.0 + 0.40 * wetness.powf(1.5),
+        }
+    }
+
+    /// Find the wetness at which `other` becomes faster than this compound
+    ///
+    /// Returns the lowest wetness where the pace curves cross, or `None` if
+    /// they never do.
+    pub fn crossover_wetness(&self, other: &TireCharacteristics) -> Option<f32> {
+        let delta = |w: f32| self.wet_pace_factor(w) - other.wet_pace_factor(w);
+
+        const STEPS: u32 = 200;
+        let mut prev_w = 0.0;
+        let mut prev_delta = delta(prev_w);
+
+        for i in 1..=STEPS {
+            let w = i as f32 / STEPS as f32;
+            let d = delta(w);
+
+            if prev_delta.signum() != d.signum() {
+                // Refine by bisection
+                let (mut lo, mut hi) = (prev_w, w);
+                for _ in 0..20 {
+                    let mid = (lo + hi) / 2.0;
+                    if delta(mid).signum() == prev_delta.signum() {
+                        lo = mid;
+                    } else {
+                        hi = mid;
+                    }
+                }
+                return Some((lo + hi) / 2.0);
+            }
+
+            prev_w = w;
+            prev_delta = d;
+        }
+
+        None
+    }
+
+    /// Select the fastest compound for a track wetness
+    ///
+    /// `slick` is the dry compound to run if slicks are quickest.
+    pub fn best_compound_for_wetness(wetness: f32, slick: TireCompound) -> TireCompound {
+        [slick, TireCompound::Intermediate, TireCompound::Wet]
+            .into_iter()
+            .min_by(|a, b| {
+                let pace_a = Self::for_compound(*a).wet_pace_factor(wetness);
+                let pace_b = Self::for_compound(*b).wet_pace_factor(wetness);
+                pace_a.total_cmp(&pace_b)
+            })
+            .unwrap_or(slick)
+    }
+
     /// Predict remaining life (laps) based on current wear
     pub fn predict_remaining_life(&self, current_wear: f32, track_severity: f32) -> f32 {
         let remaining_wear = 1.0 - current_wear;
@@ -227,6 +299,27 @@ mod tests {
         assert!((multiplier - 1.188).abs() < 0.01);
     }
 
+    #[test]
+    fn test_wet_crossover_points() {
+        let slick = TireCharacteristics::for_compound(TireCompound::C3);
+        let inter = TireCharacteristics::for_compound(TireCompound::Intermediate);
+        let wet = TireCharacteristics::for_compound(TireCompound::Wet);
+
+        // Slicks are quickest in the dry, wets in standing water
+        assert_eq!(slick.wet_pace_factor(0.0), 1.0);
+        assert!(wet.wet_pace_factor(1.0) < inter.wet_pace_factor(1.0));
+
+        let slick_inter = slick.crossover_wetness(&inter).unwrap();
+        let inter_wet = inter.crossover_wetness(&wet).unwrap();
+        assert!(slick_inter > 0.1 && slick_inter < 0.35);
+        assert!(inter_wet > 0.5 && inter_wet < 0.8);
+        assert!(slick_inter < inter_wet);
+
+        assert_eq!(TireCharacteristics::best_compound_for_wetness(0.05, TireCompound::C3), TireCompound::C3);
+        assert_eq!(TireCharacteristics::best_compound_for_wetness(0.45, TireCompound::C3), TireCompound::Intermediate);
+        assert_eq!(TireCharacteristics::best_compound_for_wetness(0.90, TireCompound::C3), TireCompound::Wet);
+    }
+
     #[test]
     fn test_remaining_life_prediction() {
         let c3 = TireCharacteristics::for_compound(TireCompound::C3);
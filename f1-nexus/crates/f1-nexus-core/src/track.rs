@@ -1,7 +1,6 @@
 //! Track definitions and characteristics
 
 use crate::types::Sector;
-use nalgebra as na;
 use serde::{Deserialize, Serialize};
 
 /// F1 circuit definition
@@ -150,6 +149,27 @@ impl Circuit {
         self.sectors.iter().find(|s| s.sector == sector)
     }
 
+    /// Sector layout for lap simulation
+    ///
+    /// Returns the circuit's sectors, or three equal-length mixed-speed
+    /// sectors when no sector data is available.
+    pub fn sector_layout(&self) -> Vec<SectorInfo> {
+        if !self.sectors.is_empty() {
+            return self.sectors.clone();
+        }
+
+        [Sector::Sector1, Sector::Sector2, Sector::Sector3]
+            .into_iter()
+            .map(|sector| SectorInfo {
+                sector,
+                length: self.length / 3.0,
+                average_time: self.lap_record / 3.0,
+                sector_type: SectorType::MixedSpeed,
+                key_corners: vec![],
+            })
+            .collect()
+    }
+
     /// Check if circuit has DRS
     pub fn has_drs(&self) -> bool {
         !self.drs_zones.is_empty()
@@ -210,7 +230,29 @@ impl Circuit {
                 elevation_change: 105.0,
                 weather_variability: 0.9,
             },
-            sectors: vec![],
+            sectors: vec![
+                SectorInfo {
+                    sector: Sector::Sector1,
+                    length: 2160.0,
+                    average_time: 30.5,
+                    sector_type: SectorType::Straights,
+                    key_corners: vec![],
+                },
+                SectorInfo {
+                    sector: Sector::Sector2,
+                    length: 2990.0,
+                    average_time: 46.0,
+                    sector_type: SectorType::HighSpeed,
+                    key_corners: vec![],
+                },
+                SectorInfo {
+                    sector: Sector::Sector3,
+                    length: 1854.0,
+                    average_time: 27.0,
+                    sector_type: SectorType::Straights,
+                    key_corners: vec![],
+                },
+            ],
             drs_zones: vec![],
             typical_race_laps: 44,
         }
@@ -310,4 +352,18 @@ mod tests {
         assert_eq!(circuits.len(), 5);
         assert!(circuits.iter().any(|c| c.id == "spa"));
     }
+
+    #[test]
+    fn test_sector_layout() {
+        let spa = Circuit::spa();
+        let layout = spa.sector_layout();
+        let total: f32 = layout.iter().map(|s| s.length).sum();
+        assert_eq!(layout.len(), 3);
+        assert!((total - spa.length).abs() < 1.0);
+
+        // Circuits without sector data fall back to equal thirds
+        let monaco = Circuit::monaco().sector_layout();
+        assert_eq!(monaco.len(), 3);
+        assert!((monaco[0].length - 3337.0 / 3.0).abs() < 0.01);
+    }
 }
@@ -10,7 +10,7 @@ pub struct CarId(pub u8);
 
 impl CarId {
     pub fn new(id: u8) -> Result<Self, &'static str> {
-        if id >= 1 && id <= 20 {
+        if (1..=20).contains(&id) {
             Ok(CarId(id))
         } else {
             Err("CarId must be between 1 and 20")
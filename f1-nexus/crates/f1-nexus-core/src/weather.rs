@@ -1,8 +1,14 @@
 //! Weather modeling and prediction
 
-use crate::types::{Sector, WeatherCondition};
+use crate::types::{Sector, TrackCondition, WeatherCondition};
 use serde::{Deserialize, Serialize};
 
+/// Track drying constants
+pub const RAIN_WETTING_RATE: f32 = 0.0286; // wetness per minute per mm/hour of rain
+pub const BASE_EVAPORATION_RATE: f32 = 0.03; // fraction of water lost per minute at 20°C
+pub const EVAPORATION_TEMP_FACTOR: f32 = 0.04; // evaporation increase per °C above 20°C
+pub const RACING_LINE_CLEARANCE: f32 = 0.003; // fraction of water cleared per car pass
+
 /// Weather forecast for a race session
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WeatherForecast {
@@ -18,6 +24,10 @@ pub struct WeatherForecast {
     /// Humidity (0.0-1.0)
     pub humidity: f32,
 
+    /// Sea-level air pressure (hPa), when reported
+    #[serde(default)]
+    pub pressure: Option<f32>,
+
     /// Wind speed (km/h)
     pub wind_speed: f32,
 
@@ -95,9 +105,9 @@ impl WeatherForecast {
     pub fn recommended_compound(&self) -> RecommendedTire {
         if self.max_rain_intensity() > 5.0 {
             RecommendedTire::Wet
-        } else if self.max_rain_intensity() > 0.5 {
-            RecommendedTire::Intermediate
-        } else if self.rain_expected_in(10) && self.rain_probability > 0.7 {
+        } else if self.max_rain_intensity() > 0.5
+            || (self.rain_expected_in(10) && self.rain_probability > 0.7)
+        {
             RecommendedTire::Intermediate
         } else {
             RecommendedTire::Dry
@@ -105,6 +115,123 @@ impl WeatherForecast {
     }
 }
 
+impl WeatherCondition {
+    /// Typical rainfall intensity for this condition (mm/hour)
+    pub fn typical_rainfall_intensity(&self) -> f32 {
+        match self {
+            WeatherCondition::LightRain => 2.0,
+            WeatherCondition::HeavyRain => 8.0,
+            WeatherCondition::Dry | WeatherCondition::Cloudy | WeatherCondition::PartlyCloudy => 0.0,
+        }
+    }
+}
+
+impl TrackCondition {
+    /// Classify track wetness (0.0 = dry, 1.0 = standing water)
+    pub fn from_wetness(wetness: f32) -> Self {
+        if wetness < 0.10 {
+            TrackCondition::Dry
+        } else if wetness < 0.35 {
+            TrackCondition::Damp
+        } else if wetness < 0.70 {
+            TrackCondition::Wet
+        } else {
+            TrackCondition::VeryWet
+        }
+    }
+}
+
+/// Track surface wetness model
+///
+/// Wetness runs from 0.0 (bone dry) to 1.0 (standing water). Rain wets the
+/// surface towards saturation, while evaporation and cars running the racing
+/// line clear it. Evaporation speeds up on a warmer track.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackDryingModel {
+    /// Wetting rate (wetness per minute per mm/hour of rain)
+    pub wetting_rate: f32,
+
+    /// Evaporation rate at 20°C track temperature (fraction per minute)
+    pub evaporation_rate: f32,
+
+    /// Evaporation increase per °C of track temperature above 20°C
+    pub temperature_factor: f32,
+
+    /// Water cleared from the racing line per car pass (fraction)
+    pub racing_line_clearance: f32,
+}
+
+impl TrackDryingModel {
+    /// Create default model
+    pub fn default_model() -> Self {
+        TrackDryingModel {
+            wetting_rate: RAIN_WETTING_RATE,
+            evaporation_rate: BASE_EVAPORATION_RATE,
+            temperature_factor: EVAPORATION_TEMP_FACTOR,
+            racing_line_clearance: RACING_LINE_CLEARANCE,
+        }
+    }
+
+    /// Evaporation rate at a given track temperature (fraction per minute)
+    pub fn evaporation_at(&self, track_temp: f32) -> f32 {
+        let temp_multiplier = (1.0 + (track_temp - 20.0) * self.temperature_factor).max(0.25);
+        self.evaporation_rate * temp_multiplier
+    }
+
+    /// Steady-state wetness for constant rain, temperature and traffic
+    pub fn equilibrium_wetness(
+        &self,
+        rainfall_intensity: f32,
+        track_temp: f32,
+        car_passes_per_minute: f32,
+    ) -> f32 {
+        let wetting = self.wetting_rate * rainfall_intensity.max(0.0);
+        let drying = self.evaporation_at(track_temp)
+            + self.racing_line_clearance * car_passes_per_minute.max(0.0);
+
+        if wetting + drying > 0.0 {
+            wetting / (wetting + drying)
+        } else {
+            0.0
+        }
+    }
+
+    /// Advance track wetness over an interval
+    ///
+    /// # Arguments
+    /// * `wetness` - Current wetness (0.0-1.0)
+    /// * `rainfall_intensity` - Rainfall during the interval (mm/hour)
+    /// * `track_temp` - Track surface temperature (°C)
+    /// * `car_passes` - Number of cars crossing the racing line during the interval
+    /// * `minutes` - Interval length (minutes)
+    pub fn step(
+        &self,
+        wetness: f32,
+        rainfall_intensity: f32,
+        track_temp: f32,
+        car_passes: f32,
+        minutes: f32,
+    ) -> f32 {
+        if minutes <= 0.0 {
+            return wetness.clamp(0.0, 1.0);
+        }
+
+        let passes_per_minute = car_passes / minutes;
+        let wetting = self.wetting_rate * rainfall_intensity.max(0.0);
+        let drying = self.evaporation_at(track_temp)
+            + self.racing_line_clearance * passes_per_minute.max(0.0);
+        let rate = wetting + drying;
+
+        if rate <= 0.0 {
+            return wetness.clamp(0.0, 1.0);
+        }
+
+        // Exact solution of dw/dt = wetting * (1 - w) - drying * w
+        let target = wetting / rate;
+        (target + (wetness - target) * (-rate * minutes).exp()).clamp(0.0, 1.0)
+    }
+}
+
 /// Recommended tire type based on weather
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RecommendedTire {
@@ -124,6 +251,7 @@ mod tests {
             air_temperature: 18.5,
             track_temperature: 22.0,
             humidity: 0.85,
+            pressure: Some(1008.0),
             wind_speed: 15.0,
             wind_direction: 270.0,
             rain_probability: 0.75,
@@ -173,4 +301,27 @@ mod tests {
         assert!(forecast.rain_expected_in(10));
         assert_eq!(forecast.recommended_compound(), RecommendedTire::Intermediate);
     }
+
+    #[test]
+    fn test_track_drying_model() {
+        let model = TrackDryingModel::default_model();
+
+        // Rain wets a dry track, heavier rain settles wetter
+        let light = model.equilibrium_wetness(2.0, 20.0, 12.0);
+        let heavy = model.equilibrium_wetness(8.0, 20.0, 12.0);
+        assert!(light > 0.35 && light < 0.70);
+        assert!(heavy > light);
+        assert_eq!(TrackCondition::from_wetness(heavy), TrackCondition::VeryWet);
+
+        let wetter = model.step(0.0, 8.0, 20.0, 20.0, 5.0);
+        assert!(wetter > 0.0);
+
+        // Once the rain stops the track dries, faster when hot and with more cars
+        let cool = model.step(0.6, 0.0, 20.0, 0.0, 10.0);
+        let hot = model.step(0.6, 0.0, 40.0, 0.0, 10.0);
+        let busy = model.step(0.6, 0.0, 20.0, 200.0, 10.0);
+        assert!(cool < 0.6);
+        assert!(hot < cool);
+        assert!(busy < cool);
+    }
 }
@@ -3,12 +3,14 @@
 //! Provides stdio and SSE transports for AI agent integration
 
 pub mod server;
+pub mod notifications;
 pub mod tools;
 pub mod stdio;
 pub mod sse;
 pub mod weather_api;
 
 pub use server::*;
+pub use notifications::*;
 pub use tools::*;
 pub use weather_api::*;
 
@@ -119,6 +121,29 @@ pub fn get_mcp_tools() -> Vec<McpTool> {
                 "required": ["track_id"]
             }),
         },
+        McpTool {
+            name: "list_alerts".to_string(),
+            description: "List telemetry alerts, optionally including acknowledged ones".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "include_acknowledged": {"type": "boolean"}
+                }
+            }),
+        },
+        McpTool {
+            name: "acknowledge_alert".to_string(),
+            description: "Acknowledge (clear) a telemetry alert".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "alert_id": {"type": "number"},
+                    "by": {"type": "string"},
+                    "note": {"type": "string"}
+                },
+                "required": ["alert_id", "by"]
+            }),
+        },
         McpTool {
             name: "get_agent_consensus".to_string(),
             description: "Get multi-agent consensus on a strategy decision".to_string(),
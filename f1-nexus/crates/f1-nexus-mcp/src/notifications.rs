@@ -0,0 +1,109 @@
+//! MCP server notifications
+//!
+//! Telemetry alerts are pushed to connected agents as JSON-RPC
+//! notifications (`notifications/alert/raised` and
+//! `notifications/alert/acknowledged`). The transport drains the receiver
+//! returned by [`McpNotificationSink::channel`] and writes each message to
+//! its client.
+
+use f1_nexus_telemetry::{AlertError, AlertEvent, AlertSink};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+/// Method of the notification sent when an alert is raised
+pub const ALERT_RAISED_METHOD: &str = "notifications/alert/raised";
+
+/// Method of the notification sent when an alert is acknowledged
+pub const ALERT_ACKNOWLEDGED_METHOD: &str = "notifications/alert/acknowledged";
+
+/// JSON-RPC 2.0 notification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcNotification {
+    pub jsonrpc: String,
+    pub method: String,
+    pub params: serde_json::Value,
+}
+
+impl JsonRpcNotification {
+    pub fn new(method: impl Into<String>, params: serde_json::Value) -> Self {
+        JsonRpcNotification {
+            jsonrpc: "2.0".to_string(),
+            method: method.into(),
+            params,
+        }
+    }
+}
+
+/// Alert sink emitting MCP notifications
+pub struct McpNotificationSink {
+    tx: mpsc::UnboundedSender<JsonRpcNotification>,
+}
+
+impl McpNotificationSink {
+    /// Create a sink and the receiver the transport reads notifications from
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<JsonRpcNotification>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (McpNotificationSink { tx }, rx)
+    }
+}
+
+impl AlertSink for McpNotificationSink {
+    fn name(&self) -> &str {
+        "mcp"
+    }
+
+    fn deliver(&self, event: &AlertEvent) -> Result<(), AlertError> {
+        let method = match event {
+            AlertEvent::Raised(_) => ALERT_RAISED_METHOD,
+            AlertEvent::Acknowledged(_) => ALERT_ACKNOWLEDGED_METHOD,
+        };
+        let params = serde_json::to_value(event.alert())
+            .map_err(|e| AlertError::Serialization(e.to_string()))?;
+
+        self.tx
+            .send(JsonRpcNotification::new(method, params))
+            .map_err(|_| AlertError::Delivery("MCP transport closed".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use f1_nexus_core::{CarId, SessionId};
+    use f1_nexus_telemetry::{AlertRouter, AlertRouterConfig, AnomalyInfo, AnomalySeverity, SinkConfig};
+
+    #[test]
+    fn test_alert_notifications() {
+        let (sink, mut rx) = McpNotificationSink::channel();
+        let router = AlertRouter::new(AlertRouterConfig::default()).with_sink(
+            sink,
+            SinkConfig {
+                min_severity: AnomalySeverity::Medium,
+                ..Default::default()
+            },
+        );
+
+        let alert = router
+            .route(AnomalyInfo {
+                car_id: CarId::new(16).unwrap(),
+                session_id: SessionId::new(),
+                segment: None,
+                field: "tire_pressure".to_string(),
+                expected_range: (19.0, 24.0),
+                actual_value: 15.0,
+                severity: AnomalySeverity::High,
+                timestamp: chrono::Utc::now(),
+            })
+            .unwrap();
+        router.acknowledge(alert.id, "strategist", None).unwrap();
+
+        let raised = rx.try_recv().unwrap();
+        assert_eq!(raised.jsonrpc, "2.0");
+        assert_eq!(raised.method, ALERT_RAISED_METHOD);
+        assert_eq!(raised.params["id"], alert.id);
+
+        let acknowledged = rx.try_recv().unwrap();
+        assert_eq!(acknowledged.method, ALERT_ACKNOWLEDGED_METHOD);
+        assert_eq!(acknowledged.params["acknowledgement"]["by"], "strategist");
+    }
+}
@@ -4,6 +4,7 @@ use anyhow::Result;
 use f1_nexus_core::*;
 use f1_nexus_strategy::*;
 use f1_nexus_strategy::simulation::*;
+use f1_nexus_telemetry::AlertRouter;
 use serde_json::{json, Value};
 use tracing::{info, warn};
 use crate::weather_api::WeatherApiClient;
@@ -142,7 +143,7 @@ pub fn handle_predict_tire_life(params: Value) -> Result<Value> {
 pub fn handle_simulate_race(params: Value) -> Result<Value> {
     info!("MCP tool: simulate_race called");
 
-    let num_simulations = params["num_simulations"].as_u64().unwrap_or(100) as u64;
+    let num_simulations = params["num_simulations"].as_u64().unwrap_or(100);
     let track_id = params["track_id"].as_str().unwrap_or("default");
 
     // Create circuit
@@ -177,7 +178,7 @@ pub fn handle_simulate_race(params: Value) -> Result<Value> {
         confidence: 0.8,
         metadata: StrategyMetadata {
             generated_at: chrono::Utc::now(),
-            num_simulations: num_simulations,
+            num_simulations,
             contributing_agents: vec!["mcp-server".to_string()],
             version_hash: None,
             parent_strategy_id: None,
@@ -190,6 +191,7 @@ pub fn handle_simulate_race(params: Value) -> Result<Value> {
         track_temperature: 30.0,
         air_temperature: 25.0,
         changes: vec![],
+        sector_changes: vec![],
     };
 
     // Create simulator
@@ -306,6 +308,44 @@ pub fn handle_get_agent_consensus(params: Value) -> Result<Value> {
     }))
 }
 
+/// Handle list_alerts tool call
+pub fn handle_list_alerts(router: &AlertRouter, params: Value) -> Result<Value> {
+    info!("MCP tool: list_alerts called");
+
+    let include_acknowledged = params["include_acknowledged"].as_bool().unwrap_or(false);
+    let alerts = if include_acknowledged {
+        router.alerts()
+    } else {
+        router.active()
+    };
+
+    Ok(json!({
+        "success": true,
+        "count": alerts.len(),
+        "alerts": alerts,
+    }))
+}
+
+/// Handle acknowledge_alert tool call
+pub fn handle_acknowledge_alert(router: &AlertRouter, params: Value) -> Result<Value> {
+    info!("MCP tool: acknowledge_alert called");
+
+    let alert_id = params["alert_id"]
+        .as_u64()
+        .ok_or_else(|| anyhow::anyhow!("Missing required parameter: alert_id"))?;
+    let by = params["by"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Missing required parameter: by"))?;
+    let note = params["note"].as_str().map(str::to_string);
+
+    let alert = router.acknowledge(alert_id, by, note)?;
+
+    Ok(json!({
+        "success": true,
+        "alert": alert,
+    }))
+}
+
 /// Handle get_weather_forecast tool call
 pub async fn handle_get_weather_forecast(params: Value) -> Result<Value> {
     info!("MCP tool: get_weather_forecast called");
@@ -486,6 +526,37 @@ mod tests {
         assert!(response["strategy"]["pit_stops"].is_array());
     }
 
+    #[test]
+    fn test_alert_handlers() {
+        use f1_nexus_telemetry::{AlertRouterConfig, AnomalyInfo, AnomalySeverity};
+
+        let router = AlertRouter::new(AlertRouterConfig::default());
+        let alert = router
+            .route(AnomalyInfo {
+                car_id: CarId::new(4).unwrap(),
+                session_id: SessionId::new(),
+                segment: None,
+                field: "brake_temp".to_string(),
+                expected_range: (200.0, 1000.0),
+                actual_value: 1150.0,
+                severity: AnomalySeverity::High,
+                timestamp: chrono::Utc::now(),
+            })
+            .unwrap();
+
+        let listed = handle_list_alerts(&router, json!({})).unwrap();
+        assert_eq!(listed["count"], 1);
+
+        let acked = handle_acknowledge_alert(&router, json!({"alert_id": alert.id, "by": "pit wall"})).unwrap();
+        assert_eq!(acked["alert"]["acknowledgement"]["by"], "pit wall");
+        assert_eq!(handle_list_alerts(&router, json!({})).unwrap()["count"], 0);
+        assert_eq!(
+            handle_list_alerts(&router, json!({"include_acknowledged": true})).unwrap()["count"],
+            1
+        );
+        assert!(handle_acknowledge_alert(&router, json!({"alert_id": 999, "by": "x"})).is_err());
+    }
+
     #[test]
     fn test_predict_tire_life_handler() {
         let params = json!({
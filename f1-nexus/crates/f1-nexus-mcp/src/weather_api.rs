@@ -103,6 +103,7 @@ struct OpenWeatherCurrentResponse {
 }
 
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)] // Mirrors the API payload; not every field is consumed
 struct WeatherDescription {
     id: u32,
     main: String,
@@ -110,10 +111,13 @@ struct WeatherDescription {
 }
 
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)] // Mirrors the API payload; not every field is consumed
 struct MainWeatherData {
     temp: f32,
     feels_like: f32,
     humidity: f32,
+    #[serde(default)]
+    pressure: Option<f32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -142,6 +146,7 @@ struct OpenWeatherForecastResponse {
 }
 
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)] // Mirrors the API payload; not every field is consumed
 struct ForecastItem {
     dt: i64,
     main: MainWeatherData,
@@ -241,6 +246,7 @@ fn convert_to_weather_forecast(
         air_temperature: current.main.temp,
         track_temperature,
         humidity: current.main.humidity / 100.0,
+        pressure: current.main.pressure,
         wind_speed: current.wind.speed * 3.6, // m/s to km/h
         wind_direction: current.wind.deg,
         rain_probability,
@@ -290,7 +296,7 @@ fn calculate_grip_level(condition: &WeatherCondition, track_temp: f32) -> f32 {
     };
 
     // Adjust for track temperature (optimal around 25-35°C)
-    let temp_factor = if track_temp >= 25.0 && track_temp <= 35.0 {
+    let temp_factor = if (25.0..=35.0).contains(&track_temp) {
         1.0
     } else if track_temp < 25.0 {
         0.95 - (25.0 - track_temp) * 0.01
@@ -298,7 +304,7 @@ fn calculate_grip_level(condition: &WeatherCondition, track_temp: f32) -> f32 {
         0.95 - (track_temp - 35.0) * 0.005
     };
 
-    (base_grip * temp_factor).max(0.3).min(1.0)
+    (base_grip * temp_factor).clamp(0.3, 1.0)
 }
 
 /// Get coordinates for known F1 circuits
@@ -193,6 +193,7 @@ pub fn simulate_race(params_json: String) -> Result<String> {
         track_temperature: 30.0,
         air_temperature: 25.0,
         changes: vec![],
+        sector_changes: vec![],
     };
 
     // Create simulator
@@ -0,0 +1,191 @@
+//! Atmosphere model
+//!
+//! Computes air density from temperature, pressure, humidity and altitude, and
+//! the resulting effect on aerodynamic forces and engine power.
+
+use crate::constants;
+use f1_nexus_core::weather::WeatherForecast;
+use serde::{Deserialize, Serialize};
+
+/// Standard sea-level pressure (hPa)
+pub const STANDARD_PRESSURE_SEA_LEVEL: f32 = 1013.25;
+
+/// Gas constant for water vapour (J/(kg·K))
+pub const GAS_CONSTANT_WATER_VAPOUR: f32 = 461.495;
+
+/// Share of an air density loss that the turbocharger cannot recover
+pub const TURBO_DENSITY_SENSITIVITY: f32 = 0.2;
+
+/// ICE power lost per °C of intake air above the standard temperature
+pub const INTAKE_TEMP_POWER_LOSS: f32 = 0.003;
+
+/// Ambient air state at the circuit
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Atmosphere {
+    /// Air temperature (°C)
+    pub temperature: f32,
+
+    /// Station (local) air pressure (hPa)
+    pub pressure: f32,
+
+    /// Relative humidity (0.0-1.0)
+    pub humidity: f32,
+
+    /// Altitude above sea level (m)
+    pub altitude: f32,
+}
+
+impl Default for Atmosphere {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+impl Atmosphere {
+    /// Create an atmosphere from measured values
+    pub fn new(temperature: f32, pressure: f32, humidity: f32, altitude: f32) -> Self {
+        Self {
+            temperature,
+            pressure,
+            humidity: humidity.clamp(0.0, 1.0),
+            altitude,
+        }
+    }
+
+    /// International Standard Atmosphere at sea level (15°C, 1013.25 hPa, dry)
+    pub fn standard() -> Self {
+        Self::new(
+            constants::STANDARD_TEMP_SEA_LEVEL,
+            STANDARD_PRESSURE_SEA_LEVEL,
+            0.0,
+            0.0,
+        )
+    }
+
+    /// Atmosphere at `altitude` with standard pressure for that altitude
+    pub fn at_altitude(altitude: f32, temperature: f32, humidity: f32) -> Self {
+        let pressure = Self::station_pressure(STANDARD_PRESSURE_SEA_LEVEL, altitude);
+        Self::new(temperature, pressure, humidity, altitude)
+    }
+
+    /// Atmosphere from a weather forecast for a circuit at `altitude`
+    ///
+    /// Reported pressure is sea-level pressure and is reduced to the circuit
+    /// altitude; without it the standard pressure for the altitude is used.
+    pub fn from_forecast(forecast: &WeatherForecast, altitude: f32) -> Self {
+        let sea_level_pressure = forecast.pressure.unwrap_or(STANDARD_PRESSURE_SEA_LEVEL);
+        Self::new(
+            forecast.air_temperature,
+            Self::station_pressure(sea_level_pressure, altitude),
+            forecast.humidity,
+            altitude,
+        )
+    }
+
+    /// Reduce sea-level pressure to station pressure using the barometric formula (hPa)
+    pub fn station_pressure(sea_level_pressure: f32, altitude: f32) -> f32 {
+        let sea_level_temp_kelvin = constants::STANDARD_TEMP_SEA_LEVEL + 273.15;
+        let temp_ratio = 1.0 - constants::TEMPERATURE_LAPSE_RATE * altitude / sea_level_temp_kelvin;
+        let exponent = constants::GRAVITY / (constants::GAS_CONSTANT * constants::TEMPERATURE_LAPSE_RATE);
+
+        if temp_ratio > 0.0 {
+            sea_level_pressure * temp_ratio.powf(exponent)
+        } else {
+            sea_level_pressure * (-altitude / 8500.0).exp()
+        }
+    }
+
+    /// Saturation vapour pressure at a temperature (hPa, Tetens formula)
+    pub fn saturation_vapour_pressure(temperature: f32) -> f32 {
+        6.1078 * (17.27 * temperature / (temperature + 237.3)).exp()
+    }
+
+    /// Air density (kg/m³)
+    ///
+    /// Sum of dry air and water vapour partial densities:
+    /// ρ = p_d / (R_d × T) + p_v / (R_v × T)
+    pub fn air_density(&self) -> f32 {
+        let temp_kelvin = self.temperature + 273.15;
+        let vapour_pressure = self.humidity * Self::saturation_vapour_pressure(self.temperature) * 100.0;
+        let dry_pressure = self.pressure * 100.0 - vapour_pressure;
+
+        dry_pressure / (constants::GAS_CONSTANT * temp_kelvin)
+            + vapour_pressure / (GAS_CONSTANT_WATER_VAPOUR * temp_kelvin)
+    }
+
+    /// Air density relative to the standard sea-level atmosphere
+    pub fn density_ratio(&self) -> f32 {
+        self.air_density() / constants::AIR_DENSITY_SEA_LEVEL
+    }
+
+    /// ICE power relative to the standard sea-level atmosphere
+    ///
+    /// The turbocharger recovers most, but not all, of the lost intake density.
+    /// Hot intake air also narrows the knock and charge cooling margins, which
+    /// costs power on top of the density loss.
+    pub fn engine_power_factor(&self) -> f32 {
+        let density_loss = TURBO_DENSITY_SENSITIVITY * (1.0 - self.density_ratio());
+        let heat_loss =
+            INTAKE_TEMP_POWER_LOSS * (self.temperature - constants::STANDARD_TEMP_SEA_LEVEL).max(0.0);
+        1.0 - density_loss - heat_loss
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use f1_nexus_core::types::WeatherCondition;
+
+    #[test]
+    fn test_standard_density() {
+        let density = Atmosphere::standard().air_density();
+        assert!((density - constants::AIR_DENSITY_SEA_LEVEL).abs() < 0.005);
+        assert!((Atmosphere::standard().engine_power_factor() - 1.0).abs() < 0.005);
+    }
+
+    #[test]
+    fn test_temperature_and_humidity_reduce_density() {
+        let cool = Atmosphere::new(15.0, 1013.25, 0.0, 0.0);
+        let hot = Atmosphere::new(35.0, 1013.25, 0.0, 0.0);
+        let hot_humid = Atmosphere::new(35.0, 1013.25, 0.9, 0.0);
+
+        assert!(hot.air_density() < cool.air_density());
+        assert!(hot_humid.air_density() < hot.air_density());
+        assert!(hot.engine_power_factor() < cool.engine_power_factor());
+    }
+
+    #[test]
+    fn test_mexico_altitude() {
+        let mexico = Atmosphere::at_altitude(2240.0, 20.0, 0.5);
+
+        assert!((mexico.pressure - 772.0).abs() < 10.0);
+        assert!(mexico.density_ratio() < 0.8);
+        assert!(mexico.engine_power_factor() > 0.9 && mexico.engine_power_factor() < 1.0);
+    }
+
+    #[test]
+    fn test_from_forecast() {
+        let forecast = WeatherForecast {
+            overall_condition: WeatherCondition::Dry,
+            air_temperature: 30.0,
+            track_temperature: 45.0,
+            humidity: 0.4,
+            pressure: Some(1020.0),
+            wind_speed: 5.0,
+            wind_direction: 90.0,
+            rain_probability: 0.0,
+            rainfall_intensity: 0.0,
+            sector_conditions: vec![],
+            predictions: vec![],
+        };
+
+        let atmosphere = Atmosphere::from_forecast(&forecast, 0.0);
+        assert_eq!(atmosphere.temperature, 30.0);
+        assert_eq!(atmosphere.pressure, 1020.0);
+        assert_eq!(atmosphere.humidity, 0.4);
+
+        let no_pressure = WeatherForecast { pressure: None, ..forecast };
+        let high = Atmosphere::from_forecast(&no_pressure, 2240.0);
+        assert!(high.pressure < 800.0);
+    }
+}
@@ -0,0 +1,877 @@
+//! Quasi-steady-state lap time simulation
+//!
+//! Treats the car as a point mass travelling along a circuit described as a
+//! sequence of straights and constant-radius corners. Corner speeds are limited
+//! by tire grip plus downforce, acceleration by engine power, drag and traction,
+//! and braking by grip plus downforce and drag. Grip comes from the tire force
+//! model, so compound, temperature and wear change the lap time. Drive force comes from the
+//! power unit model, so gearing, the rev limit and MGU-K deployment shape the
+//! straight-line speed trace and the DRS gain.
+
+use crate::{constants, AerodynamicsModel, Atmosphere, PowerUnit, TireForceModel, WingConfig};
+use f1_nexus_core::telemetry::DrsStatus;
+use f1_nexus_core::track::DrsZone;
+use f1_nexus_core::types::Sector;
+use serde::{Deserialize, Serialize};
+
+/// Default integration step along the racing line (m)
+pub const DEFAULT_STEP_LENGTH: f32 = 5.0;
+
+/// Fraction of the normal load carried by the driven (rear) axle
+pub const REAR_AXLE_LOAD_FRACTION: f32 = 0.55;
+
+/// Speed used in place of zero when computing power-limited tractive force (m/s)
+const MIN_TRACTION_SPEED: f32 = 1.0;
+
+/// Upper bound when searching for a grip-limited corner speed (m/s)
+const MAX_CORNERING_SEARCH_SPEED: f32 = 150.0;
+
+/// Distance from DRS detection point to activation point (m)
+pub const DRS_DETECTION_DISTANCE: f32 = 150.0;
+
+/// Integration step for straight-line speed traces (m)
+const STRAIGHT_STEP_LENGTH: f32 = 1.0;
+
+/// Shape of a track segment
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SegmentKind {
+    /// Straight line
+    Straight,
+    /// Constant-radius corner
+    Corner {
+        /// Corner radius on the racing line (m)
+        radius: f32,
+    },
+}
+
+/// Section of a circuit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackSegment {
+    /// Segment shape
+    pub kind: SegmentKind,
+
+    /// Segment length along the racing line (m)
+    pub length: f32,
+
+    /// Timing sector this segment belongs to
+    pub sector: Sector,
+
+    /// Whether DRS may be opened on this segment
+    #[serde(default)]
+    pub drs_zone: bool,
+}
+
+impl TrackSegment {
+    /// Create a straight segment
+    pub fn straight(length: f32, sector: Sector) -> Self {
+        Self {
+            kind: SegmentKind::Straight,
+            length,
+            sector,
+            drs_zone: false,
+        }
+    }
+
+    /// Create a corner segment
+    pub fn corner(radius: f32, length: f32, sector: Sector) -> Self {
+        Self {
+            kind: SegmentKind::Corner { radius },
+            length,
+            sector,
+            drs_zone: false,
+        }
+    }
+
+    /// Mark this segment as a DRS zone
+    pub fn with_drs(mut self) -> Self {
+        self.drs_zone = true;
+        self
+    }
+
+    /// Corner radius, or `None` for a straight
+    pub fn radius(&self) -> Option<f32> {
+        match self.kind {
+            SegmentKind::Straight => None,
+            SegmentKind::Corner { radius } => Some(radius),
+        }
+    }
+}
+
+/// Circuit layout as straights and corners
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitModel {
+    /// Circuit identifier (matches `Circuit::id`)
+    pub id: String,
+
+    /// Segments in lap order, starting at the timing line
+    pub segments: Vec<TrackSegment>,
+}
+
+impl CircuitModel {
+    /// Create a circuit model from segments
+    pub fn new(id: impl Into<String>, segments: Vec<TrackSegment>) -> Self {
+        Self {
+            id: id.into(),
+            segments,
+        }
+    }
+
+    /// Total lap length (m)
+    pub fn length(&self) -> f32 {
+        self.segments.iter().map(|s| s.length).sum()
+    }
+
+    /// Approximate Monza layout (5793 m)
+    pub fn monza() -> Self {
+        use Sector::*;
+        Self::new(
+            "monza",
+            vec![
+                TrackSegment::straight(600.0, Sector1).with_drs(),
+                TrackSegment::corner(25.0, 60.0, Sector1), // Rettifilo
+                TrackSegment::corner(25.0, 60.0, Sector1),
+                TrackSegment::straight(250.0, Sector1),
+                TrackSegment::corner(320.0, 450.0, Sector1), // Curva Grande
+                TrackSegment::straight(400.0, Sector1),
+                TrackSegment::corner(30.0, 50.0, Sector1), // Roggia
+                TrackSegment::corner(30.0, 50.0, Sector1),
+                TrackSegment::straight(250.0, Sector2),
+                TrackSegment::corner(90.0, 150.0, Sector2), // Lesmo 1
+                TrackSegment::straight(150.0, Sector2),
+                TrackSegment::corner(70.0, 120.0, Sector2), // Lesmo 2
+                TrackSegment::straight(800.0, Sector2).with_drs(),
+                TrackSegment::corner(80.0, 200.0, Sector2), // Ascari
+                TrackSegment::straight(1000.0, Sector3),
+                TrackSegment::corner(130.0, 350.0, Sector3), // Parabolica
+                TrackSegment::straight(853.0, Sector3).with_drs(),
+            ],
+        )
+    }
+
+    /// Approximate Monaco layout (3337 m)
+    pub fn monaco() -> Self {
+        use Sector::*;
+        Self::new(
+            "monaco",
+            vec![
+                TrackSegment::straight(330.0, Sector1).with_drs(),
+                TrackSegment::corner(20.0, 50.0, Sector1), // Sainte Devote
+                TrackSegment::straight(450.0, Sector1),
+                TrackSegment::corner(60.0, 120.0, Sector1), // Massenet
+                TrackSegment::corner(30.0, 60.0, Sector1), // Casino
+                TrackSegment::straight(150.0, Sector1),
+                TrackSegment::corner(20.0, 50.0, Sector2), // Mirabeau
+                TrackSegment::straight(100.0, Sector2),
+                TrackSegment::corner(10.0, 40.0, Sector2), // Grand Hotel Hairpin
+                TrackSegment::straight(80.0, Sector2),
+                TrackSegment::corner(20.0, 40.0, Sector2), // Portier
+                TrackSegment::straight(600.0, Sector2), // Tunnel
+                TrackSegment::corner(15.0, 60.0, Sector2), // Nouvelle Chicane
+                TrackSegment::straight(200.0, Sector2),
+                TrackSegment::corner(40.0, 60.0, Sector3), // Tabac
+                TrackSegment::straight(100.0, Sector3),
+                TrackSegment::corner(35.0, 80.0, Sector3), // Swimming Pool
+                TrackSegment::straight(60.0, Sector3),
+                TrackSegment::corner(25.0, 60.0, Sector3),
+                TrackSegment::straight(100.0, Sector3),
+                TrackSegment::corner(15.0, 60.0, Sector3), // La Rascasse
+                TrackSegment::corner(20.0, 40.0, Sector3), // Anthony Noghes
+                TrackSegment::straight(447.0, Sector3).with_drs(),
+            ],
+        )
+    }
+
+    /// Total length of DRS zones (m)
+    pub fn drs_length(&self) -> f32 {
+        self.segments
+            .iter()
+            .filter(|s| s.drs_zone)
+            .map(|s| s.length)
+            .sum()
+    }
+}
+
+/// Point-mass vehicle parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VehicleParams {
+    /// Car mass including driver and fuel (kg)
+    pub mass: f32,
+
+    /// Power unit and gearbox
+    pub power_unit: PowerUnit,
+
+    /// Tires
+    pub tire: TireForceModel,
+
+    /// Aerodynamic setup
+    pub wing_config: WingConfig,
+}
+
+impl Default for VehicleParams {
+    fn default() -> Self {
+        Self {
+            mass: 798.0,
+            power_unit: PowerUnit::default(),
+            tire: TireForceModel::default(),
+            wing_config: WingConfig::new(15.0, 12.0, 100.0),
+        }
+    }
+}
+
+/// One point of the simulated speed trace
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SpeedTracePoint {
+    /// Distance from the timing line (m)
+    pub distance: f32,
+
+    /// Speed (km/h)
+    pub speed_kmh: f32,
+
+    /// Elapsed lap time (s)
+    pub time: f32,
+
+    /// Index of the track segment
+    pub segment: usize,
+}
+
+/// One point of a straight-line speed trace
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct StraightTracePoint {
+    /// Distance from the start of the straight (m)
+    pub distance: f32,
+
+    /// Speed (km/h)
+    pub speed_kmh: f32,
+
+    /// Elapsed time (s)
+    pub time: f32,
+
+    /// Gear engaged
+    pub gear: u8,
+
+    /// Engine speed (rpm)
+    pub rpm: f32,
+}
+
+/// Time gained by opening DRS through one zone
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct DrsZoneGain {
+    /// Zone start, from the timing line (m)
+    pub start_distance: f32,
+
+    /// Zone length (m)
+    pub length: f32,
+
+    /// Speed at the start of the zone (km/h)
+    pub entry_speed_kmh: f32,
+
+    /// Time gained over the zone (s)
+    pub time_gain: f32,
+
+    /// Speed at the end of the zone with DRS closed (km/h)
+    pub closed_exit_speed_kmh: f32,
+
+    /// Speed at the end of the zone with DRS open (km/h)
+    pub open_exit_speed_kmh: f32,
+}
+
+/// Result of a simulated lap
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LapSimResult {
+    /// Total lap time (s)
+    pub lap_time: f32,
+
+    /// Sector times (s), in order of first appearance on the lap
+    pub sector_times: Vec<(Sector, f32)>,
+
+    /// Speed trace along the lap
+    pub speed_trace: Vec<SpeedTracePoint>,
+}
+
+impl LapSimResult {
+    /// Highest speed on the lap (km/h)
+    pub fn top_speed(&self) -> f32 {
+        self.speed_trace.iter().map(|p| p.speed_kmh).fold(0.0, f32::max)
+    }
+
+    /// Lowest speed on the lap (km/h)
+    pub fn min_speed(&self) -> f32 {
+        self.speed_trace
+            .iter()
+            .map(|p| p.speed_kmh)
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    /// Time for a given sector (s)
+    pub fn sector_time(&self, sector: Sector) -> Option<f32> {
+        self.sector_times
+            .iter()
+            .find(|(s, _)| *s == sector)
+            .map(|(_, t)| *t)
+    }
+}
+
+/// Quasi-steady-state point-mass lap simulator
+#[derive(Debug, Clone)]
+pub struct LapSimulator {
+    /// Aerodynamics model (air density, areas)
+    pub aero: AerodynamicsModel,
+
+    /// Vehicle parameters
+    pub vehicle: VehicleParams,
+
+    /// Integration step along the lap (m)
+    pub step_length: f32,
+}
+
+impl Default for LapSimulator {
+    fn default() -> Self {
+        Self::new(AerodynamicsModel::default(), VehicleParams::default())
+    }
+}
+
+impl LapSimulator {
+    /// Create a lap simulator
+    pub fn new(aero: AerodynamicsModel, vehicle: VehicleParams) -> Self {
+        Self {
+            aero,
+            vehicle,
+            step_length: DEFAULT_STEP_LENGTH,
+        }
+    }
+
+    /// Apply ambient conditions to aerodynamic forces and engine power
+    pub fn set_atmosphere(&mut self, atmosphere: &Atmosphere) {
+        self.aero.update_atmosphere(atmosphere);
+        self.vehicle.power_unit.ice_power_factor = atmosphere.engine_power_factor();
+    }
+
+    /// Simulate a flying lap of `circuit` without DRS
+    pub fn simulate(&self, circuit: &CircuitModel) -> LapSimResult {
+        self.simulate_lap(circuit, false)
+    }
+
+    /// Simulate a flying lap of `circuit` with DRS open in every DRS zone
+    pub fn simulate_with_drs(&self, circuit: &CircuitModel) -> LapSimResult {
+        self.simulate_lap(circuit, true)
+    }
+
+    fn simulate_lap(&self, circuit: &CircuitModel, drs_open: bool) -> LapSimResult {
+        let points = self.discretize(circuit);
+        let n = points.len();
+        if n == 0 {
+            return LapSimResult {
+                lap_time: 0.0,
+                sector_times: vec![],
+                speed_trace: vec![],
+            };
+        }
+
+        let closed = self.aero_coefficients(DrsStatus::Unavailable);
+        let open = self.aero_coefficients(DrsStatus::Activated);
+        let coefficients: Vec<(f32, f32)> = points
+            .iter()
+            .map(|p| if drs_open && p.drs_zone { open } else { closed })
+            .collect();
+        let mass = self.vehicle.mass;
+        let tire = &self.vehicle.tire;
+
+        // Grip-limited speed at every point
+        let limits: Vec<f32> = points
+            .iter()
+            .zip(&coefficients)
+            .map(|(p, &(downforce_k, _))| self.max_cornering_speed(p.radius, downforce_k))
+            .collect();
+
+        // Forward pass: accelerate out of each corner. Run twice so the lap
+        // starts at the speed it finishes with.
+        let lap_length = circuit.length();
+        let mut speed = vec![0.0f32; n];
+        let mut start_speed = limits[0].min(self.top_speed(DrsStatus::Unavailable) / 3.6);
+        let power_unit = &self.vehicle.power_unit;
+        for _ in 0..2 {
+            speed[0] = start_speed;
+            let mut mgu_k_deployed = 0.0f32;
+            for i in 0..n {
+                let next_distance = points.get(i + 1).map_or(lap_length, |p| p.distance);
+                let ds = next_distance - points[i].distance;
+                let v = speed[i];
+                let (downforce_k, drag_k) = coefficients[i];
+                let normal = mass * constants::GRAVITY + downforce_k * v * v;
+                let long_grip = tire.longitudinal_limit(normal, lateral_force(mass, v, points[i].radius));
+                let mgu_k_available = mgu_k_deployed < power_unit.mgu_k_energy_per_lap;
+                let power_limited = power_unit.wheel_power(v, mgu_k_available) / v.max(MIN_TRACTION_SPEED);
+                let traction_limited = long_grip * REAR_AXLE_LOAD_FRACTION;
+                if mgu_k_available && power_limited < traction_limited {
+                    mgu_k_deployed += power_unit.mgu_k_power() * ds / v.max(MIN_TRACTION_SPEED);
+                }
+                let tractive = power_limited.min(traction_limited);
+                let accel = (tractive - drag_k * v * v) / mass;
+                let next = (v * v + 2.0 * accel * ds).max(0.0).sqrt();
+                if i + 1 < n {
+                    speed[i + 1] = next.min(limits[i + 1]);
+                } else {
+                    start_speed = next.min(limits[0]);
+                }
+            }
+        }
+
+        // Backward pass: brake into each corner, wrapping into the next lap
+        let mut next_speed = speed[0];
+        let mut next_distance = lap_length;
+        for i in (0..n).rev() {
+            let ds = next_distance - points[i].distance;
+            let v = next_speed;
+            let (downforce_k, drag_k) = coefficients[i];
+            let normal = mass * constants::GRAVITY + downforce_k * v * v;
+            let long_grip = tire.longitudinal_limit(normal, lateral_force(mass, v, points[i].radius));
+            let decel = (long_grip + drag_k * v * v) / mass;
+            let entry = (v * v + 2.0 * decel * ds).sqrt();
+            speed[i] = speed[i].min(entry);
+            next_speed = speed[i];
+            next_distance = points[i].distance;
+        }
+
+        // Integrate time along the trace
+        let mut speed_trace = Vec::with_capacity(n);
+        let mut sector_times: Vec<(Sector, f32)> = Vec::new();
+        let mut time = 0.0f32;
+        for i in 0..n {
+            speed_trace.push(SpeedTracePoint {
+                distance: points[i].distance,
+                speed_kmh: speed[i] * 3.6,
+                time,
+                segment: points[i].segment,
+            });
+
+            let (ds, v_next) = if i + 1 < n {
+                (points[i + 1].distance - points[i].distance, speed[i + 1])
+            } else {
+                (lap_length - points[i].distance, speed[0])
+            };
+            let dt = 2.0 * ds / (speed[i] + v_next).max(f32::EPSILON);
+            time += dt;
+
+            let sector = circuit.segments[points[i].segment].sector;
+            match sector_times.iter_mut().find(|(s, _)| *s == sector) {
+                Some((_, t)) => *t += dt,
+                None => sector_times.push((sector, dt)),
+            }
+        }
+
+        LapSimResult {
+            lap_time: time,
+            sector_times,
+            speed_trace,
+        }
+    }
+
+    /// Downforce and drag per unit dynamic speed squared (N per (m/s)²)
+    fn aero_coefficients(&self, drs: DrsStatus) -> (f32, f32) {
+        // Both forces scale with v², so evaluating at 1 m/s gives the coefficient
+        let unit_speed_kmh = 3.6;
+        let wing = &self.vehicle.wing_config;
+        let downforce = self.aero.calculate_downforce(unit_speed_kmh, wing, drs).abs();
+        let drag = self.aero.calculate_drag_with_drs(unit_speed_kmh, wing, drs);
+        (downforce, drag)
+    }
+
+    /// Maximum steady-state speed through a corner of `radius` (m/s)
+    ///
+    /// Solves F_tire(m·g + k·v²) = m·v²/r for v by bisection, since load
+    /// sensitivity makes tire force non-linear in load. Returns infinity on
+    /// straights or when grip still exceeds the required lateral force at the
+    /// search limit.
+    fn max_cornering_speed(&self, radius: Option<f32>, downforce_k: f32) -> f32 {
+        let Some(radius) = radius else {
+            return f32::INFINITY;
+        };
+        let mass = self.vehicle.mass;
+        let tire = &self.vehicle.tire;
+        let surplus = |v: f32| {
+            tire.max_force(mass * constants::GRAVITY + downforce_k * v * v) - mass * v * v / radius
+        };
+
+        let mut high = MAX_CORNERING_SEARCH_SPEED;
+        if surplus(high) > 0.0 {
+            return f32::INFINITY;
+        }
+
+        let mut low = 0.0;
+        for _ in 0..50 {
+            let mid = 0.5 * (low + high);
+            if surplus(mid) > 0.0 {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+
+    /// Top speed on an unlimited straight (km/h)
+    ///
+    /// The speed where drive force equals drag, capped by the rev limit in top gear.
+    pub fn top_speed(&self, drs: DrsStatus) -> f32 {
+        let (_, drag_k) = self.aero_coefficients(drs);
+        let power_unit = &self.vehicle.power_unit;
+        let surplus = |v: f32| power_unit.wheel_power(v, true) / v - drag_k * v * v;
+
+        let mut high = power_unit.rev_limited_speed();
+        if surplus(high) > 0.0 {
+            return high * 3.6;
+        }
+
+        let mut low = MIN_TRACTION_SPEED;
+        for _ in 0..50 {
+            let mid = 0.5 * (low + high);
+            if surplus(mid) > 0.0 {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        low * 3.6
+    }
+
+    /// Speed trace along a straight of `length` metres at full throttle
+    pub fn straight_speed_trace(
+        &self,
+        entry_speed_kmh: f32,
+        length: f32,
+        drs: DrsStatus,
+    ) -> Vec<StraightTracePoint> {
+        let (downforce_k, drag_k) = self.aero_coefficients(drs);
+        let mass = self.vehicle.mass;
+        let tire = &self.vehicle.tire;
+        let power_unit = &self.vehicle.power_unit;
+
+        let steps = (length / STRAIGHT_STEP_LENGTH).ceil().max(1.0) as usize;
+        let ds = length / steps as f32;
+        let mut trace = Vec::with_capacity(steps + 1);
+        let mut v = (entry_speed_kmh / 3.6).max(MIN_TRACTION_SPEED);
+        let mut time = 0.0f32;
+
+        for k in 0..=steps {
+            let gear = power_unit.select_gear(v);
+            trace.push(StraightTracePoint {
+                distance: k as f32 * ds,
+                speed_kmh: v * 3.6,
+                time,
+                gear: gear.map_or(power_unit.gear_ratios.len() as u8, |g| g.gear),
+                rpm: gear.map_or(power_unit.rev_limit, |g| g.rpm),
+            });
+            if k == steps {
+                break;
+            }
+
+            let traction = tire.max_force(mass * constants::GRAVITY + downforce_k * v * v) * REAR_AXLE_LOAD_FRACTION;
+            let drive = (power_unit.wheel_power(v, true) / v).min(traction);
+            let accel = (drive - drag_k * v * v) / mass;
+            let next = (v * v + 2.0 * accel * ds).max(MIN_TRACTION_SPEED * MIN_TRACTION_SPEED).sqrt();
+            time += 2.0 * ds / (v + next);
+            v = next;
+        }
+
+        trace
+    }
+
+    /// Time gained by opening DRS over a zone entered at `entry_speed_kmh`
+    pub fn drs_zone_gain(&self, entry_speed_kmh: f32, length: f32) -> DrsZoneGain {
+        let closed = self.straight_speed_trace(entry_speed_kmh, length, DrsStatus::Unavailable);
+        let open = self.straight_speed_trace(entry_speed_kmh, length, DrsStatus::Activated);
+        let closed_end = closed.last().copied();
+        let open_end = open.last().copied();
+
+        DrsZoneGain {
+            start_distance: 0.0,
+            length,
+            entry_speed_kmh,
+            time_gain: closed_end.map_or(0.0, |p| p.time) - open_end.map_or(0.0, |p| p.time),
+            closed_exit_speed_kmh: closed_end.map_or(entry_speed_kmh, |p| p.speed_kmh),
+            open_exit_speed_kmh: open_end.map_or(entry_speed_kmh, |p| p.speed_kmh),
+        }
+    }
+
+    /// DRS gain for every DRS zone of `circuit`
+    ///
+    /// Consecutive DRS segments form one zone, including a zone that spans the
+    /// timing line. Entry speeds come from a simulated lap without DRS.
+    pub fn drs_zone_gains(&self, circuit: &CircuitModel) -> Vec<DrsZoneGain> {
+        let lap = self.simulate(circuit);
+        let lap_length = circuit.length();
+
+        // (start distance, length) of each run of DRS segments
+        let mut zones: Vec<(f32, f32)> = Vec::new();
+        let mut distance = 0.0f32;
+        let mut previous_drs = false;
+        for segment in &circuit.segments {
+            if segment.drs_zone {
+                match zones.last_mut() {
+                    Some((_, length)) if previous_drs => *length += segment.length,
+                    _ => zones.push((distance, segment.length)),
+                }
+            }
+            previous_drs = segment.drs_zone;
+            distance += segment.length;
+        }
+
+        // Join a zone ending at the timing line with one starting there
+        if zones.len() > 1 && circuit.segments.first().is_some_and(|s| s.drs_zone) && previous_drs {
+            let (_, first_length) = zones.remove(0);
+            if let Some((_, length)) = zones.last_mut() {
+                *length += first_length;
+            }
+        }
+
+        zones
+            .into_iter()
+            .map(|(start, length)| {
+                let entry_speed = lap
+                    .speed_trace
+                    .iter()
+                    .find(|p| p.distance >= start)
+                    .or(lap.speed_trace.first())
+                    .map_or(0.0, |p| p.speed_kmh);
+                DrsZoneGain {
+                    start_distance: start % lap_length,
+                    ..self.drs_zone_gain(entry_speed, length)
+                }
+            })
+            .collect()
+    }
+
+    /// Circuit DRS zones with physics-based expected time gains
+    ///
+    /// The built-in `Circuit` definitions ship without DRS zones; assign these
+    /// to `Circuit::drs_zones` for `RaceSimulator::overtake_probability` to
+    /// account for DRS.
+    pub fn drs_zones(&self, circuit: &CircuitModel) -> Vec<DrsZone> {
+        let lap_length = circuit.length();
+        self.drs_zone_gains(circuit)
+            .into_iter()
+            .enumerate()
+            .map(|(i, gain)| DrsZone {
+                zone_id: i as u8 + 1,
+                detection_point: (gain.start_distance - DRS_DETECTION_DISTANCE).rem_euclid(lap_length),
+                activation_point: gain.start_distance,
+                end_point: (gain.start_distance + gain.length) % lap_length,
+                expected_time_gain: gain.time_gain,
+            })
+            .collect()
+    }
+
+    /// Split the circuit into integration points
+    fn discretize(&self, circuit: &CircuitModel) -> Vec<TracePoint> {
+        let mut points = Vec::new();
+        let mut distance = 0.0f32;
+        let step = self.step_length.max(0.1);
+
+        for (index, segment) in circuit.segments.iter().enumerate() {
+            let steps = (segment.length / step).ceil().max(1.0) as usize;
+            let ds = segment.length / steps as f32;
+            for k in 0..steps {
+                points.push(TracePoint {
+                    distance: distance + k as f32 * ds,
+                    radius: segment.radius(),
+                    drs_zone: segment.drs_zone,
+                    segment: index,
+                });
+            }
+            distance += segment.length;
+        }
+
+        points
+    }
+}
+
+/// Integration point along the lap
+#[derive(Debug, Clone, Copy)]
+struct TracePoint {
+    distance: f32,
+    radius: Option<f32>,
+    drs_zone: bool,
+    segment: usize,
+}
+
+/// Lateral force needed to hold `speed` through a corner of `radius` (N)
+fn lateral_force(mass: f32, speed: f32, radius: Option<f32>) -> f32 {
+    radius.map_or(0.0, |r| mass * speed * speed / r)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monza_lap_time() {
+        let sim = LapSimulator::default();
+        let circuit = CircuitModel::monza();
+        let result = sim.simulate(&circuit);
+
+        assert!((circuit.length() - 5793.0).abs() < 1.0);
+        // Real pole laps are ~80 s; a point-mass model should be in the same range
+        assert!(result.lap_time > 65.0 && result.lap_time < 100.0, "lap time {}", result.lap_time);
+        assert!(result.top_speed() > 280.0 && result.top_speed() < 380.0);
+        assert!(result.min_speed() > 60.0 && result.min_speed() < 140.0);
+    }
+
+    #[test]
+    fn test_sector_times_sum_to_lap_time() {
+        let result = LapSimulator::default().simulate(&CircuitModel::monza());
+
+        assert_eq!(result.sector_times.len(), 3);
+        let total: f32 = result.sector_times.iter().map(|(_, t)| t).sum();
+        assert!((total - result.lap_time).abs() < 1e-3);
+        assert!(result.sector_time(Sector::Sector2).unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_speed_trace_respects_corner_limits() {
+        let sim = LapSimulator::default();
+        let circuit = CircuitModel::monza();
+        let result = sim.simulate(&circuit);
+        let (downforce_k, _) = sim.aero_coefficients(DrsStatus::Unavailable);
+
+        for point in &result.speed_trace {
+            let radius = circuit.segments[point.segment].radius();
+            let limit = sim.max_cornering_speed(radius, downforce_k) * 3.6;
+            assert!(point.speed_kmh <= limit + 0.1);
+        }
+    }
+
+    #[test]
+    fn test_more_power_is_faster() {
+        let circuit = CircuitModel::monza();
+        let base = LapSimulator::default().simulate(&circuit);
+
+        let mut vehicle = VehicleParams::default();
+        vehicle.power_unit.ice_peak_power *= 1.1;
+        let powerful = LapSimulator::new(AerodynamicsModel::default(), vehicle).simulate(&circuit);
+
+        assert!(powerful.lap_time < base.lap_time);
+        assert!(powerful.top_speed() > base.top_speed());
+    }
+
+    #[test]
+    fn test_more_grip_is_faster() {
+        let circuit = CircuitModel::monza();
+        let base = LapSimulator::default().simulate(&circuit);
+
+        let vehicle = VehicleParams {
+            tire: TireForceModel {
+                peak_friction: 2.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let grippy = LapSimulator::new(AerodynamicsModel::default(), vehicle).simulate(&circuit);
+
+        assert!(grippy.lap_time < base.lap_time);
+        assert!(grippy.min_speed() > base.min_speed());
+    }
+
+    #[test]
+    fn test_drs_improves_lap_time() {
+        let sim = LapSimulator::default();
+        let circuit = CircuitModel::monza();
+        let closed = sim.simulate(&circuit);
+        let open = sim.simulate_with_drs(&circuit);
+
+        assert!(circuit.drs_length() > 0.0);
+        assert!(open.lap_time < closed.lap_time);
+        assert!(open.top_speed() > closed.top_speed());
+    }
+
+    #[test]
+    fn test_top_speed_with_drs() {
+        let sim = LapSimulator::default();
+        let closed = sim.top_speed(DrsStatus::Unavailable);
+        let open = sim.top_speed(DrsStatus::Activated);
+
+        assert!(closed > 280.0 && closed < 360.0, "top speed {}", closed);
+        assert!(open > closed);
+        assert!(open <= sim.vehicle.power_unit.rev_limited_speed() * 3.6 + 0.1);
+    }
+
+    #[test]
+    fn test_straight_speed_trace() {
+        let sim = LapSimulator::default();
+        let trace = sim.straight_speed_trace(120.0, 1000.0, DrsStatus::Unavailable);
+
+        assert!((trace.last().unwrap().distance - 1000.0).abs() < 0.01);
+        assert!(trace.windows(2).all(|w| w[1].speed_kmh >= w[0].speed_kmh - 0.01));
+        assert!(trace.windows(2).all(|w| w[1].gear >= w[0].gear));
+        assert!(trace.iter().all(|p| p.rpm <= sim.vehicle.power_unit.rev_limit));
+        assert!(trace.last().unwrap().speed_kmh <= sim.top_speed(DrsStatus::Unavailable) + 0.5);
+    }
+
+    #[test]
+    fn test_drs_zone_gains() {
+        let sim = LapSimulator::default();
+        let circuit = CircuitModel::monza();
+        let gains = sim.drs_zone_gains(&circuit);
+
+        // Main straight spans the timing line and counts as one zone
+        assert_eq!(gains.len(), 2);
+        assert!(gains.iter().all(|g| g.time_gain > 0.0 && g.time_gain < 1.0));
+        assert!(gains.iter().all(|g| g.open_exit_speed_kmh > g.closed_exit_speed_kmh));
+        let main = gains.iter().find(|g| (g.length - 1453.0).abs() < 1.0).unwrap();
+        let serraglio = gains.iter().find(|g| (g.length - 800.0).abs() < 1.0).unwrap();
+        assert!(main.time_gain > serraglio.time_gain);
+
+        let zones = sim.drs_zones(&circuit);
+        assert_eq!(zones.len(), 2);
+        assert!(zones.iter().all(|z| z.expected_time_gain > 0.0));
+        assert!(zones.iter().all(|z| z.detection_point < circuit.length()));
+    }
+
+    #[test]
+    fn test_compound_differences_from_tire_model() {
+        use f1_nexus_core::tire::TireCompound;
+
+        let circuit = CircuitModel::monaco();
+        let lap_time = |tire: TireForceModel| {
+            let vehicle = VehicleParams {
+                tire,
+                ..Default::default()
+            };
+            LapSimulator::new(AerodynamicsModel::default(), vehicle)
+                .simulate(&circuit)
+                .lap_time
+        };
+
+        let hard = lap_time(TireForceModel::for_compound(TireCompound::C1));
+        let medium = lap_time(TireForceModel::for_compound(TireCompound::C3));
+        let soft = lap_time(TireForceModel::for_compound(TireCompound::C5));
+        assert!(soft < medium && medium < hard, "{} {} {}", soft, medium, hard);
+
+        // Worn or cold tires give up lap time
+        let worn = lap_time(TireForceModel::default().with_wear(1.0));
+        let cold = lap_time(TireForceModel::default().with_temperature(70.0));
+        assert!(worn > medium);
+        assert!(cold > medium);
+    }
+
+    #[test]
+    fn test_atmosphere_effects() {
+        let circuit = CircuitModel::monza();
+        let standard = LapSimulator::default();
+
+        // Mexico City altitude: less drag and downforce, slightly less power
+        let mut mexico = LapSimulator::default();
+        mexico.set_atmosphere(&Atmosphere::at_altitude(2240.0, 20.0, 0.5));
+        assert!(mexico.top_speed(DrsStatus::Unavailable) > standard.top_speed(DrsStatus::Unavailable));
+        assert!(mexico.vehicle.power_unit.ice_power_factor < 1.0);
+
+        let standard_lap = standard.simulate(&circuit);
+        let mexico_lap = mexico.simulate(&circuit);
+        assert!(mexico_lap.min_speed() < standard_lap.min_speed());
+
+        // A hot day costs lap time: power and downforce losses outweigh the drag saving
+        let mut hot = LapSimulator::default();
+        hot.set_atmosphere(&Atmosphere::new(38.0, 1010.0, 0.7, 0.0));
+        assert!(hot.vehicle.power_unit.ice_power_factor < mexico.vehicle.power_unit.ice_power_factor);
+        assert!(hot.simulate(&circuit).lap_time > standard_lap.lap_time + 0.1);
+    }
+}
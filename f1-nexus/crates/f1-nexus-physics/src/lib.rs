@@ -2,6 +2,23 @@
 //!
 //! This module provides accurate aerodynamic calculations for Formula 1 cars,
 //! including downforce, drag, ground effect, and DRS (Drag Reduction System) modeling.
+//! The `lapsim` module combines these with the `powertrain` model into a
+//! point-mass lap time simulation, and `setup` searches wing and ride height
+//! settings against it. `atmosphere` turns weather into air density and engine
+//! power effects, and `tire` models grip from compound, load, temperature and
+//! wear.
+
+pub mod atmosphere;
+pub mod lapsim;
+pub mod powertrain;
+pub mod setup;
+pub mod tire;
+
+pub use atmosphere::*;
+pub use lapsim::*;
+pub use powertrain::*;
+pub use setup::*;
+pub use tire::*;
 
 use f1_nexus_core::telemetry::{AeroData, DrsStatus};
 use serde::{Deserialize, Serialize};
@@ -116,35 +133,34 @@ impl AerodynamicsModel {
         Self::new(air_density, constants::F1_FRONTAL_AREA, constants::F1_WING_AREA)
     }
 
-    /// Calculate air density at a given altitude using barometric formula
+    /// Create model with air density from an atmosphere
+    pub fn from_atmosphere(atmosphere: &Atmosphere) -> Self {
+        Self::new(
+            atmosphere.air_density(),
+            constants::F1_FRONTAL_AREA,
+            constants::F1_WING_AREA,
+        )
+    }
+
+    /// Calculate dry air density at a given altitude and temperature
     ///
-    /// Uses the International Standard Atmosphere (ISA) model:
-    /// ρ = ρ₀ × (1 - L×h/T₀)^((g×M)/(R×L) - 1)
+    /// Pressure follows the International Standard Atmosphere (ISA) barometric
+    /// formula for the altitude; density then follows the ideal gas law at the
+    /// actual temperature:
+    /// ρ = p(h) / (R × T)
     /// where:
-    /// - ρ₀ = sea level density
-    /// - L = temperature lapse rate
-    /// - h = altitude
-    /// - T₀ = sea level temperature
-    /// - g = gravitational acceleration
-    /// - M = molar mass of air (0.029 kg/mol)
-    /// - R = universal gas constant (8.314 J/(mol·K))
-    pub fn calculate_air_density(altitude_meters: f32, _temperature_celsius: f32) -> f32 {
-        let sea_level_temp_kelvin = constants::STANDARD_TEMP_SEA_LEVEL + 273.15;
-
-        // Temperature ratio at altitude using standard lapse rate
-        let temp_ratio = 1.0 - (constants::TEMPERATURE_LAPSE_RATE * altitude_meters / sea_level_temp_kelvin);
-
-        // Barometric formula exponent: (g*M)/(R*L) where M=0.029, R=8.314
-        // This simplifies to approximately g/(R_specific * L) = 9.81/(287.05 * 0.0065) ≈ 5.256
-        let exponent = (constants::GRAVITY / (constants::GAS_CONSTANT * constants::TEMPERATURE_LAPSE_RATE)) - 1.0;
+    /// - p(h) = ISA pressure at altitude h
+    /// - R = gas constant for dry air
+    /// - T = air temperature (K)
+    ///
+    /// Use `Atmosphere` to include humidity and measured pressure.
+    pub fn calculate_air_density(altitude_meters: f32, temperature_celsius: f32) -> f32 {
+        Atmosphere::at_altitude(altitude_meters, temperature_celsius, 0.0).air_density()
+    }
 
-        // Calculate density using barometric formula
-        if temp_ratio > 0.0 {
-            constants::AIR_DENSITY_SEA_LEVEL * temp_ratio.powf(exponent)
-        } else {
-            // For very high altitudes, use exponential approximation
-            constants::AIR_DENSITY_SEA_LEVEL * (-altitude_meters / 8500.0).exp()
-        }
+    /// Update air density as conditions change during a session
+    pub fn update_atmosphere(&mut self, atmosphere: &Atmosphere) {
+        self.air_density = atmosphere.air_density();
     }
 
     /// Calculate total downforce generated by the car
@@ -337,9 +353,33 @@ impl AerodynamicsModel {
         speed_ms * 3.6
     }
 
+    /// Calculate maximum corner speed from a tire force model
+    ///
+    /// Like `calculate_corner_speed`, but the available lateral force comes
+    /// from the tire model, so load sensitivity, compound, temperature and
+    /// wear all affect the result.
+    ///
+    /// # Returns
+    /// Maximum corner speed in km/h
+    pub fn calculate_corner_speed_with_tire(
+        &self,
+        radius_meters: f32,
+        downforce_newtons: f32,
+        tire: &TireForceModel,
+    ) -> f32 {
+        // Typical F1 car mass (kg) - minimum weight with driver
+        let car_mass = 798.0;
+
+        let normal_force = car_mass * constants::GRAVITY + downforce_newtons.abs();
+        let max_lateral_force = tire.max_force(normal_force);
+
+        ((max_lateral_force / car_mass) * radius_meters).sqrt() * 3.6
+    }
+
     /// Calculate optimal wing angles for a given speed and corner
     ///
-    /// Balances downforce for corner grip vs. drag for straight-line speed
+    /// Balances downforce for corner grip vs. drag for straight-line speed.
+    /// Use `SetupOptimizer` to search setups against a full circuit model.
     pub fn optimize_wing_angles(
         &self,
         target_speed_kmh: f32,
@@ -500,6 +540,26 @@ mod tests {
         assert!(corner_speed < 280.0, "Corner speed too high");
     }
 
+    #[test]
+    fn test_corner_speed_with_tire_model() {
+        use f1_nexus_core::tire::TireCompound;
+
+        let model = AerodynamicsModel::default();
+        let downforce = 3000.0;
+        let medium = TireForceModel::default();
+        let soft = TireForceModel::for_compound(TireCompound::C5);
+
+        let medium_speed = model.calculate_corner_speed_with_tire(50.0, downforce, &medium);
+        let soft_speed = model.calculate_corner_speed_with_tire(50.0, downforce, &soft);
+        let worn_speed = model.calculate_corner_speed_with_tire(50.0, downforce, &medium.clone().with_wear(1.0));
+
+        assert!(soft_speed > medium_speed);
+        assert!(worn_speed < medium_speed);
+        // Close to the scalar model with the same reference friction
+        let scalar = model.calculate_corner_speed(50.0, downforce, 1.8);
+        assert!((medium_speed - scalar).abs() < 5.0);
+    }
+
     #[test]
     fn test_ground_effect_optimal_ride_height() {
         let model = AerodynamicsModel::default();
@@ -586,6 +646,19 @@ mod tests {
         assert!(model_altitude.air_density() < model_sea_level.air_density());
     }
 
+    #[test]
+    fn test_air_density_depends_on_temperature() {
+        let cool = AerodynamicsModel::calculate_air_density(0.0, 10.0);
+        let hot = AerodynamicsModel::calculate_air_density(0.0, 35.0);
+        assert!(hot < cool);
+
+        let mut model = AerodynamicsModel::default();
+        let config = WingConfig::new(15.0, 12.0, 100.0);
+        let drag_standard = model.calculate_drag(300.0, &config);
+        model.update_atmosphere(&Atmosphere::new(35.0, 1013.25, 0.6, 0.0));
+        assert!(model.calculate_drag(300.0, &config) < drag_standard);
+    }
+
     #[test]
     fn test_complete_aerodynamics_scenario() {
         // Simulate a complete lap scenario
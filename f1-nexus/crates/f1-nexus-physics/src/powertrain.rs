@@ -0,0 +1,217 @@
+//! Power unit and gearbox model
+//!
+//! Combines internal combustion engine (ICE) power, MGU-K deployment, gear
+//! ratios and the rev limit into the power available at the wheels for a given
+//! road speed.
+
+use f1_nexus_core::telemetry::ErsMode;
+use serde::{Deserialize, Serialize};
+
+/// Peak ICE power (W)
+pub const ICE_PEAK_POWER: f32 = 560_000.0;
+
+/// Engine speed at peak ICE power (rpm)
+pub const ICE_PEAK_POWER_RPM: f32 = 11_000.0;
+
+/// FIA rev limit (rpm)
+pub const REV_LIMIT: f32 = 15_000.0;
+
+/// Maximum MGU-K deployment power (W)
+pub const MGU_K_MAX_POWER: f32 = 120_000.0;
+
+/// Maximum MGU-K energy deployed per lap (J)
+pub const MGU_K_ENERGY_PER_LAP: f32 = 4_000_000.0;
+
+/// Power unit, gearbox and drivetrain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerUnit {
+    /// Peak ICE power (W)
+    pub ice_peak_power: f32,
+
+    /// Engine speed at peak ICE power (rpm)
+    pub peak_power_rpm: f32,
+
+    /// Rev limit (rpm)
+    pub rev_limit: f32,
+
+    /// Maximum MGU-K power (W)
+    pub mgu_k_max_power: f32,
+
+    /// MGU-K energy that may be deployed per lap (J)
+    pub mgu_k_energy_per_lap: f32,
+
+    /// ERS deployment mode
+    pub ers_mode: ErsMode,
+
+    /// Gearbox ratios, first to top gear
+    pub gear_ratios: Vec<f32>,
+
+    /// Final drive ratio
+    pub final_drive: f32,
+
+    /// Rolling radius of the rear tires (m)
+    pub wheel_radius: f32,
+
+    /// Drivetrain efficiency (0.0-1.0)
+    pub drivetrain_efficiency: f32,
+
+    /// ICE power multiplier for ambient conditions (1.0 = standard atmosphere)
+    pub ice_power_factor: f32,
+}
+
+impl Default for PowerUnit {
+    fn default() -> Self {
+        Self {
+            ice_peak_power: ICE_PEAK_POWER,
+            peak_power_rpm: ICE_PEAK_POWER_RPM,
+            rev_limit: REV_LIMIT,
+            mgu_k_max_power: MGU_K_MAX_POWER,
+            mgu_k_energy_per_lap: MGU_K_ENERGY_PER_LAP,
+            ers_mode: ErsMode::High,
+            gear_ratios: vec![5.50, 4.12, 3.29, 2.74, 2.32, 2.00, 1.76, 1.56],
+            final_drive: 3.4,
+            wheel_radius: 0.33,
+            drivetrain_efficiency: 0.95,
+            ice_power_factor: 1.0,
+        }
+    }
+}
+
+/// Selected gear and engine speed
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GearSelection {
+    /// Gear number (1 = first)
+    pub gear: u8,
+
+    /// Engine speed (rpm)
+    pub rpm: f32,
+}
+
+impl PowerUnit {
+    /// ICE power at an engine speed (W)
+    ///
+    /// Rises linearly from 40% at idle to peak power, then falls to 75% at the
+    /// rev limit. No power is available above the rev limit.
+    pub fn ice_power(&self, rpm: f32) -> f32 {
+        let shape = if rpm > self.rev_limit {
+            0.0
+        } else if rpm <= self.peak_power_rpm {
+            0.4 + 0.6 * rpm.max(0.0) / self.peak_power_rpm
+        } else {
+            let over = (rpm - self.peak_power_rpm) / (self.rev_limit - self.peak_power_rpm);
+            1.0 - 0.25 * over * over
+        };
+        self.ice_peak_power * self.ice_power_factor * shape
+    }
+
+    /// MGU-K power for the current ERS mode (W)
+    pub fn mgu_k_power(&self) -> f32 {
+        let fraction = match self.ers_mode {
+            ErsMode::None => 0.0,
+            ErsMode::Low => 0.33,
+            ErsMode::Medium => 0.66,
+            ErsMode::High | ErsMode::Hotlap | ErsMode::Overtake => 1.0,
+        };
+        self.mgu_k_max_power * fraction
+    }
+
+    /// Engine speed in a gear at a road speed (rpm)
+    ///
+    /// Gears outside `1..=gear_ratios.len()` are clamped to first or top gear.
+    pub fn engine_rpm(&self, speed_ms: f32, gear: u8) -> f32 {
+        let index = (gear as usize).clamp(1, self.gear_ratios.len().max(1)) - 1;
+        let ratio = self.gear_ratios.get(index).copied().unwrap_or(0.0) * self.final_drive;
+        speed_ms / self.wheel_radius * ratio * 60.0 / (2.0 * std::f32::consts::PI)
+    }
+
+    /// Gear giving the most ICE power at a road speed
+    ///
+    /// Returns `None` when even top gear is above the rev limit.
+    pub fn select_gear(&self, speed_ms: f32) -> Option<GearSelection> {
+        (1..=self.gear_ratios.len() as u8)
+            .map(|gear| GearSelection {
+                gear,
+                rpm: self.engine_rpm(speed_ms, gear),
+            })
+            .filter(|g| g.rpm <= self.rev_limit)
+            .max_by(|a, b| self.ice_power(a.rpm).total_cmp(&self.ice_power(b.rpm)))
+    }
+
+    /// Power delivered to the wheels at a road speed (W)
+    ///
+    /// `mgu_k_available` is false once the lap's deployment energy is spent.
+    pub fn wheel_power(&self, speed_ms: f32, mgu_k_available: bool) -> f32 {
+        let Some(selection) = self.select_gear(speed_ms) else {
+            return 0.0;
+        };
+        let mgu_k = if mgu_k_available { self.mgu_k_power() } else { 0.0 };
+        (self.ice_power(selection.rpm) + mgu_k) * self.drivetrain_efficiency
+    }
+
+    /// Road speed at the rev limit in top gear (m/s)
+    pub fn rev_limited_speed(&self) -> f32 {
+        let top_gear = self.gear_ratios.len() as u8;
+        self.rev_limit / self.engine_rpm(1.0, top_gear)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ice_power_curve() {
+        let pu = PowerUnit::default();
+        assert_eq!(pu.ice_power(ICE_PEAK_POWER_RPM), ICE_PEAK_POWER);
+        assert!(pu.ice_power(8_000.0) < ICE_PEAK_POWER);
+        assert!(pu.ice_power(REV_LIMIT) < ICE_PEAK_POWER);
+        assert_eq!(pu.ice_power(REV_LIMIT + 1.0), 0.0);
+    }
+
+    #[test]
+    fn test_gear_selection_respects_rev_limit() {
+        let pu = PowerUnit::default();
+
+        for kmh in [60.0f32, 120.0, 200.0, 280.0, 340.0] {
+            let selection = pu.select_gear(kmh / 3.6).unwrap();
+            assert!(selection.rpm <= REV_LIMIT);
+        }
+
+        // Higher speeds need higher gears
+        let slow = pu.select_gear(80.0 / 3.6).unwrap();
+        let fast = pu.select_gear(300.0 / 3.6).unwrap();
+        assert!(fast.gear > slow.gear);
+
+        // Beyond the top-gear rev limit there is no drive
+        let limit = pu.rev_limited_speed();
+        assert!(limit * 3.6 > 330.0 && limit * 3.6 < 380.0);
+        assert!(pu.select_gear(limit + 1.0).is_none());
+        assert_eq!(pu.wheel_power(limit + 1.0, true), 0.0);
+    }
+
+    #[test]
+    fn test_engine_rpm_clamps_gear() {
+        let pu = PowerUnit::default();
+        let speed = 100.0 / 3.6;
+        let top_gear = pu.gear_ratios.len() as u8;
+
+        assert_eq!(pu.engine_rpm(speed, 0), pu.engine_rpm(speed, 1));
+        assert_eq!(pu.engine_rpm(speed, top_gear + 1), pu.engine_rpm(speed, top_gear));
+        assert_eq!(pu.engine_rpm(speed, u8::MAX), pu.engine_rpm(speed, top_gear));
+
+        let no_gears = PowerUnit { gear_ratios: vec![], ..PowerUnit::default() };
+        assert_eq!(no_gears.engine_rpm(speed, 1), 0.0);
+    }
+
+    #[test]
+    fn test_mgu_k_deployment() {
+        let mut pu = PowerUnit::default();
+        let speed = 250.0 / 3.6;
+        let with_ers = pu.wheel_power(speed, true);
+        let without_ers = pu.wheel_power(speed, false);
+        assert!((with_ers - without_ers - MGU_K_MAX_POWER * pu.drivetrain_efficiency).abs() < 1.0);
+
+        pu.ers_mode = ErsMode::None;
+        assert_eq!(pu.wheel_power(speed, true), without_ers);
+    }
+}
@@ -0,0 +1,348 @@
+//! Car setup optimization
+//!
+//! Searches wing angles and ride height against a full circuit model using the
+//! lap simulator, reporting the lap-time-optimal setup, one-at-a-time
+//! sensitivity curves around it, and the top speed / DRS trade-off.
+
+use crate::{CircuitModel, LapSimulator, WingConfig};
+use serde::{Deserialize, Serialize};
+
+/// Inclusive range of values to search for one setup parameter
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ParameterRange {
+    pub min: f32,
+    pub max: f32,
+    pub step: f32,
+}
+
+impl ParameterRange {
+    /// Create a parameter range
+    pub fn new(min: f32, max: f32, step: f32) -> Self {
+        Self { min, max, step }
+    }
+
+    /// All values in the range, including both ends
+    pub fn values(&self) -> Vec<f32> {
+        if self.step <= 0.0 || self.max <= self.min {
+            return vec![self.min];
+        }
+
+        let steps = ((self.max - self.min) / self.step).round() as usize;
+        (0..=steps)
+            .map(|i| (self.min + i as f32 * self.step).min(self.max))
+            .collect()
+    }
+}
+
+/// Setup search space and objective
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetupSearchSpace {
+    /// Front wing angle (degrees)
+    pub front_wing: ParameterRange,
+
+    /// Rear wing angle (degrees)
+    pub rear_wing: ParameterRange,
+
+    /// Ride height (mm)
+    pub ride_height: ParameterRange,
+
+    /// Share of laps run with DRS open in the zones (0.0 = race, 1.0 = qualifying)
+    pub drs_usage: f32,
+
+    /// Minimum top speed without DRS (km/h), e.g. to defend on long straights
+    pub min_top_speed: Option<f32>,
+}
+
+impl Default for SetupSearchSpace {
+    fn default() -> Self {
+        Self {
+            front_wing: ParameterRange::new(0.0, 20.0, 2.5),
+            rear_wing: ParameterRange::new(0.0, 25.0, 2.5),
+            ride_height: ParameterRange::new(75.0, 125.0, 12.5),
+            drs_usage: 0.0,
+            min_top_speed: None,
+        }
+    }
+}
+
+/// Simulated performance of one setup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetupEvaluation {
+    /// Setup evaluated
+    pub wing_config: WingConfig,
+
+    /// Lap time without DRS (s)
+    pub lap_time: f32,
+
+    /// Lap time with DRS open in every zone (s)
+    pub drs_lap_time: f32,
+
+    /// Top speed without DRS (km/h)
+    pub top_speed: f32,
+
+    /// Top speed with DRS open (km/h)
+    pub drs_top_speed: f32,
+
+    /// Objective value (s, lower is better)
+    pub score: f32,
+}
+
+impl SetupEvaluation {
+    /// Lap time gained by opening DRS (s)
+    pub fn drs_gain(&self) -> f32 {
+        self.lap_time - self.drs_lap_time
+    }
+}
+
+/// One point of a sensitivity curve
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SensitivityPoint {
+    /// Parameter value
+    pub value: f32,
+
+    /// Lap time without DRS (s)
+    pub lap_time: f32,
+
+    /// Top speed without DRS (km/h)
+    pub top_speed: f32,
+
+    /// Lap time gained by opening DRS (s)
+    pub drs_gain: f32,
+}
+
+/// Lap time response to each parameter, holding the others at the optimum
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SetupSensitivity {
+    pub front_wing: Vec<SensitivityPoint>,
+    pub rear_wing: Vec<SensitivityPoint>,
+    pub ride_height: Vec<SensitivityPoint>,
+}
+
+/// Result of a setup search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetupOptimizationResult {
+    /// Circuit the setup was optimized for
+    pub circuit_id: String,
+
+    /// Best setup found
+    pub best: SetupEvaluation,
+
+    /// Sensitivity curves around the best setup
+    pub sensitivity: SetupSensitivity,
+
+    /// Number of setups evaluated
+    pub evaluations: usize,
+}
+
+/// Grid search over wing angles and ride height
+#[derive(Debug, Clone)]
+pub struct SetupOptimizer {
+    /// Lap simulator (its wing configuration is replaced per candidate)
+    pub simulator: LapSimulator,
+
+    /// Search space and objective
+    pub search: SetupSearchSpace,
+}
+
+impl Default for SetupOptimizer {
+    fn default() -> Self {
+        Self::new(LapSimulator::default(), SetupSearchSpace::default())
+    }
+}
+
+impl SetupOptimizer {
+    /// Create a setup optimizer
+    pub fn new(simulator: LapSimulator, search: SetupSearchSpace) -> Self {
+        Self { simulator, search }
+    }
+
+    /// Simulate one setup on `circuit`
+    pub fn evaluate(&self, circuit: &CircuitModel, wing_config: WingConfig) -> SetupEvaluation {
+        let mut simulator = self.simulator.clone();
+        simulator.vehicle.wing_config = wing_config.clone();
+
+        let closed = simulator.simulate(circuit);
+        let open = simulator.simulate_with_drs(circuit);
+        let drs_usage = self.search.drs_usage.clamp(0.0, 1.0);
+
+        SetupEvaluation {
+            wing_config,
+            lap_time: closed.lap_time,
+            drs_lap_time: open.lap_time,
+            top_speed: closed.top_speed(),
+            drs_top_speed: open.top_speed(),
+            score: closed.lap_time * (1.0 - drs_usage) + open.lap_time * drs_usage,
+        }
+    }
+
+    /// Find the best setup for `circuit`
+    ///
+    /// Returns `None` if no setup in the search space reaches `min_top_speed`.
+    pub fn optimize(&self, circuit: &CircuitModel) -> Option<SetupOptimizationResult> {
+        let mut best: Option<SetupEvaluation> = None;
+        let mut evaluations = 0;
+
+        for front in self.search.front_wing.values() {
+            for rear in self.search.rear_wing.values() {
+                for height in self.search.ride_height.values() {
+                    let evaluation = self.evaluate(circuit, WingConfig::new(front, rear, height));
+                    evaluations += 1;
+
+                    if !self.meets_constraints(&evaluation) {
+                        continue;
+                    }
+                    if best.as_ref().map_or(true, |b| evaluation.score < b.score) {
+                        best = Some(evaluation);
+                    }
+                }
+            }
+        }
+
+        let best = best?;
+        let sensitivity = self.sensitivity(circuit, &best.wing_config);
+
+        Some(SetupOptimizationResult {
+            circuit_id: circuit.id.clone(),
+            best,
+            sensitivity,
+            evaluations,
+        })
+    }
+
+    /// Sweep each parameter across its range, holding the others at `center`
+    pub fn sensitivity(&self, circuit: &CircuitModel, center: &WingConfig) -> SetupSensitivity {
+        let sweep = |range: &ParameterRange, make: &dyn Fn(f32) -> WingConfig| {
+            range
+                .values()
+                .into_iter()
+                .map(|value| {
+                    let evaluation = self.evaluate(circuit, make(value));
+                    SensitivityPoint {
+                        value,
+                        lap_time: evaluation.lap_time,
+                        top_speed: evaluation.top_speed,
+                        drs_gain: evaluation.drs_gain(),
+                    }
+                })
+                .collect()
+        };
+
+        SetupSensitivity {
+            front_wing: sweep(&self.search.front_wing, &|v| {
+                WingConfig::new(v, center.rear_wing_angle, center.ride_height)
+            }),
+            rear_wing: sweep(&self.search.rear_wing, &|v| {
+                WingConfig::new(center.front_wing_angle, v, center.ride_height)
+            }),
+            ride_height: sweep(&self.search.ride_height, &|v| {
+                WingConfig::new(center.front_wing_angle, center.rear_wing_angle, v)
+            }),
+        }
+    }
+
+    fn meets_constraints(&self, evaluation: &SetupEvaluation) -> bool {
+        self.search
+            .min_top_speed
+            .map_or(true, |min| evaluation.top_speed >= min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coarse_search() -> SetupSearchSpace {
+        SetupSearchSpace {
+            front_wing: ParameterRange::new(0.0, 20.0, 5.0),
+            rear_wing: ParameterRange::new(0.0, 25.0, 5.0),
+            ride_height: ParameterRange::new(75.0, 125.0, 25.0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parameter_range_values() {
+        let values = ParameterRange::new(75.0, 125.0, 12.5).values();
+        assert_eq!(values, vec![75.0, 87.5, 100.0, 112.5, 125.0]);
+        assert_eq!(ParameterRange::new(10.0, 10.0, 1.0).values(), vec![10.0]);
+    }
+
+    #[test]
+    fn test_optimum_beats_every_sensitivity_point() {
+        let optimizer = SetupOptimizer::new(LapSimulator::default(), coarse_search());
+        let circuit = CircuitModel::monaco();
+        let result = optimizer.optimize(&circuit).unwrap();
+
+        assert_eq!(result.evaluations, 5 * 6 * 3);
+        let curves = [
+            &result.sensitivity.front_wing,
+            &result.sensitivity.rear_wing,
+            &result.sensitivity.ride_height,
+        ];
+        for curve in curves {
+            assert!(!curve.is_empty());
+            for point in curve {
+                assert!(point.lap_time >= result.best.lap_time - 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_wing_trades_top_speed_for_lap_time() {
+        let optimizer = SetupOptimizer::new(LapSimulator::default(), coarse_search());
+        let circuit = CircuitModel::monza();
+        let low = optimizer.evaluate(&circuit, WingConfig::new(0.0, 0.0, 100.0));
+        let high = optimizer.evaluate(&circuit, WingConfig::new(20.0, 25.0, 100.0));
+
+        assert!(low.top_speed > high.top_speed + 30.0);
+        assert!(low.drs_gain() > 0.0 && high.drs_gain() > 0.0);
+    }
+
+    #[test]
+    fn test_monza_more_drag_sensitive_than_monaco() {
+        let optimizer = SetupOptimizer::new(LapSimulator::default(), coarse_search());
+        let relative_wing_gain = |circuit: &CircuitModel| {
+            let low = optimizer.evaluate(circuit, WingConfig::new(0.0, 0.0, 100.0));
+            let high = optimizer.evaluate(circuit, WingConfig::new(20.0, 25.0, 100.0));
+            (low.lap_time - high.lap_time) / low.lap_time
+        };
+
+        let monza = CircuitModel::monza();
+        let monaco = CircuitModel::monaco();
+        assert!(relative_wing_gain(&monza) < relative_wing_gain(&monaco));
+
+        // Longer DRS zones make DRS worth more at Monza
+        let setup = WingConfig::new(10.0, 10.0, 100.0);
+        assert!(
+            optimizer.evaluate(&monza, setup.clone()).drs_gain()
+                > optimizer.evaluate(&monaco, setup).drs_gain()
+        );
+    }
+
+    #[test]
+    fn test_min_top_speed_constraint() {
+        let circuit = CircuitModel::monza();
+        let unconstrained = SetupOptimizer::new(LapSimulator::default(), coarse_search())
+            .optimize(&circuit)
+            .unwrap();
+
+        let search = SetupSearchSpace {
+            min_top_speed: Some(unconstrained.best.top_speed + 20.0),
+            ..coarse_search()
+        };
+        let constrained = SetupOptimizer::new(LapSimulator::default(), search)
+            .optimize(&circuit)
+            .unwrap();
+
+        assert!(constrained.best.top_speed >= unconstrained.best.top_speed + 20.0);
+        assert!(constrained.best.lap_time >= unconstrained.best.lap_time);
+
+        let impossible = SetupSearchSpace {
+            min_top_speed: Some(500.0),
+            ..coarse_search()
+        };
+        assert!(SetupOptimizer::new(LapSimulator::default(), impossible)
+            .optimize(&circuit)
+            .is_none());
+    }
+}
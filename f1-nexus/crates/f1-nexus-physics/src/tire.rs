@@ -0,0 +1,190 @@
+//! Tire force model
+//!
+//! Replaces a single grip coefficient with a friction model that depends on
+//! the compound, normal load (load sensitivity), tire temperature and wear.
+//! Longitudinal and lateral forces share the available grip through a
+//! friction ellipse.
+
+use f1_nexus_core::tire::{TireCharacteristics, TireCompound};
+use serde::{Deserialize, Serialize};
+
+/// Peak friction coefficient of the reference compound at the reference load
+pub const REFERENCE_PEAK_FRICTION: f32 = 1.8;
+
+/// Grip level of the reference compound (C3)
+pub const REFERENCE_GRIP_LEVEL: f32 = 0.85;
+
+/// Normal load per tire at which peak friction is quoted (N)
+pub const REFERENCE_TIRE_LOAD: f32 = 2500.0;
+
+/// Fractional friction loss per unit of relative load increase
+pub const LOAD_SENSITIVITY: f32 = 0.1;
+
+/// Friction lost on a fully worn tire (fraction of peak)
+pub const MAX_WEAR_GRIP_LOSS: f32 = 0.15;
+
+/// Number of tires sharing the car's normal load
+const TIRE_COUNT: f32 = 4.0;
+
+/// Tire friction as a function of load, temperature and wear
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TireForceModel {
+    /// Compound characteristics
+    pub characteristics: TireCharacteristics,
+
+    /// Peak friction coefficient at the reference load, new and in its window
+    pub peak_friction: f32,
+
+    /// Normal load per tire at which `peak_friction` applies (N)
+    pub reference_load: f32,
+
+    /// Fractional friction loss per unit of relative load increase
+    pub load_sensitivity: f32,
+
+    /// Tire surface temperature (°C)
+    pub temperature: f32,
+
+    /// Tire wear (0.0 = new, 1.0 = fully worn)
+    pub wear: f32,
+}
+
+impl Default for TireForceModel {
+    fn default() -> Self {
+        Self::for_compound(TireCompound::C3)
+    }
+}
+
+impl TireForceModel {
+    /// New tire of `compound` at the middle of its operating window
+    ///
+    /// Peak friction scales with the compound's grip level relative to C3.
+    pub fn for_compound(compound: TireCompound) -> Self {
+        let characteristics = TireCharacteristics::for_compound(compound);
+        let (min_temp, max_temp) = characteristics.optimal_temp_range;
+        Self {
+            peak_friction: REFERENCE_PEAK_FRICTION * characteristics.grip_level / REFERENCE_GRIP_LEVEL,
+            reference_load: REFERENCE_TIRE_LOAD,
+            load_sensitivity: LOAD_SENSITIVITY,
+            temperature: 0.5 * (min_temp + max_temp),
+            wear: 0.0,
+            characteristics,
+        }
+    }
+
+    /// Set the tire temperature (°C)
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
+    /// Set the tire wear (0.0-1.0)
+    pub fn with_wear(mut self, wear: f32) -> Self {
+        self.wear = wear.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Tire compound
+    pub fn compound(&self) -> TireCompound {
+        self.characteristics.compound
+    }
+
+    /// Friction multiplier from tire temperature
+    pub fn temperature_factor(&self) -> f32 {
+        self.characteristics.grip_multiplier_for_temp(self.temperature)
+    }
+
+    /// Friction multiplier from wear
+    pub fn wear_factor(&self) -> f32 {
+        1.0 - MAX_WEAR_GRIP_LOSS * self.wear.clamp(0.0, 1.0)
+    }
+
+    /// Friction multiplier from load per tire
+    ///
+    /// Friction falls as load rises: μ = μ₀ × (1 - k × (Fz / Fz₀ - 1)).
+    pub fn load_factor(&self, tire_load: f32) -> f32 {
+        let relative = tire_load.max(0.0) / self.reference_load - 1.0;
+        (1.0 - self.load_sensitivity * relative).clamp(0.5, 1.3)
+    }
+
+    /// Effective friction coefficient with the car's total normal load (N)
+    pub fn friction_coefficient(&self, normal_load: f32) -> f32 {
+        self.peak_friction
+            * self.temperature_factor()
+            * self.wear_factor()
+            * self.load_factor(normal_load / TIRE_COUNT)
+    }
+
+    /// Maximum tire force in any single direction for a total normal load (N)
+    pub fn max_force(&self, normal_load: f32) -> f32 {
+        self.friction_coefficient(normal_load) * normal_load.max(0.0)
+    }
+
+    /// Longitudinal force still available while generating `lateral_force` (N)
+    ///
+    /// Friction ellipse: (Fx / F_max)² + (Fy / F_max)² ≤ 1.
+    pub fn longitudinal_limit(&self, normal_load: f32, lateral_force: f32) -> f32 {
+        let max_force = self.max_force(normal_load);
+        if max_force <= 0.0 {
+            return 0.0;
+        }
+        let usage = (lateral_force.abs() / max_force).min(1.0);
+        max_force * (1.0 - usage * usage).sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_softer_compounds_grip_more() {
+        let hard = TireForceModel::for_compound(TireCompound::C1);
+        let medium = TireForceModel::default();
+        let soft = TireForceModel::for_compound(TireCompound::C5);
+        let load = 10_000.0;
+
+        assert_eq!(medium.peak_friction, REFERENCE_PEAK_FRICTION);
+        assert!(soft.friction_coefficient(load) > medium.friction_coefficient(load));
+        assert!(medium.friction_coefficient(load) > hard.friction_coefficient(load));
+    }
+
+    #[test]
+    fn test_load_sensitivity() {
+        let tire = TireForceModel::default();
+        let light = tire.friction_coefficient(8_000.0);
+        let heavy = tire.friction_coefficient(16_000.0);
+
+        // Friction coefficient falls with load, but total force still rises
+        assert!(heavy < light);
+        assert!(tire.max_force(16_000.0) > tire.max_force(8_000.0));
+    }
+
+    #[test]
+    fn test_temperature_and_wear_reduce_grip() {
+        let tire = TireForceModel::default();
+        let load = 10_000.0;
+        let cold = tire.clone().with_temperature(60.0);
+        let overheated = tire.clone().with_temperature(135.0);
+        let worn = tire.clone().with_wear(0.8);
+
+        assert_eq!(tire.temperature_factor(), 1.0);
+        assert!(cold.max_force(load) < tire.max_force(load));
+        assert!(overheated.max_force(load) < tire.max_force(load));
+        assert!(worn.max_force(load) < tire.max_force(load));
+        assert_eq!(tire.clone().with_wear(2.0).wear, 1.0);
+    }
+
+    #[test]
+    fn test_friction_ellipse() {
+        let tire = TireForceModel::default();
+        let load = 10_000.0;
+        let max = tire.max_force(load);
+
+        assert_eq!(tire.longitudinal_limit(load, 0.0), max);
+        assert_eq!(tire.longitudinal_limit(load, max * 1.5), 0.0);
+
+        let lateral = 0.6 * max;
+        let longitudinal = tire.longitudinal_limit(load, lateral);
+        assert!((longitudinal - 0.8 * max).abs() < 1.0);
+    }
+}
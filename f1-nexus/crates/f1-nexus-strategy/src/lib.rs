@@ -9,12 +9,16 @@ pub mod simulation;
 use f1_nexus_core::{
     Circuit, FuelConsumptionModel, LapNumber, PitStop, PitStopReason, RaceStrategy,
     StintNumber, TireCharacteristics, TireCompound, DegradationFactors,
-    FuelStrategy, ErsDeploymentPlan, StrategyMetadata, TrackCharacteristics,
+    FuelStrategy, ErsDeploymentPlan, StrategyMetadata, TrackCondition, TrackDryingModel,
 };
 use f1_nexus_core::strategy::ErsMode;
+use simulation::{overtake_probability, tire_time_loss, CompoundSwitchRecommendation, WeatherConditions};
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap};
 
+/// Pass probability at which a tire offset on a rival counts as an overtaking opportunity
+const OVERTAKE_OPPORTUNITY_PROBABILITY: f32 = 0.5;
+
 /// Pit stop optimization configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OptimizationConfig {
@@ -333,6 +337,78 @@ pub fn calculate_pit_window(
     }
 }
 
+/// Find the best lap to switch between slicks and wet-weather tires
+///
+/// Projects track wetness from `weather` and compares staying out on
+/// `current_compound` against pitting at the end of each remaining lap for the
+/// compound that is fastest on the following lap. Returns `None` when staying
+/// out is quicker over the rest of the race.
+pub fn optimize_crossover_lap(
+    config: &OptimizationConfig,
+    weather: &WeatherConditions,
+    current_compound: TireCompound,
+    tire_age: u16,
+    current_lap: u16,
+) -> Option<CompoundSwitchRecommendation> {
+    if current_lap == 0 || current_lap >= config.total_laps {
+        return None;
+    }
+
+    let drying_model = TrackDryingModel::default_model();
+    let nominal_lap_time = config.circuit.lap_record * 1.03;
+    let wetness = weather.wetness_profile(&drying_model, config.total_laps, nominal_lap_time);
+    let slick = config
+        .available_compounds
+        .iter()
+        .copied()
+        .find(|c| c.is_slick())
+        .unwrap_or(TireCompound::C3);
+
+    let stint_time = |compound: TireCompound, age: u16, from: u16, to: u16| -> f32 {
+        (from..=to)
+            .map(|lap| {
+                let stint_age = age + (lap - from) + 1;
+                calculate_wet_lap_time(compound, stint_age, config, lap, wetness[lap as usize - 1])
+            })
+            .sum()
+    };
+
+    let stay_out = stint_time(current_compound, tire_age, current_lap, config.total_laps);
+    let mut best: Option<(f32, CompoundSwitchRecommendation)> = None;
+
+    for pit_lap in current_lap..config.total_laps {
+        let next_wetness = wetness[pit_lap as usize];
+        let new_compound = TireCharacteristics::best_compound_for_wetness(next_wetness, slick);
+        if new_compound == current_compound {
+            continue;
+        }
+
+        let total = stint_time(current_compound, tire_age, current_lap, pit_lap)
+            + estimate_time_loss(config, pit_lap)
+            + stint_time(new_compound, 0, pit_lap + 1, config.total_laps);
+
+        if total < stay_out && best.as_ref().map_or(true, |(t, _)| total < *t) {
+            let lap_time_gain = nominal_lap_time
+                * (TireCharacteristics::for_compound(current_compound).wet_pace_factor(next_wetness)
+                    - TireCharacteristics::for_compound(new_compound).wet_pace_factor(next_wetness));
+
+            best = Some((
+                total,
+                CompoundSwitchRecommendation {
+                    lap: LapNumber(pit_lap),
+                    from_compound: current_compound,
+                    to_compound: new_compound,
+                    track_wetness: wetness[pit_lap as usize - 1],
+                    track_condition: TrackCondition::from_wetness(wetness[pit_lap as usize - 1]),
+                    lap_time_gain,
+                },
+            ));
+        }
+    }
+
+    best.map(|(_, recommendation)| recommendation)
+}
+
 /// Estimate time loss for a pit stop on a given lap
 pub fn estimate_time_loss(config: &OptimizationConfig, lap: u16) -> f32 {
     // Base pit loss = pit lane time + tire change time
@@ -604,7 +680,7 @@ fn calculate_lap_time(
 
     // Fuel load impact
     let fuel_remaining = config.fuel_model.fuel_needed_for_laps(
-        (config.total_laps - lap) as u16,
+        config.total_laps - lap,
         config.starting_fuel,
     );
     let fuel_penalty = (fuel_remaining / config.starting_fuel) * 0.3; // Up to 0.3s
@@ -615,6 +691,20 @@ fn calculate_lap_time(
     base_time + wear_penalty + fuel_penalty - grip_bonus
 }
 
+fn calculate_wet_lap_time(
+    compound: TireCompound,
+    tire_age: u16,
+    config: &OptimizationConfig,
+    lap: u16,
+    track_wetness: f32,
+) -> f32 {
+    let tire_chars = TireCharacteristics::for_compound(compound);
+    let base_time = config.circuit.lap_record * 1.03;
+
+    calculate_lap_time(compound, tire_age, config, lap)
+        + base_time * (tire_chars.wet_pace_factor(track_wetness) - 1.0)
+}
+
 fn update_dp_state(
     dp: &mut HashMap<(u16, u8, TireCompound), DPState>,
     key: (u16, u8, TireCompound),
@@ -767,12 +857,22 @@ fn count_overtaking_opportunities(strategy: &RaceStrategy, config: &Optimization
         }
     }
 
-    // Tire advantage opportunities (fresher tires than competitors)
+    // Tire advantage opportunities (fresher tires than competitors), counted
+    // when the offset, with DRS, makes the pass more likely than not
     for competitor in &config.competitors_ahead {
         if let Some(comp_pit_lap) = competitor.estimated_pit_lap {
+            let rival_tires = TireCharacteristics::for_compound(competitor.current_compound);
             for pit_stop in &strategy.pit_stops {
                 if pit_stop.lap.0 > comp_pit_lap && pit_stop.lap.0 - comp_pit_lap < 10 {
-                    opportunities += 1;
+                    let tire_offset = pit_stop.lap.0 - comp_pit_lap;
+                    let own_tires = TireCharacteristics::for_compound(pit_stop.compound);
+                    let pace_advantage =
+                        tire_time_loss(&rival_tires, tire_offset as f32) - tire_time_loss(&own_tires, 0.0);
+                    let probability =
+                        overtake_probability(&config.circuit, competitor.gap_seconds, pace_advantage);
+                    if probability >= OVERTAKE_OPPORTUNITY_PROBABILITY {
+                        opportunities += 1;
+                    }
                 }
             }
         }
@@ -784,6 +884,7 @@ fn count_overtaking_opportunities(strategy: &RaceStrategy, config: &Optimization
 #[cfg(test)]
 mod tests {
     use super::*;
+    use f1_nexus_core::{TrackCharacteristics, WeatherCondition};
 
     fn create_test_config() -> OptimizationConfig {
         OptimizationConfig {
@@ -931,6 +1032,71 @@ mod tests {
         assert!(comparison.breakdown.pit_loss_difference == 0.0); // Same pit loss
     }
 
+    #[test]
+    fn test_overtaking_opportunities_use_drs_gain() {
+        let mut config = create_test_config();
+        config.circuit = Circuit::spa();
+        config.competitors_ahead = vec![CompetitorState {
+            position: 4,
+            current_lap: 12,
+            current_compound: TireCompound::C4,
+            tire_age: 12,
+            estimated_pit_lap: Some(16),
+            gap_seconds: 0.3,
+        }];
+
+        // Stopping nine laps after the rival for fresh softs
+        let strategy = RaceStrategy {
+            id: "late-stop".to_string(),
+            starting_compound: TireCompound::C3,
+            pit_stops: vec![PitStop {
+                lap: LapNumber(25),
+                compound: TireCompound::C5,
+                pit_loss: 20.5,
+                reason: PitStopReason::Mandatory,
+                confidence: 0.9,
+            }],
+            fuel_strategy: FuelStrategy {
+                starting_fuel: 110.0,
+                fuel_saving_per_lap: 0.0,
+                fuel_saving_laps: vec![],
+                minimum_buffer: 1.0,
+            },
+            ers_plan: ErsDeploymentPlan {
+                default_mode: ErsMode::Medium,
+                lap_overrides: BTreeMap::new(),
+                overtake_laps: vec![],
+            },
+            expected_lap_times: BTreeMap::new(),
+            predicted_race_time: 5400.0,
+            confidence: 0.85,
+            metadata: StrategyMetadata {
+                generated_at: chrono::Utc::now(),
+                num_simulations: 1000,
+                contributing_agents: vec!["test".to_string()],
+                version_hash: None,
+                parent_strategy_id: None,
+            },
+        };
+
+        // The tire offset alone is not enough to pass at Spa
+        assert_eq!(count_overtaking_opportunities(&strategy, &config), 0);
+
+        // With a DRS zone it is
+        config.circuit.drs_zones = vec![f1_nexus_core::DrsZone {
+            zone_id: 1,
+            detection_point: 5800.0,
+            activation_point: 6000.0,
+            end_point: 300.0,
+            expected_time_gain: 0.35,
+        }];
+        assert_eq!(count_overtaking_opportunities(&strategy, &config), 1);
+
+        // Out of DRS range it is not
+        config.competitors_ahead[0].gap_seconds = 1.5;
+        assert_eq!(count_overtaking_opportunities(&strategy, &config), 0);
+    }
+
     #[test]
     fn test_tire_age_calculation() {
         let pit_stops = vec![
@@ -1133,8 +1299,58 @@ mod tests {
         );
 
         // Both should return valid scores between 0 and 1
-        assert!(score_c5 >= 0.0 && score_c5 <= 1.0);
-        assert!(score_c1 >= 0.0 && score_c1 <= 1.0);
+        assert!((0.0..=1.0).contains(&score_c5));
+        assert!((0.0..=1.0).contains(&score_c1));
     }
-}
 
+    #[test]
+    fn test_optimize_crossover_lap_rain() {
+        let config = create_test_config();
+        let weather = WeatherConditions {
+            initial_condition: WeatherCondition::Dry,
+            track_temperature: 25.0,
+            air_temperature: 20.0,
+            changes: vec![(LapNumber(20), WeatherCondition::HeavyRain, 16.0)],
+            sector_changes: vec![],
+        };
+
+        let rec = optimize_crossover_lap(&config, &weather, TireCompound::C3, 10, 15)
+            .expect("should recommend switching to wet-weather tires");
+
+        assert!(!rec.to_compound.is_slick());
+        assert!(rec.lap.0 >= 19 && rec.lap.0 < 30);
+        assert!(rec.lap_time_gain > 0.0);
+    }
+
+    #[test]
+    fn test_optimize_crossover_lap_drying() {
+        let config = create_test_config();
+        let weather = WeatherConditions {
+            initial_condition: WeatherCondition::HeavyRain,
+            track_temperature: 20.0,
+            air_temperature: 16.0,
+            changes: vec![(LapNumber(5), WeatherCondition::Dry, 30.0)],
+            sector_changes: vec![],
+        };
+
+        let rec = optimize_crossover_lap(&config, &weather, TireCompound::Wet, 4, 5)
+            .expect("should recommend leaving full wets on a drying track");
+
+        assert_eq!(rec.from_compound, TireCompound::Wet);
+        assert_ne!(rec.to_compound, TireCompound::Wet);
+    }
+
+    #[test]
+    fn test_optimize_crossover_lap_dry_race() {
+        let config = create_test_config();
+        let weather = WeatherConditions {
+            initial_condition: WeatherCondition::Dry,
+            track_temperature: 30.0,
+            air_temperature: 25.0,
+            changes: vec![],
+            sector_changes: vec![],
+        };
+
+        assert!(optimize_crossover_lap(&config, &weather, TireCompound::C3, 5, 10).is_none());
+    }
+}
@@ -6,15 +6,46 @@
 //! - Fuel consumption and weight effects
 //! - Pit stop execution and time loss
 //! - Weather condition changes
+//! - Track wetness and wet/dry compound crossover
+//! - Per-sector microclimate weather
+//! - Air density effects on downforce, engine power and tire wear
 //! - Strategy validation and warnings
 
 use f1_nexus_core::{
-    Circuit, FuelConsumptionModel, LapNumber, RaceStrategy,
-    TireCharacteristics, TireCompound, DegradationFactors,
-    WeatherForecast, WeatherCondition,
+    Circuit, FuelConsumptionModel, LapNumber, RaceStrategy, Sector, SectorInfo,
+    SectorWeather, TireCharacteristics, TireCompound, DegradationFactors,
+    TrackCondition, TrackDryingModel, WeatherForecast, WeatherCondition, GRID_SIZE,
 };
+use f1_nexus_physics::Atmosphere;
 use serde::{Deserialize, Serialize};
 
+/// Pace loss (fraction of lap time) beyond which a compound is flagged as wrong
+const WRONG_TIRE_PACE_LOSS: f32 = 0.03;
+
+/// Gap to the car ahead within which DRS may be used (seconds)
+pub const DRS_DETECTION_GAP: f32 = 1.0;
+
+/// Time advantage needed to complete a pass on an easy circuit (seconds)
+const OVERTAKE_MARGIN: f32 = 0.3;
+
+/// Lap time sensitivity to reported sector grip (fraction per unit of grip lost)
+const SECTOR_GRIP_SENSITIVITY: f32 = 0.05;
+
+/// Lap time lost per unit of relative air density lost at a maximum-downforce circuit
+const DENSITY_PACE_SENSITIVITY: f32 = 0.02;
+
+/// Lap time lost per unit of engine power lost at a minimum-downforce circuit
+const POWER_PACE_SENSITIVITY: f32 = 0.06;
+
+/// Extra tire wear per unit of relative air density lost (less downforce, more sliding)
+const DENSITY_WEAR_SENSITIVITY: f32 = 0.5;
+
+/// Air temperature change per °C of track temperature change during a race
+const AIR_TRACK_TEMP_COUPLING: f32 = 0.5;
+
+/// Relative humidity while it is raining
+const RAIN_HUMIDITY: f32 = 0.95;
+
 /// Race simulator for lap-by-lap prediction
 #[derive(Debug, Clone)]
 pub struct RaceSimulator {
@@ -29,6 +60,13 @@ pub struct RaceSimulator {
 
     /// Weather conditions (initial and forecasted changes)
     pub weather: WeatherConditions,
+
+    /// Track wetness evolution model
+    pub drying_model: TrackDryingModel,
+
+    /// Ambient air at the start of the race; `None` keeps the standard
+    /// atmosphere throughout
+    pub atmosphere: Option<Atmosphere>,
 }
 
 /// Weather conditions for simulation
@@ -45,6 +83,10 @@ pub struct WeatherConditions {
 
     /// Weather changes during race (lap number -> new condition)
     pub changes: Vec<(LapNumber, WeatherCondition, f32)>, // (lap, condition, track_temp)
+
+    /// Sector microclimate changes (lap number -> per-sector conditions)
+    #[serde(default)]
+    pub sector_changes: Vec<(LapNumber, Vec<SectorWeather>)>,
 }
 
 impl WeatherConditions {
@@ -55,6 +97,11 @@ impl WeatherConditions {
             track_temperature: forecast.track_temperature,
             air_temperature: forecast.air_temperature,
             changes: vec![],
+            sector_changes: if forecast.sector_conditions.is_empty() {
+                vec![]
+            } else {
+                vec![(LapNumber(1), forecast.sector_conditions.clone())]
+            },
         }
     }
 
@@ -77,6 +124,85 @@ impl WeatherConditions {
             .map(|(_, _, temp)| *temp)
             .unwrap_or(self.track_temperature)
     }
+
+    /// Get the weather in one sector for a specific lap
+    ///
+    /// Uses the most recent sector microclimate change unless a later lap-level
+    /// change has superseded it; otherwise the lap-level weather applies to the
+    /// whole track.
+    pub fn sector_weather_at_lap(&self, lap: LapNumber, sector: Sector) -> SectorWeather {
+        let last_lap_change = self
+            .changes
+            .iter()
+            .rev()
+            .find(|(change_lap, _, _)| change_lap.0 <= lap.0)
+            .map(|(change_lap, _, _)| change_lap.0)
+            .unwrap_or(0);
+
+        self.sector_changes
+            .iter()
+            .rev()
+            .find(|(change_lap, _)| change_lap.0 <= lap.0)
+            .filter(|(change_lap, _)| change_lap.0 >= last_lap_change)
+            .and_then(|(_, sectors)| sectors.iter().find(|s| s.sector == sector))
+            .cloned()
+            .unwrap_or_else(|| {
+                let condition = self.condition_at_lap(lap);
+                SectorWeather {
+                    sector,
+                    condition,
+                    rain_intensity: condition.typical_rainfall_intensity(),
+                    track_temp: self.track_temp_at_lap(lap),
+                    grip_level: 1.0,
+                }
+            })
+    }
+
+    /// Get rainfall intensity for a specific lap (mm/hour)
+    pub fn rainfall_at_lap(&self, lap: LapNumber) -> f32 {
+        self.condition_at_lap(lap).typical_rainfall_intensity()
+    }
+
+    /// Track wetness at the start of the race
+    ///
+    /// A race starting in the rain begins at the equilibrium wetness for that
+    /// rainfall; otherwise the track starts dry.
+    pub fn initial_wetness(&self, model: &TrackDryingModel, car_passes_per_minute: f32) -> f32 {
+        model.equilibrium_wetness(
+            self.initial_condition.typical_rainfall_intensity(),
+            self.track_temperature,
+            car_passes_per_minute,
+        )
+    }
+
+    /// Project track wetness at the start of each lap
+    ///
+    /// Assumes a constant `lap_time` (seconds) and a full field on track.
+    pub fn wetness_profile(
+        &self,
+        model: &TrackDryingModel,
+        total_laps: u16,
+        lap_time: f32,
+    ) -> Vec<f32> {
+        let minutes_per_lap = lap_time / 60.0;
+        let car_passes = GRID_SIZE as f32;
+        let mut wetness = self.initial_wetness(model, car_passes / minutes_per_lap);
+        let mut profile = Vec::with_capacity(total_laps as usize);
+
+        for lap in 1..=total_laps {
+            profile.push(wetness);
+            let lap_number = LapNumber(lap);
+            wetness = model.step(
+                wetness,
+                self.rainfall_at_lap(lap_number),
+                self.track_temp_at_lap(lap_number),
+                car_passes,
+                minutes_per_lap,
+            );
+        }
+
+        profile
+    }
 }
 
 /// Complete simulation result
@@ -97,6 +223,16 @@ pub struct SimulationResult {
     /// Fuel remaining at end of each lap (kg)
     pub fuel_history: Vec<f32>,
 
+    /// Sector times for each lap (seconds, in circuit sector order)
+    pub sector_times: Vec<Vec<f32>>,
+
+    /// Track wetness at end of each lap, averaged over the lap distance
+    /// (0.0 = dry, 1.0 = standing water)
+    pub track_wetness_history: Vec<f32>,
+
+    /// Laps where a different compound became the fastest choice
+    pub compound_switches: Vec<CompoundSwitchRecommendation>,
+
     /// Simulation warnings
     pub warnings: Vec<String>,
 
@@ -135,6 +271,28 @@ pub struct PitStopEvent {
     pub fuel_remaining: f32,
 }
 
+/// Recommendation to switch compound as the track wets or dries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompoundSwitchRecommendation {
+    /// Lap at the end of which to pit
+    pub lap: LapNumber,
+
+    /// Compound currently fitted
+    pub from_compound: TireCompound,
+
+    /// Recommended compound
+    pub to_compound: TireCompound,
+
+    /// Track wetness on that lap (0.0-1.0)
+    pub track_wetness: f32,
+
+    /// Track condition on that lap
+    pub track_condition: TrackCondition,
+
+    /// Lap time advantage of the recommended compound (seconds per lap)
+    pub lap_time_gain: f32,
+}
+
 impl RaceSimulator {
     /// Create a new race simulator
     pub fn new(
@@ -148,23 +306,94 @@ impl RaceSimulator {
             strategy,
             fuel_model,
             weather,
+            drying_model: TrackDryingModel::default_model(),
+            atmosphere: None,
         }
     }
 
+    /// Set the ambient air at the start of the race, e.g.
+    /// `Atmosphere::from_forecast` at the circuit altitude
+    ///
+    /// Without it the simulation uses the standard atmosphere and ignores air
+    /// effects.
+    pub fn set_atmosphere(&mut self, atmosphere: Atmosphere) {
+        self.atmosphere = Some(atmosphere);
+    }
+
+    /// Ambient air on a lap
+    ///
+    /// Follows the race's weather changes from the starting atmosphere: air
+    /// temperature moves with track temperature, and rain saturates the air.
+    pub fn atmosphere_at_lap(&self, lap: LapNumber) -> Atmosphere {
+        let Some(start) = self.atmosphere else {
+            return Atmosphere::standard();
+        };
+
+        let track_temp_change = self.weather.track_temp_at_lap(lap) - self.weather.track_temperature;
+        let humidity = if self.weather.rainfall_at_lap(lap) > 0.0 {
+            start.humidity.max(RAIN_HUMIDITY)
+        } else {
+            start.humidity
+        };
+        Atmosphere::new(
+            start.temperature + AIR_TRACK_TEMP_COUPLING * track_temp_change,
+            start.pressure,
+            humidity,
+            start.altitude,
+        )
+    }
+
+    /// Lap time and tire wear multipliers for the ambient air on a lap
+    ///
+    /// Thin air costs downforce, and with it corner speed and tire life, and
+    /// engine power, and with it straight-line speed. The split follows the
+    /// circuit's downforce level. Both are 1.0 in the standard atmosphere.
+    pub fn atmosphere_factors(&self, lap: LapNumber) -> (f32, f32) {
+        let atmosphere = self.atmosphere_at_lap(lap);
+        let downforce = self.circuit.characteristics.downforce_level.clamp(0.0, 1.0);
+        let density_loss = 1.0 - atmosphere.density_ratio();
+        let power_loss = 1.0 - atmosphere.engine_power_factor();
+
+        let pace = 1.0
+            + DENSITY_PACE_SENSITIVITY * downforce * density_loss
+            + POWER_PACE_SENSITIVITY * (1.0 - downforce) * power_loss;
+        let wear = (1.0 + DENSITY_WEAR_SENSITIVITY * density_loss).max(0.0);
+        (pace, wear)
+    }
+
     /// Simulate the complete race lap-by-lap
     pub fn simulate_race(&self) -> SimulationResult {
         let total_laps = self.circuit.typical_race_laps;
         let mut lap_times = Vec::with_capacity(total_laps as usize);
+        let mut sector_times = Vec::with_capacity(total_laps as usize);
         let mut pit_stop_events = Vec::new();
         let mut tire_history = vec![(LapNumber(1), self.strategy.starting_compound)];
         let mut fuel_history = Vec::with_capacity(total_laps as usize);
+        let mut track_wetness_history = Vec::with_capacity(total_laps as usize);
+        let mut compound_switches: Vec<CompoundSwitchRecommendation> = Vec::new();
         let mut warnings = Vec::new();
 
         // Initialize state
         let mut current_fuel = self.strategy.fuel_strategy.starting_fuel;
         let mut current_compound = self.strategy.starting_compound;
         let mut tire_age = 0u16;
+        let mut tire_wear = 0.0f32;
         let mut total_time = 0.0f32;
+        let car_passes = GRID_SIZE as f32;
+        let sectors = self.circuit.sector_layout();
+        let initial_passes_per_minute = car_passes / (self.base_lap_time() / 60.0);
+        let mut sector_wetness: Vec<f32> = sectors
+            .iter()
+            .map(|info| {
+                let weather = self.weather.sector_weather_at_lap(LapNumber(1), info.sector);
+                self.drying_model.equilibrium_wetness(
+                    weather.rain_intensity,
+                    weather.track_temp,
+                    initial_passes_per_minute,
+                )
+            })
+            .collect();
+        let slick = self.preferred_slick();
 
         // Simulate each lap
         for lap in 1..=total_laps {
@@ -173,18 +402,43 @@ impl RaceSimulator {
             // Check if we're pitting this lap
             let is_pit_lap = self.strategy.pit_stop_on_lap(lap_number).is_some();
 
-            // Calculate lap time BEFORE pit stop
+            // Calculate lap time BEFORE pit stop; tire wear is counted in
+            // standard-atmosphere laps
+            let (pace_factor, wear_factor) = self.atmosphere_factors(lap_number);
             tire_age += 1;
-            let lap_time = self.calculate_lap_time(
-                lap_number,
+            tire_wear += wear_factor;
+            let sector_weather: Vec<SectorWeather> = sectors
+                .iter()
+                .map(|info| self.weather.sector_weather_at_lap(lap_number, info.sector))
+                .collect();
+            let lap_sector_times = self.calculate_sector_times(
                 current_compound,
-                tire_age,
+                tire_wear,
+                pace_factor,
                 current_fuel,
+                &sectors,
+                &sector_weather,
+                &sector_wetness,
             );
+            let lap_time: f32 = lap_sector_times.iter().sum();
 
             lap_times.push(lap_time);
+            sector_times.push(lap_sector_times);
             total_time += lap_time;
 
+            // Evolve each sector's wetness over the lap
+            for (wetness, weather) in sector_wetness.iter_mut().zip(&sector_weather) {
+                *wetness = self.drying_model.step(
+                    *wetness,
+                    weather.rain_intensity,
+                    weather.track_temp,
+                    car_passes,
+                    lap_time / 60.0,
+                );
+            }
+            let track_wetness = distance_weighted(&sectors, &sector_wetness, |w| w);
+            track_wetness_history.push(track_wetness);
+
             // Update fuel consumption
             let fuel_consumed = self.fuel_model.consumption_per_lap(current_fuel);
             current_fuel -= fuel_consumed;
@@ -233,25 +487,50 @@ impl RaceSimulator {
                 // Change tires
                 current_compound = pit_stop.compound;
                 tire_age = 0;
+                tire_wear = 0.0;
                 tire_history.push((lap_number, current_compound));
             }
 
             // Check tire degradation warnings
             let tire_chars = TireCharacteristics::for_compound(current_compound);
-            if tire_age > tire_chars.typical_life {
+            if tire_wear > tire_chars.typical_life as f32 {
                 warnings.push(format!(
-                    "Tire age exceeded typical life at lap {}: {} laps on {} (typical: {})",
-                    lap, tire_age, format!("{:?}", current_compound), tire_chars.typical_life
+                    "Tire age exceeded typical life at lap {}: {} laps on {:?} (typical: {})",
+                    lap, tire_age, current_compound, tire_chars.typical_life
                 ));
             }
 
-            // Check weather changes
-            let current_weather = self.weather.condition_at_lap(lap_number);
-            if self.is_wrong_tire_for_weather(current_compound, current_weather) {
-                warnings.push(format!(
-                    "Wrong tire compound at lap {}: {:?} tires in {:?} conditions",
-                    lap, current_compound, current_weather
-                ));
+            // Check wet/dry crossover against the compound now fitted
+            let track_condition = TrackCondition::from_wetness(track_wetness);
+            let best_compound = best_compound_for_sectors(&sectors, &sector_wetness, slick);
+            let pace_loss = sector_pace_factor(current_compound, &sectors, &sector_wetness)
+                - sector_pace_factor(best_compound, &sectors, &sector_wetness);
+
+            if best_compound.is_slick() != current_compound.is_slick()
+                || (!best_compound.is_slick() && best_compound != current_compound)
+            {
+                let already_recommended = compound_switches
+                    .last()
+                    .map(|r| r.from_compound == current_compound && r.to_compound == best_compound)
+                    .unwrap_or(false);
+
+                if !already_recommended && lap < total_laps {
+                    compound_switches.push(CompoundSwitchRecommendation {
+                        lap: lap_number,
+                        from_compound: current_compound,
+                        to_compound: best_compound,
+                        track_wetness,
+                        track_condition,
+                        lap_time_gain: pace_loss * self.base_lap_time(),
+                    });
+                }
+
+                if pace_loss > WRONG_TIRE_PACE_LOSS {
+                    warnings.push(format!(
+                        "Wrong tire compound at lap {}: {:?} tires in {:?} conditions",
+                        lap, current_compound, track_condition
+                    ));
+                }
             }
         }
 
@@ -280,6 +559,9 @@ impl RaceSimulator {
             pit_stops: pit_stop_events,
             tire_history,
             fuel_history,
+            sector_times,
+            track_wetness_history,
+            compound_switches,
             warnings,
             estimated_position: None, // Can be enhanced with competitor simulation
             average_lap_time,
@@ -288,45 +570,82 @@ impl RaceSimulator {
         }
     }
 
-    /// Calculate lap time considering all factors
-    fn calculate_lap_time(
+    /// Probability of completing an overtake on the next lap
+    ///
+    /// See [`overtake_probability`].
+    pub fn overtake_probability(&self, gap: f32, pace_advantage: f32) -> f32 {
+        overtake_probability(&self.circuit, gap, pace_advantage)
+    }
+
+    /// Base race-pace lap time (slightly slower than lap record)
+    fn base_lap_time(&self) -> f32 {
+        self.circuit.lap_record * 1.03
+    }
+
+    /// Dry compound to recommend when the track is dry enough for slicks
+    fn preferred_slick(&self) -> TireCompound {
+        std::iter::once(self.strategy.starting_compound)
+            .chain(self.strategy.pit_stops.iter().map(|ps| ps.compound))
+            .find(|c| c.is_slick())
+            .unwrap_or(TireCompound::C3)
+    }
+
+    /// Calculate sector times considering all factors
+    ///
+    /// Lap-wide effects (tire wear, fuel, compound grip) are shared across
+    /// sectors by distance; temperature, wetness and grip use each sector's
+    /// own weather. `tire_wear` is the stint length in standard-atmosphere laps
+    /// and `pace_factor` the atmosphere's lap time multiplier.
+    #[allow(clippy::too_many_arguments)]
+    fn calculate_sector_times(
         &self,
-        lap: LapNumber,
         compound: TireCompound,
-        tire_age: u16,
+        tire_wear: f32,
+        pace_factor: f32,
         current_fuel: f32,
-    ) -> f32 {
+        sectors: &[SectorInfo],
+        sector_weather: &[SectorWeather],
+        sector_wetness: &[f32],
+    ) -> Vec<f32> {
         let tire_chars = TireCharacteristics::for_compound(compound);
 
-        // Base lap time (slightly slower than lap record for realistic race pace)
-        let base_time = self.circuit.lap_record * 1.03;
+        // Base lap time (slightly slower than lap record for realistic race pace),
+        // adjusted for air density
+        let base_time = self.base_lap_time() * pace_factor;
 
-        // 1. Tire degradation penalty
-        let wear_ratio = tire_age as f32 / tire_chars.typical_life as f32;
-        let degradation_penalty = wear_ratio.powf(1.5) * 1.5; // Non-linear degradation
+        // 1. Tire degradation penalty less the compound's grip advantage
+        let tire_penalty = tire_time_loss(&tire_chars, tire_wear);
 
         // 2. Fuel weight penalty (heavier car = slower)
         // Each kg of fuel costs ~0.03s per lap
         let fuel_penalty = (current_fuel / 110.0) * 0.35;
 
-        // 3. Track temperature effect
-        let track_temp = self.weather.track_temp_at_lap(lap);
-        let temp_penalty = self.calculate_temperature_penalty(track_temp, &tire_chars);
-
-        // 4. Weather condition penalty
-        let weather = self.weather.condition_at_lap(lap);
-        let weather_penalty = self.calculate_weather_penalty(weather, compound);
-
-        // 5. Tire compound grip advantage
-        let grip_bonus = (tire_chars.grip_level - 0.75) * 0.8;
-
-        // 6. Circuit-specific tire degradation
+        // 3. Circuit-specific tire degradation
+        let wear_ratio = tire_wear / tire_chars.typical_life as f32;
         let track_severity = self.circuit.characteristics.tire_severity;
         let track_deg_penalty = (track_severity - 1.0) * wear_ratio * 0.5;
 
-        // Combine all factors
-        base_time + degradation_penalty + fuel_penalty + temp_penalty
-            + weather_penalty + track_deg_penalty - grip_bonus
+        let lap_time = base_time + tire_penalty + fuel_penalty + track_deg_penalty;
+        let total_length: f32 = sectors.iter().map(|s| s.length).sum();
+
+        sectors
+            .iter()
+            .zip(sector_weather)
+            .zip(sector_wetness)
+            .map(|((info, weather), &wetness)| {
+                // 4. Sector track temperature effect
+                let temp_penalty = self.calculate_temperature_penalty(weather.track_temp, &tire_chars);
+
+                // 5. Sector wetness penalty (compound pace relative to slicks in the dry)
+                let wet_penalty = base_time * (tire_chars.wet_pace_factor(wetness) - 1.0);
+
+                // 6. Sector surface grip
+                let grip_penalty =
+                    base_time * (1.0 - weather.grip_level.clamp(0.0, 1.0)) * SECTOR_GRIP_SENSITIVITY;
+
+                (info.length / total_length) * (lap_time + temp_penalty + wet_penalty + grip_penalty)
+            })
+            .collect()
     }
 
     /// Calculate penalty from track temperature
@@ -345,63 +664,66 @@ impl RaceSimulator {
             delta * 0.08 // 0.08s per degree above optimal
         }
     }
+}
 
-    /// Calculate penalty from weather conditions
-    fn calculate_weather_penalty(&self, weather: WeatherCondition, compound: TireCompound) -> f32 {
-        let is_dry_compound = matches!(
-            compound,
-            TireCompound::C0 | TireCompound::C1 | TireCompound::C2 |
-            TireCompound::C3 | TireCompound::C4 | TireCompound::C5
-        );
+/// Distance-weighted average of a per-sector quantity
+/// Probability of completing an overtake on `circuit` on the next lap
+///
+/// `gap` is the time behind the car ahead and `pace_advantage` the lap time
+/// advantage over it (seconds). Within DRS range the circuit's DRS zone
+/// gains add to the advantage; the built-in circuits have none, so assign
+/// zones calibrated by the physics power unit model
+/// (`LapSimulator::drs_zones`) for circuit-specific gains. The margin needed
+/// to pass grows with the circuit's overtaking difficulty.
+pub fn overtake_probability(circuit: &Circuit, gap: f32, pace_advantage: f32) -> f32 {
+    let drs_gain: f32 = if gap <= DRS_DETECTION_GAP {
+        circuit.drs_zones.iter().map(|z| z.expected_time_gain).sum()
+    } else {
+        0.0
+    };
+    let difficulty = circuit.characteristics.overtaking_difficulty;
+    let required = OVERTAKE_MARGIN * (1.0 + 2.0 * difficulty);
 
-        match weather {
-            WeatherCondition::Dry | WeatherCondition::Cloudy | WeatherCondition::PartlyCloudy => {
-                if is_dry_compound {
-                    0.0 // Correct choice
-                } else if compound == TireCompound::Intermediate {
-                    2.5 // Inters on dry
-                } else if compound == TireCompound::Wet {
-                    if weather == WeatherCondition::Dry { 5.0 } else { 4.0 }
-                } else {
-                    1.0
-                }
-            }
-            WeatherCondition::LightRain => {
-                if compound == TireCompound::Intermediate {
-                    0.0 // Correct choice
-                } else if is_dry_compound {
-                    5.0 // Dry tires in rain - dangerous
-                } else {
-                    1.0 // Wets in light rain - OK but not optimal
-                }
-            }
-            WeatherCondition::HeavyRain => {
-                if compound == TireCompound::Wet {
-                    0.0 // Correct choice
-                } else if compound == TireCompound::Intermediate {
-                    3.0 // Inters in heavy rain - risky
-                } else if is_dry_compound {
-                    15.0 // Dry tires in heavy rain - very dangerous
-                } else {
-                    1.0
-                }
-            }
-        }
-    }
+    ((pace_advantage + drs_gain - gap.max(0.0)) / required).clamp(0.0, 1.0)
+}
 
-    /// Check if tire compound is inappropriate for weather
-    fn is_wrong_tire_for_weather(&self, compound: TireCompound, weather: WeatherCondition) -> bool {
-        let is_dry_compound = matches!(
-            compound,
-            TireCompound::C0 | TireCompound::C1 | TireCompound::C2 |
-            TireCompound::C3 | TireCompound::C4 | TireCompound::C5
-        );
+/// Lap time lost to `tire_wear` laps of wear, less the compound's grip advantage (seconds)
+pub(crate) fn tire_time_loss(tire_chars: &TireCharacteristics, tire_wear: f32) -> f32 {
+    let wear_ratio = tire_wear / tire_chars.typical_life as f32;
+    let degradation_penalty = wear_ratio.powf(1.5) * 1.5; // Non-linear degradation
+    let grip_bonus = (tire_chars.grip_level - 0.75) * 0.8;
+    degradation_penalty - grip_bonus
+}
 
-        (match weather {
-            WeatherCondition::LightRain | WeatherCondition::HeavyRain => is_dry_compound,
-            _ => false,
-        }) || (weather == WeatherCondition::HeavyRain && compound == TireCompound::Intermediate)
-    }
+fn distance_weighted(sectors: &[SectorInfo], values: &[f32], f: impl Fn(f32) -> f32) -> f32 {
+    let total_length: f32 = sectors.iter().map(|s| s.length).sum();
+    sectors
+        .iter()
+        .zip(values)
+        .map(|(info, &value)| info.length * f(value))
+        .sum::<f32>()
+        / total_length
+}
+
+/// Lap pace factor of a compound over sectors with differing wetness
+fn sector_pace_factor(compound: TireCompound, sectors: &[SectorInfo], sector_wetness: &[f32]) -> f32 {
+    let tire_chars = TireCharacteristics::for_compound(compound);
+    distance_weighted(sectors, sector_wetness, |w| tire_chars.wet_pace_factor(w))
+}
+
+/// Fastest compound over a lap with differing sector wetness
+fn best_compound_for_sectors(
+    sectors: &[SectorInfo],
+    sector_wetness: &[f32],
+    slick: TireCompound,
+) -> TireCompound {
+    [slick, TireCompound::Intermediate, TireCompound::Wet]
+        .into_iter()
+        .min_by(|a, b| {
+            sector_pace_factor(*a, sectors, sector_wetness)
+                .total_cmp(&sector_pace_factor(*b, sectors, sector_wetness))
+        })
+        .unwrap_or(slick)
 }
 
 /// Helper function to create a simple race simulator
@@ -418,6 +740,7 @@ pub fn create_simulator(
         track_temperature: 30.0,
         air_temperature: 25.0,
         changes: vec![],
+        sector_changes: vec![],
     };
 
     RaceSimulator::new(circuit, strategy, fuel_model, weather)
@@ -487,6 +810,7 @@ mod tests {
             track_temperature: 30.0,
             air_temperature: 25.0,
             changes: vec![],
+            sector_changes: vec![],
         };
 
         let simulator = RaceSimulator::new(circuit, strategy, fuel_model, weather);
@@ -605,6 +929,7 @@ mod tests {
                 (LapNumber(15), WeatherCondition::LightRain, 18.0),
                 (LapNumber(30), WeatherCondition::Dry, 22.0),
             ],
+            sector_changes: vec![],
         };
 
         let simulator = RaceSimulator::new(circuit, strategy, fuel_model, weather);
@@ -667,6 +992,7 @@ mod tests {
             track_temperature: 15.0, // Cold
             air_temperature: 12.0,
             changes: vec![],
+            sector_changes: vec![],
         };
 
         let cold_sim = RaceSimulator::new(
@@ -683,6 +1009,7 @@ mod tests {
             track_temperature: 50.0, // Very hot
             air_temperature: 35.0,
             changes: vec![],
+            sector_changes: vec![],
         };
 
         let hot_sim = RaceSimulator::new(circuit, strategy, fuel_model, hot_weather);
@@ -717,6 +1044,7 @@ mod tests {
             air_temperature: 22.0,
             track_temperature: 28.0,
             humidity: 0.6,
+            pressure: None,
             wind_speed: 10.0,
             wind_direction: 180.0,
             rain_probability: 0.2,
@@ -731,5 +1059,247 @@ mod tests {
         assert_eq!(weather.track_temperature, 28.0);
         assert_eq!(weather.air_temperature, 22.0);
         assert!(weather.changes.is_empty());
+        assert!(weather.sector_changes.is_empty());
+    }
+
+    #[test]
+    fn test_track_wetness_and_crossover() {
+        let circuit = Circuit::spa();
+        let fuel_model = FuelConsumptionModel::default_model();
+        let weather = WeatherConditions {
+            initial_condition: WeatherCondition::Dry,
+            track_temperature: 25.0,
+            air_temperature: 20.0,
+            changes: vec![
+                (LapNumber(10), WeatherCondition::HeavyRain, 16.0),
+                (LapNumber(20), WeatherCondition::Dry, 24.0),
+            ],
+            sector_changes: vec![],
+        };
+
+        // Pit for full wets in the rain and stay out on them until lap 40
+        let mut strategy = create_test_strategy();
+        strategy.pit_stops[0].lap = LapNumber(12);
+        strategy.pit_stops[0].compound = TireCompound::Wet;
+
+        let simulator = RaceSimulator::new(circuit, strategy, fuel_model, weather);
+        let result = simulator.simulate_race();
+
+        assert_eq!(result.track_wetness_history.len(), result.lap_times.len());
+        assert!(result.track_wetness_history[5] < 0.01);
+        assert!(result.track_wetness_history[18] > 0.5);
+        assert!(result.track_wetness_history[43] < result.track_wetness_history[18]);
+
+        // Slicks in heavy rain, then wets on a drying track
+        let to_wet = result
+            .compound_switches
+            .iter()
+            .find(|r| !r.to_compound.is_slick())
+            .expect("expected a switch to wet-weather tires");
+        assert!(to_wet.lap.0 >= 10 && to_wet.lap.0 < 20);
+        assert!(to_wet.lap_time_gain > 0.0);
+        assert!(result
+            .compound_switches
+            .iter()
+            .any(|r| r.lap.0 > 20 && r.from_compound == TireCompound::Wet));
+        assert!(result.warnings.iter().any(|w| w.contains("Wrong tire compound")));
+    }
+
+    #[test]
+    fn test_wet_race_start() {
+        let weather = WeatherConditions {
+            initial_condition: WeatherCondition::HeavyRain,
+            track_temperature: 18.0,
+            air_temperature: 15.0,
+            changes: vec![],
+            sector_changes: vec![],
+        };
+        let profile = weather.wetness_profile(&TrackDryingModel::default_model(), 10, 90.0);
+
+        assert_eq!(profile.len(), 10);
+        assert!(profile[0] > 0.7);
+        assert!(profile.windows(2).all(|w| (w[0] - w[1]).abs() < 0.01));
+    }
+
+    fn sector_weather(sector: Sector, condition: WeatherCondition, grip_level: f32) -> SectorWeather {
+        SectorWeather {
+            sector,
+            condition,
+            rain_intensity: condition.typical_rainfall_intensity(),
+            track_temp: 20.0,
+            grip_level,
+        }
+    }
+
+    #[test]
+    fn test_sector_microclimate() {
+        let circuit = Circuit::spa();
+        let fuel_model = FuelConsumptionModel::default_model();
+        let dry = WeatherConditions {
+            initial_condition: WeatherCondition::Dry,
+            track_temperature: 20.0,
+            air_temperature: 16.0,
+            changes: vec![],
+            sector_changes: vec![],
+        };
+
+        // Rain arrives only in sector 2 (Les Combes to Stavelot) from lap 10
+        let mut local_rain = dry.clone();
+        local_rain.sector_changes = vec![(
+            LapNumber(10),
+            vec![
+                sector_weather(Sector::Sector1, WeatherCondition::Dry, 1.0),
+                sector_weather(Sector::Sector2, WeatherCondition::HeavyRain, 0.6),
+                sector_weather(Sector::Sector3, WeatherCondition::Dry, 1.0),
+            ],
+        )];
+
+        let strategy = create_test_strategy();
+        let dry_result =
+            RaceSimulator::new(circuit.clone(), strategy.clone(), fuel_model.clone(), dry).simulate_race();
+        let wet_result =
+            RaceSimulator::new(circuit, strategy, fuel_model, local_rain).simulate_race();
+
+        assert_eq!(wet_result.sector_times.len(), wet_result.lap_times.len());
+        for (sectors, lap_time) in wet_result.sector_times.iter().zip(&wet_result.lap_times) {
+            assert_eq!(sectors.len(), 3);
+            assert!((sectors.iter().sum::<f32>() - lap_time).abs() < 1e-3);
+        }
+
+        // Identical before the rain
+        assert!((dry_result.lap_times[4] - wet_result.lap_times[4]).abs() < 1e-3);
+
+        // Only sector 2 loses time once it is wet
+        let lap = 15;
+        assert!((wet_result.sector_times[lap][0] - dry_result.sector_times[lap][0]).abs() < 1e-3);
+        assert!(wet_result.sector_times[lap][1] > dry_result.sector_times[lap][1] + 2.0);
+        assert!((wet_result.sector_times[lap][2] - dry_result.sector_times[lap][2]).abs() < 1e-3);
+
+        // Partially wet track is less wet on average than sector 2 alone
+        assert!(wet_result.track_wetness_history[lap] > 0.2);
+        assert!(wet_result.track_wetness_history[lap] < 0.6);
+    }
+
+    #[test]
+    fn test_sector_weather_superseded_by_lap_change() {
+        let weather = WeatherConditions {
+            initial_condition: WeatherCondition::Dry,
+            track_temperature: 25.0,
+            air_temperature: 20.0,
+            changes: vec![(LapNumber(20), WeatherCondition::HeavyRain, 18.0)],
+            sector_changes: vec![(
+                LapNumber(5),
+                vec![sector_weather(Sector::Sector1, WeatherCondition::LightRain, 0.7)],
+            )],
+        };
+
+        let s1 = weather.sector_weather_at_lap(LapNumber(2), Sector::Sector1);
+        assert_eq!(s1.condition, WeatherCondition::Dry);
+        assert_eq!(s1.track_temp, 25.0);
+
+        let s1 = weather.sector_weather_at_lap(LapNumber(10), Sector::Sector1);
+        assert_eq!(s1.condition, WeatherCondition::LightRain);
+        let s2 = weather.sector_weather_at_lap(LapNumber(10), Sector::Sector2);
+        assert_eq!(s2.condition, WeatherCondition::Dry);
+
+        let s1 = weather.sector_weather_at_lap(LapNumber(25), Sector::Sector1);
+        assert_eq!(s1.condition, WeatherCondition::HeavyRain);
+        assert_eq!(s1.track_temp, 18.0);
+    }
+
+    #[test]
+    fn test_overtake_probability_uses_drs_gain() {
+        let weather = WeatherConditions {
+            initial_condition: WeatherCondition::Dry,
+            track_temperature: 25.0,
+            air_temperature: 20.0,
+            changes: vec![],
+            sector_changes: vec![],
+        };
+        let mut circuit = Circuit::spa();
+        let fuel_model = FuelConsumptionModel::default_model();
+        let without_drs =
+            RaceSimulator::new(circuit.clone(), create_test_strategy(), fuel_model.clone(), weather.clone());
+
+        circuit.drs_zones = vec![f1_nexus_core::DrsZone {
+            zone_id: 1,
+            detection_point: 5800.0,
+            activation_point: 6000.0,
+            end_point: 300.0,
+            expected_time_gain: 0.35,
+        }];
+        let with_drs = RaceSimulator::new(circuit, create_test_strategy(), fuel_model.clone(), weather.clone());
+
+        let p_without = without_drs.overtake_probability(0.5, 0.6);
+        let p_with = with_drs.overtake_probability(0.5, 0.6);
+        assert!(p_with > p_without);
+
+        // Out of DRS range the zones do not help
+        assert_eq!(with_drs.overtake_probability(1.5, 0.6), without_drs.overtake_probability(1.5, 0.6));
+
+        // Harder to pass at Monaco with the same pace
+        let monaco = RaceSimulator::new(Circuit::monaco(), create_test_strategy(), fuel_model, weather);
+        assert!(monaco.overtake_probability(0.5, 0.9) < without_drs.overtake_probability(0.5, 0.9));
+    }
+
+    #[test]
+    fn test_atmosphere_slows_laps_and_wears_tires() {
+        let weather = WeatherConditions {
+            initial_condition: WeatherCondition::Dry,
+            track_temperature: 30.0,
+            air_temperature: 15.0,
+            changes: vec![],
+            sector_changes: vec![],
+        };
+        let fuel_model = FuelConsumptionModel::default_model();
+        let standard = RaceSimulator::new(Circuit::monza(), create_test_strategy(), fuel_model, weather);
+        let (standard_pace, standard_wear) = standard.atmosphere_factors(LapNumber(1));
+        assert!((standard_pace - 1.0).abs() < 0.001 && (standard_wear - 1.0).abs() < 0.001);
+
+        let mut hot = standard.clone();
+        hot.set_atmosphere(Atmosphere::new(38.0, 1010.0, 0.7, 0.0));
+        let mut mexico = standard.clone();
+        mexico.set_atmosphere(Atmosphere::at_altitude(2240.0, 20.0, 0.5));
+
+        // Hotter or thinner air: slower laps and faster tire wear
+        let (hot_pace, hot_wear) = hot.atmosphere_factors(LapNumber(1));
+        assert!(hot_pace > 1.0 && hot_wear > 1.0);
+        assert!(mexico.atmosphere_factors(LapNumber(1)).1 > hot_wear);
+
+        let standard_result = standard.simulate_race();
+        let hot_result = hot.simulate_race();
+        assert!(hot_result.total_time > standard_result.total_time);
+        assert!(hot_result.fastest_lap > standard_result.fastest_lap);
+
+        // Wear grows faster on the hot day, so late-stint laps lose more time
+        let stint_loss = |r: &SimulationResult| r.lap_times[18] - r.lap_times[1];
+        assert!(stint_loss(&hot_result) > stint_loss(&standard_result));
+    }
+
+    #[test]
+    fn test_atmosphere_follows_weather_changes() {
+        let weather = WeatherConditions {
+            initial_condition: WeatherCondition::Dry,
+            track_temperature: 40.0,
+            air_temperature: 30.0,
+            changes: vec![(LapNumber(20), WeatherCondition::HeavyRain, 24.0)],
+            sector_changes: vec![],
+        };
+        let fuel_model = FuelConsumptionModel::default_model();
+        let mut simulator = RaceSimulator::new(Circuit::monza(), create_test_strategy(), fuel_model, weather);
+
+        // The standard atmosphere applies until the caller opts in
+        assert_eq!(simulator.atmosphere_at_lap(LapNumber(25)), Atmosphere::standard());
+
+        simulator.set_atmosphere(Atmosphere::new(30.0, 1010.0, 0.4, 0.0));
+        let dry = simulator.atmosphere_at_lap(LapNumber(10));
+        assert_eq!(dry.temperature, 30.0);
+        assert_eq!(dry.humidity, 0.4);
+
+        // Rain cools the track and the air with it, and saturates the air
+        let wet = simulator.atmosphere_at_lap(LapNumber(25));
+        assert_eq!(wet.temperature, 22.0);
+        assert!(wet.humidity >= 0.95);
+        assert!(simulator.atmosphere_factors(LapNumber(25)).0 < simulator.atmosphere_factors(LapNumber(10)).0);
     }
 }
@@ -0,0 +1,755 @@
+//! Alert routing and notification sinks
+//!
+//! `AlertRouter` turns detected anomalies into alerts and fans them out to
+//! pluggable sinks (JSONL file, HTTP webhook, WebSocket stream, MCP
+//! notifications). Repeats of the same alert (same session, car and field)
+//! are de-duplicated within a window, each sink has its own severity filter
+//! and rate limit, and alerts stay listed until the pit wall acknowledges
+//! them.
+//!
+//! Timing uses anomaly timestamps, so replays behave like live sessions.
+
+use crate::{AnomalyInfo, AnomalySeverity};
+use chrono::{DateTime, Utc};
+use f1_nexus_core::{CarId, SessionId};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Alert identifier, unique within a router
+pub type AlertId = u64;
+
+/// An alert raised by the router
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutedAlert {
+    /// Alert identifier
+    pub id: AlertId,
+
+    /// Most recent anomaly for this alert
+    pub anomaly: AnomalyInfo,
+
+    /// When the alert was first raised
+    pub first_seen: DateTime<Utc>,
+
+    /// When the anomaly was last reported
+    pub last_seen: DateTime<Utc>,
+
+    /// Number of times the anomaly was reported, duplicates included
+    pub occurrences: u32,
+
+    /// Acknowledgement, once the alert has been cleared
+    pub acknowledgement: Option<Acknowledgement>,
+}
+
+impl RoutedAlert {
+    /// Alert severity
+    pub fn severity(&self) -> AnomalySeverity {
+        self.anomaly.severity
+    }
+
+    /// Whether the alert has been acknowledged
+    pub fn is_acknowledged(&self) -> bool {
+        self.acknowledgement.is_some()
+    }
+}
+
+/// Acknowledgement of an alert
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Acknowledgement {
+    /// Who cleared the alert
+    pub by: String,
+
+    /// When the alert was cleared
+    pub at: DateTime<Utc>,
+
+    /// Optional note
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Event delivered to sinks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AlertEvent {
+    /// New alert, or an existing alert that escalated or re-occurred
+    Raised(RoutedAlert),
+
+    /// Alert cleared by the pit wall
+    Acknowledged(RoutedAlert),
+}
+
+impl AlertEvent {
+    /// Alert the event refers to
+    pub fn alert(&self) -> &RoutedAlert {
+        match self {
+            AlertEvent::Raised(alert) | AlertEvent::Acknowledged(alert) => alert,
+        }
+    }
+}
+
+/// Destination for alert events
+pub trait AlertSink: Send + Sync {
+    /// Sink name, used in logs and statistics
+    fn name(&self) -> &str;
+
+    /// Deliver an event
+    ///
+    /// Called on the telemetry path, so sinks doing network I/O should
+    /// queue the event and return.
+    fn deliver(&self, event: &AlertEvent) -> Result<(), AlertError>;
+}
+
+/// Token bucket rate limit
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Events allowed in a burst
+    pub burst: u32,
+
+    /// Time to refill the whole burst
+    pub per: Duration,
+}
+
+impl RateLimit {
+    /// Allow `burst` events per `per`
+    pub fn new(burst: u32, per: Duration) -> Self {
+        RateLimit { burst, per }
+    }
+}
+
+/// Per-sink routing configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SinkConfig {
+    /// Alerts below this severity are not delivered
+    pub min_severity: AnomalySeverity,
+
+    /// Limit on raised alerts; acknowledgements are never limited
+    pub rate_limit: Option<RateLimit>,
+}
+
+impl Default for SinkConfig {
+    fn default() -> Self {
+        SinkConfig {
+            min_severity: AnomalySeverity::Low,
+            rate_limit: None,
+        }
+    }
+}
+
+/// Delivery statistics for one sink
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SinkStats {
+    /// Events delivered
+    pub delivered: u64,
+
+    /// Events below the sink's minimum severity
+    pub filtered: u64,
+
+    /// Raised alerts dropped by the rate limit
+    pub rate_limited: u64,
+
+    /// Deliveries that returned an error
+    pub failed: u64,
+}
+
+/// Alert router configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRouterConfig {
+    /// Repeats of an alert within this window are folded into it
+    pub dedup_window: Duration,
+
+    /// Maximum alerts kept; the least recently seen are dropped first
+    pub max_alerts: usize,
+}
+
+impl Default for AlertRouterConfig {
+    fn default() -> Self {
+        AlertRouterConfig {
+            dedup_window: Duration::from_secs(30),
+            max_alerts: 1000,
+        }
+    }
+}
+
+/// Identity used to de-duplicate alerts
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct AlertKey {
+    session_id: SessionId,
+    car_id: CarId,
+    field: String,
+}
+
+impl AlertKey {
+    fn of(anomaly: &AnomalyInfo) -> Self {
+        AlertKey {
+            session_id: anomaly.session_id,
+            car_id: anomaly.car_id,
+            field: anomaly.field.clone(),
+        }
+    }
+}
+
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Option<DateTime<Utc>>,
+}
+
+impl TokenBucket {
+    fn try_take(&mut self, limit: &RateLimit, now: DateTime<Utc>) -> bool {
+        let capacity = limit.burst as f64;
+        match self.last_refill {
+            None => self.tokens = capacity,
+            Some(last) => {
+                let elapsed = (now - last).to_std().unwrap_or_default().as_secs_f64();
+                let per = limit.per.as_secs_f64();
+                let refill = if per > 0.0 { elapsed * capacity / per } else { capacity };
+                self.tokens = (self.tokens + refill).min(capacity);
+            }
+        }
+        self.last_refill = Some(self.last_refill.map_or(now, |last| last.max(now)));
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+struct RegisteredSink {
+    sink: Box<dyn AlertSink>,
+    config: SinkConfig,
+    bucket: Mutex<TokenBucket>,
+    stats: Mutex<SinkStats>,
+}
+
+impl RegisteredSink {
+    fn dispatch(&self, event: &AlertEvent) {
+        let alert = event.alert();
+        if alert.severity() < self.config.min_severity {
+            self.stats.lock().filtered += 1;
+            return;
+        }
+
+        if let (AlertEvent::Raised(_), Some(limit)) = (event, &self.config.rate_limit) {
+            if !self.bucket.lock().try_take(limit, alert.last_seen) {
+                self.stats.lock().rate_limited += 1;
+                return;
+            }
+        }
+
+        match self.sink.deliver(event) {
+            Ok(()) => self.stats.lock().delivered += 1,
+            Err(e) => {
+                warn!("Alert sink '{}' failed to deliver alert {}: {}", self.sink.name(), alert.id, e);
+                self.stats.lock().failed += 1;
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct RouterState {
+    next_id: AlertId,
+    alerts: HashMap<AlertId, RoutedAlert>,
+    keys: HashMap<AlertKey, AlertId>,
+}
+
+impl RouterState {
+    fn remove(&mut self, id: AlertId) {
+        if let Some(alert) = self.alerts.remove(&id) {
+            self.keys.remove(&AlertKey::of(&alert.anomaly));
+        }
+    }
+
+    /// Drop acknowledged alerts that have been quiet for the dedup window
+    fn prune(&mut self, now: DateTime<Utc>, window: chrono::Duration) {
+        let stale: Vec<AlertId> = self
+            .alerts
+            .values()
+            .filter(|alert| alert.is_acknowledged() && now - alert.last_seen > window)
+            .map(|alert| alert.id)
+            .collect();
+        for id in stale {
+            self.remove(id);
+        }
+    }
+
+    fn enforce_capacity(&mut self, max_alerts: usize) {
+        while self.alerts.len() > max_alerts {
+            let oldest = self
+                .alerts
+                .values()
+                .min_by_key(|alert| (!alert.is_acknowledged(), alert.last_seen))
+                .map(|alert| alert.id);
+            match oldest {
+                Some(id) => self.remove(id),
+                None => break,
+            }
+        }
+    }
+}
+
+/// Routes anomalies to alert sinks
+pub struct AlertRouter {
+    config: AlertRouterConfig,
+    sinks: Vec<RegisteredSink>,
+    state: Mutex<RouterState>,
+}
+
+impl AlertRouter {
+    /// Create a router without sinks
+    pub fn new(config: AlertRouterConfig) -> Self {
+        AlertRouter {
+            config,
+            sinks: Vec::new(),
+            state: Mutex::new(RouterState {
+                next_id: 1,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Register a sink
+    pub fn add_sink(&mut self, sink: impl AlertSink + 'static, config: SinkConfig) {
+        self.sinks.push(RegisteredSink {
+            sink: Box::new(sink),
+            config,
+            bucket: Mutex::new(TokenBucket {
+                tokens: 0.0,
+                last_refill: None,
+            }),
+            stats: Mutex::new(SinkStats::default()),
+        });
+    }
+
+    /// Register a sink (builder form)
+    pub fn with_sink(mut self, sink: impl AlertSink + 'static, config: SinkConfig) -> Self {
+        self.add_sink(sink, config);
+        self
+    }
+
+    /// Router configuration
+    pub fn config(&self) -> &AlertRouterConfig {
+        &self.config
+    }
+
+    /// Route an anomaly
+    ///
+    /// Returns the alert when it was sent to the sinks, or `None` when the
+    /// anomaly was folded into an existing alert.
+    pub fn route(&self, anomaly: AnomalyInfo) -> Option<RoutedAlert> {
+        let now = anomaly.timestamp;
+        let window = chrono::Duration::from_std(self.config.dedup_window)
+            .unwrap_or(chrono::Duration::MAX);
+        let key = AlertKey::of(&anomaly);
+
+        let raised = {
+            let mut state = self.state.lock();
+            state.prune(now, window);
+
+            let existing = state.keys.get(&key).copied();
+            let raised = match existing.and_then(|id| state.alerts.get_mut(&id)) {
+                Some(alert) => {
+                    let escalated = anomaly.severity > alert.anomaly.severity;
+                    let expired = now - alert.last_seen > window;
+                    alert.occurrences += 1;
+                    alert.last_seen = alert.last_seen.max(now);
+                    alert.anomaly = anomaly;
+
+                    if escalated || expired {
+                        alert.acknowledgement = None;
+                        Some(alert.clone())
+                    } else {
+                        None
+                    }
+                }
+                None => {
+                    let id = state.next_id;
+                    state.next_id += 1;
+                    let alert = RoutedAlert {
+                        id,
+                        anomaly,
+                        first_seen: now,
+                        last_seen: now,
+                        occurrences: 1,
+                        acknowledgement: None,
+                    };
+                    state.keys.insert(key, id);
+                    state.alerts.insert(id, alert.clone());
+                    Some(alert)
+                }
+            };
+            state.enforce_capacity(self.config.max_alerts);
+            raised
+        };
+
+        if let Some(alert) = &raised {
+            self.dispatch(&AlertEvent::Raised(alert.clone()));
+        }
+        raised
+    }
+
+    /// Acknowledge an alert, notifying the sinks
+    ///
+    /// The alert keeps absorbing repeats until it has been quiet for the
+    /// dedup window; an escalation raises it again.
+    pub fn acknowledge(
+        &self,
+        id: AlertId,
+        by: impl Into<String>,
+        note: Option<String>,
+    ) -> Result<RoutedAlert, AlertError> {
+        let alert = {
+            let mut state = self.state.lock();
+            let alert = state.alerts.get_mut(&id).ok_or(AlertError::UnknownAlert(id))?;
+            alert.acknowledgement = Some(Acknowledgement {
+                by: by.into(),
+                at: Utc::now(),
+                note,
+            });
+            alert.clone()
+        };
+
+        self.dispatch(&AlertEvent::Acknowledged(alert.clone()));
+        Ok(alert)
+    }
+
+    /// Look up an alert
+    pub fn get(&self, id: AlertId) -> Option<RoutedAlert> {
+        self.state.lock().alerts.get(&id).cloned()
+    }
+
+    /// Unacknowledged alerts, oldest first
+    pub fn active(&self) -> Vec<RoutedAlert> {
+        let mut alerts: Vec<RoutedAlert> = self
+            .state
+            .lock()
+            .alerts
+            .values()
+            .filter(|alert| !alert.is_acknowledged())
+            .cloned()
+            .collect();
+        alerts.sort_by_key(|alert| alert.id);
+        alerts
+    }
+
+    /// All retained alerts, oldest first
+    pub fn alerts(&self) -> Vec<RoutedAlert> {
+        let mut alerts: Vec<RoutedAlert> = self.state.lock().alerts.values().cloned().collect();
+        alerts.sort_by_key(|alert| alert.id);
+        alerts
+    }
+
+    /// Delivery statistics per sink, in registration order
+    pub fn sink_stats(&self) -> Vec<(String, SinkStats)> {
+        self.sinks
+            .iter()
+            .map(|entry| (entry.sink.name().to_string(), entry.stats.lock().clone()))
+            .collect()
+    }
+
+    fn dispatch(&self, event: &AlertEvent) {
+        for sink in &self.sinks {
+            sink.dispatch(event);
+        }
+    }
+}
+
+/// Appends alert events to a JSON Lines file
+pub struct JsonlSink {
+    name: String,
+    file: Mutex<File>,
+}
+
+impl JsonlSink {
+    /// Open `path` for appending, creating it if needed
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, AlertError> {
+        let path = path.as_ref();
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(JsonlSink {
+            name: format!("jsonl:{}", path.display()),
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl AlertSink for JsonlSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn deliver(&self, event: &AlertEvent) -> Result<(), AlertError> {
+        let mut line =
+            serde_json::to_vec(event).map_err(|e| AlertError::Serialization(e.to_string()))?;
+        line.push(b'\n');
+        self.file.lock().write_all(&line)?;
+        Ok(())
+    }
+}
+
+/// Posts alert events as JSON to an HTTP webhook
+///
+/// Events are queued and posted by a background task, so delivery never
+/// blocks the telemetry path. Must be created inside a Tokio runtime.
+pub struct WebhookSink {
+    name: String,
+    queue: mpsc::Sender<AlertEvent>,
+}
+
+impl WebhookSink {
+    /// Queue capacity used by [`WebhookSink::new`]
+    pub const DEFAULT_QUEUE_SIZE: usize = 256;
+
+    /// Post to `url` with a 5 second request timeout
+    pub fn new(url: impl Into<String>) -> Self {
+        Self::with_options(url, Duration::from_secs(5), Self::DEFAULT_QUEUE_SIZE)
+    }
+
+    /// Post to `url` with a request timeout and queue capacity
+    pub fn with_options(url: impl Into<String>, timeout: Duration, queue_size: usize) -> Self {
+        let url = url.into();
+        let (queue, mut rx) = mpsc::channel::<AlertEvent>(queue_size.max(1));
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .unwrap_or_default();
+
+        let target = url.clone();
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                match client.post(&target).json(&event).send().await {
+                    Ok(response) if !response.status().is_success() => {
+                        warn!("Webhook {} rejected alert {}: {}", target, event.alert().id, response.status());
+                    }
+                    Ok(_) => {}
+                    Err(e) => warn!("Webhook {} failed for alert {}: {}", target, event.alert().id, e),
+                }
+            }
+        });
+
+        WebhookSink {
+            name: format!("webhook:{}", url),
+            queue,
+        }
+    }
+}
+
+impl AlertSink for WebhookSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn deliver(&self, event: &AlertEvent) -> Result<(), AlertError> {
+        self.queue.try_send(event.clone()).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(_) => AlertError::Delivery("webhook queue full".to_string()),
+            mpsc::error::TrySendError::Closed(_) => {
+                AlertError::Delivery("webhook task stopped".to_string())
+            }
+        })
+    }
+}
+
+/// Alert routing errors
+#[derive(Debug, thiserror::Error)]
+pub enum AlertError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+
+    #[error("Delivery error: {0}")]
+    Delivery(String),
+
+    #[error("Unknown alert: {0}")]
+    UnknownAlert(AlertId),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// Collects delivered events in memory
+    #[derive(Clone, Default)]
+    struct MemorySink {
+        events: Arc<Mutex<Vec<AlertEvent>>>,
+    }
+
+    impl AlertSink for MemorySink {
+        fn name(&self) -> &str {
+            "memory"
+        }
+
+        fn deliver(&self, event: &AlertEvent) -> Result<(), AlertError> {
+            self.events.lock().push(event.clone());
+            Ok(())
+        }
+    }
+
+    fn anomaly(field: &str, severity: AnomalySeverity, at: DateTime<Utc>) -> AnomalyInfo {
+        static SESSION: std::sync::OnceLock<SessionId> = std::sync::OnceLock::new();
+        AnomalyInfo {
+            car_id: CarId::new(20).unwrap(),
+            session_id: *SESSION.get_or_init(SessionId::new),
+            segment: None,
+            field: field.to_string(),
+            expected_range: (80.0, 110.0),
+            actual_value: 125.0,
+            severity,
+            timestamp: at,
+        }
+    }
+
+    #[test]
+    fn test_deduplicates_within_window() {
+        let sink = MemorySink::default();
+        let router = AlertRouter::new(AlertRouterConfig::default())
+            .with_sink(sink.clone(), SinkConfig::default());
+        let start = Utc::now();
+
+        let first = router.route(anomaly("engine_temp", AnomalySeverity::Medium, start));
+        assert!(first.is_some());
+        for i in 1..5 {
+            let at = start + chrono::Duration::seconds(i);
+            assert!(router.route(anomaly("engine_temp", AnomalySeverity::Medium, at)).is_none());
+        }
+        // A different field is a different alert
+        assert!(router.route(anomaly("oil_temp", AnomalySeverity::Medium, start)).is_some());
+
+        let alert = router.get(first.unwrap().id).unwrap();
+        assert_eq!(alert.occurrences, 5);
+        assert_eq!(sink.events.lock().len(), 2);
+
+        // Escalation and re-occurrence after a quiet window are raised again
+        let at = start + chrono::Duration::seconds(6);
+        assert!(router.route(anomaly("engine_temp", AnomalySeverity::High, at)).is_some());
+        let at = start + chrono::Duration::seconds(60);
+        let again = router.route(anomaly("engine_temp", AnomalySeverity::High, at)).unwrap();
+        assert_eq!(again.id, alert.id);
+        assert_eq!(sink.events.lock().len(), 4);
+    }
+
+    #[test]
+    fn test_severity_filter_and_rate_limit() {
+        let all = MemorySink::default();
+        let critical = MemorySink::default();
+        let router = AlertRouter::new(AlertRouterConfig::default())
+            .with_sink(
+                all.clone(),
+                SinkConfig {
+                    rate_limit: Some(RateLimit::new(2, Duration::from_secs(10))),
+                    ..Default::default()
+                },
+            )
+            .with_sink(
+                critical.clone(),
+                SinkConfig {
+                    min_severity: AnomalySeverity::High,
+                    ..Default::default()
+                },
+            );
+        let start = Utc::now();
+
+        for field in ["a", "b", "c"] {
+            router.route(anomaly(field, AnomalySeverity::Medium, start));
+        }
+        router.route(anomaly("d", AnomalySeverity::High, start));
+        assert_eq!(all.events.lock().len(), 2);
+        assert_eq!(critical.events.lock().len(), 1);
+
+        // Tokens refill over time
+        router.route(anomaly("e", AnomalySeverity::Low, start + chrono::Duration::seconds(5)));
+        assert_eq!(all.events.lock().len(), 3);
+
+        let stats = router.sink_stats();
+        assert_eq!(stats[0].1.rate_limited, 2);
+        assert_eq!(stats[1].1.filtered, 4);
+    }
+
+    #[test]
+    fn test_acknowledgement() {
+        let sink = MemorySink::default();
+        let router = AlertRouter::new(AlertRouterConfig::default())
+            .with_sink(sink.clone(), SinkConfig::default());
+        let start = Utc::now();
+
+        let alert = router.route(anomaly("brake_temp", AnomalySeverity::High, start)).unwrap();
+        assert_eq!(router.active().len(), 1);
+
+        let acked = router.acknowledge(alert.id, "race engineer", None).unwrap();
+        assert_eq!(acked.acknowledgement.unwrap().by, "race engineer");
+        assert!(router.active().is_empty());
+        assert!(matches!(sink.events.lock().last(), Some(AlertEvent::Acknowledged(_))));
+        assert!(matches!(router.acknowledge(99, "x", None), Err(AlertError::UnknownAlert(99))));
+
+        // Repeats stay silenced, then the alert is dropped once quiet
+        let at = start + chrono::Duration::seconds(1);
+        assert!(router.route(anomaly("brake_temp", AnomalySeverity::High, at)).is_none());
+        let at = start + chrono::Duration::seconds(120);
+        router.route(anomaly("oil_temp", AnomalySeverity::Low, at));
+        assert!(router.get(alert.id).is_none());
+    }
+
+    #[test]
+    fn test_jsonl_sink() {
+        let path = std::env::temp_dir().join(format!("f1-nexus-alerts-{}.jsonl", std::process::id()));
+        let router = AlertRouter::new(AlertRouterConfig::default())
+            .with_sink(JsonlSink::open(&path).unwrap(), SinkConfig::default());
+
+        let alert = router.route(anomaly("fuel", AnomalySeverity::Medium, Utc::now())).unwrap();
+        router.acknowledge(alert.id, "pit wall", Some("known sensor fault".to_string())).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let events: Vec<AlertEvent> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], AlertEvent::Raised(a) if a.id == alert.id));
+        assert!(matches!(&events[1], AlertEvent::Acknowledged(a) if a.is_acknowledged()));
+    }
+
+    #[tokio::test]
+    async fn test_webhook_sink_posts_to_receiver() {
+        use axum::{extract::State, routing::post, Json, Router};
+
+        let (tx, mut rx) = mpsc::unbounded_channel::<serde_json::Value>();
+        let app = Router::new()
+            .route(
+                "/alerts",
+                post(|State(tx): State<mpsc::UnboundedSender<serde_json::Value>>, Json(body): Json<serde_json::Value>| async move {
+                    let _ = tx.send(body);
+                }),
+            )
+            .with_state(tx);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let router = AlertRouter::new(AlertRouterConfig::default()).with_sink(
+            WebhookSink::new(format!("http://{}/alerts", addr)),
+            SinkConfig::default(),
+        );
+        let alert = router.route(anomaly("oil_pressure", AnomalySeverity::High, Utc::now())).unwrap();
+
+        let body = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(body["event"], "raised");
+        assert_eq!(body["id"], alert.id);
+        assert_eq!(body["anomaly"]["field"], "oil_pressure");
+    }
+}
@@ -1,72 +1,67 @@
-//! Anomaly detection using neural models
+//! Rule-based anomaly alerts
 
-use crate::{TelemetryConfig, TelemetryError};
-use f1_nexus_core::TelemetrySnapshot;
+use crate::{RuleEngine, RulePack, TelemetryConfig, TelemetryError};
+use chrono::{DateTime, Utc};
+use f1_nexus_core::{CarId, SessionId, TelemetrySnapshot};
 use serde::{Deserialize, Serialize};
 
-/// Legacy anomaly detector using simple rule-based inference
+/// Legacy anomaly detector running the `legacy` rule pack
 /// Note: Use `processor::AnomalyDetector` for statistical anomaly detection
 pub struct LegacyAnomalyDetector {
-    config: TelemetryConfig,
-    // In production: temporal-neural-solver model would be here
+    engine: RuleEngine,
 }
 
 impl LegacyAnomalyDetector {
     pub fn new(config: TelemetryConfig) -> Self {
-        LegacyAnomalyDetector { config }
+        Self::with_rules(config, RulePack::legacy())
     }
 
-    /// Detect anomalies in telemetry snapshot
-    pub fn detect(&self, snapshot: &TelemetrySnapshot) -> Result<Option<AnomalyAlert>, TelemetryError> {
-        if !self.config.enable_anomaly_detection {
-            return Ok(None);
-        }
-
-        // Check for critical tire temperature
-        if snapshot.has_critical_tire_temp() {
-            return Ok(Some(AnomalyAlert {
-                anomaly_type: AnomalyType::CriticalTireTemperature,
-                severity: Severity::High,
-                description: "Tire temperature exceeded critical threshold".to_string(),
-                confidence: 1.0,
-                recommended_action: Some("Reduce pace or pit immediately".to_string()),
-            }));
-        }
-
-        // Check for sudden speed drop (potential crash/spin)
-        if snapshot.motion.speed < 50.0 && snapshot.inputs.throttle > 0.5 {
-            return Ok(Some(AnomalyAlert {
-                anomaly_type: AnomalyType::SuddenSpeedLoss,
-                severity: Severity::Critical,
-                description: "Sudden speed loss detected".to_string(),
-                confidence: 0.95,
-                recommended_action: Some("Check for damage".to_string()),
-            }));
-        }
-
-        // Check for fuel shortage risk
-        if snapshot.estimated_fuel_laps() < 5.0 {
-            return Ok(Some(AnomalyAlert {
-                anomaly_type: AnomalyType::FuelShortage,
-                severity: Severity::Medium,
-                description: format!("Low fuel: {:.1} laps remaining", snapshot.estimated_fuel_laps()),
-                confidence: 0.92,
-                recommended_action: Some("Enable fuel-saving mode".to_string()),
-            }));
+    /// Create a detector running a custom rule pack
+    pub fn with_rules(config: TelemetryConfig, pack: RulePack) -> Self {
+        LegacyAnomalyDetector {
+            engine: RuleEngine::new(config, pack),
         }
+    }
 
-        Ok(None)
+    /// Detect anomalies in telemetry snapshot
+    ///
+    /// Returns the first alert raised, in rule pack order.
+    pub fn detect(&self, snapshot: &TelemetrySnapshot) -> Result<Option<AnomalyAlert>, TelemetryError> {
+        Ok(self.engine.evaluate(snapshot).into_iter().next())
     }
 }
 
 /// Anomaly alert
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnomalyAlert {
+    /// Rule that raised the alert
+    pub rule_id: String,
+
+    pub car_id: CarId,
+    pub session_id: SessionId,
+
+    /// Track segment whose baseline was used, if any
+    #[serde(default)]
+    pub segment: Option<u16>,
+
     pub anomaly_type: AnomalyType,
     pub severity: Severity,
     pub description: String,
     pub confidence: f32,
     pub recommended_action: Option<String>,
+
+    /// Channel (or rule-defined field) that triggered the alert
+    pub field: String,
+
+    /// Value observed on `field`
+    pub actual_value: f32,
+
+    /// Expected value range (min, max), if known
+    #[serde(default)]
+    pub expected_range: Option<(f32, f32)>,
+
+    /// Timestamp of the snapshot that raised the alert
+    pub timestamp: DateTime<Utc>,
 }
 
 /// Types of anomalies
@@ -90,3 +85,33 @@ pub enum Severity {
     High,
     Critical,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::tests::create_test_snapshot;
+
+    #[test]
+    fn test_legacy_rules_in_priority_order() {
+        let detector = LegacyAnomalyDetector::new(TelemetryConfig::default());
+        let mut snapshot = create_test_snapshot();
+        assert!(detector.detect(&snapshot).unwrap().is_none());
+
+        snapshot.fuel.remaining = 6.0;
+        let alert = detector.detect(&snapshot).unwrap().unwrap();
+        assert_eq!(alert.anomaly_type, AnomalyType::FuelShortage);
+        assert_eq!(alert.description, "Low fuel: 4.0 laps remaining");
+
+        // Spin with throttle applied outranks low fuel
+        snapshot.motion.speed = 30.0;
+        let alert = detector.detect(&snapshot).unwrap().unwrap();
+        assert_eq!(alert.anomaly_type, AnomalyType::SuddenSpeedLoss);
+        assert_eq!(alert.severity, Severity::Critical);
+        assert_eq!(alert.car_id, snapshot.car_id);
+
+        snapshot.tires.rear_left.surface_temp = 125.0;
+        let alert = detector.detect(&snapshot).unwrap().unwrap();
+        assert_eq!(alert.anomaly_type, AnomalyType::CriticalTireTemperature);
+        assert_eq!(alert.recommended_action.as_deref(), Some("Reduce pace or pit immediately"));
+    }
+}
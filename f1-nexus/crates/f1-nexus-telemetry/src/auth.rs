@@ -0,0 +1,391 @@
+//! Stream authentication, team scoping and TLS
+//!
+//! Clients present a token as `Authorization: Bearer <token>` or, since
+//! browsers cannot set headers on WebSocket requests, as `?token=`. A token is
+//! either a static API key or a JWT verified locally; both resolve to a team,
+//! and a team only receives the cars listed for it.
+
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio_rustls::rustls;
+
+/// Authentication errors
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    #[error("Missing access token")]
+    MissingToken,
+
+    #[error("Invalid access token: {0}")]
+    InvalidToken(String),
+
+    #[error("Team '{0}' has no access")]
+    UnknownTeam(String),
+
+    #[error("TLS configuration error: {0}")]
+    Tls(String),
+}
+
+/// Who may connect and which cars they see
+///
+/// Authentication is off while there are no API keys and no JWT settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuthConfig {
+    /// Static API keys and the team each belongs to
+    #[serde(default)]
+    pub api_keys: HashMap<String, String>,
+
+    /// Verify JWTs locally
+    #[serde(default)]
+    pub jwt: Option<JwtConfig>,
+
+    /// Cars each team may see
+    #[serde(default)]
+    pub teams: HashMap<String, Vec<u8>>,
+
+    /// Teams that see every car, e.g. race control
+    #[serde(default)]
+    pub all_access_teams: Vec<String>,
+}
+
+/// JWT verification settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JwtConfig {
+    /// Signing algorithm
+    #[serde(default = "default_algorithm")]
+    pub algorithm: Algorithm,
+
+    /// Shared secret for HS algorithms, PEM public key otherwise
+    pub key: String,
+
+    /// Required `iss` claim
+    #[serde(default)]
+    pub issuer: Option<String>,
+
+    /// Required `aud` claim
+    #[serde(default)]
+    pub audience: Option<String>,
+}
+
+fn default_algorithm() -> Algorithm {
+    Algorithm::HS256
+}
+
+/// Claims read from a stream JWT
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamClaims {
+    /// Team the token was issued to
+    pub team: String,
+
+    /// Cars to narrow the team's access to
+    #[serde(default)]
+    pub cars: Option<Vec<u8>>,
+
+    /// Expiry (seconds since the epoch)
+    pub exp: u64,
+}
+
+/// Cars an authenticated client may see
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccessScope {
+    /// Team the client authenticated as; `None` with authentication off
+    pub team: Option<String>,
+
+    /// Visible cars; `None` for every car
+    pub car_ids: Option<Vec<u8>>,
+}
+
+impl AccessScope {
+    /// Every car, no team
+    pub fn unrestricted() -> Self {
+        AccessScope::default()
+    }
+
+    pub fn allows(&self, car_id: u8) -> bool {
+        self.car_ids.as_ref().map_or(true, |cars| cars.contains(&car_id))
+    }
+}
+
+impl AuthConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.api_keys.is_empty() || self.jwt.is_some()
+    }
+
+    /// Resolve a token to the cars its holder may see
+    pub fn authenticate(&self, token: Option<&str>) -> Result<AccessScope, AuthError> {
+        if !self.is_enabled() {
+            return Ok(AccessScope::unrestricted());
+        }
+        let token = token.filter(|t| !t.is_empty()).ok_or(AuthError::MissingToken)?;
+
+        if let Some(team) = self.api_keys.get(token) {
+            return self.scope_for(team, None);
+        }
+        match &self.jwt {
+            Some(jwt) => {
+                let claims = jwt.verify(token)?;
+                self.scope_for(&claims.team, claims.cars)
+            }
+            None => Err(AuthError::InvalidToken("unknown API key".to_string())),
+        }
+    }
+
+    fn scope_for(&self, team: &str, narrowed: Option<Vec<u8>>) -> Result<AccessScope, AuthError> {
+        let allowed = if self.all_access_teams.iter().any(|t| t == team) {
+            None
+        } else {
+            let cars = self.teams.get(team).ok_or_else(|| AuthError::UnknownTeam(team.to_string()))?;
+            Some(cars.clone())
+        };
+        let car_ids = match (allowed, narrowed) {
+            (Some(allowed), Some(narrowed)) => Some(narrowed.into_iter().filter(|c| allowed.contains(c)).collect()),
+            (allowed, None) => allowed,
+            (None, narrowed) => narrowed,
+        };
+        Ok(AccessScope {
+            team: Some(team.to_string()),
+            car_ids,
+        })
+    }
+}
+
+impl JwtConfig {
+    /// Check the signature, expiry, issuer and audience of a token
+    pub fn verify(&self, token: &str) -> Result<StreamClaims, AuthError> {
+        let key = match self.algorithm {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => Ok(DecodingKey::from_secret(self.key.as_bytes())),
+            Algorithm::ES256 | Algorithm::ES384 => DecodingKey::from_ec_pem(self.key.as_bytes()),
+            Algorithm::EdDSA => DecodingKey::from_ed_pem(self.key.as_bytes()),
+            _ => DecodingKey::from_rsa_pem(self.key.as_bytes()),
+        }
+        .map_err(|e| AuthError::InvalidToken(format!("bad verification key: {}", e)))?;
+
+        let mut validation = Validation::new(self.algorithm);
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        match &self.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+
+        jsonwebtoken::decode::<StreamClaims>(token, &key, &validation)
+            .map(|data| data.claims)
+            .map_err(|e| AuthError::InvalidToken(e.to_string()))
+    }
+}
+
+/// Token from an `Authorization: Bearer` header value
+pub fn bearer_token(authorization: &str) -> Option<&str> {
+    authorization
+        .strip_prefix("Bearer ")
+        .or_else(|| authorization.strip_prefix("bearer "))
+        .map(str::trim)
+}
+
+/// Certificate and private key for serving over TLS
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TlsConfig {
+    /// PEM certificate chain
+    pub cert_path: PathBuf,
+
+    /// PEM private key
+    pub key_path: PathBuf,
+}
+
+impl TlsConfig {
+    pub fn new(cert_path: impl Into<PathBuf>, key_path: impl Into<PathBuf>) -> Self {
+        TlsConfig {
+            cert_path: cert_path.into(),
+            key_path: key_path.into(),
+        }
+    }
+
+    /// Load the certificate and key into a rustls server configuration
+    pub fn server_config(&self) -> Result<Arc<rustls::ServerConfig>, AuthError> {
+        let read = |path: &PathBuf| {
+            std::fs::read(path).map_err(|e| AuthError::Tls(format!("{}: {}", path.display(), e)))
+        };
+        let certs = rustls_pemfile::certs(&mut &read(&self.cert_path)?[..])
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AuthError::Tls(format!("{}: {}", self.cert_path.display(), e)))?;
+        if certs.is_empty() {
+            return Err(AuthError::Tls(format!("{}: no certificates", self.cert_path.display())));
+        }
+        let key = rustls_pemfile::private_key(&mut &read(&self.key_path)?[..])
+            .map_err(|e| AuthError::Tls(format!("{}: {}", self.key_path.display(), e)))?
+            .ok_or_else(|| AuthError::Tls(format!("{}: no private key", self.key_path.display())))?;
+
+        let config = rustls::ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(|e| AuthError::Tls(e.to_string()))?
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .map_err(|e| AuthError::Tls(e.to_string()))?;
+        Ok(Arc::new(config))
+    }
+}
+
+/// Limit on concurrently open connections
+#[derive(Debug, Clone)]
+pub struct ConnectionLimit {
+    open: Arc<AtomicUsize>,
+    max: usize,
+}
+
+/// One open connection; releases its slot when dropped
+#[derive(Debug)]
+pub struct ConnectionPermit {
+    open: Arc<AtomicUsize>,
+}
+
+impl ConnectionLimit {
+    pub fn new(max: usize) -> Self {
+        ConnectionLimit {
+            open: Arc::new(AtomicUsize::new(0)),
+            max,
+        }
+    }
+
+    /// Take a slot, or `None` when `max` connections are open
+    pub fn try_acquire(&self) -> Option<ConnectionPermit> {
+        self.open
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |open| (open < self.max).then_some(open + 1))
+            .ok()
+            .map(|_| ConnectionPermit {
+                open: Arc::clone(&self.open),
+            })
+    }
+
+    /// Connections currently open
+    pub fn open(&self) -> usize {
+        self.open.load(Ordering::Acquire)
+    }
+
+    pub fn max(&self) -> usize {
+        self.max
+    }
+}
+
+impl Drop for ConnectionPermit {
+    fn drop(&mut self) {
+        self.open.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{EncodingKey, Header};
+
+    fn config() -> AuthConfig {
+        AuthConfig {
+            api_keys: [("k-mcl".to_string(), "mclaren".to_string()), ("k-fia".to_string(), "fia".to_string())]
+                .into_iter()
+                .collect(),
+            jwt: Some(JwtConfig {
+                algorithm: Algorithm::HS256,
+                key: "pit-wall-secret".to_string(),
+                issuer: Some("f1-nexus".to_string()),
+                audience: None,
+            }),
+            teams: [("mclaren".to_string(), vec![4, 81]), ("ferrari".to_string(), vec![16, 44])]
+                .into_iter()
+                .collect(),
+            all_access_teams: vec!["fia".to_string()],
+        }
+    }
+
+    fn token(secret: &str, team: &str, cars: Option<Vec<u8>>, expires_in: i64) -> String {
+        #[derive(Serialize)]
+        struct Claims<'a> {
+            team: &'a str,
+            cars: Option<Vec<u8>>,
+            exp: i64,
+            iss: &'a str,
+        }
+        let claims = Claims {
+            team,
+            cars,
+            exp: chrono::Utc::now().timestamp() + expires_in,
+            iss: "f1-nexus",
+        };
+        jsonwebtoken::encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_bytes())).unwrap()
+    }
+
+    #[test]
+    fn test_api_keys_and_team_scopes() {
+        let auth = config();
+        let mclaren = auth.authenticate(Some("k-mcl")).unwrap();
+        assert_eq!(mclaren.team.as_deref(), Some("mclaren"));
+        assert!(mclaren.allows(81));
+        assert!(!mclaren.allows(16));
+
+        let fia = auth.authenticate(Some("k-fia")).unwrap();
+        assert!(fia.allows(1) && fia.allows(44));
+
+        assert!(matches!(auth.authenticate(None), Err(AuthError::MissingToken)));
+        assert!(matches!(auth.authenticate(Some("k-nope")), Err(AuthError::InvalidToken(_))));
+
+        // Authentication off: everyone sees everything
+        assert_eq!(AuthConfig::default().authenticate(None).unwrap(), AccessScope::unrestricted());
+        assert_eq!(bearer_token("Bearer k-mcl"), Some("k-mcl"));
+        assert_eq!(bearer_token("Basic abc"), None);
+    }
+
+    #[test]
+    fn test_jwt_verification() {
+        let auth = config();
+        let scope = auth.authenticate(Some(&token("pit-wall-secret", "ferrari", None, 600))).unwrap();
+        assert_eq!(scope.car_ids, Some(vec![16, 44]));
+
+        // Claims can narrow a team's cars but not widen them
+        let narrowed = token("pit-wall-secret", "ferrari", Some(vec![16, 4]), 600);
+        assert_eq!(auth.authenticate(Some(&narrowed)).unwrap().car_ids, Some(vec![16]));
+
+        for bad in [
+            token("wrong-secret", "ferrari", None, 600),
+            token("pit-wall-secret", "ferrari", None, -600),
+        ] {
+            assert!(matches!(auth.authenticate(Some(&bad)), Err(AuthError::InvalidToken(_))));
+        }
+        let unknown = token("pit-wall-secret", "haas", None, 600);
+        assert!(matches!(auth.authenticate(Some(&unknown)), Err(AuthError::UnknownTeam(team)) if team == "haas"));
+    }
+
+    #[test]
+    fn test_connection_limit() {
+        let limit = ConnectionLimit::new(2);
+        let first = limit.try_acquire().unwrap();
+        let _second = limit.try_acquire().unwrap();
+        assert!(limit.try_acquire().is_none());
+        assert_eq!(limit.open(), 2);
+
+        drop(first);
+        assert!(limit.try_acquire().is_some());
+        assert_eq!(limit.open(), 1);
+    }
+
+    #[test]
+    fn test_tls_config_loads_local_certificates() {
+        let dir = std::env::temp_dir();
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert_path = dir.join(format!("f1-nexus-cert-{}.pem", std::process::id()));
+        let key_path = dir.join(format!("f1-nexus-key-{}.pem", std::process::id()));
+        std::fs::write(&cert_path, cert.cert.pem()).unwrap();
+        std::fs::write(&key_path, cert.signing_key.serialize_pem()).unwrap();
+
+        assert!(TlsConfig::new(&cert_path, &key_path).server_config().is_ok());
+        assert!(matches!(
+            TlsConfig::new(dir.join("f1-nexus-missing.pem"), &key_path).server_config(),
+            Err(AuthError::Tls(_))
+        ));
+        assert!(matches!(TlsConfig::new(&key_path, &key_path).server_config(), Err(AuthError::Tls(_))));
+
+        std::fs::remove_file(&cert_path).ok();
+        std::fs::remove_file(&key_path).ok();
+    }
+}
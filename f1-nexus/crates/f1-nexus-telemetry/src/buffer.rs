@@ -1,4 +1,512 @@
 //! Ring buffer for telemetry data
+//!
+//! `TelemetryBuffer` keeps the recent history of every car as per-channel
+//! columns sorted by timestamp, bounded by a sample count and a retention
+//! window. It answers range queries by time or lap, min/max/mean
+//! downsampling and lap-aligned slices for overlays, and keeps the latest
+//! full snapshots so late-joining stream clients can backfill.
+//!
+//! Laps are assumed not to decrease within a car's stream.
 
-// Placeholder for ring buffer implementation
-pub struct TelemetryBuffer;
+use crate::Channel;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use f1_nexus_core::{CarId, LapNumber, SessionId, TelemetrySnapshot};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+/// Buffer configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BufferConfig {
+    /// Maximum channel samples kept per car
+    pub capacity: usize,
+
+    /// Samples older than this, relative to the car's newest sample, are dropped
+    pub retention: Duration,
+
+    /// Maximum full snapshots kept per car for backfill
+    pub snapshot_capacity: usize,
+
+    /// Buffered channels
+    pub channels: Vec<Channel>,
+}
+
+impl Default for BufferConfig {
+    fn default() -> Self {
+        BufferConfig {
+            capacity: 30_000,
+            retention: Duration::from_secs(60),
+            snapshot_capacity: 3_000,
+            channels: Channel::ALL.to_vec(),
+        }
+    }
+}
+
+/// One buffered channel value
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Sample {
+    pub timestamp: DateTime<Utc>,
+    pub lap: LapNumber,
+    pub value: f32,
+}
+
+/// Aggregate of the samples in one time bucket
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Bucket {
+    /// Bucket start
+    pub start: DateTime<Utc>,
+    pub count: usize,
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+}
+
+/// Sample positioned relative to the start of its lap
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AlignedSample {
+    /// Seconds since the lap's first sample
+    pub offset_secs: f32,
+    pub value: f32,
+}
+
+/// One lap of a channel, aligned to the lap start
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LapSlice {
+    pub lap: LapNumber,
+
+    /// Timestamp of the lap's first buffered sample
+    pub start: DateTime<Utc>,
+
+    /// Whether the whole lap is buffered (neither evicted nor in progress)
+    pub complete: bool,
+
+    pub samples: Vec<AlignedSample>,
+}
+
+/// Aggregate `samples` into consecutive buckets of `width`, starting at the
+/// first sample; empty buckets are skipped
+pub fn downsample(samples: &[Sample], width: Duration) -> Vec<Bucket> {
+    let Some(first) = samples.first() else {
+        return Vec::new();
+    };
+    let width_us = (width.as_micros() as i64).max(1);
+    let mut buckets: Vec<Bucket> = Vec::new();
+
+    for sample in samples {
+        let elapsed_us = (sample.timestamp - first.timestamp).num_microseconds().unwrap_or(i64::MAX);
+        let start = first.timestamp + chrono::Duration::microseconds(elapsed_us / width_us * width_us);
+        match buckets.last_mut() {
+            Some(bucket) if bucket.start == start => {
+                bucket.mean += (sample.value - bucket.mean) / (bucket.count + 1) as f32;
+                bucket.count += 1;
+                bucket.min = bucket.min.min(sample.value);
+                bucket.max = bucket.max.max(sample.value);
+            }
+            _ => buckets.push(Bucket {
+                start,
+                count: 1,
+                min: sample.value,
+                max: sample.value,
+                mean: sample.value,
+            }),
+        }
+    }
+    buckets
+}
+
+/// History of one car
+struct CarBuffer {
+    timestamps: VecDeque<DateTime<Utc>>,
+    laps: VecDeque<u16>,
+    columns: Vec<VecDeque<f32>>,
+    snapshots: VecDeque<TelemetrySnapshot>,
+}
+
+impl CarBuffer {
+    fn new(channels: usize) -> Self {
+        CarBuffer {
+            timestamps: VecDeque::new(),
+            laps: VecDeque::new(),
+            columns: (0..channels).map(|_| VecDeque::new()).collect(),
+            snapshots: VecDeque::new(),
+        }
+    }
+
+    fn push(&mut self, config: &BufferConfig, snapshot: &TelemetrySnapshot) {
+        // Late samples are inserted in timestamp order
+        let at = self.timestamps.partition_point(|t| *t <= snapshot.timestamp);
+        self.timestamps.insert(at, snapshot.timestamp);
+        self.laps.insert(at, snapshot.lap.0);
+        for (column, channel) in self.columns.iter_mut().zip(&config.channels) {
+            column.insert(at, channel.value(snapshot));
+        }
+
+        let at = self.snapshots.partition_point(|s| s.timestamp <= snapshot.timestamp);
+        self.snapshots.insert(at, snapshot.clone());
+
+        self.trim(config);
+    }
+
+    fn trim(&mut self, config: &BufferConfig) {
+        let Some(&newest) = self.timestamps.back() else {
+            return;
+        };
+        // A retention reaching past the representable range expires nothing
+        let cutoff = window_start(newest, config.retention).unwrap_or(DateTime::<Utc>::MIN_UTC);
+
+        let expired = self.timestamps.partition_point(|t| *t < cutoff);
+        let excess = self.timestamps.len().saturating_sub(config.capacity);
+        let drop = expired.max(excess);
+        self.timestamps.drain(..drop);
+        self.laps.drain(..drop);
+        for column in &mut self.columns {
+            column.drain(..drop);
+        }
+
+        let expired = self.snapshots.partition_point(|s| s.timestamp < cutoff);
+        let excess = self.snapshots.len().saturating_sub(config.snapshot_capacity);
+        self.snapshots.drain(..expired.max(excess));
+    }
+
+    fn samples(&self, column: usize, range: std::ops::Range<usize>) -> Vec<Sample> {
+        range
+            .map(|i| Sample {
+                timestamp: self.timestamps[i],
+                lap: LapNumber(self.laps[i]),
+                value: self.columns[column][i],
+            })
+            .collect()
+    }
+
+    fn time_range(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> std::ops::Range<usize> {
+        let start = self.timestamps.partition_point(|t| *t < from);
+        let end = self.timestamps.partition_point(|t| *t <= to);
+        start..end.max(start)
+    }
+
+    fn lap_range(&self, laps: &RangeInclusive<u16>) -> std::ops::Range<usize> {
+        let start = self.laps.partition_point(|lap| lap < laps.start());
+        let end = self.laps.partition_point(|lap| lap <= laps.end());
+        start..end.max(start)
+    }
+}
+
+/// Per-car, per-channel telemetry history
+pub struct TelemetryBuffer {
+    config: BufferConfig,
+    cars: DashMap<(SessionId, CarId), CarBuffer>,
+}
+
+impl TelemetryBuffer {
+    pub fn new(config: BufferConfig) -> Self {
+        TelemetryBuffer {
+            config,
+            cars: DashMap::new(),
+        }
+    }
+
+    /// Buffer configuration
+    pub fn config(&self) -> &BufferConfig {
+        &self.config
+    }
+
+    /// Append a snapshot
+    pub fn push(&self, snapshot: &TelemetrySnapshot) {
+        self.cars
+            .entry((snapshot.session_id, snapshot.car_id))
+            .or_insert_with(|| CarBuffer::new(self.config.channels.len()))
+            .push(&self.config, snapshot);
+    }
+
+    /// Number of buffered samples for a car
+    pub fn len(&self, session_id: SessionId, car_id: CarId) -> usize {
+        self.cars
+            .get(&(session_id, car_id))
+            .map_or(0, |car| car.timestamps.len())
+    }
+
+    /// Whether nothing is buffered
+    pub fn is_empty(&self) -> bool {
+        self.cars.iter().all(|car| car.timestamps.is_empty())
+    }
+
+    /// Cars with buffered data
+    pub fn cars(&self) -> Vec<(SessionId, CarId)> {
+        self.cars.iter().map(|entry| *entry.key()).collect()
+    }
+
+    /// Latest snapshot of a car
+    pub fn latest(&self, session_id: SessionId, car_id: CarId) -> Option<TelemetrySnapshot> {
+        self.cars.get(&(session_id, car_id))?.snapshots.back().cloned()
+    }
+
+    /// Samples of a channel with `from <= timestamp <= to`
+    pub fn range(
+        &self,
+        session_id: SessionId,
+        car_id: CarId,
+        channel: Channel,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Vec<Sample> {
+        let Some(column) = self.column(channel) else {
+            return Vec::new();
+        };
+        self.cars.get(&(session_id, car_id)).map_or_else(Vec::new, |car| {
+            let range = car.time_range(from, to);
+            car.samples(column, range)
+        })
+    }
+
+    /// Samples of a channel for the last `window` of the car's data
+    pub fn last(&self, session_id: SessionId, car_id: CarId, channel: Channel, window: Duration) -> Vec<Sample> {
+        let Some(newest) = self.cars.get(&(session_id, car_id)).and_then(|car| car.timestamps.back().copied()) else {
+            return Vec::new();
+        };
+        let from = window_start(newest, window).unwrap_or(DateTime::<Utc>::MIN_UTC);
+        self.range(session_id, car_id, channel, from, newest)
+    }
+
+    /// Newest buffered snapshot of a car taken before `timestamp`
+    pub fn before(&self, session_id: SessionId, car_id: CarId, timestamp: DateTime<Utc>) -> Option<TelemetrySnapshot> {
+        let car = self.cars.get(&(session_id, car_id))?;
+        let at = car.snapshots.partition_point(|s| s.timestamp < timestamp);
+        at.checked_sub(1).map(|i| car.snapshots[i].clone())
+    }
+
+    /// Samples of a channel within a range of laps
+    pub fn laps(
+        &self,
+        session_id: SessionId,
+        car_id: CarId,
+        channel: Channel,
+        laps: RangeInclusive<u16>,
+    ) -> Vec<Sample> {
+        let Some(column) = self.column(channel) else {
+            return Vec::new();
+        };
+        self.cars.get(&(session_id, car_id)).map_or_else(Vec::new, |car| {
+            let range = car.lap_range(&laps);
+            car.samples(column, range)
+        })
+    }
+
+    /// Time range query aggregated into buckets of `width`
+    pub fn downsampled(
+        &self,
+        session_id: SessionId,
+        car_id: CarId,
+        channel: Channel,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        width: Duration,
+    ) -> Vec<Bucket> {
+        downsample(&self.range(session_id, car_id, channel, from, to), width)
+    }
+
+    /// One slice per lap, each aligned to its lap's first sample
+    pub fn lap_slices(
+        &self,
+        session_id: SessionId,
+        car_id: CarId,
+        channel: Channel,
+        laps: RangeInclusive<u16>,
+    ) -> Vec<LapSlice> {
+        let Some(column) = self.column(channel) else {
+            return Vec::new();
+        };
+        let Some(car) = self.cars.get(&(session_id, car_id)) else {
+            return Vec::new();
+        };
+        let (Some(&first_lap), Some(&last_lap)) = (car.laps.front(), car.laps.back()) else {
+            return Vec::new();
+        };
+
+        let mut slices = Vec::new();
+        let mut range = car.lap_range(&laps);
+        while !range.is_empty() {
+            let lap = car.laps[range.start];
+            let end = range.start + car.laps.range(range.clone()).take_while(|l| **l == lap).count();
+            let start = car.timestamps[range.start];
+            slices.push(LapSlice {
+                lap: LapNumber(lap),
+                start,
+                complete: lap != first_lap && lap != last_lap,
+                samples: (range.start..end)
+                    .map(|i| AlignedSample {
+                        offset_secs: (car.timestamps[i] - start).num_microseconds().unwrap_or(0) as f32 / 1.0e6,
+                        value: car.columns[column][i],
+                    })
+                    .collect(),
+            });
+            range.start = end;
+        }
+        slices
+    }
+
+    /// Snapshots from the last `window` of each matching car, oldest first
+    pub fn backfill(&self, window: Duration, include: impl Fn(SessionId, CarId) -> bool) -> Vec<TelemetrySnapshot> {
+        let mut snapshots: Vec<TelemetrySnapshot> = self
+            .cars
+            .iter()
+            .filter(|entry| include(entry.key().0, entry.key().1))
+            .flat_map(|entry| {
+                let snapshots = &entry.value().snapshots;
+                let from = snapshots.back().and_then(|s| window_start(s.timestamp, window));
+                snapshots
+                    .iter()
+                    .filter(|s| from.map_or(true, |from| s.timestamp >= from))
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        snapshots.sort_by_key(|s| s.timestamp);
+        snapshots
+    }
+
+    /// Drop every car of a session; returns the number removed
+    pub fn evict_session(&self, session_id: SessionId) -> usize {
+        let mut removed = 0;
+        self.cars.retain(|(session, _), _| {
+            let evict = *session == session_id;
+            removed += usize::from(evict);
+            !evict
+        });
+        removed
+    }
+
+    /// Drop one car of a session; returns whether it was buffered
+    pub fn evict_car(&self, session_id: SessionId, car_id: CarId) -> bool {
+        self.cars.remove(&(session_id, car_id)).is_some()
+    }
+
+    fn column(&self, channel: Channel) -> Option<usize> {
+        self.config.channels.iter().position(|c| *c == channel)
+    }
+}
+
+/// Start of the `window` ending at `newest`; `None` when it reaches past the
+/// representable time range
+fn window_start(newest: DateTime<Utc>, window: Duration) -> Option<DateTime<Utc>> {
+    chrono::Duration::from_std(window)
+        .ok()
+        .and_then(|window| newest.checked_sub_signed(window))
+}
+
+impl Default for TelemetryBuffer {
+    fn default() -> Self {
+        Self::new(BufferConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::tests::create_test_snapshot;
+
+    /// Three laps of 10 s sampled at 10 Hz with speed ramping 200 → 299
+    fn fill(buffer: &TelemetryBuffer) -> TelemetrySnapshot {
+        let mut snapshot = create_test_snapshot();
+        let start = snapshot.timestamp;
+        for i in 0..300 {
+            snapshot.timestamp = start + chrono::Duration::milliseconds(i * 100);
+            snapshot.lap = LapNumber(1 + (i / 100) as u16);
+            snapshot.motion.speed = 200.0 + (i % 100) as f32;
+            buffer.push(&snapshot);
+        }
+        snapshot
+    }
+
+    #[test]
+    fn test_range_and_lap_queries() {
+        let buffer = TelemetryBuffer::default();
+        let last = fill(&buffer);
+        let (session, car) = (last.session_id, last.car_id);
+        assert_eq!(buffer.len(session, car), 300);
+
+        let recent = buffer.last(session, car, Channel::Speed, Duration::from_secs(1));
+        assert_eq!(recent.len(), 11);
+        assert_eq!(recent.last().unwrap().value, 299.0);
+
+        let lap_two = buffer.laps(session, car, Channel::Speed, 2..=2);
+        assert_eq!(lap_two.len(), 100);
+        assert!(lap_two.iter().all(|s| s.lap == LapNumber(2)));
+
+        // Late sample lands in timestamp order
+        let mut late = last.clone();
+        late.timestamp = last.timestamp - chrono::Duration::milliseconds(50);
+        late.motion.speed = 1.0;
+        buffer.push(&late);
+        let recent = buffer.last(session, car, Channel::Speed, Duration::from_millis(100));
+        assert_eq!(recent.iter().map(|s| s.value).collect::<Vec<_>>(), vec![298.0, 1.0, 299.0]);
+    }
+
+    #[test]
+    fn test_retention_and_capacity() {
+        let buffer = TelemetryBuffer::new(BufferConfig {
+            retention: Duration::from_secs(5),
+            snapshot_capacity: 10,
+            ..Default::default()
+        });
+        let last = fill(&buffer);
+        assert_eq!(buffer.len(last.session_id, last.car_id), 51);
+        assert_eq!(buffer.backfill(Duration::from_secs(60), |_, _| true).len(), 10);
+
+        let buffer = TelemetryBuffer::new(BufferConfig {
+            capacity: 50,
+            ..Default::default()
+        });
+        let last = fill(&buffer);
+        assert_eq!(buffer.len(last.session_id, last.car_id), 50);
+
+        // Windows beyond the representable time range mean "everything"
+        let buffer = TelemetryBuffer::new(BufferConfig {
+            retention: Duration::MAX,
+            ..Default::default()
+        });
+        let last = fill(&buffer);
+        assert_eq!(buffer.len(last.session_id, last.car_id), 300);
+        assert_eq!(buffer.last(last.session_id, last.car_id, Channel::Speed, Duration::MAX).len(), 300);
+        assert_eq!(buffer.backfill(Duration::MAX, |_, _| true).len(), 300);
+    }
+
+    #[test]
+    fn test_downsample() {
+        let buffer = TelemetryBuffer::default();
+        let last = fill(&buffer);
+        let from = last.timestamp - chrono::Duration::seconds(30);
+        let buckets = buffer.downsampled(last.session_id, last.car_id, Channel::Speed, from, last.timestamp, Duration::from_secs(1));
+
+        assert_eq!(buckets.len(), 30);
+        assert_eq!(buckets[0].count, 10);
+        assert_eq!((buckets[0].min, buckets[0].max), (200.0, 209.0));
+        assert!((buckets[0].mean - 204.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_lap_slices_and_backfill() {
+        let buffer = TelemetryBuffer::default();
+        let last = fill(&buffer);
+        let slices = buffer.lap_slices(last.session_id, last.car_id, Channel::Speed, 1..=3);
+
+        assert_eq!(slices.len(), 3);
+        assert_eq!(slices.iter().map(|s| s.complete).collect::<Vec<_>>(), vec![false, true, false]);
+        let lap_two = &slices[1];
+        assert_eq!(lap_two.samples[0].offset_secs, 0.0);
+        assert!((lap_two.samples[50].offset_secs - 5.0).abs() < 1e-4);
+        assert_eq!(lap_two.samples[50].value, slices[0].samples[50].value);
+
+        let mut other = last.clone();
+        other.car_id = CarId::new(2).unwrap();
+        buffer.push(&other);
+        let backfill = buffer.backfill(Duration::from_secs(2), |_, car| car.0 == 1);
+        assert_eq!(backfill.len(), 21);
+        assert!(backfill.windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+        assert_eq!(buffer.backfill(Duration::from_secs(2), |_, _| true).len(), 22);
+
+        assert_eq!(buffer.evict_session(last.session_id), 2);
+        assert!(buffer.is_empty());
+    }
+}
@@ -0,0 +1,634 @@
+//! Wire encodings for the telemetry stream
+//!
+//! Clients pick an encoding when connecting (`?encoding=json|msgpack|cbor|delta`)
+//! and may ask for compression (`?compress=true`). `StreamEncoder` is
+//! the server side of one connection and `StreamDecoder` the client side;
+//! both keep per-connection state, so frames must be decoded in the order
+//! they were sent.
+//!
+//! Compression is done by the application, not the WebSocket layer: this is
+//! not the permessage-deflate extension, and nothing is negotiated in the
+//! handshake. Each frame payload is raw DEFLATE with a sliding window shared
+//! across the connection's messages and the sync-flush trailer `00 00 ff ff`
+//! removed, and the client must inflate it itself. Compressed frames are
+//! always binary.
+
+use crate::stream::telemetry_message;
+use crate::{StreamError, StreamMessage};
+use f1_nexus_core::TelemetrySnapshot;
+use flate2::{Compress, Decompress, FlushCompress, FlushDecompress, Status};
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+/// Message encoding of a stream connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamEncoding {
+    /// JSON text frames
+    #[default]
+    Json,
+
+    /// MessagePack binary frames
+    #[serde(rename = "msgpack")]
+    MessagePack,
+
+    /// CBOR binary frames
+    Cbor,
+
+    /// Binary frames carrying only the telemetry fields that changed since
+    /// the previous snapshot sent for the same car
+    Delta,
+}
+
+impl StreamEncoding {
+    /// Name used in the `encoding` query parameter
+    pub fn name(&self) -> &'static str {
+        match self {
+            StreamEncoding::Json => "json",
+            StreamEncoding::MessagePack => "msgpack",
+            StreamEncoding::Cbor => "cbor",
+            StreamEncoding::Delta => "delta",
+        }
+    }
+}
+
+/// One encoded WebSocket frame
+#[derive(Debug, Clone, PartialEq)]
+pub enum Frame {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl Frame {
+    /// Payload size in bytes
+    pub fn len(&self) -> usize {
+        match self {
+            Frame::Text(text) => text.len(),
+            Frame::Binary(bytes) => bytes.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Frame::Text(text) => text.as_bytes(),
+            Frame::Binary(bytes) => bytes,
+        }
+    }
+}
+
+/// Delta frame kinds (first byte of a `Delta` frame)
+const FRAME_MESSAGE: u8 = 0;
+const FRAME_KEYFRAME: u8 = 1;
+const FRAME_DELTA: u8 = 2;
+
+/// Sync-flush trailer removed from each compressed message
+const DEFLATE_TRAILER: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+/// Largest message a compressed frame may inflate to
+const MAX_INFLATED_BYTES: usize = 64 * 1024 * 1024;
+
+/// Snapshot flattened to leaf values in path order
+#[derive(Debug, Clone, PartialEq)]
+struct FlatSnapshot {
+    paths: Vec<String>,
+    values: Vec<Value>,
+}
+
+impl FlatSnapshot {
+    fn from_snapshot(snapshot: &TelemetrySnapshot) -> Result<Self, StreamError> {
+        let value = serde_json::to_value(snapshot).map_err(serialization)?;
+        let mut flat = FlatSnapshot {
+            paths: Vec::new(),
+            values: Vec::new(),
+        };
+        flat.collect(String::new(), value);
+        Ok(flat)
+    }
+
+    fn collect(&mut self, prefix: String, value: Value) {
+        match value {
+            Value::Object(map) if !map.is_empty() => {
+                for (key, value) in map {
+                    let path = if prefix.is_empty() { key } else { format!("{}.{}", prefix, key) };
+                    self.collect(path, value);
+                }
+            }
+            leaf => {
+                self.paths.push(prefix);
+                self.values.push(leaf);
+            }
+        }
+    }
+
+    fn to_snapshot(&self) -> Result<TelemetrySnapshot, StreamError> {
+        let mut root = serde_json::Map::new();
+        for (path, value) in self.paths.iter().zip(&self.values) {
+            let mut node = &mut root;
+            let mut keys = path.split('.').peekable();
+            while let Some(key) = keys.next() {
+                if keys.peek().is_none() {
+                    node.insert(key.to_string(), value.clone());
+                } else {
+                    node = node
+                        .entry(key)
+                        .or_insert_with(|| Value::Object(serde_json::Map::new()))
+                        .as_object_mut()
+                        .ok_or_else(|| StreamError::SerializationError(format!("conflicting path '{}'", path)))?;
+                }
+            }
+        }
+        serde_json::from_value(Value::Object(root)).map_err(serialization)
+    }
+}
+
+/// Binary form of a message
+///
+/// The JSON form of `StreamMessage::Telemetry` repeats `session_id` and
+/// `car_id` next to the flattened snapshot, which serde cannot read back, so
+/// binary encodings carry the snapshot on its own.
+#[derive(Serialize)]
+enum WireRef<'a> {
+    Telemetry(&'a TelemetrySnapshot),
+    Message(&'a StreamMessage),
+}
+
+#[derive(Deserialize)]
+enum Wire {
+    Telemetry(TelemetrySnapshot),
+    Message(StreamMessage),
+}
+
+impl<'a> From<&'a StreamMessage> for WireRef<'a> {
+    fn from(msg: &'a StreamMessage) -> Self {
+        match msg {
+            StreamMessage::Telemetry { snapshot, .. } => WireRef::Telemetry(snapshot),
+            msg => WireRef::Message(msg),
+        }
+    }
+}
+
+impl From<Wire> for StreamMessage {
+    fn from(wire: Wire) -> Self {
+        match wire {
+            Wire::Telemetry(snapshot) => telemetry_message(snapshot),
+            Wire::Message(msg) => msg,
+        }
+    }
+}
+
+/// Keyframe payload: full snapshot plus the id later deltas refer to
+#[derive(Serialize, Deserialize)]
+struct KeyFrame {
+    stream: u16,
+    snapshot: TelemetrySnapshot,
+}
+
+/// Delta payload: changed leaf values by index into the keyframe's paths
+#[derive(Serialize, Deserialize)]
+struct DeltaFrame {
+    stream: u16,
+    changes: Vec<(u16, Value)>,
+}
+
+/// Per-car state of the delta encoder
+struct CarStream {
+    id: u16,
+    last: FlatSnapshot,
+    since_keyframe: u32,
+}
+
+/// Server side of one connection
+pub struct StreamEncoder {
+    encoding: StreamEncoding,
+    compress: Option<Compress>,
+    keyframe_interval: u32,
+    streams: HashMap<(String, u8), CarStream>,
+}
+
+impl StreamEncoder {
+    /// Encoder for `encoding`, compressing frames when `compress` is set
+    pub fn new(encoding: StreamEncoding, compress: bool) -> Self {
+        StreamEncoder {
+            encoding,
+            compress: compress.then(|| Compress::new(flate2::Compression::fast(), false)),
+            keyframe_interval: 250,
+            streams: HashMap::new(),
+        }
+    }
+
+    /// Send a full snapshot every `interval` telemetry frames per car
+    pub fn with_keyframe_interval(mut self, interval: u32) -> Self {
+        self.keyframe_interval = interval.max(1);
+        self
+    }
+
+    pub fn encoding(&self) -> StreamEncoding {
+        self.encoding
+    }
+
+    /// Encode the next message of this connection
+    pub fn encode(&mut self, msg: &StreamMessage) -> Result<Frame, StreamError> {
+        let frame = match self.encoding {
+            StreamEncoding::Json => Frame::Text(serde_json::to_string(msg).map_err(serialization)?),
+            StreamEncoding::MessagePack => Frame::Binary(to_msgpack(&WireRef::from(msg))?),
+            StreamEncoding::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::into_writer(&WireRef::from(msg), &mut bytes).map_err(serialization)?;
+                Frame::Binary(bytes)
+            }
+            StreamEncoding::Delta => Frame::Binary(self.encode_delta(msg)?),
+        };
+
+        match &mut self.compress {
+            Some(compress) => Ok(Frame::Binary(deflate(compress, frame.as_bytes())?)),
+            None => Ok(frame),
+        }
+    }
+
+    fn encode_delta(&mut self, msg: &StreamMessage) -> Result<Vec<u8>, StreamError> {
+        let StreamMessage::Telemetry { session_id, car_id, snapshot } = msg else {
+            return Ok(prefixed(FRAME_MESSAGE, &to_msgpack(msg)?));
+        };
+
+        let flat = FlatSnapshot::from_snapshot(snapshot)?;
+        let next_id = self.streams.len() as u16;
+        let interval = self.keyframe_interval;
+        let key = (session_id.clone(), *car_id);
+
+        if let Some(car) = self.streams.get_mut(&key) {
+            car.since_keyframe += 1;
+            if car.last.paths == flat.paths && car.since_keyframe < interval {
+                let changes = flat
+                    .values
+                    .iter()
+                    .zip(&car.last.values)
+                    .enumerate()
+                    .filter(|(_, (new, old))| new != old)
+                    .map(|(i, (new, _))| (i as u16, new.clone()))
+                    .collect();
+                car.last = flat;
+                let delta = DeltaFrame { stream: car.id, changes };
+                return Ok(prefixed(FRAME_DELTA, &to_msgpack(&delta)?));
+            }
+            car.last = flat;
+            car.since_keyframe = 0;
+            let keyframe = KeyFrame { stream: car.id, snapshot: snapshot.clone() };
+            return Ok(prefixed(FRAME_KEYFRAME, &to_msgpack(&keyframe)?));
+        }
+
+        self.streams.insert(
+            key,
+            CarStream {
+                id: next_id,
+                last: flat,
+                since_keyframe: 0,
+            },
+        );
+        let keyframe = KeyFrame { stream: next_id, snapshot: snapshot.clone() };
+        Ok(prefixed(FRAME_KEYFRAME, &to_msgpack(&keyframe)?))
+    }
+}
+
+/// Client side of one connection
+pub struct StreamDecoder {
+    encoding: StreamEncoding,
+    inflate: Option<Decompress>,
+    streams: HashMap<u16, FlatSnapshot>,
+}
+
+impl StreamDecoder {
+    /// Decoder for `encoding`, inflating frames when `compress` is set
+    pub fn new(encoding: StreamEncoding, compress: bool) -> Self {
+        StreamDecoder {
+            encoding,
+            inflate: compress.then(|| Decompress::new(false)),
+            streams: HashMap::new(),
+        }
+    }
+
+    /// Decode the next frame of this connection
+    pub fn decode(&mut self, frame: &[u8]) -> Result<StreamMessage, StreamError> {
+        let inflated;
+        let bytes = match &mut self.inflate {
+            Some(decompress) => {
+                inflated = inflate(decompress, frame, MAX_INFLATED_BYTES)?;
+                &inflated[..]
+            }
+            None => frame,
+        };
+
+        match self.encoding {
+            StreamEncoding::Json => decode_json(bytes),
+            StreamEncoding::MessagePack => rmp_serde::from_slice::<Wire>(bytes).map(Into::into).map_err(serialization),
+            StreamEncoding::Cbor => ciborium::from_reader::<Wire, _>(bytes).map(Into::into).map_err(serialization),
+            StreamEncoding::Delta => self.decode_delta(bytes),
+        }
+    }
+
+    fn decode_delta(&mut self, bytes: &[u8]) -> Result<StreamMessage, StreamError> {
+        let (&kind, payload) = bytes
+            .split_first()
+            .ok_or_else(|| StreamError::SerializationError("empty delta frame".to_string()))?;
+        match kind {
+            FRAME_MESSAGE => rmp_serde::from_slice(payload).map_err(serialization),
+            FRAME_KEYFRAME => {
+                let keyframe: KeyFrame = rmp_serde::from_slice(payload).map_err(serialization)?;
+                let flat = FlatSnapshot::from_snapshot(&keyframe.snapshot)?;
+                self.streams.insert(keyframe.stream, flat);
+                Ok(telemetry_message(keyframe.snapshot))
+            }
+            FRAME_DELTA => {
+                let delta: DeltaFrame = rmp_serde::from_slice(payload).map_err(serialization)?;
+                let flat = self.streams.get_mut(&delta.stream).ok_or_else(|| {
+                    StreamError::SerializationError(format!("delta for unknown stream {}", delta.stream))
+                })?;
+                for (index, value) in delta.changes {
+                    let slot = flat.values.get_mut(index as usize).ok_or_else(|| {
+                        StreamError::SerializationError(format!("delta field {} out of range", index))
+                    })?;
+                    *slot = value;
+                }
+                Ok(telemetry_message(flat.to_snapshot()?))
+            }
+            other => Err(StreamError::SerializationError(format!("unknown delta frame kind {}", other))),
+        }
+    }
+}
+
+/// Rust client for `TelemetryStreamServer`
+pub struct StreamClient {
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    decoder: StreamDecoder,
+}
+
+impl StreamClient {
+    /// Connect to a `/ws/telemetry` endpoint, requesting `encoding` and
+    /// optionally compression
+    pub async fn connect(url: &str, encoding: StreamEncoding, compress: bool) -> Result<Self, StreamError> {
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let url = format!("{}{}encoding={}&compress={}", url, separator, encoding.name(), compress);
+        let (socket, _) = tokio_tungstenite::connect_async(url.as_str())
+            .await
+            .map_err(|e| StreamError::ConnectionError(e.to_string()))?;
+        Ok(StreamClient {
+            socket,
+            decoder: StreamDecoder::new(encoding, compress),
+        })
+    }
+
+    /// Send a request, always as JSON
+    pub async fn send(&mut self, request: &crate::ClientRequest) -> Result<(), StreamError> {
+        let json = serde_json::to_string(request).map_err(serialization)?;
+        self.socket
+            .send(WsMessage::Text(json))
+            .await
+            .map_err(|e| StreamError::ConnectionError(e.to_string()))
+    }
+
+    /// Next message from the server; `None` once the connection closes
+    pub async fn next(&mut self) -> Option<Result<StreamMessage, StreamError>> {
+        loop {
+            let frame = match self.socket.next().await? {
+                Ok(frame) => frame,
+                Err(e) => return Some(Err(StreamError::ConnectionError(e.to_string()))),
+            };
+            match frame {
+                WsMessage::Text(text) => return Some(self.decoder.decode(text.as_bytes())),
+                WsMessage::Binary(bytes) => return Some(self.decoder.decode(&bytes)),
+                WsMessage::Close(_) => return None,
+                _ => continue,
+            }
+        }
+    }
+}
+
+/// JSON telemetry repeats `session_id` and `car_id`; reading it as a map
+/// first keeps one of each
+fn decode_json(bytes: &[u8]) -> Result<StreamMessage, StreamError> {
+    let value: Value = serde_json::from_slice(bytes).map_err(serialization)?;
+    if value.get("type").and_then(Value::as_str) == Some("telemetry") {
+        return serde_json::from_value(value).map(telemetry_message).map_err(serialization);
+    }
+    serde_json::from_value(value).map_err(serialization)
+}
+
+fn serialization(e: impl std::fmt::Display) -> StreamError {
+    StreamError::SerializationError(e.to_string())
+}
+
+fn to_msgpack<T: Serialize>(value: &T) -> Result<Vec<u8>, StreamError> {
+    rmp_serde::to_vec_named(value).map_err(serialization)
+}
+
+fn prefixed(kind: u8, payload: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(payload.len() + 1);
+    bytes.push(kind);
+    bytes.extend_from_slice(payload);
+    bytes
+}
+
+fn deflate(compress: &mut Compress, input: &[u8]) -> Result<Vec<u8>, StreamError> {
+    let mut out = Vec::with_capacity(input.len() / 2 + 64);
+    let start_in = compress.total_in();
+    loop {
+        let consumed = (compress.total_in() - start_in) as usize;
+        compress
+            .compress_vec(&input[consumed..], &mut out, FlushCompress::Sync)
+            .map_err(serialization)?;
+        // Sync flush is complete once all input is consumed and output space remains
+        if (compress.total_in() - start_in) as usize == input.len() && out.len() < out.capacity() {
+            break;
+        }
+        out.reserve(out.capacity().max(64));
+    }
+    if out.ends_with(&DEFLATE_TRAILER) {
+        out.truncate(out.len() - DEFLATE_TRAILER.len());
+    }
+    Ok(out)
+}
+
+/// Inflate one frame, failing if it would exceed `limit` bytes
+fn inflate(decompress: &mut Decompress, input: &[u8], limit: usize) -> Result<Vec<u8>, StreamError> {
+    let mut data = Vec::with_capacity(input.len() + DEFLATE_TRAILER.len());
+    data.extend_from_slice(input);
+    data.extend_from_slice(&DEFLATE_TRAILER);
+
+    let mut out = Vec::with_capacity((input.len() * 4 + 64).min(limit));
+    let start_in = decompress.total_in();
+    loop {
+        let (before_in, before_out) = (decompress.total_in(), decompress.total_out());
+        let consumed = (before_in - start_in) as usize;
+        let status = decompress
+            .decompress_vec(&data[consumed..], &mut out, FlushDecompress::Sync)
+            .map_err(serialization)?;
+        // Bytes after the end of the stream are ignored
+        if status == Status::StreamEnd {
+            break;
+        }
+        if (decompress.total_in() - start_in) as usize == data.len() && out.len() < out.capacity() {
+            break;
+        }
+        if (decompress.total_in(), decompress.total_out()) == (before_in, before_out) {
+            return Err(serialization("compressed frame is truncated"));
+        }
+        if out.len() >= limit {
+            return Err(serialization(format!("frame inflates past {} bytes", limit)));
+        }
+        let room = out.capacity().max(64).min(limit - out.len());
+        out.reserve(room);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::tests::create_test_snapshot;
+    use chrono::Utc;
+    use f1_nexus_core::CarId;
+
+    /// Two cars at 1 kHz, with the fields that move between samples changing
+    fn messages(samples: i64) -> Vec<StreamMessage> {
+        let start = Utc::now();
+        let template = create_test_snapshot();
+        let mut messages = vec![StreamMessage::SessionStart {
+            session_id: template.session_id.0.to_string(),
+            timestamp: start.to_rfc3339(),
+        }];
+        for ms in 0..samples {
+            for car in [1, 2] {
+                let mut snapshot = template.clone();
+                snapshot.car_id = CarId(car);
+                snapshot.timestamp = start + chrono::Duration::milliseconds(ms);
+                snapshot.motion.speed = 250.0 + ms as f32 * 0.1;
+                snapshot.inputs.throttle = if ms % 50 < 25 { 1.0 } else { 0.8 };
+                snapshot.tires.front_left.surface_temp = 95.0 + (ms / 10) as f32 * 0.1;
+                messages.push(telemetry_message(snapshot));
+            }
+        }
+        messages.push(StreamMessage::Gap { missed: 3 });
+        messages.push(StreamMessage::TelemetryFields {
+            session_id: template.session_id.0.to_string(),
+            car_id: 1,
+            timestamp: start.to_rfc3339(),
+            lap: 1,
+            values: [("speed".to_string(), 251.5)].into_iter().collect(),
+        });
+        messages
+    }
+
+    fn roundtrip(encoding: StreamEncoding, compress: bool, messages: &[StreamMessage]) -> usize {
+        let mut encoder = StreamEncoder::new(encoding, compress).with_keyframe_interval(50);
+        let mut decoder = StreamDecoder::new(encoding, compress);
+        let mut bytes = 0;
+        for msg in messages {
+            let frame = encoder.encode(msg).unwrap();
+            assert_eq!(matches!(frame, Frame::Text(_)), encoding == StreamEncoding::Json && !compress);
+            bytes += frame.len();
+            let decoded = decoder.decode(frame.as_bytes()).unwrap();
+            assert_eq!(
+                serde_json::to_value(&decoded).unwrap(),
+                serde_json::to_value(msg).unwrap(),
+                "{:?} compress={} changed the message",
+                encoding,
+                compress
+            );
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_every_encoding_roundtrips() {
+        let messages = messages(120);
+        for encoding in [
+            StreamEncoding::Json,
+            StreamEncoding::MessagePack,
+            StreamEncoding::Cbor,
+            StreamEncoding::Delta,
+        ] {
+            for compress in [false, true] {
+                roundtrip(encoding, compress, &messages);
+            }
+        }
+    }
+
+    #[test]
+    fn test_compact_encodings_are_smaller() {
+        let messages = messages(200);
+        let json = roundtrip(StreamEncoding::Json, false, &messages);
+        let msgpack = roundtrip(StreamEncoding::MessagePack, false, &messages);
+        let delta = roundtrip(StreamEncoding::Delta, false, &messages);
+        let delta_deflate = roundtrip(StreamEncoding::Delta, true, &messages);
+        let json_deflate = roundtrip(StreamEncoding::Json, true, &messages);
+
+        assert!(msgpack < json, "msgpack {} vs json {}", msgpack, json);
+        assert!(delta * 10 < json, "delta {} vs json {}", delta, json);
+        assert!(json_deflate * 5 < json, "deflate {} vs json {}", json_deflate, json);
+        assert!(delta_deflate < delta, "deflate {} vs delta {}", delta_deflate, delta);
+    }
+
+    #[test]
+    fn test_delta_frames_carry_changed_fields_only() {
+        let messages = messages(2);
+        let mut encoder = StreamEncoder::new(StreamEncoding::Delta, false);
+        let kinds: Vec<u8> = messages
+            .iter()
+            .map(|msg| match encoder.encode(msg).unwrap() {
+                Frame::Binary(bytes) => bytes[0],
+                Frame::Text(_) => panic!("delta frames are binary"),
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![FRAME_MESSAGE, FRAME_KEYFRAME, FRAME_KEYFRAME, FRAME_DELTA, FRAME_DELTA, FRAME_MESSAGE, FRAME_MESSAGE]
+        );
+
+        let Frame::Binary(bytes) = encoder.encode(&messages[3]).unwrap() else {
+            unreachable!()
+        };
+        let delta: DeltaFrame = rmp_serde::from_slice(&bytes[1..]).unwrap();
+        // Same snapshot again: nothing changed
+        assert!(delta.changes.is_empty());
+
+        // A delta without its keyframe cannot be decoded
+        let mut decoder = StreamDecoder::new(StreamEncoding::Delta, false);
+        assert!(decoder.decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_inflate_terminates_on_hostile_frames() {
+        let finished = |data: &[u8]| {
+            let mut compress = Compress::new(flate2::Compression::fast(), false);
+            let mut out = Vec::with_capacity(data.len() + 64);
+            compress.compress_vec(data, &mut out, FlushCompress::Finish).unwrap();
+            out
+        };
+
+        // A finished stream followed by junk ends at the end of the stream
+        let mut frame = finished(b"hello");
+        frame.extend_from_slice(b"trailing junk");
+        let out = inflate(&mut Decompress::new(false), &frame, MAX_INFLATED_BYTES).unwrap();
+        assert_eq!(out, b"hello");
+
+        // Output beyond the limit is refused
+        let bomb = finished(&[0u8; 100_000]);
+        assert!(inflate(&mut Decompress::new(false), &bomb, 1024).is_err());
+
+        // A stream cut off mid-block fails instead of spinning
+        let mut encoder = StreamEncoder::new(StreamEncoding::Json, true);
+        let Frame::Binary(bytes) = encoder.encode(&messages(1)[1]).unwrap() else {
+            unreachable!()
+        };
+        let mut decoder = StreamDecoder::new(StreamEncoding::Json, true);
+        assert!(decoder.decode(&bytes[..bytes.len() / 2]).is_err());
+    }
+}
@@ -0,0 +1,714 @@
+//! Distance-aligned lap comparison
+//!
+//! Two laps (same car or different cars) are resampled onto a common
+//! distance grid, giving a cumulative delta-time trace with speed, throttle
+//! and brake overlays. Corners are located on the reference lap from its
+//! speed trace (or supplied explicitly) and numbered in track order, so each
+//! one can be matched against the circuit's `Corner` definitions. Per corner
+//! the comparison reports the time gained or lost along with the minimum
+//! speed, braking point and throttle pickup point of both laps.
+
+use crate::buffer::TelemetryBuffer;
+use crate::rules::Channel;
+use f1_nexus_core::{CarId, Circuit, LapNumber, SessionId, TelemetrySnapshot};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Most points a comparison grid may hold
+const MAX_GRID_POINTS: f32 = 1.0e6;
+
+/// One sample of a lap, positioned by distance
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TracePoint {
+    /// Distance from the start of the lap (m)
+    pub distance: f32,
+
+    /// Seconds since the start of the lap
+    pub time: f32,
+
+    /// Speed (km/h)
+    pub speed: f32,
+
+    /// Throttle (0.0-1.0)
+    pub throttle: f32,
+
+    /// Brake (0.0-1.0)
+    pub brake: f32,
+}
+
+/// A single lap as a distance-ordered trace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LapTrace {
+    pub car_id: CarId,
+    pub lap: LapNumber,
+    pub points: Vec<TracePoint>,
+}
+
+impl LapTrace {
+    /// Build a trace from points with known distances
+    ///
+    /// Points are sorted by distance.
+    pub fn new(car_id: CarId, lap: LapNumber, mut points: Vec<TracePoint>) -> Self {
+        points.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        LapTrace { car_id, lap, points }
+    }
+
+    /// Build a trace from one lap of snapshots, integrating distance from speed
+    pub fn from_snapshots(snapshots: &[TelemetrySnapshot]) -> Result<Self, ComparisonError> {
+        let first = snapshots.first().ok_or(ComparisonError::NotEnoughSamples)?;
+        let mut points = Vec::with_capacity(snapshots.len());
+        let mut distance = 0.0;
+        let mut previous: Option<(f32, f32)> = None;
+
+        for snapshot in snapshots {
+            let time = (snapshot.timestamp - first.timestamp).num_microseconds().unwrap_or(0) as f32 / 1.0e6;
+            if let Some((last_time, last_speed)) = previous {
+                distance += 0.5 * (last_speed + snapshot.motion.speed) / 3.6 * (time - last_time).max(0.0);
+            }
+            previous = Some((time, snapshot.motion.speed));
+            points.push(TracePoint {
+                distance,
+                time,
+                speed: snapshot.motion.speed,
+                throttle: snapshot.inputs.throttle,
+                brake: snapshot.inputs.brake,
+            });
+        }
+
+        Self::checked(LapTrace {
+            car_id: first.car_id,
+            lap: first.lap,
+            points,
+        })
+    }
+
+    /// Build a trace from a lap held in a telemetry buffer
+    ///
+    /// The buffer must track the speed, throttle and brake channels and hold
+    /// the whole lap.
+    pub fn from_buffer(
+        buffer: &TelemetryBuffer,
+        session_id: SessionId,
+        car_id: CarId,
+        lap: LapNumber,
+    ) -> Result<Self, ComparisonError> {
+        let slice = |channel| {
+            buffer
+                .lap_slices(session_id, car_id, channel, lap.0..=lap.0)
+                .into_iter()
+                .next()
+                .filter(|slice| slice.complete)
+                .ok_or(ComparisonError::LapNotAvailable { car_id, lap })
+        };
+        let speed = slice(Channel::Speed)?;
+        let throttle = slice(Channel::Throttle)?;
+        let brake = slice(Channel::Brake)?;
+
+        let mut points = Vec::with_capacity(speed.samples.len());
+        let mut distance = 0.0;
+        for (i, sample) in speed.samples.iter().enumerate() {
+            if let Some(last) = i.checked_sub(1).map(|j| speed.samples[j]) {
+                distance += 0.5 * (last.value + sample.value) / 3.6 * (sample.offset_secs - last.offset_secs);
+            }
+            points.push(TracePoint {
+                distance,
+                time: sample.offset_secs,
+                speed: sample.value,
+                throttle: throttle.samples.get(i).map_or(0.0, |s| s.value),
+                brake: brake.samples.get(i).map_or(0.0, |s| s.value),
+            });
+        }
+
+        Self::checked(LapTrace { car_id, lap, points })
+    }
+
+    fn checked(trace: LapTrace) -> Result<Self, ComparisonError> {
+        if trace.points.len() < 2 || !(trace.length() > 0.0 && trace.length().is_finite()) {
+            return Err(ComparisonError::NotEnoughSamples);
+        }
+        Ok(trace)
+    }
+
+    /// Distance covered by the trace (m)
+    pub fn length(&self) -> f32 {
+        match (self.points.first(), self.points.last()) {
+            (Some(first), Some(last)) => last.distance - first.distance,
+            _ => 0.0,
+        }
+    }
+
+    /// Time covered by the trace (seconds)
+    pub fn lap_time(&self) -> f32 {
+        match (self.points.first(), self.points.last()) {
+            (Some(first), Some(last)) => last.time - first.time,
+            _ => 0.0,
+        }
+    }
+
+    /// Interpolated sample at `distance`, clamped to the trace
+    pub fn at(&self, distance: f32) -> TracePoint {
+        let i = self.points.partition_point(|p| p.distance < distance);
+        if i == 0 {
+            return self.points[0];
+        }
+        let Some(&after) = self.points.get(i) else {
+            return self.points[self.points.len() - 1];
+        };
+        let before = self.points[i - 1];
+        let span = after.distance - before.distance;
+        let t = if span > 0.0 { (distance - before.distance) / span } else { 0.0 };
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        TracePoint {
+            distance,
+            time: lerp(before.time, after.time),
+            speed: lerp(before.speed, after.speed),
+            throttle: lerp(before.throttle, after.throttle),
+            brake: lerp(before.brake, after.brake),
+        }
+    }
+
+    /// Copy of the trace starting at zero and stretched to `length`
+    fn normalized(&self, length: f32) -> LapTrace {
+        let origin = self.points[0];
+        let scale = length / self.length();
+        LapTrace {
+            car_id: self.car_id,
+            lap: self.lap,
+            points: self
+                .points
+                .iter()
+                .map(|p| TracePoint {
+                    distance: (p.distance - origin.distance) * scale,
+                    time: p.time - origin.time,
+                    ..*p
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Comparison configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComparisonConfig {
+    /// Spacing of the common distance grid (m)
+    pub resolution: f32,
+
+    /// Stretch both laps to the circuit length before aligning them, which
+    /// absorbs differences in line and integration drift
+    pub normalize_distance: bool,
+
+    /// Speed drop from the preceding peak that marks a corner (km/h)
+    pub min_corner_speed_drop: f32,
+
+    /// Brake pressure that counts as the braking point (0.0-1.0)
+    pub brake_threshold: f32,
+
+    /// Throttle that counts as the pickup point after the apex (0.0-1.0)
+    pub throttle_threshold: f32,
+}
+
+impl Default for ComparisonConfig {
+    fn default() -> Self {
+        ComparisonConfig {
+            resolution: 5.0,
+            normalize_distance: true,
+            min_corner_speed_drop: 20.0,
+            brake_threshold: 0.1,
+            throttle_threshold: 0.2,
+        }
+    }
+}
+
+/// Location of a corner on the lap
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CornerMarker {
+    /// Corner number, matching `Corner::number`
+    pub number: u8,
+    pub name: Option<String>,
+
+    /// Start of the corner segment (m)
+    pub start: f32,
+
+    /// Reference apex (m)
+    pub apex: f32,
+
+    /// End of the corner segment (m)
+    pub end: f32,
+}
+
+/// How one lap drove one corner
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CornerMetrics {
+    /// Minimum speed in the corner (km/h)
+    pub min_speed: f32,
+
+    /// Where the minimum speed was reached (m)
+    pub min_speed_distance: f32,
+
+    /// Start of the braking run into the minimum (m)
+    pub braking_point: Option<f32>,
+
+    /// First point after the minimum where the throttle was picked up (m)
+    pub throttle_pickup: Option<f32>,
+
+    /// Time spent in the corner segment (seconds)
+    pub segment_time: f32,
+}
+
+/// One corner compared between the two laps
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CornerComparison {
+    pub corner: CornerMarker,
+    pub reference: CornerMetrics,
+    pub target: CornerMetrics,
+
+    /// Time lost by the target in this corner (seconds, negative = gained)
+    pub time_delta: f32,
+}
+
+/// Both laps at one point of the distance grid
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ComparisonPoint {
+    pub distance: f32,
+
+    /// Cumulative time of the target behind the reference (seconds)
+    pub delta: f32,
+
+    pub reference_speed: f32,
+    pub target_speed: f32,
+    pub reference_throttle: f32,
+    pub target_throttle: f32,
+    pub reference_brake: f32,
+    pub target_brake: f32,
+}
+
+/// Identifies one side of a comparison
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ComparedLap {
+    pub car_id: CarId,
+    pub lap: LapNumber,
+    pub lap_time: f32,
+}
+
+/// Result of comparing a target lap against a reference lap
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LapComparison {
+    pub reference: ComparedLap,
+    pub target: ComparedLap,
+
+    /// Target lap time minus reference lap time (seconds)
+    pub lap_time_delta: f32,
+
+    /// Delta trace and overlays on the common distance grid
+    pub points: Vec<ComparisonPoint>,
+
+    /// Corner-by-corner gains and losses, in track order
+    pub corners: Vec<CornerComparison>,
+}
+
+impl LapComparison {
+    /// Corner where the target lost the most time
+    pub fn biggest_loss(&self) -> Option<&CornerComparison> {
+        self.corners
+            .iter()
+            .filter(|c| c.time_delta > 0.0)
+            .max_by(|a, b| a.time_delta.total_cmp(&b.time_delta))
+    }
+
+    /// Corner where the target gained the most time
+    pub fn biggest_gain(&self) -> Option<&CornerComparison> {
+        self.corners
+            .iter()
+            .filter(|c| c.time_delta < 0.0)
+            .min_by(|a, b| a.time_delta.total_cmp(&b.time_delta))
+    }
+}
+
+/// Comparison errors
+#[derive(Debug, Error)]
+pub enum ComparisonError {
+    #[error("Lap trace needs at least two samples covering some distance")]
+    NotEnoughSamples,
+
+    #[error("Lap {} of car {} is not fully buffered", lap.0, car_id.0)]
+    LapNotAvailable { car_id: CarId, lap: LapNumber },
+
+    #[error("A {resolution} m grid over {length} m needs too many points")]
+    ResolutionTooFine { length: f32, resolution: f32 },
+}
+
+/// Compares laps on a circuit
+pub struct LapComparator {
+    config: ComparisonConfig,
+    track_length: f32,
+    corner_names: Vec<(u8, Option<String>)>,
+    corners: Option<Vec<CornerMarker>>,
+}
+
+impl LapComparator {
+    /// Comparator for a circuit; corners are detected from each reference lap
+    pub fn new(circuit: &Circuit, config: ComparisonConfig) -> Self {
+        let corner_names = circuit
+            .sectors
+            .iter()
+            .flat_map(|sector| sector.key_corners.iter())
+            .map(|corner| (corner.number, corner.name.clone()))
+            .collect();
+
+        LapComparator {
+            config,
+            track_length: circuit.length,
+            corner_names,
+            corners: None,
+        }
+    }
+
+    /// Use a fixed corner map instead of detecting corners
+    pub fn with_corners(mut self, mut corners: Vec<CornerMarker>) -> Self {
+        corners.sort_by(|a, b| a.start.total_cmp(&b.start));
+        self.corners = Some(corners);
+        self
+    }
+
+    /// Get configuration
+    pub fn config(&self) -> &ComparisonConfig {
+        &self.config
+    }
+
+    /// Compare `target` against `reference`
+    pub fn compare(&self, reference: &LapTrace, target: &LapTrace) -> Result<LapComparison, ComparisonError> {
+        let reference = LapTrace::checked(reference.clone())?;
+        let target = LapTrace::checked(target.clone())?;
+        let length = if self.config.normalize_distance && self.track_length > 0.0 {
+            self.track_length
+        } else {
+            reference.length().min(target.length())
+        };
+        let reference = reference.normalized(length);
+        let target = target.normalized(length);
+
+        let step = self.config.resolution.max(0.1);
+        if !(length.is_finite() && length / step <= MAX_GRID_POINTS) {
+            return Err(ComparisonError::ResolutionTooFine { length, resolution: step });
+        }
+        let steps = (length / step).ceil() as usize;
+        let points = (0..=steps)
+            .map(|i| {
+                let distance = (i as f32 * step).min(length);
+                let r = reference.at(distance);
+                let t = target.at(distance);
+                ComparisonPoint {
+                    distance,
+                    delta: t.time - r.time,
+                    reference_speed: r.speed,
+                    target_speed: t.speed,
+                    reference_throttle: r.throttle,
+                    target_throttle: t.throttle,
+                    reference_brake: r.brake,
+                    target_brake: t.brake,
+                }
+            })
+            .collect();
+
+        let markers = match &self.corners {
+            Some(corners) => corners.clone(),
+            None => self.detect_corners(&reference),
+        };
+        let corners = markers
+            .into_iter()
+            .map(|corner| {
+                let reference_metrics = self.corner_metrics(&reference, &corner);
+                let target_metrics = self.corner_metrics(&target, &corner);
+                CornerComparison {
+                    time_delta: target_metrics.segment_time - reference_metrics.segment_time,
+                    reference: reference_metrics,
+                    target: target_metrics,
+                    corner,
+                }
+            })
+            .collect();
+
+        let side = |trace: &LapTrace| ComparedLap {
+            car_id: trace.car_id,
+            lap: trace.lap,
+            lap_time: trace.at(length).time,
+        };
+        let (reference, target) = (side(&reference), side(&target));
+        Ok(LapComparison {
+            lap_time_delta: target.lap_time - reference.lap_time,
+            reference,
+            target,
+            points,
+            corners,
+        })
+    }
+
+    /// Locate corners on a lap from its speed trace
+    ///
+    /// A corner is a speed minimum at least `min_corner_speed_drop` below the
+    /// speeds on both sides of it. Corners are numbered 1.. in track order, so
+    /// flat-out turns the speed trace cannot see shift the numbering; pass a
+    /// fixed map with `with_corners` where that matters. Segments meet halfway
+    /// between apexes and cover the whole lap, so the corner deltas add up to
+    /// the lap time delta.
+    pub fn detect_corners(&self, trace: &LapTrace) -> Vec<CornerMarker> {
+        let drop = self.config.min_corner_speed_drop;
+        let mut apexes = Vec::new();
+        let mut peak = trace.points[0].speed;
+        let mut minimum: Option<TracePoint> = None;
+
+        for &point in &trace.points[1..] {
+            match minimum {
+                None if point.speed >= peak => peak = point.speed,
+                None if peak - point.speed >= drop => minimum = Some(point),
+                None => {}
+                Some(low) if point.speed < low.speed => minimum = Some(point),
+                Some(low) if point.speed - low.speed >= drop => {
+                    apexes.push(low.distance);
+                    minimum = None;
+                    peak = point.speed;
+                }
+                Some(_) => {}
+            }
+        }
+        apexes.extend(minimum.map(|low| low.distance));
+        // Corner numbers are `u8`; beyond that the trace is noise
+        apexes.truncate(u8::MAX as usize);
+
+        let length = trace.length();
+        (0..apexes.len())
+            .map(|i| {
+                let number = i as u8 + 1;
+                CornerMarker {
+                    number,
+                    name: self
+                        .corner_names
+                        .iter()
+                        .find(|(n, _)| *n == number)
+                        .and_then(|(_, name)| name.clone()),
+                    start: if i == 0 { 0.0 } else { (apexes[i - 1] + apexes[i]) / 2.0 },
+                    apex: apexes[i],
+                    end: apexes.get(i + 1).map_or(length, |next| (apexes[i] + next) / 2.0),
+                }
+            })
+            .collect()
+    }
+
+    fn corner_metrics(&self, trace: &LapTrace, corner: &CornerMarker) -> CornerMetrics {
+        let points = &trace.points;
+        let first = points.partition_point(|p| p.distance < corner.start);
+        let last = points.partition_point(|p| p.distance <= corner.end);
+        let (low_index, low) = points[first..last]
+            .iter()
+            .enumerate()
+            .min_by(|a, b| a.1.speed.total_cmp(&b.1.speed))
+            .map(|(i, p)| (first + i, *p))
+            .unwrap_or_else(|| (first.min(points.len() - 1), trace.at(corner.apex)));
+
+        // The braking run leading into the minimum may start before the
+        // segment when the car brakes early
+        let braking = |p: &TracePoint| p.brake >= self.config.brake_threshold;
+        let from = first.min(low_index);
+        let braking_point = points[from..=low_index]
+            .iter()
+            .rposition(braking)
+            .map(|i| {
+                let run_end = from + i;
+                let run_start = points[..=run_end].iter().rposition(|p| !braking(p)).map_or(0, |j| j + 1);
+                points[run_start].distance
+            });
+
+        CornerMetrics {
+            min_speed: low.speed,
+            min_speed_distance: low.distance,
+            braking_point,
+            throttle_pickup: points[low_index..last.max(low_index)]
+                .iter()
+                .find(|p| p.throttle >= self.config.throttle_threshold)
+                .map(|p| p.distance),
+            segment_time: trace.at(corner.end).time - trace.at(corner.start).time,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lap with two corners: (apex, min speed, braking distance) each
+    fn synthetic_lap(car: u8, corners: [(f32, f32, f32); 2]) -> LapTrace {
+        let top = 300.0;
+        let mut points = Vec::new();
+        let mut time = 0.0;
+        for d in 0..=4000 {
+            let distance = d as f32;
+            let mut speed: f32 = top;
+            let mut braking = false;
+            for (apex, min, brake_len) in corners {
+                if distance <= apex && distance >= apex - brake_len {
+                    let t = (apex - distance) / brake_len;
+                    speed = speed.min(min + (top - min) * t);
+                    braking = true;
+                } else if distance > apex && distance <= apex + 400.0 {
+                    speed = speed.min(min + (top - min) * (distance - apex) / 400.0);
+                }
+            }
+            if d > 0 {
+                time += 1.0 / (speed / 3.6);
+            }
+            points.push(TracePoint {
+                distance,
+                time,
+                speed,
+                throttle: if braking { 0.0 } else { 1.0 },
+                brake: if braking { 1.0 } else { 0.0 },
+            });
+        }
+        LapTrace::new(CarId(car), LapNumber(3), points)
+    }
+
+    fn circuit() -> Circuit {
+        Circuit {
+            length: 4000.0,
+            sectors: vec![],
+            ..Circuit::monza()
+        }
+    }
+
+    #[test]
+    fn test_delta_trace_and_corners() {
+        let reference = synthetic_lap(1, [(1000.0, 100.0, 150.0), (3000.0, 150.0, 120.0)]);
+        // Brakes 20 m earlier and carries less speed into turn 1
+        let target = synthetic_lap(2, [(1000.0, 90.0, 170.0), (3000.0, 150.0, 120.0)]);
+
+        let comparator = LapComparator::new(&circuit(), ComparisonConfig::default());
+        let comparison = comparator.compare(&reference, &target).unwrap();
+
+        assert!(comparison.lap_time_delta > 0.0);
+        let last = comparison.points.last().unwrap();
+        assert!((last.distance - 4000.0).abs() < 1e-3);
+        assert!((last.delta - comparison.lap_time_delta).abs() < 1e-3);
+        assert_eq!(comparison.points[0].delta, 0.0);
+
+        assert_eq!(comparison.corners.len(), 2);
+        let turn1 = &comparison.corners[0];
+        assert_eq!(turn1.corner.number, 1);
+        assert!((turn1.corner.apex - 1000.0).abs() < 1.0);
+        assert!((turn1.reference.min_speed - 100.0).abs() < 1e-3);
+        assert!((turn1.target.min_speed - 90.0).abs() < 1e-3);
+        assert_eq!(turn1.reference.braking_point, Some(850.0));
+        assert_eq!(turn1.target.braking_point, Some(830.0));
+        assert_eq!(turn1.reference.throttle_pickup, Some(1001.0));
+
+        let turn3 = &comparison.corners[1];
+        assert_eq!(turn3.corner.number, 2);
+        assert!(turn3.time_delta.abs() < 1e-3);
+        assert_eq!(comparison.biggest_loss().unwrap().corner.number, 1);
+
+        // Corner segments cover every time difference in this lap
+        let total: f32 = comparison.corners.iter().map(|c| c.time_delta).sum();
+        assert!((total - comparison.lap_time_delta).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_corner_numbers_and_fixed_map() {
+        let mut circuit = circuit();
+        circuit.sectors = circuit.sector_layout();
+        circuit.sectors[0].key_corners = vec![f1_nexus_core::Corner {
+            number: 1,
+            name: Some("Variante del Rettifilo".to_string()),
+            apex_speed: 100.0,
+            entry_speed: 300.0,
+            exit_speed: 200.0,
+            corner_type: f1_nexus_core::CornerType::Chicane,
+            gear: 2,
+        }];
+        let reference = synthetic_lap(1, [(1000.0, 100.0, 150.0), (3000.0, 150.0, 120.0)]);
+
+        let comparator = LapComparator::new(&circuit, ComparisonConfig::default());
+        let corners = comparator.detect_corners(&reference);
+        assert_eq!(corners.len(), 2);
+        assert_eq!(corners[0].number, 1);
+        assert_eq!(corners[0].name.as_deref(), Some("Variante del Rettifilo"));
+        assert_eq!((corners[0].start, corners[0].end), (0.0, 2000.0));
+        assert_eq!(corners[1].number, 2);
+        assert_eq!(corners[1].name, None);
+
+        let fixed = vec![CornerMarker {
+            number: 7,
+            name: None,
+            start: 2800.0,
+            apex: 3000.0,
+            end: 3500.0,
+        }];
+        let comparison = comparator
+            .with_corners(fixed)
+            .compare(&reference, &reference)
+            .unwrap();
+        assert_eq!(comparison.corners.len(), 1);
+        assert_eq!(comparison.corners[0].corner.number, 7);
+        assert_eq!(comparison.lap_time_delta, 0.0);
+    }
+
+    #[test]
+    fn test_bounded_grid_and_corner_count() {
+        // A 1,000 km trace is refused at the minimum resolution
+        let long = LapTrace::new(
+            CarId(1),
+            LapNumber(1),
+            (0..=2)
+                .map(|i| TracePoint {
+                    distance: i as f32 * 5.0e5,
+                    time: i as f32 * 1.0e4,
+                    speed: 180.0,
+                    throttle: 1.0,
+                    brake: 0.0,
+                })
+                .collect(),
+        );
+        let config = ComparisonConfig { resolution: 0.0, normalize_distance: false, ..Default::default() };
+        let comparator = LapComparator::new(&circuit(), config);
+        assert!(matches!(
+            comparator.compare(&long, &long),
+            Err(ComparisonError::ResolutionTooFine { .. })
+        ));
+
+        // 300 dips of 50 km/h are numbered up to 255 without overflowing
+        let points = (0..=3000)
+            .map(|d| TracePoint {
+                distance: d as f32,
+                time: d as f32 / 50.0,
+                speed: if d % 10 == 5 { 150.0 } else { 200.0 },
+                throttle: 1.0,
+                brake: 0.0,
+            })
+            .collect();
+        let noisy = LapTrace::new(CarId(1), LapNumber(1), points);
+        let corners = comparator.detect_corners(&noisy);
+        assert_eq!(corners.len(), u8::MAX as usize);
+        assert_eq!(corners.last().unwrap().number, u8::MAX);
+        assert_eq!(corners.last().unwrap().end, noisy.length());
+    }
+
+    #[test]
+    fn test_trace_from_snapshots() {
+        let base = crate::processor::tests::create_test_snapshot();
+        let snapshots: Vec<_> = (0..=10)
+            .map(|i| {
+                let mut snapshot = base.clone();
+                snapshot.timestamp = base.timestamp + chrono::Duration::seconds(i);
+                snapshot.motion.speed = 180.0;
+                snapshot
+            })
+            .collect();
+
+        let trace = LapTrace::from_snapshots(&snapshots).unwrap();
+        assert!((trace.length() - 500.0).abs() < 1e-3);
+        assert!((trace.lap_time() - 10.0).abs() < 1e-6);
+        assert!((trace.at(250.0).time - 5.0).abs() < 1e-4);
+        assert!(matches!(
+            LapTrace::from_snapshots(&snapshots[..1]),
+            Err(ComparisonError::NotEnoughSamples)
+        ));
+    }
+}
@@ -0,0 +1,598 @@
+//! Per-subscriber delivery with explicit loss policies
+//!
+//! `tokio::sync::broadcast` shares one ring buffer between all receivers, so
+//! a slow receiver silently loses messages. A `DeliveryHub` gives every
+//! subscriber its own bounded queue and a `DeliveryPolicy` deciding what
+//! happens when that queue is full:
+//!
+//! - `Lossless`: `publish` waits for space (backpressure on the publisher);
+//!   `send` cannot wait, so there it drops the oldest like `DropOldest`
+//! - `DropOldest`: the oldest message is dropped and the receiver gets a
+//!   `Delivery::Gap` before the next message
+//! - `Conflate`: a newer message replaces the queued one with the same
+//!   conflation key (latest snapshot per car), messages without a key queue
+//!   normally and the oldest is dropped when full
+//!
+//! Every subscriber keeps counters of delivered, dropped, conflated and
+//! overflowed messages.
+
+use f1_nexus_core::{CarId, SessionId, TelemetrySnapshot};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// What a subscriber's full queue does with a new message
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryPolicy {
+    /// Hold the publisher until there is space; never lose a message
+    Lossless,
+    /// Drop the oldest queued message and report a gap
+    #[default]
+    DropOldest,
+    /// Keep only the latest message per conflation key
+    Conflate,
+}
+
+/// Queue settings of one subscriber
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubscriberConfig {
+    /// Name shown in metrics
+    pub name: String,
+    pub policy: DeliveryPolicy,
+
+    /// Queued messages before the policy applies
+    pub capacity: usize,
+}
+
+impl SubscriberConfig {
+    pub fn new(name: impl Into<String>, policy: DeliveryPolicy, capacity: usize) -> Self {
+        SubscriberConfig {
+            name: name.into(),
+            policy,
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn lossless(name: impl Into<String>, capacity: usize) -> Self {
+        Self::new(name, DeliveryPolicy::Lossless, capacity)
+    }
+
+    pub fn drop_oldest(name: impl Into<String>, capacity: usize) -> Self {
+        Self::new(name, DeliveryPolicy::DropOldest, capacity)
+    }
+
+    pub fn conflate(name: impl Into<String>, capacity: usize) -> Self {
+        Self::new(name, DeliveryPolicy::Conflate, capacity)
+    }
+}
+
+impl Default for SubscriberConfig {
+    fn default() -> Self {
+        Self::drop_oldest("subscriber", 1024)
+    }
+}
+
+/// Messages that can be conflated
+pub trait Conflatable: Clone + Send + Sync + 'static {
+    type Key: Eq + Hash + Clone + Send + Sync + 'static;
+
+    /// Messages with equal keys replace each other under `Conflate`;
+    /// `None` is never conflated
+    fn conflation_key(&self) -> Option<Self::Key>;
+}
+
+impl Conflatable for TelemetrySnapshot {
+    type Key = (SessionId, CarId);
+
+    fn conflation_key(&self) -> Option<Self::Key> {
+        Some((self.session_id, self.car_id))
+    }
+}
+
+impl Conflatable for crate::TelemetryEvent {
+    type Key = (SessionId, CarId);
+
+    /// Snapshots conflate per car; anomalies, warnings and stream events never do
+    fn conflation_key(&self) -> Option<Self::Key> {
+        match self {
+            crate::TelemetryEvent::Snapshot(snapshot) => snapshot.conflation_key(),
+            _ => None,
+        }
+    }
+}
+
+/// What a receiver gets
+#[derive(Debug, Clone, PartialEq)]
+pub enum Delivery<T> {
+    Message(T),
+    /// Messages dropped since the previous delivery
+    Gap { missed: u64 },
+}
+
+/// Counters of one subscriber
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubscriberMetrics {
+    pub id: u64,
+    pub name: String,
+    pub policy: DeliveryPolicy,
+    pub capacity: usize,
+    pub queued: usize,
+    pub delivered: u64,
+
+    /// Messages dropped to make room
+    pub dropped: u64,
+
+    /// Messages replaced by a newer one with the same key
+    pub conflated: u64,
+
+    /// Gap notifications delivered
+    pub gaps: u64,
+
+    /// Publishes that had to wait for space (lossless)
+    pub backpressure_waits: u64,
+
+    /// Messages dropped by synchronous sends to a full lossless queue
+    pub overflowed: u64,
+}
+
+struct Queue<T: Conflatable> {
+    slots: VecDeque<(Option<T::Key>, T)>,
+    /// Sequence number of the front slot
+    head: u64,
+    /// Sequence number of the queued message per key
+    index: HashMap<T::Key, u64>,
+    missed: u64,
+    delivered: u64,
+    dropped: u64,
+    conflated: u64,
+    gaps: u64,
+    backpressure_waits: u64,
+    overflowed: u64,
+}
+
+impl<T: Conflatable> Queue<T> {
+    fn new() -> Self {
+        Queue {
+            slots: VecDeque::new(),
+            head: 0,
+            index: HashMap::new(),
+            missed: 0,
+            delivered: 0,
+            dropped: 0,
+            conflated: 0,
+            gaps: 0,
+            backpressure_waits: 0,
+            overflowed: 0,
+        }
+    }
+
+    fn push_back(&mut self, key: Option<T::Key>, message: T) {
+        if let Some(key) = &key {
+            self.index.insert(key.clone(), self.head + self.slots.len() as u64);
+        }
+        self.slots.push_back((key, message));
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        let (key, message) = self.slots.pop_front()?;
+        if let Some(key) = key {
+            if self.index.get(&key) == Some(&self.head) {
+                self.index.remove(&key);
+            }
+        }
+        self.head += 1;
+        Some(message)
+    }
+
+    fn drop_front(&mut self) {
+        if self.pop_front().is_some() {
+            self.dropped += 1;
+            self.missed += 1;
+        }
+    }
+
+    /// Queue under a non-blocking policy
+    fn offer(&mut self, policy: DeliveryPolicy, capacity: usize, message: T) {
+        match policy {
+            DeliveryPolicy::Lossless => {
+                while self.slots.len() >= capacity {
+                    self.overflowed += 1;
+                    self.drop_front();
+                }
+                self.push_back(None, message);
+            }
+            DeliveryPolicy::DropOldest => {
+                while self.slots.len() >= capacity {
+                    self.drop_front();
+                }
+                self.push_back(None, message);
+            }
+            DeliveryPolicy::Conflate => {
+                let key = message.conflation_key();
+                let queued = key.as_ref().and_then(|k| self.index.get(k)).copied();
+                match queued {
+                    Some(seq) if seq >= self.head => {
+                        self.slots[(seq - self.head) as usize].1 = message;
+                        self.conflated += 1;
+                    }
+                    _ => {
+                        while self.slots.len() >= capacity {
+                            self.drop_front();
+                        }
+                        self.push_back(key, message);
+                    }
+                }
+            }
+        }
+    }
+
+    fn take(&mut self) -> Option<Delivery<T>> {
+        if self.missed > 0 {
+            self.gaps += 1;
+            return Some(Delivery::Gap { missed: std::mem::take(&mut self.missed) });
+        }
+        let message = self.pop_front()?;
+        self.delivered += 1;
+        Some(Delivery::Message(message))
+    }
+}
+
+struct Subscriber<T: Conflatable> {
+    id: u64,
+    config: SubscriberConfig,
+    queue: Mutex<Queue<T>>,
+    readable: Notify,
+    writable: Notify,
+    closed: AtomicBool,
+}
+
+impl<T: Conflatable> Subscriber<T> {
+    fn metrics(&self) -> SubscriberMetrics {
+        let queue = self.queue.lock();
+        SubscriberMetrics {
+            id: self.id,
+            name: self.config.name.clone(),
+            policy: self.config.policy,
+            capacity: self.config.capacity,
+            queued: queue.slots.len(),
+            delivered: queue.delivered,
+            dropped: queue.dropped,
+            conflated: queue.conflated,
+            gaps: queue.gaps,
+            backpressure_waits: queue.backpressure_waits,
+            overflowed: queue.overflowed,
+        }
+    }
+
+    async fn publish(&self, message: T) {
+        if self.config.policy != DeliveryPolicy::Lossless {
+            self.queue.lock().offer(self.config.policy, self.config.capacity, message);
+            self.readable.notify_one();
+            return;
+        }
+
+        let mut waited = false;
+        loop {
+            let notified = self.writable.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            {
+                let mut queue = self.queue.lock();
+                if self.closed.load(Ordering::Acquire) {
+                    return;
+                }
+                if queue.slots.len() < self.config.capacity {
+                    queue.push_back(None, message);
+                    break;
+                }
+                if !waited {
+                    queue.backpressure_waits += 1;
+                    waited = true;
+                }
+            }
+            notified.await;
+        }
+        self.readable.notify_one();
+    }
+
+    fn send(&self, message: T) {
+        self.queue.lock().offer(self.config.policy, self.config.capacity, message);
+        self.readable.notify_one();
+    }
+}
+
+struct HubInner<T: Conflatable> {
+    subscribers: RwLock<Vec<Arc<Subscriber<T>>>>,
+    next_id: AtomicU64,
+    senders: AtomicUsize,
+    closed: AtomicBool,
+}
+
+/// Fan-out to subscribers with per-subscriber queues
+pub struct DeliveryHub<T: Conflatable> {
+    inner: Arc<HubInner<T>>,
+}
+
+impl<T: Conflatable> DeliveryHub<T> {
+    pub fn new() -> Self {
+        DeliveryHub {
+            inner: Arc::new(HubInner {
+                subscribers: RwLock::new(Vec::new()),
+                next_id: AtomicU64::new(1),
+                senders: AtomicUsize::new(1),
+                closed: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Add a subscriber
+    pub fn subscribe(&self, config: SubscriberConfig) -> DeliveryReceiver<T> {
+        let subscriber = Arc::new(Subscriber {
+            id: self.inner.next_id.fetch_add(1, Ordering::Relaxed),
+            config: SubscriberConfig {
+                capacity: config.capacity.max(1),
+                ..config
+            },
+            queue: Mutex::new(Queue::new()),
+            readable: Notify::new(),
+            writable: Notify::new(),
+            closed: AtomicBool::new(false),
+        });
+        self.inner.subscribers.write().push(Arc::clone(&subscriber));
+        DeliveryReceiver {
+            subscriber,
+            hub: Arc::clone(&self.inner),
+        }
+    }
+
+    /// Deliver to every subscriber, waiting on full lossless queues;
+    /// returns the number of subscribers
+    pub async fn publish(&self, message: T) -> usize {
+        let subscribers = self.live();
+        for subscriber in &subscribers {
+            subscriber.publish(message.clone()).await;
+        }
+        subscribers.len()
+    }
+
+    /// Deliver without waiting; full lossless queues drop their oldest
+    /// message and count the overflow. Returns the number of subscribers.
+    pub fn send(&self, message: T) -> usize {
+        let subscribers = self.live();
+        for subscriber in &subscribers {
+            subscriber.send(message.clone());
+        }
+        subscribers.len()
+    }
+
+    /// Number of connected subscribers
+    pub fn subscriber_count(&self) -> usize {
+        self.live().len()
+    }
+
+    /// Counters per connected subscriber, by id
+    pub fn metrics(&self) -> Vec<SubscriberMetrics> {
+        self.live().iter().map(|s| s.metrics()).collect()
+    }
+
+    /// Subscribers whose receiver is still alive, pruning the others
+    fn live(&self) -> Vec<Arc<Subscriber<T>>> {
+        let subscribers = self.inner.subscribers.read();
+        if subscribers.iter().all(|s| !s.closed.load(Ordering::Acquire)) {
+            return subscribers.clone();
+        }
+        drop(subscribers);
+        let mut subscribers = self.inner.subscribers.write();
+        subscribers.retain(|s| !s.closed.load(Ordering::Acquire));
+        subscribers.clone()
+    }
+}
+
+impl<T: Conflatable> Default for DeliveryHub<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Conflatable> Clone for DeliveryHub<T> {
+    fn clone(&self) -> Self {
+        self.inner.senders.fetch_add(1, Ordering::AcqRel);
+        DeliveryHub {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T: Conflatable> Drop for DeliveryHub<T> {
+    fn drop(&mut self) {
+        if self.inner.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.inner.closed.store(true, Ordering::Release);
+            for subscriber in self.inner.subscribers.read().iter() {
+                subscriber.readable.notify_one();
+            }
+        }
+    }
+}
+
+/// Receiving end of one subscription; dropping it unsubscribes
+pub struct DeliveryReceiver<T: Conflatable> {
+    subscriber: Arc<Subscriber<T>>,
+    hub: Arc<HubInner<T>>,
+}
+
+impl<T: Conflatable> DeliveryReceiver<T> {
+    /// Next message or gap; `None` once every hub handle is dropped and the
+    /// queue is drained
+    pub async fn recv(&mut self) -> Option<Delivery<T>> {
+        let subscriber = Arc::clone(&self.subscriber);
+        loop {
+            let notified = subscriber.readable.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if let Some(delivery) = self.try_recv() {
+                return Some(delivery);
+            }
+            if self.hub.closed.load(Ordering::Acquire) {
+                return None;
+            }
+            notified.await;
+        }
+    }
+
+    /// Next message or gap, if one is queued
+    pub fn try_recv(&mut self) -> Option<Delivery<T>> {
+        let delivery = self.subscriber.queue.lock().take();
+        if delivery.is_some() {
+            self.subscriber.writable.notify_waiters();
+        }
+        delivery
+    }
+
+    /// Subscription settings
+    pub fn config(&self) -> &SubscriberConfig {
+        &self.subscriber.config
+    }
+
+    /// Counters of this subscription
+    pub fn metrics(&self) -> SubscriberMetrics {
+        self.subscriber.metrics()
+    }
+}
+
+impl<T: Conflatable> Drop for DeliveryReceiver<T> {
+    fn drop(&mut self) {
+        self.subscriber.closed.store(true, Ordering::Release);
+        self.subscriber.writable.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::tests::create_test_snapshot;
+    use std::time::Duration;
+
+    fn snapshot(car: u8, speed: f32) -> TelemetrySnapshot {
+        let mut snapshot = create_test_snapshot();
+        snapshot.car_id = CarId(car);
+        snapshot.motion.speed = speed;
+        snapshot
+    }
+
+    fn speeds(rx: &mut DeliveryReceiver<TelemetrySnapshot>) -> Vec<Delivery<f32>> {
+        std::iter::from_fn(|| rx.try_recv())
+            .map(|d| match d {
+                Delivery::Message(s) => Delivery::Message(s.motion.speed),
+                Delivery::Gap { missed } => Delivery::Gap { missed },
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_lossless_applies_backpressure() {
+        let hub = DeliveryHub::new();
+        let mut rx = hub.subscribe(SubscriberConfig::lossless("recorder", 2));
+
+        let publisher = {
+            let hub = hub.clone();
+            tokio::spawn(async move {
+                for i in 0..10 {
+                    hub.publish(snapshot(1, i as f32)).await;
+                }
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!publisher.is_finished());
+        assert_eq!(rx.metrics().queued, 2);
+
+        let mut received = Vec::new();
+        while received.len() < 10 {
+            match rx.recv().await.unwrap() {
+                Delivery::Message(s) => received.push(s.motion.speed),
+                Delivery::Gap { .. } => panic!("lossless subscriber saw a gap"),
+            }
+        }
+        publisher.await.unwrap();
+        assert_eq!(received, (0..10).map(|i| i as f32).collect::<Vec<_>>());
+        let metrics = rx.metrics();
+        assert_eq!((metrics.delivered, metrics.dropped), (10, 0));
+        assert!(metrics.backpressure_waits > 0);
+
+        // Synchronous sends never block, so a full queue drops its oldest
+        for i in 0..5 {
+            hub.send(snapshot(1, i as f32));
+        }
+        assert_eq!(rx.metrics().overflowed, 3);
+        assert_eq!(
+            speeds(&mut rx),
+            vec![Delivery::Gap { missed: 3 }, Delivery::Message(3.0), Delivery::Message(4.0)]
+        );
+    }
+
+    #[test]
+    fn test_drop_oldest_reports_gaps() {
+        let hub = DeliveryHub::new();
+        let mut rx = hub.subscribe(SubscriberConfig::drop_oldest("dashboard", 3));
+        for i in 0..5 {
+            assert_eq!(hub.send(snapshot(1, i as f32)), 1);
+        }
+        assert_eq!(
+            speeds(&mut rx),
+            vec![Delivery::Gap { missed: 2 }, Delivery::Message(2.0), Delivery::Message(3.0), Delivery::Message(4.0)]
+        );
+        let metrics = hub.metrics();
+        assert_eq!((metrics[0].dropped, metrics[0].gaps, metrics[0].delivered), (2, 1, 3));
+        assert_eq!(metrics[0].name, "dashboard");
+    }
+
+    #[test]
+    fn test_conflate_keeps_latest_per_car() {
+        let hub = DeliveryHub::new();
+        let mut rx = hub.subscribe(SubscriberConfig::conflate("dashboard", 8));
+        let session = SessionId::new();
+        for i in 0..4 {
+            for car in [1, 2] {
+                let mut s = snapshot(car, car as f32 * 100.0 + i as f32);
+                s.session_id = session;
+                hub.send(s);
+            }
+        }
+        assert_eq!(speeds(&mut rx), vec![Delivery::Message(103.0), Delivery::Message(203.0)]);
+        assert_eq!(rx.metrics().conflated, 6);
+
+        // Once delivered, the next snapshot for the car queues again
+        let mut s = snapshot(1, 104.0);
+        s.session_id = session;
+        hub.send(s);
+        assert_eq!(speeds(&mut rx), vec![Delivery::Message(104.0)]);
+    }
+
+    #[tokio::test]
+    async fn test_unsubscribe_and_close() {
+        let hub = DeliveryHub::<TelemetrySnapshot>::new();
+        let rx = hub.subscribe(SubscriberConfig::lossless("a", 1));
+        let mut other = hub.subscribe(SubscriberConfig::default());
+        assert_eq!(hub.subscriber_count(), 2);
+
+        // A dropped lossless receiver releases a waiting publisher
+        hub.publish(snapshot(1, 1.0)).await;
+        let publish = {
+            let hub = hub.clone();
+            tokio::spawn(async move { hub.publish(snapshot(1, 2.0)).await })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        drop(rx);
+        publish.await.unwrap();
+        assert_eq!(hub.subscriber_count(), 1);
+
+        drop(hub);
+        assert!(matches!(other.recv().await, Some(Delivery::Message(_))));
+        assert!(matches!(other.recv().await, Some(Delivery::Message(_))));
+        assert!(other.recv().await.is_none());
+    }
+}
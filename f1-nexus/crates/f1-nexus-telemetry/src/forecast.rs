@@ -0,0 +1,658 @@
+//! Short-horizon telemetry forecasting
+//!
+//! Each watched channel is tracked per car by a local linear trend Kalman
+//! filter (level and slope, white-noise acceleration). Forecasts a few
+//! seconds ahead carry an uncertainty band, and a predicted threshold
+//! crossing within the horizon is emitted once as an `EarlyWarning` until
+//! the trend moves away from the limit again.
+//!
+//! Timing uses snapshot timestamps, so replays behave like live sessions.
+
+use crate::{AnomalyInfo, Channel, Comparison, Severity};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use f1_nexus_core::{CarId, SessionId, TelemetrySnapshot};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Local linear trend Kalman filter over one channel
+#[derive(Debug, Clone)]
+pub struct TrendFilter {
+    /// Process noise spectral density (units²/s³)
+    process_noise: f64,
+    /// Measurement noise variance (units²)
+    measurement_variance: f64,
+    level: f64,
+    slope: f64,
+    covariance: [[f64; 2]; 2],
+    last_time: Option<DateTime<Utc>>,
+    samples: u64,
+}
+
+impl TrendFilter {
+    /// Filter with process noise density and measurement noise std dev
+    pub fn new(process_noise: f64, measurement_noise: f64) -> Self {
+        TrendFilter {
+            process_noise,
+            measurement_variance: measurement_noise * measurement_noise,
+            level: 0.0,
+            slope: 0.0,
+            covariance: [[0.0; 2]; 2],
+            last_time: None,
+            samples: 0,
+        }
+    }
+
+    /// Number of measurements incorporated
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// Current level estimate
+    pub fn level(&self) -> f64 {
+        self.level
+    }
+
+    /// Current slope estimate (units per second)
+    pub fn slope(&self) -> f64 {
+        self.slope
+    }
+
+    /// Incorporate a measurement taken at `time`; non-finite values are ignored
+    pub fn update(&mut self, time: DateTime<Utc>, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        let Some(last_time) = self.last_time else {
+            self.level = value;
+            self.slope = 0.0;
+            // Unknown slope: start with a wide prior
+            self.covariance = [[self.measurement_variance, 0.0], [0.0, 1.0e3]];
+            self.last_time = Some(time);
+            self.samples = 1;
+            return;
+        };
+
+        let dt = (time - last_time).num_microseconds().unwrap_or(0) as f64 / 1.0e6;
+        if dt > 0.0 {
+            let (level, slope, covariance) = self.project(dt);
+            self.level = level;
+            self.slope = slope;
+            self.covariance = covariance;
+            self.last_time = Some(time);
+        }
+
+        // Measurement update with H = [1, 0]
+        let p = self.covariance;
+        let innovation = value - self.level;
+        let innovation_variance = p[0][0] + self.measurement_variance;
+        let gain = [p[0][0] / innovation_variance, p[1][0] / innovation_variance];
+
+        self.level += gain[0] * innovation;
+        self.slope += gain[1] * innovation;
+        self.covariance = [
+            [(1.0 - gain[0]) * p[0][0], (1.0 - gain[0]) * p[0][1]],
+            [p[1][0] - gain[1] * p[0][0], p[1][1] - gain[1] * p[0][1]],
+        ];
+        self.samples += 1;
+    }
+
+    /// Forecast `horizon` seconds past the last measurement
+    pub fn forecast(&self, horizon: f64, z: f64) -> ForecastPoint {
+        let (mean, _, covariance) = self.project(horizon.max(0.0));
+        let std_dev = (covariance[0][0] + self.measurement_variance).max(0.0).sqrt();
+        ForecastPoint {
+            horizon_secs: horizon as f32,
+            mean: mean as f32,
+            lower: (mean - z * std_dev) as f32,
+            upper: (mean + z * std_dev) as f32,
+            std_dev: std_dev as f32,
+        }
+    }
+
+    /// Propagate state and covariance `dt` seconds ahead
+    fn project(&self, dt: f64) -> (f64, f64, [[f64; 2]; 2]) {
+        let p = self.covariance;
+        let q = self.process_noise;
+
+        // P' = F P Fᵀ + Q with F = [[1, dt], [0, 1]]
+        let p00 = p[0][0] + dt * (p[1][0] + p[0][1]) + dt * dt * p[1][1] + q * dt.powi(3) / 3.0;
+        let p01 = p[0][1] + dt * p[1][1] + q * dt * dt / 2.0;
+        let p11 = p[1][1] + q * dt;
+
+        (
+            self.level + self.slope * dt,
+            self.slope,
+            [[p00, p01], [p01, p11]],
+        )
+    }
+}
+
+/// Forecast value with its uncertainty band
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ForecastPoint {
+    /// Seconds ahead of the last measurement
+    pub horizon_secs: f32,
+
+    /// Expected value
+    pub mean: f32,
+
+    /// Lower edge of the band
+    pub lower: f32,
+
+    /// Upper edge of the band
+    pub upper: f32,
+
+    /// Forecast standard deviation
+    pub std_dev: f32,
+}
+
+/// Channel watched for predicted threshold crossings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelForecast {
+    /// Channel to forecast
+    pub channel: Channel,
+
+    /// Direction of the crossing (e.g. `>` for overheating)
+    pub op: Comparison,
+
+    /// Threshold whose crossing is predicted
+    pub limit: f32,
+
+    /// Severity of the early warning
+    pub severity: Severity,
+
+    /// Process noise spectral density (units²/s³)
+    pub process_noise: f64,
+
+    /// Measurement noise standard deviation (units)
+    pub measurement_noise: f64,
+}
+
+impl ChannelForecast {
+    /// Watch `channel` crossing `limit` with unit noise parameters
+    pub fn new(channel: Channel, op: Comparison, limit: f32, severity: Severity) -> Self {
+        ChannelForecast {
+            channel,
+            op,
+            limit,
+            severity,
+            process_noise: 1.0,
+            measurement_noise: 1.0,
+        }
+    }
+
+    /// Set the filter noise parameters
+    pub fn with_noise(mut self, process_noise: f64, measurement_noise: f64) -> Self {
+        self.process_noise = process_noise;
+        self.measurement_noise = measurement_noise;
+        self
+    }
+
+    /// Whether `value` is beyond the limit
+    fn crossed(&self, value: f32) -> bool {
+        self.op.test(value, self.limit)
+    }
+
+    /// Band edge closest to the limit
+    fn pessimistic(&self, point: &ForecastPoint) -> f32 {
+        match self.op {
+            Comparison::Gt | Comparison::Ge => point.upper,
+            Comparison::Lt | Comparison::Le => point.lower,
+        }
+    }
+
+    /// Band edge furthest from the limit
+    fn optimistic(&self, point: &ForecastPoint) -> f32 {
+        match self.op {
+            Comparison::Gt | Comparison::Ge => point.lower,
+            Comparison::Lt | Comparison::Le => point.upper,
+        }
+    }
+}
+
+/// Forecaster configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForecastConfig {
+    /// How far ahead crossings are predicted
+    pub horizon: Duration,
+
+    /// Time resolution of band crossing estimates
+    pub resolution: Duration,
+
+    /// Band half-width in standard deviations (1.645 ≈ 90 % band)
+    pub band_z: f64,
+
+    /// Measurements needed before warnings are emitted
+    pub min_samples: u64,
+
+    /// Watched channels
+    pub channels: Vec<ChannelForecast>,
+
+    /// Cars not observed for this long are evicted
+    pub idle_timeout: Duration,
+
+    /// Maximum number of cars (car × session) tracked
+    pub max_cars: usize,
+}
+
+impl Default for ForecastConfig {
+    fn default() -> Self {
+        use Channel::*;
+
+        let mut channels = Vec::new();
+        for channel in [TireTempFrontLeft, TireTempFrontRight, TireTempRearLeft, TireTempRearRight] {
+            channels.push(
+                ChannelForecast::new(channel, Comparison::Gt, 115.0, Severity::Medium).with_noise(0.5, 1.0),
+            );
+        }
+        for channel in [BrakeTempFrontLeft, BrakeTempFrontRight, BrakeTempRearLeft, BrakeTempRearRight] {
+            channels.push(
+                ChannelForecast::new(channel, Comparison::Gt, 1000.0, Severity::High).with_noise(50.0, 5.0),
+            );
+        }
+        channels.push(
+            ChannelForecast::new(ErsBattery, Comparison::Lt, 0.05, Severity::Low).with_noise(1.0e-4, 0.005),
+        );
+        channels.push(
+            ChannelForecast::new(FuelRemaining, Comparison::Lt, 2.0, Severity::High).with_noise(1.0e-4, 0.05),
+        );
+
+        ForecastConfig {
+            horizon: Duration::from_secs(10),
+            resolution: Duration::from_millis(250),
+            band_z: 1.645,
+            min_samples: 10,
+            channels,
+            idle_timeout: Duration::from_secs(600),
+            max_cars: 10_000,
+        }
+    }
+}
+
+/// Predicted threshold crossing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EarlyWarning {
+    pub car_id: CarId,
+    pub session_id: SessionId,
+    pub channel: Channel,
+    pub op: Comparison,
+    pub limit: f32,
+    pub severity: Severity,
+
+    /// Filtered current value
+    pub current: f32,
+
+    /// Trend (units per second)
+    pub slope: f32,
+
+    /// Seconds until the expected value crosses the limit
+    pub crossing_in_secs: f32,
+
+    /// Earliest crossing according to the uncertainty band
+    pub earliest_crossing_secs: f32,
+
+    /// Latest crossing according to the band, if within the horizon
+    pub latest_crossing_secs: Option<f32>,
+
+    /// Probability of being beyond the limit at the horizon
+    pub probability: f32,
+
+    /// Forecast at the horizon
+    pub forecast: ForecastPoint,
+
+    /// Timestamp of the snapshot that triggered the warning
+    pub timestamp: DateTime<Utc>,
+}
+
+impl From<EarlyWarning> for AnomalyInfo {
+    fn from(warning: EarlyWarning) -> Self {
+        let expected_range = match warning.op {
+            Comparison::Gt | Comparison::Ge => (f32::MIN, warning.limit),
+            Comparison::Lt | Comparison::Le => (warning.limit, f32::MAX),
+        };
+        AnomalyInfo {
+            car_id: warning.car_id,
+            session_id: warning.session_id,
+            segment: None,
+            field: format!("forecast:{}", warning.channel.name()),
+            expected_range,
+            actual_value: warning.forecast.mean,
+            severity: warning.severity.into(),
+            timestamp: warning.timestamp,
+        }
+    }
+}
+
+struct ChannelState {
+    filter: TrendFilter,
+    warned: bool,
+}
+
+/// Filters of every watched channel for one car
+struct CarState {
+    channels: Vec<ChannelState>,
+    last_seen: Instant,
+}
+
+/// Per-car forecasts over the telemetry stream
+pub struct Forecaster {
+    config: ForecastConfig,
+    states: DashMap<(SessionId, CarId), CarState>,
+}
+
+impl Forecaster {
+    pub fn new(config: ForecastConfig) -> Self {
+        Forecaster {
+            config,
+            states: DashMap::new(),
+        }
+    }
+
+    /// Forecaster configuration
+    pub fn config(&self) -> &ForecastConfig {
+        &self.config
+    }
+
+    /// Update the car's filters and return new early warnings
+    pub fn observe(&self, snapshot: &TelemetrySnapshot) -> Vec<EarlyWarning> {
+        let horizon = self.config.horizon.as_secs_f64();
+        let key = (snapshot.session_id, snapshot.car_id);
+
+        // Evict before taking the entry: DashMap shard locks are not reentrant
+        if !self.states.contains_key(&key) {
+            self.evict_idle(self.config.idle_timeout);
+            self.enforce_capacity(self.config.max_cars.saturating_sub(1));
+        }
+        let mut car = self.states.entry(key).or_insert_with(|| CarState {
+            channels: self
+                .config
+                .channels
+                .iter()
+                .map(|watch| ChannelState {
+                    filter: TrendFilter::new(watch.process_noise, watch.measurement_noise),
+                    warned: false,
+                })
+                .collect(),
+            last_seen: Instant::now(),
+        });
+        car.last_seen = Instant::now();
+
+        let mut warnings = Vec::new();
+        for (watch, state) in self.config.channels.iter().zip(car.channels.iter_mut()) {
+            state.filter.update(snapshot.timestamp, watch.channel.value(snapshot) as f64);
+            if state.filter.samples() < self.config.min_samples {
+                continue;
+            }
+
+            let crossing = self.crossing_time(watch, &state.filter);
+            match crossing {
+                Some(crossing_in) if !state.warned => {
+                    state.warned = true;
+                    let (earliest, latest) = self.band_crossings(watch, &state.filter, crossing_in);
+                    let forecast = state.filter.forecast(horizon, self.config.band_z);
+                    warnings.push(EarlyWarning {
+                        car_id: snapshot.car_id,
+                        session_id: snapshot.session_id,
+                        channel: watch.channel,
+                        op: watch.op,
+                        limit: watch.limit,
+                        severity: watch.severity,
+                        current: state.filter.level() as f32,
+                        slope: state.filter.slope() as f32,
+                        crossing_in_secs: crossing_in as f32,
+                        earliest_crossing_secs: earliest,
+                        latest_crossing_secs: latest,
+                        probability: exceedance_probability(watch, &forecast),
+                        forecast,
+                        timestamp: snapshot.timestamp,
+                    });
+                }
+                Some(_) => {}
+                None => state.warned = false,
+            }
+        }
+        warnings
+    }
+
+    /// Forecast one channel of a car `horizon` ahead
+    pub fn forecast(
+        &self,
+        session_id: SessionId,
+        car_id: CarId,
+        channel: Channel,
+        horizon: Duration,
+    ) -> Option<ForecastPoint> {
+        let states = self.states.get(&(session_id, car_id))?;
+        let index = self.config.channels.iter().position(|watch| watch.channel == channel)?;
+        let filter = &states.channels[index].filter;
+        (filter.samples() > 0).then(|| filter.forecast(horizon.as_secs_f64(), self.config.band_z))
+    }
+
+    /// Number of cars being tracked
+    pub fn car_count(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Drop cars not observed within `max_idle`; returns the number removed
+    pub fn evict_idle(&self, max_idle: Duration) -> usize {
+        self.evict_where(|_, car| car.last_seen.elapsed() > max_idle)
+    }
+
+    /// Drop every car of a session; returns the number removed
+    pub fn evict_session(&self, session_id: SessionId) -> usize {
+        self.evict_where(|(session, _), _| *session == session_id)
+    }
+
+    /// Drop one car of a session; returns whether it was tracked
+    pub fn evict_car(&self, session_id: SessionId, car_id: CarId) -> bool {
+        self.states.remove(&(session_id, car_id)).is_some()
+    }
+
+    /// Drop matching cars; counted during the sweep, since other threads may
+    /// insert cars concurrently
+    fn evict_where(&self, mut evict: impl FnMut(&(SessionId, CarId), &CarState) -> bool) -> usize {
+        let mut removed = 0;
+        self.states.retain(|key, car| {
+            let drop = evict(key, car);
+            removed += usize::from(drop);
+            !drop
+        });
+        removed
+    }
+
+    /// Evict least recently observed cars until at most `capacity` remain
+    fn enforce_capacity(&self, capacity: usize) {
+        let excess = self.states.len().saturating_sub(capacity);
+        if excess == 0 {
+            return;
+        }
+
+        let mut by_age: Vec<((SessionId, CarId), Instant)> =
+            self.states.iter().map(|entry| (*entry.key(), entry.value().last_seen)).collect();
+        by_age.sort_by_key(|(_, last_seen)| *last_seen);
+        for (key, _) in by_age.into_iter().take(excess) {
+            self.states.remove(&key);
+        }
+    }
+
+    /// Seconds until the expected value crosses the limit, if within the
+    /// horizon and not already crossed
+    fn crossing_time(&self, watch: &ChannelForecast, filter: &TrendFilter) -> Option<f64> {
+        let level = filter.level();
+        let slope = filter.slope();
+        if watch.crossed(level as f32) || slope == 0.0 {
+            return None;
+        }
+        let time = (watch.limit as f64 - level) / slope;
+        (time > 0.0 && time <= self.config.horizon.as_secs_f64()).then_some(time)
+    }
+
+    /// Earliest and latest crossing times from the band edges
+    fn band_crossings(&self, watch: &ChannelForecast, filter: &TrendFilter, crossing_in: f64) -> (f32, Option<f32>) {
+        let horizon = self.config.horizon.as_secs_f64();
+        let step = self.config.resolution.as_secs_f64().max(1.0e-3);
+        let steps = (horizon / step).ceil() as usize;
+        let mut earliest = crossing_in as f32;
+        let mut latest = None;
+
+        for i in 1..=steps {
+            let t = (i as f64 * step).min(horizon);
+            let point = filter.forecast(t, self.config.band_z);
+            if watch.crossed(watch.pessimistic(&point)) {
+                earliest = earliest.min(t as f32);
+                break;
+            }
+        }
+        for i in 1..=steps {
+            let t = (i as f64 * step).min(horizon);
+            let point = filter.forecast(t, self.config.band_z);
+            if watch.crossed(watch.optimistic(&point)) {
+                latest = Some((t as f32).max(crossing_in as f32));
+                break;
+            }
+        }
+        (earliest, latest)
+    }
+}
+
+/// Probability that the value is beyond the limit at the forecast point
+fn exceedance_probability(watch: &ChannelForecast, point: &ForecastPoint) -> f32 {
+    if point.std_dev <= 0.0 {
+        return if watch.crossed(point.mean) { 1.0 } else { 0.0 };
+    }
+    let z = ((point.mean - watch.limit) / point.std_dev) as f64;
+    let above = normal_cdf(z) as f32;
+    match watch.op {
+        Comparison::Gt | Comparison::Ge => above,
+        Comparison::Lt | Comparison::Le => 1.0 - above,
+    }
+}
+
+/// Standard normal CDF (Abramowitz & Stegun 7.1.26)
+fn normal_cdf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs() / std::f64::consts::SQRT_2);
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-(x * x) / 2.0).exp();
+    if x >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::tests::create_test_snapshot;
+
+    #[test]
+    fn test_trend_filter_tracks_ramp() {
+        let mut filter = TrendFilter::new(0.1, 0.5);
+        let start = Utc::now();
+        for i in 0..100 {
+            let t = start + chrono::Duration::milliseconds(i * 100);
+            filter.update(t, 90.0 + 0.2 * i as f64);
+        }
+
+        // 2 °C/s ramp, last value 109.8; non-finite readings are skipped
+        filter.update(start + chrono::Duration::milliseconds(9_950), f64::NAN);
+        filter.update(start + chrono::Duration::milliseconds(9_950), f64::INFINITY);
+        assert_eq!(filter.samples(), 100);
+        assert!((filter.slope() - 2.0).abs() < 0.1);
+        let point = filter.forecast(5.0, 1.645);
+        assert!((point.mean - 119.8).abs() < 1.0);
+        assert!(point.lower < point.mean && point.mean < point.upper);
+
+        // Band widens with the horizon
+        let near = filter.forecast(1.0, 1.645);
+        assert!(point.upper - point.lower > near.upper - near.lower);
+    }
+
+    #[test]
+    fn test_warns_before_crossing() {
+        let forecaster = Forecaster::new(ForecastConfig::default());
+        let mut snapshot = create_test_snapshot();
+        let start = snapshot.timestamp;
+        let mut warnings = Vec::new();
+
+        // Front left heats up at 1.5 °C/s from 95 °C; the 115 °C limit is
+        // reached after ~13 s
+        for i in 0..200 {
+            snapshot.timestamp = start + chrono::Duration::milliseconds(i * 100);
+            snapshot.tires.front_left.surface_temp = 95.0 + 0.15 * i as f32;
+            warnings.extend(forecaster.observe(&snapshot));
+        }
+
+        let warning = warnings
+            .iter()
+            .find(|w| w.channel == Channel::TireTempFrontLeft)
+            .expect("no early warning");
+        let emitted_at = (warning.timestamp - start).num_milliseconds() as f32 / 1000.0;
+        assert!(emitted_at < 13.3);
+        assert!(warning.crossing_in_secs <= 10.0);
+        assert!(warning.earliest_crossing_secs <= warning.crossing_in_secs);
+        assert!(warning.probability > 0.5);
+        assert!((warning.slope - 1.5).abs() < 0.1);
+
+        // Expected value reaches the limit within the horizon, and the band straddles it
+        assert!(warning.forecast.mean >= 115.0 && warning.forecast.mean < 116.0);
+        assert!(warning.forecast.lower < 115.0 && warning.forecast.upper > 115.0);
+
+        // One warning per activation, none for steady channels
+        assert_eq!(warnings.iter().filter(|w| w.channel == Channel::TireTempFrontLeft).count(), 1);
+        assert!(warnings.iter().all(|w| w.channel == Channel::TireTempFrontLeft));
+    }
+
+    #[test]
+    fn test_falling_channel_and_eviction() {
+        let config = ForecastConfig {
+            channels: vec![ChannelForecast::new(Channel::ErsBattery, Comparison::Lt, 0.05, Severity::Low)
+                .with_noise(1.0e-4, 0.005)],
+            ..Default::default()
+        };
+        let forecaster = Forecaster::new(config);
+        let mut snapshot = create_test_snapshot();
+        let start = snapshot.timestamp;
+        let mut warnings = Vec::new();
+
+        for i in 0..150 {
+            snapshot.timestamp = start + chrono::Duration::milliseconds(i * 100);
+            snapshot.power_unit.ers_battery = (0.7 - 0.005 * i as f32).max(0.0);
+            warnings.extend(forecaster.observe(&snapshot));
+        }
+
+        assert_eq!(warnings.len(), 1);
+        let info = AnomalyInfo::from(warnings[0].clone());
+        assert_eq!(info.field, "forecast:ers_battery");
+        assert!(forecaster
+            .forecast(snapshot.session_id, snapshot.car_id, Channel::ErsBattery, Duration::from_secs(5))
+            .is_some());
+
+        assert_eq!(forecaster.evict_session(snapshot.session_id), 1);
+        assert_eq!(forecaster.car_count(), 0);
+
+        forecaster.observe(&snapshot);
+        assert_eq!(forecaster.evict_idle(Duration::from_secs(60)), 0);
+        assert_eq!(forecaster.evict_idle(Duration::ZERO), 1);
+    }
+
+    #[test]
+    fn test_forecaster_caps_tracked_cars() {
+        let forecaster = Forecaster::new(ForecastConfig {
+            max_cars: 2,
+            ..Default::default()
+        });
+        let mut snapshot = create_test_snapshot();
+        for car in 1..=3 {
+            snapshot.car_id = CarId::new(car).unwrap();
+            forecaster.observe(&snapshot);
+        }
+
+        // The least recently observed car made room for the third
+        assert_eq!(forecaster.car_count(), 2);
+        assert!(forecaster
+            .forecast(snapshot.session_id, CarId::new(1).unwrap(), Channel::ErsBattery, Duration::from_secs(1))
+            .is_none());
+    }
+}
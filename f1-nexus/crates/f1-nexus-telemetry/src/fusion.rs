@@ -0,0 +1,575 @@
+//! Sensor fusion for channels the source does not provide
+//!
+//! Feeds like OpenF1 only carry speed, RPM, gear, pedals and DRS, plus a
+//! separate car position trace; `from_openf1` leaves every other field at
+//! zero. `SensorFusion` reconstructs what it can from the available traces:
+//!
+//! - longitudinal acceleration and G from the speed trace
+//! - lateral G and yaw rate from the curvature of the position trace
+//! - speed from the position trace when the source has none
+//! - fuel remaining and consumption from the lap count and a
+//!   `FuelConsumptionModel`
+//!
+//! Every channel of the result is tagged as measured, estimated or missing,
+//! with a one-sigma uncertainty in the channel's units, so downstream models
+//! can tell real data from inferred data.
+
+use crate::Channel;
+use chrono::{DateTime, Utc};
+use f1_nexus_core::{CarId, FuelConsumptionModel, OpenF1Location, TelemetrySnapshot, MAX_FUEL_CAPACITY};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+/// Standard gravity (m/s²)
+const GRAVITY: f32 = 9.806_65;
+
+/// Where a channel value comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataSource {
+    /// Reported by the source
+    Measured,
+    /// Inferred from other channels or a model
+    Estimated,
+    /// Neither reported nor inferable; the value is a placeholder
+    Missing,
+}
+
+/// Source and uncertainty of one channel
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ChannelQuality {
+    pub source: DataSource,
+
+    /// One-sigma uncertainty in the channel's units; `None` when missing
+    pub uncertainty: Option<f32>,
+}
+
+impl ChannelQuality {
+    pub fn measured(uncertainty: f32) -> Self {
+        ChannelQuality {
+            source: DataSource::Measured,
+            uncertainty: Some(uncertainty),
+        }
+    }
+
+    pub fn estimated(uncertainty: f32) -> Self {
+        ChannelQuality {
+            source: DataSource::Estimated,
+            uncertainty: Some(uncertainty),
+        }
+    }
+
+    pub fn missing() -> Self {
+        ChannelQuality {
+            source: DataSource::Missing,
+            uncertainty: None,
+        }
+    }
+
+    /// Quality of a value computed from several channels: the weakest source
+    /// and the largest uncertainty
+    pub fn combine(qualities: impl IntoIterator<Item = ChannelQuality>) -> Self {
+        let mut combined = ChannelQuality::measured(0.0);
+        for quality in qualities {
+            combined.source = match (combined.source, quality.source) {
+                (DataSource::Missing, _) | (_, DataSource::Missing) => DataSource::Missing,
+                (DataSource::Estimated, _) | (_, DataSource::Estimated) => DataSource::Estimated,
+                _ => DataSource::Measured,
+            };
+            combined.uncertainty = combined.uncertainty.zip(quality.uncertainty).map(|(a, b)| a.max(b));
+        }
+        combined
+    }
+}
+
+/// Car position in track coordinates (m)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PositionSample {
+    pub timestamp: DateTime<Utc>,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl PositionSample {
+    /// Convert an OpenF1 location, scaling its coordinates to metres
+    pub fn from_openf1(location: &OpenF1Location, scale: f32) -> Option<Self> {
+        let timestamp = DateTime::parse_from_rfc3339(&location.date).ok()?.with_timezone(&Utc);
+        Some(PositionSample {
+            timestamp,
+            x: location.x? * scale,
+            y: location.y? * scale,
+            z: location.z.unwrap_or(0.0) * scale,
+        })
+    }
+
+    fn distance(&self, other: &PositionSample) -> f32 {
+        (other.x - self.x).hypot(other.y - self.y)
+    }
+}
+
+/// Sensor fusion configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FusionConfig {
+    /// Channels the source reports; all others are estimated or missing
+    pub measured: Vec<Channel>,
+
+    /// Speed sensor noise (km/h, one sigma)
+    pub speed_noise: f32,
+
+    /// Position noise (m, one sigma)
+    pub position_noise: f32,
+
+    /// Scale from OpenF1 location units to metres
+    pub location_scale: f32,
+
+    /// Minimum span of the speed difference behind longitudinal G
+    pub derivative_window: Duration,
+
+    /// Minimum chord of the three positions behind a curvature estimate (m)
+    pub curvature_chord: f32,
+
+    /// Oldest position still used for the current snapshot
+    pub max_location_age: Duration,
+
+    /// History kept per car
+    pub history: Duration,
+
+    /// Fuel load at the start of lap 1 (kg)
+    pub start_fuel: f32,
+    pub start_fuel_uncertainty: f32,
+    pub fuel_model: FuelConsumptionModel,
+
+    /// Relative uncertainty of the per-lap consumption
+    pub consumption_uncertainty: f32,
+}
+
+impl FusionConfig {
+    /// Channels `TelemetrySnapshot::from_openf1` fills in
+    pub const OPENF1_CHANNELS: [Channel; 4] = [Channel::Speed, Channel::Rpm, Channel::Throttle, Channel::Brake];
+}
+
+impl Default for FusionConfig {
+    fn default() -> Self {
+        FusionConfig {
+            measured: FusionConfig::OPENF1_CHANNELS.to_vec(),
+            speed_noise: 1.0,
+            position_noise: 0.5,
+            location_scale: 0.1,
+            derivative_window: Duration::from_millis(250),
+            curvature_chord: 20.0,
+            max_location_age: Duration::from_secs(1),
+            history: Duration::from_secs(3),
+            start_fuel: MAX_FUEL_CAPACITY,
+            start_fuel_uncertainty: 1.0,
+            fuel_model: FuelConsumptionModel::default_model(),
+            consumption_uncertainty: 0.05,
+        }
+    }
+}
+
+/// Snapshot with a quality tag per channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FusedSnapshot {
+    pub snapshot: TelemetrySnapshot,
+    pub quality: HashMap<Channel, ChannelQuality>,
+}
+
+impl FusedSnapshot {
+    /// Quality of a channel; untagged channels count as missing
+    pub fn quality(&self, channel: Channel) -> ChannelQuality {
+        self.quality.get(&channel).copied().unwrap_or_else(ChannelQuality::missing)
+    }
+
+    pub fn source(&self, channel: Channel) -> DataSource {
+        self.quality(channel).source
+    }
+
+    pub fn is_measured(&self, channel: Channel) -> bool {
+        self.source(channel) == DataSource::Measured
+    }
+
+    pub fn uncertainty(&self, channel: Channel) -> Option<f32> {
+        self.quality(channel).uncertainty
+    }
+
+    /// Channel value, unless the channel is missing
+    pub fn value(&self, channel: Channel) -> Option<f32> {
+        (self.source(channel) != DataSource::Missing).then(|| channel.value(&self.snapshot))
+    }
+
+    /// Channels with the given source, in `Channel::ALL` order
+    pub fn channels(&self, source: DataSource) -> Vec<Channel> {
+        Channel::ALL.into_iter().filter(|c| self.source(*c) == source).collect()
+    }
+}
+
+#[derive(Debug, Default)]
+struct CarState {
+    speeds: VecDeque<(DateTime<Utc>, f32)>,
+    positions: VecDeque<PositionSample>,
+    lap_start: Option<(u16, DateTime<Utc>)>,
+    last_lap_secs: Option<f32>,
+}
+
+fn seconds(duration: chrono::Duration) -> f32 {
+    duration.num_microseconds().unwrap_or(0) as f32 / 1.0e6
+}
+
+/// Reconstructs channels the source does not report
+pub struct SensorFusion {
+    config: FusionConfig,
+    cars: HashMap<CarId, CarState>,
+}
+
+impl SensorFusion {
+    pub fn new(config: FusionConfig) -> Self {
+        SensorFusion {
+            config,
+            cars: HashMap::new(),
+        }
+    }
+
+    /// Get configuration
+    pub fn config(&self) -> &FusionConfig {
+        &self.config
+    }
+
+    /// Record a car position
+    pub fn observe_position(&mut self, car_id: CarId, sample: PositionSample) {
+        let state = self.cars.entry(car_id).or_default();
+        if state.positions.back().map_or(true, |last| last.timestamp < sample.timestamp) {
+            state.positions.push_back(sample);
+        }
+    }
+
+    /// Record an OpenF1 location; returns false if it has no coordinates
+    pub fn observe_openf1_location(&mut self, location: &OpenF1Location) -> bool {
+        match PositionSample::from_openf1(location, self.config.location_scale) {
+            Some(sample) => {
+                self.observe_position(CarId(location.driver_number), sample);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Forget a car's history
+    pub fn reset(&mut self, car_id: CarId) {
+        self.cars.remove(&car_id);
+    }
+
+    /// Fill the unmeasured channels of a snapshot and tag every channel
+    pub fn fuse(&mut self, mut snapshot: TelemetrySnapshot) -> FusedSnapshot {
+        let config = &self.config;
+        let now = snapshot.timestamp;
+        let state = self.cars.entry(snapshot.car_id).or_default();
+        let horizon = now - chrono::Duration::from_std(config.history).unwrap_or_default();
+        while state.speeds.front().is_some_and(|(t, _)| *t < horizon) {
+            state.speeds.pop_front();
+        }
+        while state.positions.front().is_some_and(|p| p.timestamp < horizon) {
+            state.positions.pop_front();
+        }
+
+        let mut quality: HashMap<Channel, ChannelQuality> = Channel::ALL
+            .into_iter()
+            .map(|channel| {
+                let q = if config.measured.contains(&channel) {
+                    ChannelQuality::measured(if channel == Channel::Speed { config.speed_noise } else { 0.0 })
+                } else {
+                    ChannelQuality::missing()
+                };
+                (channel, q)
+            })
+            .collect();
+        let measured = |channel: Channel| config.measured.contains(&channel);
+
+        // Position triple ending at the latest fix, spanning at least the chord
+        let recent = state
+            .positions
+            .iter()
+            .rposition(|p| p.timestamp <= now)
+            .filter(|&i| seconds(now - state.positions[i].timestamp) <= config.max_location_age.as_secs_f32());
+        let triple = recent.and_then(|last| {
+            let end = &state.positions[last];
+            let first = (0..last).rev().find(|&i| state.positions[i].distance(end) >= config.curvature_chord)?;
+            let mid_time = state.positions[first].timestamp + (end.timestamp - state.positions[first].timestamp) / 2;
+            let mid = (first + 1..last).min_by_key(|&i| (state.positions[i].timestamp - mid_time).num_microseconds().unwrap_or(0).abs())?;
+            Some((state.positions[first], state.positions[mid], *end))
+        });
+
+        if !measured(Channel::Speed) {
+            if let Some((a, _, c)) = triple {
+                let dt = seconds(c.timestamp - a.timestamp);
+                if dt > 0.0 {
+                    snapshot.motion.speed = a.distance(&c) / dt * 3.6;
+                    let sigma = std::f32::consts::SQRT_2 * config.position_noise / dt * 3.6;
+                    quality.insert(Channel::Speed, ChannelQuality::estimated(sigma));
+                }
+            }
+        }
+        let speed_quality = quality[&Channel::Speed];
+        if speed_quality.source != DataSource::Missing && state.speeds.back().map_or(true, |(t, _)| *t < now) {
+            state.speeds.push_back((now, snapshot.motion.speed));
+        }
+        let speed_sigma = speed_quality.uncertainty.unwrap_or(0.0) / 3.6;
+        let speed = snapshot.motion.speed / 3.6;
+
+        // Backward difference over at least the derivative window
+        let window = config.derivative_window.as_secs_f32();
+        let before = state.speeds.iter().rev().find(|(t, _)| seconds(now - *t) >= window).copied();
+        if let Some((then, previous)) = before.filter(|_| speed_quality.source != DataSource::Missing) {
+            let dt = seconds(now - then);
+            let acceleration = (speed - previous / 3.6) / dt;
+            let sigma = std::f32::consts::SQRT_2 * speed_sigma / dt;
+            if !measured(Channel::Acceleration) {
+                snapshot.motion.acceleration = acceleration;
+                quality.insert(Channel::Acceleration, ChannelQuality::estimated(sigma));
+            }
+            if !measured(Channel::LongitudinalG) {
+                snapshot.motion.longitudinal_g = acceleration / GRAVITY;
+                quality.insert(Channel::LongitudinalG, ChannelQuality::estimated(sigma / GRAVITY));
+            }
+        }
+
+        // Signed curvature of the circle through the three positions,
+        // positive when turning anticlockwise in track coordinates
+        if let Some((a, b, c)) = triple.filter(|_| speed_quality.source != DataSource::Missing) {
+            let (abx, aby) = (b.x - a.x, b.y - a.y);
+            let (bcx, bcy) = (c.x - b.x, c.y - b.y);
+            let cross = abx * bcy - aby * bcx;
+            let sides = a.distance(&b) * b.distance(&c) * a.distance(&c);
+            if sides > 0.0 {
+                let curvature = 2.0 * cross / sides;
+                // Sagitta error over the chord: kappa = 8s / L²
+                let chord = a.distance(&c);
+                let curvature_sigma = 8.0 * config.position_noise / (chord * chord);
+                if !measured(Channel::LateralG) {
+                    snapshot.motion.lateral_g = speed * speed * curvature / GRAVITY;
+                    let sigma = (speed * speed * curvature_sigma).hypot(2.0 * speed * curvature * speed_sigma);
+                    quality.insert(Channel::LateralG, ChannelQuality::estimated(sigma / GRAVITY));
+                }
+                if !measured(Channel::YawRate) {
+                    snapshot.motion.yaw_rate = speed * curvature;
+                    let sigma = (speed * curvature_sigma).hypot(curvature * speed_sigma);
+                    quality.insert(Channel::YawRate, ChannelQuality::estimated(sigma));
+                }
+            }
+        }
+
+        // Fuel from completed laps plus the elapsed share of the current one
+        let lap = snapshot.lap.0;
+        match state.lap_start {
+            Some((current, _)) if current == lap => {}
+            Some((previous, start)) => {
+                state.last_lap_secs = (lap == previous + 1).then(|| seconds(now - start));
+                state.lap_start = Some((lap, now));
+            }
+            None => state.lap_start = Some((lap, now)),
+        }
+        if !measured(Channel::FuelRemaining) {
+            let model = &config.fuel_model;
+            let mut fuel = config.start_fuel;
+            for _ in 1..lap.max(1) {
+                fuel -= model.consumption_per_lap(fuel);
+            }
+            let elapsed = state.lap_start.map(|(_, start)| seconds(now - start)).unwrap_or(0.0);
+            let share = state.last_lap_secs.map(|secs| (elapsed / secs).clamp(0.0, 1.0)).unwrap_or(0.0);
+            fuel = (fuel - model.consumption_per_lap(fuel) * share).max(0.0);
+            let burned = config.start_fuel - fuel;
+            snapshot.fuel.remaining = fuel;
+            quality.insert(
+                Channel::FuelRemaining,
+                ChannelQuality::estimated(config.start_fuel_uncertainty + config.consumption_uncertainty * burned),
+            );
+            if snapshot.fuel.consumption_rate <= 0.0 {
+                snapshot.fuel.consumption_rate = model.consumption_per_lap(fuel);
+            }
+        }
+
+        // Derived channels inherit the weakest of their inputs
+        let derived = |inputs: &[Channel]| ChannelQuality::combine(inputs.iter().map(|c| quality[c]));
+        let tire_temps = [
+            Channel::TireTempFrontLeft,
+            Channel::TireTempFrontRight,
+            Channel::TireTempRearLeft,
+            Channel::TireTempRearRight,
+        ];
+        let brake_temps = [
+            Channel::BrakeTempFrontLeft,
+            Channel::BrakeTempFrontRight,
+            Channel::BrakeTempRearLeft,
+            Channel::BrakeTempRearRight,
+        ];
+        let mut updates = Vec::new();
+        if !measured(Channel::PedalOverlap) {
+            updates.push((Channel::PedalOverlap, derived(&[Channel::Throttle, Channel::Brake])));
+        }
+        for channel in [Channel::TireTempMax, Channel::TireTempMin, Channel::TireTempAvg] {
+            if !measured(channel) {
+                updates.push((channel, derived(&tire_temps)));
+            }
+        }
+        for channel in [Channel::BrakeTempMax, Channel::BrakeTempAvg] {
+            if !measured(channel) {
+                updates.push((channel, derived(&brake_temps)));
+            }
+        }
+        if !measured(Channel::FuelLaps) {
+            let fuel = quality[&Channel::FuelRemaining];
+            let rate = snapshot.fuel.consumption_rate;
+            let laps = ChannelQuality {
+                uncertainty: fuel.uncertainty.filter(|_| rate > 0.0).map(|sigma| sigma / rate),
+                ..fuel
+            };
+            updates.push((Channel::FuelLaps, laps));
+        }
+        quality.extend(updates);
+
+        FusedSnapshot { snapshot, quality }
+    }
+}
+
+impl Default for SensorFusion {
+    fn default() -> Self {
+        Self::new(FusionConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::tests::create_test_snapshot;
+    use f1_nexus_core::LapNumber;
+
+    /// OpenF1-like snapshot: only speed, rpm and pedals set
+    fn openf1_snapshot(at_ms: i64, speed: f32) -> TelemetrySnapshot {
+        let mut snapshot = create_test_snapshot();
+        snapshot.timestamp = DateTime::from_timestamp_millis(1_784_034_000_000 + at_ms).unwrap();
+        snapshot.motion.speed = speed;
+        snapshot
+    }
+
+    #[test]
+    fn test_longitudinal_g_from_speed() {
+        let mut fusion = SensorFusion::default();
+
+        let first = fusion.fuse(openf1_snapshot(0, 100.0));
+        assert!(first.is_measured(Channel::Speed));
+        assert_eq!(first.uncertainty(Channel::Speed), Some(1.0));
+        assert_eq!(first.source(Channel::LongitudinalG), DataSource::Missing);
+        assert_eq!(first.value(Channel::LongitudinalG), None);
+        assert_eq!(first.source(Channel::TireTempMax), DataSource::Missing);
+
+        // +36 km/h per second = 10 m/s²
+        let mut last = first;
+        for step in 1..=10 {
+            last = fusion.fuse(openf1_snapshot(step * 100, 100.0 + step as f32 * 3.6));
+        }
+        assert_eq!(last.source(Channel::LongitudinalG), DataSource::Estimated);
+        assert!((last.snapshot.motion.acceleration - 10.0).abs() < 0.01);
+        assert!((last.snapshot.motion.longitudinal_g - 10.0 / GRAVITY).abs() < 0.01);
+        let sigma = last.uncertainty(Channel::Acceleration).unwrap();
+        assert!(sigma > 0.0 && sigma < 2.0);
+
+        let estimated = last.channels(DataSource::Estimated);
+        assert!(estimated.contains(&Channel::FuelRemaining));
+        assert!(!estimated.contains(&Channel::Speed));
+        assert!(last.channels(DataSource::Measured).contains(&Channel::Throttle));
+    }
+
+    #[test]
+    fn test_lateral_g_and_yaw_from_positions() {
+        for (direction, sign) in [(1.0f32, 1.0f32), (-1.0, -1.0)] {
+            let mut fusion = SensorFusion::default();
+            let car = CarId(1);
+            // 100 m radius at 50 m/s: 25 m/s² lateral, 0.5 rad/s yaw
+            for step in 0..=20 {
+                let t = step as f32 * 0.05;
+                let angle = direction * 0.5 * t;
+                fusion.observe_position(
+                    car,
+                    PositionSample {
+                        timestamp: DateTime::from_timestamp_millis(1_784_034_000_000 + step * 50).unwrap(),
+                        x: 100.0 * angle.cos(),
+                        y: 100.0 * angle.sin(),
+                        z: 0.0,
+                    },
+                );
+            }
+            let fused = fusion.fuse(openf1_snapshot(1000, 180.0));
+            assert_eq!(fused.source(Channel::LateralG), DataSource::Estimated);
+            assert!((fused.snapshot.motion.lateral_g - sign * 25.0 / GRAVITY).abs() < 0.05);
+            assert!((fused.snapshot.motion.yaw_rate - sign * 0.5).abs() < 0.01);
+            assert!(fused.uncertainty(Channel::LateralG).unwrap() > 0.0);
+        }
+
+        // Speed from positions when the source has none
+        let mut fusion = SensorFusion::new(FusionConfig {
+            measured: vec![Channel::Throttle, Channel::Brake],
+            ..FusionConfig::default()
+        });
+        for step in 0..=10 {
+            fusion.observe_position(
+                CarId(1),
+                PositionSample {
+                    timestamp: DateTime::from_timestamp_millis(1_784_034_000_000 + step * 100).unwrap(),
+                    x: step as f32 * 5.0,
+                    y: 0.0,
+                    z: 0.0,
+                },
+            );
+        }
+        let fused = fusion.fuse(openf1_snapshot(1000, 0.0));
+        assert_eq!(fused.source(Channel::Speed), DataSource::Estimated);
+        assert!((fused.snapshot.motion.speed - 180.0).abs() < 0.1);
+        assert!(fused.snapshot.motion.lateral_g.abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_fuel_estimate_from_laps() {
+        let mut fusion = SensorFusion::default();
+        let model = FuelConsumptionModel::default_model();
+        let lap_ms = 90_000;
+
+        let mut fused = None;
+        for (lap, at_ms) in [(1, 0), (2, lap_ms), (3, 2 * lap_ms), (3, 2 * lap_ms + lap_ms / 2)] {
+            let mut snapshot = openf1_snapshot(at_ms, 250.0);
+            snapshot.lap = LapNumber(lap);
+            fused = Some(fusion.fuse(snapshot));
+        }
+        let fused = fused.unwrap();
+
+        let mut expected = MAX_FUEL_CAPACITY;
+        expected -= model.consumption_per_lap(expected);
+        expected -= model.consumption_per_lap(expected);
+        expected -= model.consumption_per_lap(expected) * 0.5;
+        assert_eq!(fused.source(Channel::FuelRemaining), DataSource::Estimated);
+        assert!((fused.snapshot.fuel.remaining - expected).abs() < 1e-3);
+        let sigma = fused.uncertainty(Channel::FuelRemaining).unwrap();
+        assert!(sigma > 1.0 && sigma < 2.0);
+        assert_eq!(fused.source(Channel::FuelLaps), DataSource::Estimated);
+        assert!(fused.value(Channel::FuelLaps).unwrap() > 60.0);
+    }
+
+    #[test]
+    fn test_measured_channels_are_left_alone() {
+        let mut fusion = SensorFusion::new(FusionConfig {
+            measured: Channel::ALL.to_vec(),
+            ..FusionConfig::default()
+        });
+        let original = openf1_snapshot(0, 250.0);
+        fusion.fuse(original.clone());
+        let mut next = openf1_snapshot(500, 280.0);
+        next.motion.longitudinal_g = 1.2;
+        let fused = fusion.fuse(next.clone());
+
+        assert!(fused.channels(DataSource::Estimated).is_empty());
+        assert!(fused.channels(DataSource::Missing).is_empty());
+        assert_eq!(fused.snapshot.motion.longitudinal_g, 1.2);
+        assert_eq!(fused.snapshot.fuel.remaining, next.fuel.remaining);
+        assert_eq!(ChannelQuality::combine([ChannelQuality::measured(1.0), ChannelQuality::estimated(2.0)]), ChannelQuality::estimated(2.0));
+    }
+}
@@ -0,0 +1,1405 @@
+//! Telemetry file importers
+//!
+//! `CsvImporter` reads CSV exports through a configurable column mapping,
+//! converting each mapped column from its declared unit into the units of
+//! `TelemetrySnapshot`. `FastF1Importer` reads the CSV exports of FastF1
+//! telemetry and laps DataFrames (`df.to_csv()`), using the lap table to
+//! assign lap numbers, positions and tyres to the telemetry rows.
+//!
+//! Every import comes with a `DataQualityReport` listing missing channels,
+//! empty or unparseable cells, time gaps and values that look like they were
+//! exported in a different unit than the one declared.
+
+use crate::{LapSegmenter, TrackSample};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use f1_nexus_core::{
+    AeroData, BrakeData, CarId, DriverInputs, DrsStatus, ErsMode, FuelData, LapData, LapNumber, MotionData,
+    Position, PowerUnitData, SessionId, TelemetrySnapshot, TireCompound, TireData, TireSensor,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::Path;
+
+/// Row problems kept verbatim in a report; the rest are only counted
+const MAX_ISSUES: usize = 20;
+
+/// Snapshot field a column can be mapped to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportField {
+    CarId,
+    Lap,
+    Position,
+    Speed,
+    Rpm,
+    Gear,
+    Throttle,
+    Brake,
+    Steering,
+    Drs,
+    LateralG,
+    LongitudinalG,
+    ErsBattery,
+    FuelRemaining,
+    EngineTemp,
+    TireTempFrontLeft,
+    TireTempFrontRight,
+    TireTempRearLeft,
+    TireTempRearRight,
+    TirePressureFrontLeft,
+    TirePressureFrontRight,
+    TirePressureRearLeft,
+    TirePressureRearRight,
+    BrakeTempFrontLeft,
+    BrakeTempFrontRight,
+    BrakeTempRearLeft,
+    BrakeTempRearRight,
+    TireWearFrontLeft,
+    TireWearFrontRight,
+    TireWearRearLeft,
+    TireWearRearRight,
+    TireCompound,
+    TireAge,
+}
+
+/// Physical quantity of a field or unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dimension {
+    /// Plain number (lap, gear, rpm)
+    Count,
+    Speed,
+    Fraction,
+    Temperature,
+    Pressure,
+    Mass,
+    Acceleration,
+    Drs,
+    /// Compound name
+    Text,
+}
+
+impl ImportField {
+    /// Every field except the car id
+    pub const CHANNELS: [ImportField; 32] = [
+        ImportField::Lap,
+        ImportField::Position,
+        ImportField::Speed,
+        ImportField::Rpm,
+        ImportField::Gear,
+        ImportField::Throttle,
+        ImportField::Brake,
+        ImportField::Steering,
+        ImportField::Drs,
+        ImportField::LateralG,
+        ImportField::LongitudinalG,
+        ImportField::ErsBattery,
+        ImportField::FuelRemaining,
+        ImportField::EngineTemp,
+        ImportField::TireTempFrontLeft,
+        ImportField::TireTempFrontRight,
+        ImportField::TireTempRearLeft,
+        ImportField::TireTempRearRight,
+        ImportField::TirePressureFrontLeft,
+        ImportField::TirePressureFrontRight,
+        ImportField::TirePressureRearLeft,
+        ImportField::TirePressureRearRight,
+        ImportField::BrakeTempFrontLeft,
+        ImportField::BrakeTempFrontRight,
+        ImportField::BrakeTempRearLeft,
+        ImportField::BrakeTempRearRight,
+        ImportField::TireWearFrontLeft,
+        ImportField::TireWearFrontRight,
+        ImportField::TireWearRearLeft,
+        ImportField::TireWearRearRight,
+        ImportField::TireCompound,
+        ImportField::TireAge,
+    ];
+
+    /// Quantity the field measures
+    pub fn dimension(&self) -> Dimension {
+        use ImportField::*;
+        match self {
+            CarId | Lap | Position | Rpm | Gear | TireAge => Dimension::Count,
+            Speed => Dimension::Speed,
+            Throttle | Brake | Steering | ErsBattery | TireWearFrontLeft | TireWearFrontRight
+            | TireWearRearLeft | TireWearRearRight => Dimension::Fraction,
+            EngineTemp | TireTempFrontLeft | TireTempFrontRight | TireTempRearLeft | TireTempRearRight
+            | BrakeTempFrontLeft | BrakeTempFrontRight | BrakeTempRearLeft | BrakeTempRearRight => {
+                Dimension::Temperature
+            }
+            TirePressureFrontLeft | TirePressureFrontRight | TirePressureRearLeft | TirePressureRearRight => {
+                Dimension::Pressure
+            }
+            FuelRemaining => Dimension::Mass,
+            LateralG | LongitudinalG => Dimension::Acceleration,
+            Drs => Dimension::Drs,
+            TireCompound => Dimension::Text,
+        }
+    }
+
+    /// Unit the snapshot stores the field in
+    pub fn canonical_unit(&self) -> Unit {
+        match self.dimension() {
+            Dimension::Count => Unit::Count,
+            Dimension::Speed => Unit::Kph,
+            Dimension::Fraction => Unit::Fraction,
+            Dimension::Temperature => Unit::Celsius,
+            Dimension::Pressure => Unit::Psi,
+            Dimension::Mass => Unit::Kilogram,
+            Dimension::Acceleration => Unit::G,
+            Dimension::Drs => Unit::DrsState,
+            Dimension::Text => Unit::Text,
+        }
+    }
+}
+
+/// Unit of an imported column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Unit {
+    Count,
+    Kph,
+    Mph,
+    MetersPerSecond,
+    /// 0.0-1.0; `true`/`false` cells read as 1 and 0
+    Fraction,
+    /// 0-100
+    Percent,
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+    Psi,
+    Bar,
+    Kilopascal,
+    Kilogram,
+    Pound,
+    G,
+    MetersPerSecondSquared,
+    /// 0 = unavailable, 1 = available, 2 = open
+    DrsState,
+    /// F1 live timing DRS codes (8 = available, 10/12/14 = open)
+    DrsLiveTiming,
+    Text,
+}
+
+impl Unit {
+    pub fn dimension(&self) -> Dimension {
+        match self {
+            Unit::Count => Dimension::Count,
+            Unit::Kph | Unit::Mph | Unit::MetersPerSecond => Dimension::Speed,
+            Unit::Fraction | Unit::Percent => Dimension::Fraction,
+            Unit::Celsius | Unit::Fahrenheit | Unit::Kelvin => Dimension::Temperature,
+            Unit::Psi | Unit::Bar | Unit::Kilopascal => Dimension::Pressure,
+            Unit::Kilogram | Unit::Pound => Dimension::Mass,
+            Unit::G | Unit::MetersPerSecondSquared => Dimension::Acceleration,
+            Unit::DrsState | Unit::DrsLiveTiming => Dimension::Drs,
+            Unit::Text => Dimension::Text,
+        }
+    }
+
+    /// Convert a value in this unit to the canonical unit of its dimension
+    pub fn to_canonical(&self, value: f64) -> f64 {
+        match self {
+            Unit::Mph => value * 1.609_344,
+            Unit::MetersPerSecond => value * 3.6,
+            Unit::Percent => value / 100.0,
+            Unit::Fahrenheit => (value - 32.0) * 5.0 / 9.0,
+            Unit::Kelvin => value - 273.15,
+            Unit::Bar => value * 14.503_77,
+            Unit::Kilopascal => value * 0.145_037_7,
+            Unit::Pound => value * 0.453_592_37,
+            Unit::MetersPerSecondSquared => value / 9.806_65,
+            Unit::DrsLiveTiming => match value as i64 {
+                10..=14 => 2.0,
+                8 => 1.0,
+                _ => 0.0,
+            },
+            _ => value,
+        }
+    }
+}
+
+/// How the time column is encoded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeFormat {
+    /// RFC 3339, or a naive `YYYY-MM-DD HH:MM:SS[.f]` taken as UTC
+    #[default]
+    DateTime,
+    UnixSeconds,
+    UnixMillis,
+    /// Seconds since `start_time`
+    Seconds,
+    /// pandas timedelta (`0 days 00:01:31.234000`) since `start_time`
+    Timedelta,
+}
+
+/// One mapped column
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnMapping {
+    pub field: ImportField,
+    pub column: String,
+
+    /// Unit of the column; the field's canonical unit when omitted
+    #[serde(default)]
+    pub unit: Option<Unit>,
+}
+
+impl ColumnMapping {
+    pub fn new(field: ImportField, column: impl Into<String>) -> Self {
+        ColumnMapping {
+            field,
+            column: column.into(),
+            unit: None,
+        }
+    }
+
+    pub fn with_unit(mut self, unit: Unit) -> Self {
+        self.unit = Some(unit);
+        self
+    }
+
+    fn unit(&self) -> Unit {
+        self.unit.unwrap_or_else(|| self.field.canonical_unit())
+    }
+}
+
+/// CSV import configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CsvImportConfig {
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
+
+    pub time_column: String,
+
+    #[serde(default)]
+    pub time_format: TimeFormat,
+
+    /// Origin of relative time formats
+    #[serde(default)]
+    pub start_time: Option<DateTime<Utc>>,
+
+    /// Car id for files without a car column
+    #[serde(default)]
+    pub car_id: Option<u8>,
+
+    #[serde(default)]
+    pub columns: Vec<ColumnMapping>,
+
+    /// Intervals longer than this are reported as gaps (seconds)
+    #[serde(default = "default_gap_threshold")]
+    pub gap_threshold_secs: f32,
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+fn default_gap_threshold() -> f32 {
+    1.0
+}
+
+impl CsvImportConfig {
+    /// Config with only a time column
+    pub fn new(time_column: impl Into<String>, time_format: TimeFormat) -> Self {
+        CsvImportConfig {
+            delimiter: default_delimiter(),
+            time_column: time_column.into(),
+            time_format,
+            start_time: None,
+            car_id: None,
+            columns: Vec::new(),
+            gap_threshold_secs: default_gap_threshold(),
+        }
+    }
+
+    /// Add a column mapping
+    pub fn with_column(mut self, mapping: ColumnMapping) -> Self {
+        self.columns.push(mapping);
+        self
+    }
+
+    /// Load a config from YAML
+    pub fn from_yaml(source: &str) -> Result<Self, ImportError> {
+        let value: serde_json::Value =
+            serde_yaml::from_str(source).map_err(|e| ImportError::InvalidConfig(e.to_string()))?;
+        serde_json::from_value(value).map_err(|e| ImportError::InvalidConfig(e.to_string()))
+    }
+
+    /// Load a config from TOML
+    pub fn from_toml(source: &str) -> Result<Self, ImportError> {
+        toml::from_str(source).map_err(|e| ImportError::InvalidConfig(e.to_string()))
+    }
+
+    fn validate(&self) -> Result<(), ImportError> {
+        if !self.delimiter.is_ascii() {
+            return Err(ImportError::InvalidConfig("delimiter must be an ASCII character".to_string()));
+        }
+        if matches!(self.time_format, TimeFormat::Seconds | TimeFormat::Timedelta) && self.start_time.is_none() {
+            return Err(ImportError::InvalidConfig(
+                "relative time formats need a start_time".to_string(),
+            ));
+        }
+        if self.car_id.is_none() && !self.columns.iter().any(|c| c.field == ImportField::CarId) {
+            return Err(ImportError::InvalidConfig(
+                "map a car_id column or set a fixed car_id".to_string(),
+            ));
+        }
+        for mapping in &self.columns {
+            if mapping.unit().dimension() != mapping.field.dimension() {
+                return Err(ImportError::UnitMismatch {
+                    field: mapping.field,
+                    unit: mapping.unit(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Import errors
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+
+    #[error("Missing required column '{0}'")]
+    MissingColumn(String),
+
+    #[error("Unit {unit:?} cannot be used for {field:?}")]
+    UnitMismatch { field: ImportField, unit: Unit },
+
+    #[error("Invalid import config: {0}")]
+    InvalidConfig(String),
+}
+
+/// Period without samples for one car
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DataGap {
+    pub car_id: CarId,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub seconds: f32,
+}
+
+/// A column whose values look like a different unit than declared
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnitWarning {
+    pub field: ImportField,
+    pub column: String,
+    pub declared: Unit,
+    pub suspected: Unit,
+
+    /// Observed range after conversion to the canonical unit
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Data quality of one import
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DataQualityReport {
+    /// Data rows read
+    pub rows: usize,
+
+    /// Rows turned into snapshots or laps
+    pub imported: usize,
+    pub skipped_rows: usize,
+
+    /// First row problems, verbatim
+    pub issues: Vec<String>,
+
+    /// Mapped or expected columns absent from the file
+    pub missing_columns: Vec<String>,
+
+    /// Channels without a single value in the import
+    pub missing_channels: Vec<ImportField>,
+
+    /// Empty or NaN cells per field
+    pub empty_cells: BTreeMap<ImportField, usize>,
+
+    /// Unparseable cells per field
+    pub invalid_cells: BTreeMap<ImportField, usize>,
+
+    /// Rows older than the previous row of the same car
+    pub out_of_order: usize,
+    pub gaps: Vec<DataGap>,
+    pub unit_warnings: Vec<UnitWarning>,
+    pub cars: Vec<CarId>,
+    pub time_range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+impl DataQualityReport {
+    /// Whether nothing was skipped, unparseable, missing or suspicious
+    pub fn is_clean(&self) -> bool {
+        self.skipped_rows == 0
+            && self.missing_columns.is_empty()
+            && self.invalid_cells.is_empty()
+            && self.gaps.is_empty()
+            && self.unit_warnings.is_empty()
+    }
+
+    fn issue(&mut self, message: String) {
+        self.skipped_rows += 1;
+        if self.issues.len() < MAX_ISSUES {
+            self.issues.push(message);
+        }
+    }
+
+    /// Fill gaps, cars and time range from sorted snapshots
+    fn scan_snapshots(&mut self, snapshots: &[TelemetrySnapshot], gap_threshold_secs: f32) {
+        let mut last: HashMap<CarId, DateTime<Utc>> = HashMap::new();
+        for snapshot in snapshots {
+            if let Some(previous) = last.insert(snapshot.car_id, snapshot.timestamp) {
+                let seconds = seconds_between(previous, snapshot.timestamp);
+                if seconds > gap_threshold_secs {
+                    self.gaps.push(DataGap {
+                        car_id: snapshot.car_id,
+                        from: previous,
+                        to: snapshot.timestamp,
+                        seconds,
+                    });
+                }
+            }
+        }
+        let mut cars: Vec<CarId> = last.into_keys().collect();
+        cars.sort_by_key(|car| car.0);
+        self.cars = cars;
+        self.time_range = snapshots.first().zip(snapshots.last()).map(|(a, b)| (a.timestamp, b.timestamp));
+    }
+}
+
+/// Snapshots and laps from one import
+#[derive(Debug, Clone)]
+pub struct ImportedTelemetry {
+    /// Snapshots sorted by timestamp
+    pub snapshots: Vec<TelemetrySnapshot>,
+
+    /// Laps per car, in lap order
+    pub laps: HashMap<CarId, Vec<LapData>>,
+    pub report: DataQualityReport,
+}
+
+fn seconds_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f32 {
+    (to - from).num_microseconds().unwrap_or(0) as f32 / 1.0e6
+}
+
+/// Numeric cell; `None` for empty and NaN cells, `Err` for garbage
+fn parse_number(cell: &str) -> Result<Option<f64>, ()> {
+    match cell.trim() {
+        "" => Ok(None),
+        "True" | "true" | "TRUE" => Ok(Some(1.0)),
+        "False" | "false" | "FALSE" => Ok(Some(0.0)),
+        text => match text.parse::<f64>() {
+            Ok(value) if value.is_nan() => Ok(None),
+            Ok(value) => Ok(Some(value)),
+            Err(_) if text.eq_ignore_ascii_case("nan") || text == "NaT" => Ok(None),
+            Err(_) => Err(()),
+        },
+    }
+}
+
+/// Absolute timestamp: RFC 3339 or naive date and time taken as UTC
+fn parse_datetime(text: &str) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Some(time.with_timezone(&Utc));
+    }
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .map(|naive| Utc.from_utc_datetime(&naive))
+}
+
+/// Duration in seconds: pandas timedelta, ISO 8601 duration or plain seconds
+///
+/// Infinite or NaN durations are rejected.
+pub fn parse_duration_secs(text: &str) -> Option<f64> {
+    parse_duration(text).filter(|seconds| seconds.is_finite())
+}
+
+fn parse_duration(text: &str) -> Option<f64> {
+    let text = text.trim();
+    if text.is_empty() || text == "NaT" {
+        return None;
+    }
+    if let Ok(seconds) = text.parse::<f64>() {
+        return Some(seconds);
+    }
+    if let Some(iso) = text.strip_prefix('P') {
+        let mut seconds = 0.0;
+        let mut number = String::new();
+        for c in iso.chars() {
+            match c {
+                'T' => {}
+                'D' | 'H' | 'M' | 'S' => {
+                    let value: f64 = number.parse().ok()?;
+                    number.clear();
+                    seconds += value
+                        * match c {
+                            'D' => 86_400.0,
+                            'H' => 3_600.0,
+                            'M' => 60.0,
+                            _ => 1.0,
+                        };
+                }
+                _ => number.push(c),
+            }
+        }
+        return Some(seconds);
+    }
+
+    // [-]N days [+]HH:MM:SS[.f]
+    let (days, clock) = match text.split_once(" days ").or_else(|| text.split_once(" day ")) {
+        Some((days, clock)) => (days.trim().parse::<f64>().ok()?, clock.trim_start_matches('+')),
+        None => (0.0, text),
+    };
+    let mut parts = clock.split(':');
+    let (hours, minutes, seconds) = (parts.next()?, parts.next()?, parts.next()?);
+    Some(days * 86_400.0 + hours.parse::<f64>().ok()? * 3_600.0 + minutes.parse::<f64>().ok()? * 60.0 + seconds.parse::<f64>().ok()?)
+}
+
+/// Whole microseconds in `seconds`, if finite and within `i64`
+fn micros(seconds: f64) -> Option<i64> {
+    let micros = (seconds * 1.0e6).round();
+    (micros.is_finite() && micros.abs() < i64::MAX as f64).then_some(micros as i64)
+}
+
+/// Tyre compound from a C-number or a FastF1 compound name
+///
+/// FastF1 only names the relative compound, so SOFT, MEDIUM and HARD map to
+/// the nominal C4, C3 and C2.
+fn parse_compound(text: &str) -> Option<TireCompound> {
+    match text.trim().to_ascii_uppercase().as_str() {
+        "C0" => Some(TireCompound::C0),
+        "C1" => Some(TireCompound::C1),
+        "C2" | "HARD" => Some(TireCompound::C2),
+        "C3" | "MEDIUM" => Some(TireCompound::C3),
+        "C4" | "SOFT" => Some(TireCompound::C4),
+        "C5" | "SUPERSOFT" => Some(TireCompound::C5),
+        "INTERMEDIATE" | "INTER" => Some(TireCompound::Intermediate),
+        "WET" => Some(TireCompound::Wet),
+        _ => None,
+    }
+}
+
+/// Snapshot with every channel zeroed
+pub fn blank_snapshot(session_id: SessionId, car_id: CarId, timestamp: DateTime<Utc>) -> TelemetrySnapshot {
+    TelemetrySnapshot {
+        session_id,
+        car_id,
+        timestamp,
+        lap: LapNumber(1),
+        position: Position(0),
+        motion: MotionData {
+            speed: 0.0,
+            acceleration: 0.0,
+            lateral_g: 0.0,
+            longitudinal_g: 0.0,
+            vertical_g: 0.0,
+            yaw_rate: 0.0,
+            pitch: 0.0,
+            roll: 0.0,
+        },
+        tires: TireData {
+            front_left: TireSensor::default(),
+            front_right: TireSensor::default(),
+            rear_left: TireSensor::default(),
+            rear_right: TireSensor::default(),
+            compound: TireCompound::C3,
+            age_laps: 0,
+        },
+        power_unit: PowerUnitData {
+            rpm: 0,
+            throttle: 0.0,
+            ers_mode: ErsMode::None,
+            ers_battery: 0.0,
+            mgu_k_deployment: 0.0,
+            mgu_h_recovery: 0.0,
+            engine_temp: 0.0,
+            oil_temp: 0.0,
+            oil_pressure: 0.0,
+        },
+        aero: AeroData {
+            front_wing_angle: 0.0,
+            rear_wing_angle: 0.0,
+            downforce: 0.0,
+            drag_coefficient: 0.0,
+        },
+        brakes: BrakeData {
+            bias: 0.0,
+            pressure: 0.0,
+            front_temp: 0.0,
+            rear_temp: 0.0,
+        },
+        inputs: DriverInputs {
+            steering: 0.0,
+            throttle: 0.0,
+            brake: 0.0,
+            clutch: 0.0,
+            gear: 0,
+        },
+        fuel: FuelData {
+            remaining: 0.0,
+            consumption_rate: 0.0,
+            temperature: 0.0,
+            pressure: 0.0,
+        },
+        drs: DrsStatus::Unavailable,
+    }
+}
+
+/// Write a canonical value into its snapshot field
+fn apply(snapshot: &mut TelemetrySnapshot, field: ImportField, value: f64) {
+    let v = value as f32;
+    let tires = &mut snapshot.tires;
+    match field {
+        ImportField::CarId | ImportField::TireCompound => {}
+        ImportField::Lap => snapshot.lap = LapNumber(value as u16),
+        ImportField::Position => snapshot.position = Position(value as u8),
+        ImportField::Speed => snapshot.motion.speed = v,
+        ImportField::Rpm => snapshot.power_unit.rpm = value as u16,
+        ImportField::Gear => snapshot.inputs.gear = value as i8,
+        ImportField::Throttle => {
+            snapshot.inputs.throttle = v;
+            snapshot.power_unit.throttle = v;
+        }
+        ImportField::Brake => {
+            snapshot.inputs.brake = v;
+            snapshot.brakes.pressure = v;
+        }
+        ImportField::Steering => snapshot.inputs.steering = v,
+        ImportField::Drs => {
+            snapshot.drs = match value as i64 {
+                2 => DrsStatus::Activated,
+                1 => DrsStatus::Available,
+                _ => DrsStatus::Unavailable,
+            }
+        }
+        ImportField::LateralG => snapshot.motion.lateral_g = v,
+        ImportField::LongitudinalG => {
+            snapshot.motion.longitudinal_g = v;
+            snapshot.motion.acceleration = v * 9.806_65;
+        }
+        ImportField::ErsBattery => snapshot.power_unit.ers_battery = v,
+        ImportField::FuelRemaining => snapshot.fuel.remaining = v,
+        ImportField::EngineTemp => snapshot.power_unit.engine_temp = v,
+        ImportField::TireTempFrontLeft => tires.front_left.surface_temp = v,
+        ImportField::TireTempFrontRight => tires.front_right.surface_temp = v,
+        ImportField::TireTempRearLeft => tires.rear_left.surface_temp = v,
+        ImportField::TireTempRearRight => tires.rear_right.surface_temp = v,
+        ImportField::TirePressureFrontLeft => tires.front_left.pressure = v,
+        ImportField::TirePressureFrontRight => tires.front_right.pressure = v,
+        ImportField::TirePressureRearLeft => tires.rear_left.pressure = v,
+        ImportField::TirePressureRearRight => tires.rear_right.pressure = v,
+        ImportField::BrakeTempFrontLeft => tires.front_left.brake_temp = v,
+        ImportField::BrakeTempFrontRight => tires.front_right.brake_temp = v,
+        ImportField::BrakeTempRearLeft => tires.rear_left.brake_temp = v,
+        ImportField::BrakeTempRearRight => tires.rear_right.brake_temp = v,
+        ImportField::TireWearFrontLeft => tires.front_left.wear = v,
+        ImportField::TireWearFrontRight => tires.front_right.wear = v,
+        ImportField::TireWearRearLeft => tires.rear_left.wear = v,
+        ImportField::TireWearRearRight => tires.rear_right.wear = v,
+        ImportField::TireAge => tires.age_laps = value as u16,
+    }
+}
+
+/// Unit a column probably uses, judging by its canonical range
+fn suspected_unit(field: ImportField, declared: Unit, min: f64, max: f64) -> Option<Unit> {
+    match field.dimension() {
+        // An F1 car always tops 110 km/h somewhere in a stint and never 420
+        Dimension::Speed if max > 0.0 && max < 110.0 && declared != Unit::MetersPerSecond => {
+            Some(Unit::MetersPerSecond)
+        }
+        Dimension::Speed if max > 420.0 => Some(Unit::Kph),
+        Dimension::Fraction if max > 1.5 && declared == Unit::Fraction => Some(Unit::Percent),
+        Dimension::Fraction if max <= 0.015 && max > 0.0 && declared == Unit::Percent => Some(Unit::Fraction),
+        Dimension::Temperature if min > 200.0 => Some(Unit::Kelvin),
+        Dimension::Temperature if max > 200.0 && matches!(field.tire_corner(), Some(false)) => {
+            Some(Unit::Fahrenheit)
+        }
+        Dimension::Pressure if max > 0.0 && max < 5.0 && declared == Unit::Psi => Some(Unit::Bar),
+        Dimension::Pressure if max > 60.0 && declared == Unit::Psi => Some(Unit::Kilopascal),
+        _ => None,
+    }
+}
+
+impl ImportField {
+    /// `Some(true)` for brake temperatures, `Some(false)` for tyre temperatures
+    fn tire_corner(&self) -> Option<bool> {
+        use ImportField::*;
+        match self {
+            TireTempFrontLeft | TireTempFrontRight | TireTempRearLeft | TireTempRearRight => Some(false),
+            BrakeTempFrontLeft | BrakeTempFrontRight | BrakeTempRearLeft | BrakeTempRearRight => Some(true),
+            _ => None,
+        }
+    }
+}
+
+/// Laps derived from lap-number changes in a snapshot stream
+///
+/// A lap's time runs from its first sample to the first sample of the next
+/// lap, so the last lap of each car has no time.
+pub fn laps_from_snapshots(snapshots: &[TelemetrySnapshot]) -> HashMap<CarId, Vec<LapData>> {
+    let mut starts: HashMap<CarId, Vec<(u16, DateTime<Utc>)>> = HashMap::new();
+    for snapshot in snapshots {
+        let car = starts.entry(snapshot.car_id).or_default();
+        if car.last().map_or(true, |(lap, _)| *lap != snapshot.lap.0) {
+            car.push((snapshot.lap.0, snapshot.timestamp));
+        }
+    }
+    starts
+        .into_iter()
+        .map(|(car_id, starts)| {
+            let laps = starts
+                .iter()
+                .enumerate()
+                .map(|(i, (lap, start))| LapData {
+                    lap_number: *lap,
+                    lap_time: starts
+                        .get(i + 1)
+                        .filter(|(next, _)| *next == lap + 1)
+                        .map(|(_, end)| seconds_between(*start, *end)),
+                    sector_1_time: None,
+                    sector_2_time: None,
+                    sector_3_time: None,
+                    is_pit_lap: false,
+                })
+                .collect();
+            (car_id, laps)
+        })
+        .collect()
+}
+
+/// Imports CSV telemetry through a column mapping
+pub struct CsvImporter {
+    config: CsvImportConfig,
+    session_id: SessionId,
+    segmenter: Option<LapSegmenter>,
+}
+
+impl CsvImporter {
+    pub fn new(config: CsvImportConfig) -> Result<Self, ImportError> {
+        config.validate()?;
+        Ok(CsvImporter {
+            config,
+            session_id: SessionId::new(),
+            segmenter: None,
+        })
+    }
+
+    /// Session id given to the imported snapshots
+    pub fn with_session(mut self, session_id: SessionId) -> Self {
+        self.session_id = session_id;
+        self
+    }
+
+    /// Derive lap numbers with `segmenter` when the file has no lap column
+    pub fn with_segmenter(mut self, segmenter: LapSegmenter) -> Self {
+        self.segmenter = Some(segmenter);
+        self
+    }
+
+    /// Get configuration
+    pub fn config(&self) -> &CsvImportConfig {
+        &self.config
+    }
+
+    /// Import a CSV file
+    pub fn import_path(&self, path: impl AsRef<Path>) -> Result<ImportedTelemetry, ImportError> {
+        self.import(std::fs::File::open(path)?)
+    }
+
+    /// Import CSV data
+    pub fn import<R: Read>(&self, reader: R) -> Result<ImportedTelemetry, ImportError> {
+        let mut csv = csv::ReaderBuilder::new()
+            .delimiter(self.config.delimiter as u8)
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(reader);
+        let headers = csv.headers()?.clone();
+        let column = |name: &str| headers.iter().position(|h| h == name);
+        let time_index = column(&self.config.time_column)
+            .ok_or_else(|| ImportError::MissingColumn(self.config.time_column.clone()))?;
+
+        let mut report = DataQualityReport::default();
+        let mut columns = Vec::new();
+        for mapping in &self.config.columns {
+            match column(&mapping.column) {
+                Some(index) => columns.push((mapping, index)),
+                None => report.missing_columns.push(mapping.column.clone()),
+            }
+        }
+
+        let mut ranges: HashMap<ImportField, (f64, f64)> = HashMap::new();
+        let mut last_seen: HashMap<CarId, DateTime<Utc>> = HashMap::new();
+        let mut snapshots = Vec::new();
+
+        for (row, record) in csv.records().enumerate() {
+            report.rows += 1;
+            let line = row + 2;
+            let record = match record {
+                Ok(record) => record,
+                Err(e) => {
+                    report.issue(format!("line {}: {}", line, e));
+                    continue;
+                }
+            };
+            let cell = |index: usize| record.get(index).unwrap_or("");
+
+            let Some(timestamp) = self.parse_time(cell(time_index)) else {
+                report.issue(format!("line {}: invalid time '{}'", line, cell(time_index)));
+                continue;
+            };
+            let car_cell = columns.iter().find(|(m, _)| m.field == ImportField::CarId).map(|(_, i)| cell(*i));
+            let car_id = match car_cell {
+                Some(text) => match parse_number(text) {
+                    Ok(Some(value)) if (1.0..=99.0).contains(&value) => CarId(value as u8),
+                    _ => {
+                        report.issue(format!("line {}: invalid car '{}'", line, text));
+                        continue;
+                    }
+                },
+                None => CarId(self.config.car_id.unwrap_or(0)),
+            };
+
+            let mut snapshot = blank_snapshot(self.session_id, car_id, timestamp);
+            for (mapping, index) in &columns {
+                let text = cell(*index);
+                if mapping.field == ImportField::CarId {
+                    continue;
+                }
+                if mapping.field == ImportField::TireCompound {
+                    match parse_compound(text) {
+                        Some(compound) => {
+                            snapshot.tires.compound = compound;
+                            ranges.entry(mapping.field).or_insert((0.0, 0.0));
+                        }
+                        None if text.is_empty() => *report.empty_cells.entry(mapping.field).or_default() += 1,
+                        None => *report.invalid_cells.entry(mapping.field).or_default() += 1,
+                    }
+                    continue;
+                }
+                match parse_number(text) {
+                    Ok(Some(raw)) => {
+                        let value = mapping.unit().to_canonical(raw);
+                        let range = ranges.entry(mapping.field).or_insert((value, value));
+                        *range = (range.0.min(value), range.1.max(value));
+                        apply(&mut snapshot, mapping.field, value);
+                    }
+                    Ok(None) => *report.empty_cells.entry(mapping.field).or_default() += 1,
+                    Err(()) => *report.invalid_cells.entry(mapping.field).or_default() += 1,
+                }
+            }
+            let tires = &snapshot.tires;
+            snapshot.brakes.front_temp = (tires.front_left.brake_temp + tires.front_right.brake_temp) / 2.0;
+            snapshot.brakes.rear_temp = (tires.rear_left.brake_temp + tires.rear_right.brake_temp) / 2.0;
+
+            if last_seen.insert(car_id, timestamp).is_some_and(|previous| timestamp < previous) {
+                report.out_of_order += 1;
+            }
+            report.imported += 1;
+            snapshots.push(snapshot);
+        }
+
+        snapshots.sort_by_key(|s| s.timestamp);
+        let derive_laps = !ranges.contains_key(&ImportField::Lap) && self.segmenter.is_some();
+        if let Some(segmenter) = self.segmenter.as_ref().filter(|_| derive_laps) {
+            segmenter.evict_session(self.session_id);
+            for snapshot in &mut snapshots {
+                segmenter.relabel(snapshot, TrackSample::default());
+            }
+            segmenter.evict_session(self.session_id);
+        }
+        report.scan_snapshots(&snapshots, self.config.gap_threshold_secs);
+        report.missing_channels = ImportField::CHANNELS
+            .into_iter()
+            .filter(|field| !(ranges.contains_key(field) || derive_laps && *field == ImportField::Lap))
+            .collect();
+        for (mapping, _) in &columns {
+            if let Some(&(min, max)) = ranges.get(&mapping.field) {
+                if let Some(suspected) = suspected_unit(mapping.field, mapping.unit(), min, max) {
+                    report.unit_warnings.push(UnitWarning {
+                        field: mapping.field,
+                        column: mapping.column.clone(),
+                        declared: mapping.unit(),
+                        suspected,
+                        min,
+                        max,
+                    });
+                }
+            }
+        }
+
+        let laps = if ranges.contains_key(&ImportField::Lap) || derive_laps {
+            laps_from_snapshots(&snapshots)
+        } else {
+            HashMap::new()
+        };
+        Ok(ImportedTelemetry { snapshots, laps, report })
+    }
+
+    fn parse_time(&self, text: &str) -> Option<DateTime<Utc>> {
+        let relative = |seconds: f64| {
+            let offset = chrono::Duration::microseconds(micros(seconds)?);
+            self.config.start_time?.checked_add_signed(offset)
+        };
+        let number = |text: &str| text.trim().parse::<f64>().ok();
+        match self.config.time_format {
+            TimeFormat::DateTime => parse_datetime(text),
+            TimeFormat::UnixSeconds => Utc.timestamp_micros(micros(number(text)?)?).single(),
+            TimeFormat::UnixMillis => Utc.timestamp_micros(micros(number(text)? / 1.0e3)?).single(),
+            TimeFormat::Seconds => relative(number(text)?),
+            TimeFormat::Timedelta => relative(parse_duration_secs(text)?),
+        }
+    }
+}
+
+/// One row of a FastF1 laps table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FastF1Lap {
+    pub car_id: CarId,
+    pub lap: LapData,
+
+    /// `LapStartDate`
+    pub start: Option<DateTime<Utc>>,
+    pub compound: Option<TireCompound>,
+    pub tyre_life: Option<u16>,
+    pub position: Option<u8>,
+}
+
+/// An imported FastF1 laps table
+#[derive(Debug, Clone)]
+pub struct FastF1Laps {
+    /// Laps sorted by car and lap number
+    pub laps: Vec<FastF1Lap>,
+    pub report: DataQualityReport,
+}
+
+impl FastF1Laps {
+    /// Laps per car
+    pub fn lap_data(&self) -> HashMap<CarId, Vec<LapData>> {
+        let mut laps: HashMap<CarId, Vec<LapData>> = HashMap::new();
+        for lap in &self.laps {
+            laps.entry(lap.car_id).or_default().push(lap.lap.clone());
+        }
+        laps
+    }
+
+    /// Lap a car was on at `timestamp`
+    fn lap_at(&self, car_id: CarId, timestamp: DateTime<Utc>) -> Option<&FastF1Lap> {
+        self.laps
+            .iter()
+            .filter(|lap| lap.car_id == car_id && lap.start.is_some_and(|start| start <= timestamp))
+            .max_by_key(|lap| lap.start)
+    }
+}
+
+/// Imports FastF1 DataFrame exports
+pub struct FastF1Importer {
+    session_id: SessionId,
+    gap_threshold_secs: f32,
+}
+
+impl FastF1Importer {
+    /// Columns of the laps table the importer reads
+    pub const LAP_COLUMNS: [&'static str; 10] = [
+        "DriverNumber",
+        "LapNumber",
+        "LapTime",
+        "Sector1Time",
+        "Sector2Time",
+        "Sector3Time",
+        "PitInTime",
+        "PitOutTime",
+        "LapStartDate",
+        "Compound",
+    ];
+
+    pub fn new() -> Self {
+        FastF1Importer {
+            session_id: SessionId::new(),
+            gap_threshold_secs: default_gap_threshold(),
+        }
+    }
+
+    /// Session id given to the imported snapshots
+    pub fn with_session(mut self, session_id: SessionId) -> Self {
+        self.session_id = session_id;
+        self
+    }
+
+    /// Column mapping of a FastF1 car telemetry export (`Date`, `Speed`,
+    /// `RPM`, `nGear`, `Throttle`, `Brake`, `DRS`)
+    pub fn telemetry_config(car_id: CarId) -> CsvImportConfig {
+        CsvImportConfig {
+            car_id: Some(car_id.0),
+            ..CsvImportConfig::new("Date", TimeFormat::DateTime)
+        }
+        .with_column(ColumnMapping::new(ImportField::Speed, "Speed").with_unit(Unit::Kph))
+        .with_column(ColumnMapping::new(ImportField::Rpm, "RPM"))
+        .with_column(ColumnMapping::new(ImportField::Gear, "nGear"))
+        .with_column(ColumnMapping::new(ImportField::Throttle, "Throttle").with_unit(Unit::Percent))
+        .with_column(ColumnMapping::new(ImportField::Brake, "Brake").with_unit(Unit::Fraction))
+        .with_column(ColumnMapping::new(ImportField::Drs, "DRS").with_unit(Unit::DrsLiveTiming))
+    }
+
+    /// Import a laps table (`session.laps.to_csv()`)
+    pub fn laps<R: Read>(&self, reader: R) -> Result<FastF1Laps, ImportError> {
+        let mut csv = csv::ReaderBuilder::new().flexible(true).trim(csv::Trim::All).from_reader(reader);
+        let headers = csv.headers()?.clone();
+        let column = |name: &str| headers.iter().position(|h| h == name);
+        let driver = column("DriverNumber").ok_or_else(|| ImportError::MissingColumn("DriverNumber".to_string()))?;
+        let lap_number = column("LapNumber").ok_or_else(|| ImportError::MissingColumn("LapNumber".to_string()))?;
+
+        let mut report = DataQualityReport {
+            missing_columns: Self::LAP_COLUMNS
+                .iter()
+                .filter(|name| column(name).is_none())
+                .map(|name| name.to_string())
+                .collect(),
+            ..Default::default()
+        };
+
+        let mut laps = Vec::new();
+        for (row, record) in csv.records().enumerate() {
+            report.rows += 1;
+            let record = match record {
+                Ok(record) => record,
+                Err(e) => {
+                    report.issue(format!("line {}: {}", row + 2, e));
+                    continue;
+                }
+            };
+            let cell = |name: &str| column(name).and_then(|i| record.get(i)).unwrap_or("");
+            let (Ok(Some(car)), Ok(Some(number))) =
+                (parse_number(record.get(driver).unwrap_or("")), parse_number(record.get(lap_number).unwrap_or("")))
+            else {
+                report.issue(format!("line {}: missing driver or lap number", row + 2));
+                continue;
+            };
+            if !(1.0..=99.0).contains(&car) {
+                report.issue(format!("line {}: invalid driver '{}'", row + 2, car));
+                continue;
+            }
+            let mut duration = |name: &str, field: ImportField| {
+                let value = parse_duration_secs(cell(name)).map(|s| s as f32);
+                if value.is_none() && column(name).is_some() {
+                    *report.empty_cells.entry(field).or_default() += 1;
+                }
+                value
+            };
+            let lap_time = duration("LapTime", ImportField::Lap);
+            let sector_1_time = parse_duration_secs(cell("Sector1Time")).map(|s| s as f32);
+            let sector_2_time = parse_duration_secs(cell("Sector2Time")).map(|s| s as f32);
+            let sector_3_time = parse_duration_secs(cell("Sector3Time")).map(|s| s as f32);
+            let is_pit_lap =
+                parse_duration_secs(cell("PitInTime")).is_some() || parse_duration_secs(cell("PitOutTime")).is_some();
+
+            laps.push(FastF1Lap {
+                car_id: CarId(car as u8),
+                lap: LapData {
+                    lap_number: number as u16,
+                    lap_time,
+                    sector_1_time,
+                    sector_2_time,
+                    sector_3_time,
+                    is_pit_lap,
+                },
+                start: parse_datetime(cell("LapStartDate")),
+                compound: parse_compound(cell("Compound")),
+                tyre_life: parse_number(cell("TyreLife")).ok().flatten().map(|v| v as u16),
+                position: parse_number(cell("Position")).ok().flatten().map(|v| v as u8),
+            });
+            report.imported += 1;
+        }
+
+        laps.sort_by_key(|lap| (lap.car_id.0, lap.lap.lap_number));
+        let mut cars: Vec<CarId> = laps.iter().map(|lap| lap.car_id).collect();
+        cars.dedup();
+        report.cars = cars;
+        Ok(FastF1Laps { laps, report })
+    }
+
+    /// Import one car's telemetry (`lap.get_car_data().to_csv()`)
+    ///
+    /// With a laps table, each snapshot takes the lap number, position,
+    /// compound and tyre age of the lap it falls in, and the laps come from
+    /// the table instead of being derived.
+    pub fn telemetry<R: Read>(
+        &self,
+        reader: R,
+        car_id: CarId,
+        laps: Option<&FastF1Laps>,
+    ) -> Result<ImportedTelemetry, ImportError> {
+        let config = CsvImportConfig {
+            gap_threshold_secs: self.gap_threshold_secs,
+            ..Self::telemetry_config(car_id)
+        };
+        let mut imported = CsvImporter::new(config)?.with_session(self.session_id).import(reader)?;
+
+        if let Some(laps) = laps {
+            for snapshot in &mut imported.snapshots {
+                if let Some(lap) = laps.lap_at(car_id, snapshot.timestamp) {
+                    snapshot.lap = LapNumber(lap.lap.lap_number);
+                    if let Some(position) = lap.position {
+                        snapshot.position = Position(position);
+                    }
+                    if let Some(compound) = lap.compound {
+                        snapshot.tires.compound = compound;
+                    }
+                    snapshot.tires.age_laps = lap.tyre_life.unwrap_or(0);
+                }
+            }
+            imported.report.missing_channels.retain(|field| {
+                !matches!(
+                    field,
+                    ImportField::Lap | ImportField::Position | ImportField::TireCompound | ImportField::TireAge
+                )
+            });
+            imported.laps = laps
+                .lap_data()
+                .remove(&car_id)
+                .map(|laps| HashMap::from([(car_id, laps)]))
+                .unwrap_or_default();
+        }
+        Ok(imported)
+    }
+}
+
+impl Default for FastF1Importer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV: &str = "\
+time,car,lap,speed_mph,throttle_pct,brake,fl_temp_f,compound
+0.0,16,1,150.0,100,0,212,C3
+0.1,16,1,151.0,100,0,213,C3
+0.1,44,1,149.0,98,0,210,C3
+0.2,16,1,,100,0,214,C3
+abc,16,1,152.0,100,0,214,C3
+0.3,44,2,120.0,0,1,215,C3
+3.0,16,2,90.0,0,1,215,XX
+";
+
+    fn config() -> CsvImportConfig {
+        CsvImportConfig {
+            start_time: Some(Utc.with_ymd_and_hms(2026, 7, 26, 13, 0, 0).unwrap()),
+            ..CsvImportConfig::new("time", TimeFormat::Seconds)
+        }
+        .with_column(ColumnMapping::new(ImportField::CarId, "car"))
+        .with_column(ColumnMapping::new(ImportField::Lap, "lap"))
+        .with_column(ColumnMapping::new(ImportField::Speed, "speed_mph").with_unit(Unit::Mph))
+        .with_column(ColumnMapping::new(ImportField::Throttle, "throttle_pct").with_unit(Unit::Percent))
+        .with_column(ColumnMapping::new(ImportField::Brake, "brake"))
+        .with_column(ColumnMapping::new(ImportField::TireTempFrontLeft, "fl_temp_f").with_unit(Unit::Fahrenheit))
+        .with_column(ColumnMapping::new(ImportField::TireCompound, "compound"))
+        .with_column(ColumnMapping::new(ImportField::Rpm, "rpm"))
+    }
+
+    #[test]
+    fn test_csv_import_with_units_and_report() {
+        let imported = CsvImporter::new(config()).unwrap().import(CSV.as_bytes()).unwrap();
+        let report = &imported.report;
+
+        assert_eq!(report.rows, 7);
+        assert_eq!(report.imported, 6);
+        assert_eq!(report.skipped_rows, 1);
+        assert!(report.issues[0].contains("line 6"));
+        assert_eq!(report.missing_columns, vec!["rpm".to_string()]);
+        assert!(report.missing_channels.contains(&ImportField::Rpm));
+        assert!(!report.missing_channels.contains(&ImportField::Speed));
+        assert_eq!(report.empty_cells[&ImportField::Speed], 1);
+        assert_eq!(report.invalid_cells[&ImportField::TireCompound], 1);
+        assert_eq!(report.cars, vec![CarId(16), CarId(44)]);
+        assert_eq!(report.gaps.len(), 1);
+        assert_eq!(report.gaps[0].car_id, CarId(16));
+        assert!((report.gaps[0].seconds - 2.8).abs() < 1e-4);
+        assert!(report.unit_warnings.is_empty());
+        assert!(!report.is_clean());
+
+        let first = &imported.snapshots[0];
+        assert_eq!(first.car_id, CarId(16));
+        assert!((first.motion.speed - 241.4016).abs() < 1e-3);
+        assert_eq!(first.inputs.throttle, 1.0);
+        assert!((first.tires.front_left.surface_temp - 100.0).abs() < 1e-4);
+        assert_eq!(first.tires.compound, TireCompound::C3);
+
+        let laps = &imported.laps[&CarId(44)];
+        assert_eq!(laps.len(), 2);
+        assert!((laps[0].lap_time.unwrap() - 0.2).abs() < 1e-4);
+        assert_eq!(laps[1].lap_time, None);
+    }
+
+    #[test]
+    fn test_laps_derived_by_segmenter() {
+        let mut circuit = f1_nexus_core::Circuit::monza();
+        circuit.length = 1000.0;
+        let segmenter = LapSegmenter::new(&circuit, crate::SegmenterConfig::default()).unwrap();
+        let config = CsvImportConfig {
+            start_time: Some(Utc.with_ymd_and_hms(2026, 7, 26, 13, 0, 0).unwrap()),
+            car_id: Some(16),
+            ..CsvImportConfig::new("time", TimeFormat::Seconds)
+        }
+        .with_column(ColumnMapping::new(ImportField::Speed, "speed"));
+
+        // 180 km/h covers the 1 km lap in 20 s
+        let mut csv = String::from("time,speed\n");
+        for i in 0..=500 {
+            csv.push_str(&format!("{:.1},180\n", i as f32 * 0.1));
+        }
+        let imported = CsvImporter::new(config).unwrap().with_segmenter(segmenter).import(csv.as_bytes()).unwrap();
+
+        assert_eq!(imported.snapshots.last().unwrap().lap, LapNumber(3));
+        assert!(!imported.report.missing_channels.contains(&ImportField::Lap));
+        let laps = &imported.laps[&CarId(16)];
+        assert_eq!(laps.len(), 3);
+        assert!((laps[1].lap_time.unwrap() - 20.0).abs() < 0.2);
+    }
+
+    #[test]
+    fn test_unrepresentable_times_are_invalid() {
+        let csv = "time,car,speed\n0.0,16,200\n1e15,16,200\ninf,16,200\nNaN,16,200\n0.1,16,200\n";
+        let imported = CsvImporter::new(config()).unwrap().import(csv.as_bytes()).unwrap();
+        assert_eq!(imported.report.imported, 2);
+        assert_eq!(imported.report.skipped_rows, 3);
+
+        let unix = CsvImportConfig::new("time", TimeFormat::UnixSeconds).with_column(ColumnMapping::new(ImportField::CarId, "car"));
+        let imported = CsvImporter::new(unix).unwrap().import(csv.as_bytes()).unwrap();
+        assert_eq!(imported.report.imported, 2);
+    }
+
+    #[test]
+    fn test_unit_mismatch_and_suspect_units() {
+        let bad = CsvImportConfig {
+            car_id: Some(1),
+            ..CsvImportConfig::new("t", TimeFormat::UnixSeconds)
+        }
+        .with_column(ColumnMapping::new(ImportField::Speed, "speed").with_unit(Unit::Celsius));
+        assert!(matches!(
+            CsvImporter::new(bad),
+            Err(ImportError::UnitMismatch { field: ImportField::Speed, unit: Unit::Celsius })
+        ));
+        assert!(matches!(
+            CsvImporter::new(CsvImportConfig::new("t", TimeFormat::UnixSeconds)),
+            Err(ImportError::InvalidConfig(_))
+        ));
+
+        // Speed exported in m/s and throttle in percent, both declared canonical
+        let csv = "t;speed;throttle\n1784034000.0;80.5;100\n1784034000.5;85.0;55\n";
+        let config = CsvImportConfig {
+            delimiter: ';',
+            car_id: Some(1),
+            ..CsvImportConfig::new("t", TimeFormat::UnixSeconds)
+        }
+        .with_column(ColumnMapping::new(ImportField::Speed, "speed"))
+        .with_column(ColumnMapping::new(ImportField::Throttle, "throttle"));
+        let imported = CsvImporter::new(config).unwrap().import(csv.as_bytes()).unwrap();
+        let warnings = &imported.report.unit_warnings;
+        assert_eq!(warnings.len(), 2);
+        assert_eq!((warnings[0].field, warnings[0].suspected), (ImportField::Speed, Unit::MetersPerSecond));
+        assert_eq!((warnings[1].field, warnings[1].suspected), (ImportField::Throttle, Unit::Percent));
+        assert_eq!(imported.snapshots[1].timestamp, Utc.timestamp_opt(1_784_034_000, 500_000_000).unwrap());
+    }
+
+    #[test]
+    fn test_config_from_yaml_and_toml() {
+        let yaml = "
+time_column: Date
+car_id: 44
+columns:
+  - field: speed
+    column: Speed
+    unit: kph
+  - field: drs
+    column: DRS
+    unit: drs_live_timing
+";
+        let config = CsvImportConfig::from_yaml(yaml).unwrap();
+        assert_eq!(config.time_format, TimeFormat::DateTime);
+        assert_eq!(config.delimiter, ',');
+        assert_eq!(config.columns[1].unit, Some(Unit::DrsLiveTiming));
+
+        let toml = "
+time_column = \"Date\"
+car_id = 44
+[[columns]]
+field = \"speed\"
+column = \"Speed\"
+unit = \"kph\"
+[[columns]]
+field = \"drs\"
+column = \"DRS\"
+unit = \"drs_live_timing\"
+";
+        assert_eq!(CsvImportConfig::from_toml(toml).unwrap(), config);
+    }
+
+    #[test]
+    fn test_fastf1_laps_and_telemetry() {
+        let laps_csv = "\
+,Time,Driver,DriverNumber,LapTime,LapNumber,Stint,PitOutTime,PitInTime,Sector1Time,Sector2Time,Sector3Time,Compound,TyreLife,LapStartDate,Position
+0,0 days 01:02:03.500000,VER,1,0 days 00:01:31.250000,1.0,1.0,,,0 days 00:00:30.100000,0 days 00:00:35.050000,0 days 00:00:26.100000,SOFT,3.0,2026-07-26 13:00:00.000,1.0
+1,0 days 01:03:34.750000,VER,1,0 days 00:01:30.500000,2.0,1.0,,0 days 01:03:34.000000,0 days 00:00:29.900000,0 days 00:00:34.800000,0 days 00:00:25.800000,SOFT,4.0,2026-07-26 13:01:31.250,1.0
+2,0 days 01:02:04.000000,HAM,44,NaT,1.0,1.0,,,,,,MEDIUM,1.0,2026-07-26 13:00:00.500,2.0
+3,0 days 01:02:05.000000,XXX,300,NaT,1.0,1.0,,,,,,MEDIUM,1.0,2026-07-26 13:00:01.000,3.0
+";
+        let importer = FastF1Importer::new();
+        let laps = importer.laps(laps_csv.as_bytes()).unwrap();
+        assert_eq!(laps.laps.len(), 3);
+        assert!(laps.report.issues[0].contains("line 5"));
+        assert_eq!(laps.report.cars, vec![CarId(1), CarId(44)]);
+        assert_eq!(laps.report.empty_cells[&ImportField::Lap], 1);
+        let ver = &laps.lap_data()[&CarId(1)];
+        assert_eq!(ver[0].lap_time, Some(91.25));
+        assert_eq!(ver[0].sector_2_time, Some(35.05));
+        assert!(!ver[0].is_pit_lap);
+        assert!(ver[1].is_pit_lap);
+
+        let telemetry_csv = "\
+,Date,SessionTime,Time,RPM,Speed,nGear,Throttle,Brake,DRS,Source
+0,2026-07-26 13:00:00.100,0 days 01:00:32.350000,0 days 00:00:00.100000,11000.0,290.0,8,100.0,False,12,car
+1,2026-07-26 13:00:00.300,0 days 01:00:32.550000,0 days 00:00:00.300000,11200.0,295.0,8,100.0,False,8,car
+2,2026-07-26 13:01:31.400,0 days 01:02:03.650000,0 days 00:01:31.400000,9000.0,120.0,3,0.0,True,1,car
+";
+        let imported = importer.telemetry(telemetry_csv.as_bytes(), CarId(1), Some(&laps)).unwrap();
+        let snapshots = &imported.snapshots;
+        assert_eq!(snapshots.len(), 3);
+        assert_eq!(snapshots[0].lap, LapNumber(1));
+        assert_eq!(snapshots[0].drs, DrsStatus::Activated);
+        assert_eq!(snapshots[0].inputs.throttle, 1.0);
+        assert_eq!(snapshots[0].tires.compound, TireCompound::C4);
+        assert_eq!(snapshots[0].position, Position(1));
+        assert_eq!(snapshots[1].drs, DrsStatus::Available);
+        assert_eq!(snapshots[2].lap, LapNumber(2));
+        assert_eq!(snapshots[2].inputs.brake, 1.0);
+        assert_eq!(snapshots[2].tires.age_laps, 4);
+        assert_eq!(imported.laps[&CarId(1)].len(), 2);
+        assert!(!imported.report.missing_channels.contains(&ImportField::Lap));
+        assert!(imported.report.missing_channels.contains(&ImportField::TireTempFrontLeft));
+        assert_eq!(imported.report.gaps.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_durations() {
+        let secs = |text: &str| parse_duration_secs(text).map(|s| (s * 1000.0).round() / 1000.0);
+        assert_eq!(secs("0 days 00:01:31.234000"), Some(91.234));
+        assert_eq!(secs("-1 days +23:59:59.500000"), Some(-0.5));
+        assert_eq!(secs("P0DT0H1M31.234S"), Some(91.234));
+        assert_eq!(secs("91.5"), Some(91.5));
+        assert_eq!(secs("NaT"), None);
+        assert_eq!(secs("inf"), None);
+        assert_eq!(secs("P1e400S"), None);
+        assert_eq!(secs("soon"), None);
+    }
+}
@@ -4,16 +4,48 @@
 //! with sub-millisecond latency using SIMD optimization and neural inference.
 
 pub mod processor;
+pub mod alerts;
 pub mod stream;
 pub mod anomaly;
+pub mod auth;
 pub mod buffer;
+pub mod codec;
+pub mod compare;
+pub mod delivery;
+pub mod forecast;
+pub mod fusion;
+pub mod import;
+pub mod pipeline;
 pub mod predictor;
+pub mod recording;
+pub mod rules;
+pub mod segmenter;
+pub mod stats;
+pub mod sync;
+pub mod udp;
+pub mod websocket;
 
 pub use processor::*;
+pub use alerts::*;
 pub use stream::*;
 pub use anomaly::*;
+pub use auth::*;
 pub use buffer::*;
+pub use codec::*;
+pub use compare::*;
+pub use delivery::*;
+pub use forecast::*;
+pub use fusion::*;
+pub use import::*;
+pub use pipeline::*;
 pub use predictor::*;
+pub use recording::*;
+pub use rules::*;
+pub use segmenter::*;
+pub use stats::*;
+pub use sync::*;
+pub use udp::*;
+pub use websocket::*;
 
 use f1_nexus_core::TelemetrySnapshot;
 use std::sync::Arc;
@@ -23,15 +55,26 @@ use tokio::sync::broadcast;
 pub struct TelemetryEngine {
     processor: Arc<TelemetryProcessor>,
     anomaly_detector: Arc<AnomalyDetector>,
+    alert_router: Option<Arc<AlertRouter>>,
+    forecaster: Option<Arc<Forecaster>>,
+    segmenter: Option<Arc<LapSegmenter>>,
+    buffer: Arc<TelemetryBuffer>,
+    synchronizer: Option<parking_lot::Mutex<TelemetrySynchronizer>>,
+    resampler: Option<parking_lot::Mutex<Resampler>>,
     tx: broadcast::Sender<TelemetryEvent>,
+    hub: DeliveryHub<TelemetryEvent>,
 }
 
 /// Telemetry events
 #[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum TelemetryEvent {
     Snapshot(TelemetrySnapshot),
     Anomaly(AnomalyInfo),
     LegacyAnomaly(AnomalyAlert), // For backward compatibility
+    EarlyWarning(EarlyWarning),
+    Lap(LapEvent),
+    Frame(ResampledFrame),
     StreamStart { session_id: String },
     StreamEnd { session_id: String },
 }
@@ -40,11 +83,141 @@ impl TelemetryEngine {
     /// Create new telemetry engine
     pub fn new(config: TelemetryConfig) -> Self {
         let (tx, _) = broadcast::channel(10_000);
+        let buffer = Arc::new(TelemetryBuffer::default());
 
         TelemetryEngine {
             processor: Arc::new(TelemetryProcessor::new(config.clone())),
-            anomaly_detector: Arc::new(AnomalyDetector::new(config)),
+            anomaly_detector: Arc::new(AnomalyDetector::new(config).with_history(buffer.clone())),
+            alert_router: None,
+            forecaster: None,
+            segmenter: None,
+            buffer,
+            synchronizer: None,
+            resampler: None,
             tx,
+            hub: DeliveryHub::new(),
+        }
+    }
+
+    /// Route detected anomalies through `router` as well as the event channel
+    pub fn with_alert_router(mut self, router: Arc<AlertRouter>) -> Self {
+        self.alert_router = Some(router);
+        self
+    }
+
+    /// Record history into a shared buffer instead of a private one
+    ///
+    /// The anomaly detector reads the buffer too, so this replaces it with a
+    /// fresh one.
+    pub fn with_buffer(mut self, buffer: Arc<TelemetryBuffer>) -> Self {
+        let config = self.processor.config().clone();
+        self.anomaly_detector = Arc::new(AnomalyDetector::new(config).with_history(buffer.clone()));
+        self.buffer = buffer;
+        self
+    }
+
+    /// Telemetry history, shareable with the stream server for backfill
+    pub fn buffer(&self) -> &Arc<TelemetryBuffer> {
+        &self.buffer
+    }
+
+    /// Forecast watched channels and emit early warnings
+    pub fn with_forecaster(mut self, forecaster: Arc<Forecaster>) -> Self {
+        self.forecaster = Some(forecaster);
+        self
+    }
+
+    /// Forecaster, if one is attached
+    pub fn forecaster(&self) -> Option<&Arc<Forecaster>> {
+        self.forecaster.as_ref()
+    }
+
+    /// Derive laps, sectors and pit stops and emit them as `Lap` events
+    pub fn with_segmenter(mut self, segmenter: Arc<LapSegmenter>) -> Self {
+        self.segmenter = Some(segmenter);
+        self
+    }
+
+    /// Lap segmenter, if one is attached
+    pub fn segmenter(&self) -> Option<&Arc<LapSegmenter>> {
+        self.segmenter.as_ref()
+    }
+
+    /// Alert router, if one is attached
+    pub fn alert_router(&self) -> Option<&Arc<AlertRouter>> {
+        self.alert_router.as_ref()
+    }
+
+    /// Reconcile source clocks and reorder snapshots passed to `ingest`
+    pub fn with_synchronizer(mut self, synchronizer: TelemetrySynchronizer) -> Self {
+        self.synchronizer = Some(parking_lot::Mutex::new(synchronizer));
+        self
+    }
+
+    /// Put processed snapshots on a common time grid and emit `Frame` events
+    ///
+    /// Snapshots must reach `process` in timestamp order per car, e.g. via a
+    /// synchronizer.
+    pub fn with_resampler(mut self, resampler: Resampler) -> Self {
+        self.resampler = Some(parking_lot::Mutex::new(resampler));
+        self
+    }
+
+    /// Clock state per source, if a synchronizer is attached
+    pub fn sync_sources(&self) -> Option<Vec<SourceStatus>> {
+        self.synchronizer.as_ref().map(|sync| sync.lock().sources())
+    }
+
+    /// Synchronizer counters, if a synchronizer is attached
+    pub fn sync_stats(&self) -> Option<SyncStats> {
+        self.synchronizer.as_ref().map(|sync| sync.lock().stats())
+    }
+
+    /// Process a snapshot from `source` received at `received_at`
+    ///
+    /// With a synchronizer the snapshot is re-timed to the local clock and
+    /// buffered; whatever the watermark releases is processed in order.
+    /// Without one it is processed at once. Returns the number of snapshots
+    /// processed and the first processing error, if any.
+    pub async fn ingest(
+        &self,
+        source: &str,
+        snapshot: TelemetrySnapshot,
+        received_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<usize, TelemetryError> {
+        let released = match &self.synchronizer {
+            Some(sync) => sync.lock().push(source, snapshot, received_at),
+            None => return self.process(snapshot).await.map(|_| 1),
+        };
+        self.process_released(released).await
+    }
+
+    /// Process everything the synchronizer still holds, then emit the
+    /// resampler's remaining frames
+    pub async fn flush_sync(&self) -> Result<usize, TelemetryError> {
+        let released = match &self.synchronizer {
+            Some(sync) => sync.lock().flush(),
+            None => Vec::new(),
+        };
+        let processed = self.process_released(released).await;
+        let frames = self.resampler.as_ref().map(|r| r.lock().flush()).unwrap_or_default();
+        for frame in frames {
+            self.emit(TelemetryEvent::Frame(frame)).await;
+        }
+        processed
+    }
+
+    async fn process_released(&self, released: Vec<SyncedSnapshot>) -> Result<usize, TelemetryError> {
+        let mut first_error = None;
+        let count = released.len();
+        for synced in released {
+            if let Err(e) = self.process(synced.snapshot).await {
+                first_error.get_or_insert(e);
+            }
+        }
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(count),
         }
     }
 
@@ -53,23 +226,75 @@ impl TelemetryEngine {
         // Process telemetry (validation, normalization, etc.)
         self.processor.process(&snapshot)?;
 
+        // Record history
+        self.buffer.push(&snapshot);
+
         // Run anomaly detection (new statistical detector)
         let anomalies = self.anomaly_detector.detect(&snapshot);
         for anomaly in anomalies {
-            let _ = self.tx.send(TelemetryEvent::Anomaly(anomaly));
+            if let Some(router) = &self.alert_router {
+                router.route(anomaly.clone());
+            }
+            self.emit(TelemetryEvent::Anomaly(anomaly)).await;
+        }
+
+        // Forecast watched channels for predicted threshold crossings
+        if let Some(forecaster) = &self.forecaster {
+            for warning in forecaster.observe(&snapshot) {
+                if let Some(router) = &self.alert_router {
+                    router.route(warning.clone().into());
+                }
+                self.emit(TelemetryEvent::EarlyWarning(warning)).await;
+            }
+        }
+
+        // Lap, sector and pit events
+        if let Some(segmenter) = &self.segmenter {
+            for event in segmenter.observe(&snapshot) {
+                self.emit(TelemetryEvent::Lap(event)).await;
+            }
         }
 
+        // Frames completed by this snapshot
+        let frames = self.resampler.as_ref().map(|r| r.lock().push(snapshot.clone())).unwrap_or_default();
+
         // Broadcast processed snapshot
-        let _ = self.tx.send(TelemetryEvent::Snapshot(snapshot));
+        self.emit(TelemetryEvent::Snapshot(snapshot)).await;
+        for frame in frames {
+            self.emit(TelemetryEvent::Frame(frame)).await;
+        }
 
         Ok(())
     }
 
     /// Subscribe to telemetry events
+    ///
+    /// Broadcast receivers that fall behind lose events (`Lagged`); use
+    /// `subscribe_with` for lossless or conflated delivery.
     pub fn subscribe(&self) -> broadcast::Receiver<TelemetryEvent> {
         self.tx.subscribe()
     }
 
+    /// Subscribe with a delivery policy; lossless subscribers hold back
+    /// `process` while their queue is full
+    pub fn subscribe_with(&self, config: SubscriberConfig) -> DeliveryReceiver<TelemetryEvent> {
+        self.hub.subscribe(config)
+    }
+
+    /// Counters of the `subscribe_with` subscribers
+    pub fn delivery_metrics(&self) -> Vec<SubscriberMetrics> {
+        self.hub.metrics()
+    }
+
+    async fn emit(&self, event: TelemetryEvent) {
+        if self.hub.subscriber_count() > 0 {
+            let _ = self.tx.send(event.clone());
+            self.hub.publish(event).await;
+        } else {
+            let _ = self.tx.send(event);
+        }
+    }
+
     /// Get processing statistics
     pub fn stats(&self) -> ProcessingStats {
         self.processor.stats()
@@ -88,8 +313,15 @@ pub struct TelemetryConfig {
     /// Buffer size for sliding window analysis
     pub buffer_size: usize,
 
-    /// Enable SIMD optimizations
+    /// Validate micro-batches column by column (`TelemetryProcessor::process_batch`)
+    /// so the bounds checks vectorise, instead of one snapshot at a time
     pub enable_simd: bool,
+
+    /// Anomaly baselines not updated for this long are evicted
+    pub baseline_idle_timeout: std::time::Duration,
+
+    /// Maximum number of anomaly baselines (car × session × segment) held
+    pub max_baselines: usize,
 }
 
 impl Default for TelemetryConfig {
@@ -99,6 +331,8 @@ impl Default for TelemetryConfig {
             anomaly_threshold: 0.95,
             buffer_size: 1000,
             enable_simd: true,
+            baseline_idle_timeout: std::time::Duration::from_secs(600),
+            max_baselines: 10_000,
         }
     }
 }
@@ -114,6 +348,9 @@ pub enum TelemetryError {
 
     #[error("Anomaly detection error: {0}")]
     AnomalyDetectionError(String),
+
+    #[error("Invalid alert rule: {0}")]
+    InvalidRule(String),
 }
 
 #[cfg(test)]
@@ -126,4 +363,31 @@ mod tests {
         let _engine = TelemetryEngine::new(config);
         // Engine created successfully
     }
+
+    #[tokio::test]
+    async fn test_engine_emits_lap_events() {
+        let circuit = f1_nexus_core::Circuit::monaco();
+        let segmenter = Arc::new(LapSegmenter::new(&circuit, SegmenterConfig::default()).unwrap());
+        let engine = TelemetryEngine::new(TelemetryConfig::default()).with_segmenter(segmenter);
+        let mut rx = engine.subscribe();
+
+        // 180 km/h covers the lap in about 67 s
+        let mut snapshot = processor::tests::create_test_snapshot();
+        snapshot.motion.speed = 180.0;
+        for _ in 0..700 {
+            engine.process(snapshot.clone()).await.unwrap();
+            snapshot.timestamp += chrono::Duration::milliseconds(100);
+        }
+
+        let mut laps = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            if let TelemetryEvent::Lap(LapEvent::LapCompleted { lap, lap_time, .. }) = event {
+                laps.push((lap, lap_time));
+            }
+        }
+        assert_eq!(laps.len(), 1);
+        assert_eq!(laps[0].0, f1_nexus_core::LapNumber(1));
+        let expected = circuit.length / 50.0;
+        assert!((laps[0].1.unwrap() - expected).abs() < 0.2, "{:?}", laps[0]);
+    }
 }
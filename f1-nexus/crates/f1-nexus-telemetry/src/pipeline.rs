@@ -0,0 +1,623 @@
+//! Parallel sharded telemetry pipeline
+//!
+//! Snapshots are sharded by car across worker threads, so each car is always
+//! handled by the same worker and stays in order, and processed in
+//! micro-batches. A batch keeps the validated channels column by column so
+//! validation and statistics run as tight loops over contiguous `f32`s.
+
+use crate::{AnomalyDetector, AnomalyInfo, TelemetryConfig, TelemetryError, TelemetryProcessor};
+use chrono::{DateTime, Utc};
+use crossbeam::channel::{self, Receiver, RecvTimeoutError, Sender, TrySendError};
+use dashmap::DashMap;
+use f1_nexus_core::{CarId, TelemetrySnapshot};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Pipeline sizing
+#[derive(Debug, Clone)]
+pub struct PipelineConfig {
+    /// Worker threads; cars are assigned to workers by car number
+    pub shards: usize,
+
+    /// Snapshots processed together by a worker
+    pub batch_size: usize,
+
+    /// Longest a partial batch waits for more snapshots
+    pub max_batch_delay: Duration,
+
+    /// Snapshots queued per shard before `submit` blocks
+    pub queue_capacity: usize,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        PipelineConfig {
+            shards: std::thread::available_parallelism().map_or(4, |n| n.get()),
+            batch_size: 64,
+            max_batch_delay: Duration::from_millis(1),
+            queue_capacity: 4096,
+        }
+    }
+}
+
+/// Pipeline errors
+#[derive(Debug, thiserror::Error)]
+pub enum PipelineError {
+    #[error("Pipeline is shut down")]
+    Closed,
+
+    #[error("Queue of shard {0} is full")]
+    Full(usize),
+}
+
+/// Minimum, maximum and mean of one column
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ColumnStats {
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+}
+
+impl ColumnStats {
+    /// Stats of `values`; all zero when empty
+    pub fn of(values: &[f32]) -> Self {
+        if values.is_empty() {
+            return ColumnStats::default();
+        }
+        let (mut min, mut max, mut sum) = (f32::INFINITY, f32::NEG_INFINITY, 0.0);
+        for &v in values {
+            min = min.min(v);
+            max = max.max(v);
+            sum += v;
+        }
+        ColumnStats {
+            min,
+            max,
+            mean: sum / values.len() as f32,
+        }
+    }
+}
+
+/// Column stats of the validated channels of a batch
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BatchStats {
+    pub speed: ColumnStats,
+    pub throttle: ColumnStats,
+    pub brake: ColumnStats,
+}
+
+/// Micro-batch of snapshots with the validated channels stored column-wise
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotBatch {
+    rows: Vec<TelemetrySnapshot>,
+    speed: Vec<f32>,
+    throttle: Vec<f32>,
+    brake: Vec<f32>,
+    /// Surface temperatures, FL, FR, RL, RR
+    tire_temps: [Vec<f32>; 4],
+}
+
+impl SnapshotBatch {
+    pub fn with_capacity(capacity: usize) -> Self {
+        SnapshotBatch {
+            rows: Vec::with_capacity(capacity),
+            speed: Vec::with_capacity(capacity),
+            throttle: Vec::with_capacity(capacity),
+            brake: Vec::with_capacity(capacity),
+            tire_temps: std::array::from_fn(|_| Vec::with_capacity(capacity)),
+        }
+    }
+
+    /// Batch holding `snapshots` in order
+    pub fn from_snapshots(snapshots: impl IntoIterator<Item = TelemetrySnapshot>) -> Self {
+        let mut batch = SnapshotBatch::default();
+        for snapshot in snapshots {
+            batch.push(snapshot);
+        }
+        batch
+    }
+
+    pub fn push(&mut self, snapshot: TelemetrySnapshot) {
+        self.speed.push(snapshot.motion.speed);
+        self.throttle.push(snapshot.inputs.throttle);
+        self.brake.push(snapshot.inputs.brake);
+        let tires = &snapshot.tires;
+        for (column, tire) in self
+            .tire_temps
+            .iter_mut()
+            .zip([&tires.front_left, &tires.front_right, &tires.rear_left, &tires.rear_right])
+        {
+            column.push(tire.surface_temp);
+        }
+        self.rows.push(snapshot);
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Snapshots in push order
+    pub fn snapshots(&self) -> &[TelemetrySnapshot] {
+        &self.rows
+    }
+
+    pub fn speed(&self) -> &[f32] {
+        &self.speed
+    }
+
+    pub fn throttle(&self) -> &[f32] {
+        &self.throttle
+    }
+
+    pub fn brake(&self) -> &[f32] {
+        &self.brake
+    }
+
+    /// Validity of each row, checked one column at a time
+    ///
+    /// Applies the same bounds as `TelemetryProcessor::process`.
+    pub fn validate_columns(&self) -> Vec<bool> {
+        let mut invalid = vec![false; self.len()];
+        for (bad, &v) in invalid.iter_mut().zip(&self.speed) {
+            *bad |= !(0.0..=400.0).contains(&v);
+        }
+        for column in &self.tire_temps {
+            for (bad, t) in invalid.iter_mut().zip(column) {
+                *bad |= !(-50.0..=200.0).contains(t);
+            }
+        }
+        for column in [&self.throttle, &self.brake] {
+            for (bad, &v) in invalid.iter_mut().zip(column) {
+                *bad |= !(0.0..=1.0).contains(&v);
+            }
+        }
+        invalid.into_iter().map(|bad| !bad).collect()
+    }
+
+    /// Column stats over every row
+    pub fn stats(&self) -> BatchStats {
+        BatchStats {
+            speed: ColumnStats::of(&self.speed),
+            throttle: ColumnStats::of(&self.throttle),
+            brake: ColumnStats::of(&self.brake),
+        }
+    }
+
+    /// Empty the batch, keeping its allocations
+    pub fn clear(&mut self) {
+        self.rows.clear();
+        self.speed.clear();
+        self.throttle.clear();
+        self.brake.clear();
+        for column in &mut self.tire_temps {
+            column.clear();
+        }
+    }
+
+    fn take_rows(&mut self) -> Vec<TelemetrySnapshot> {
+        let rows = std::mem::take(&mut self.rows);
+        self.clear();
+        rows
+    }
+}
+
+/// Outcome of one processed micro-batch
+#[derive(Debug)]
+pub struct BatchResult {
+    /// Worker that processed the batch
+    pub shard: usize,
+
+    /// Snapshots that passed validation, in arrival order per car
+    pub accepted: Vec<TelemetrySnapshot>,
+
+    /// Snapshots that failed validation
+    pub rejected: Vec<(CarId, TelemetryError)>,
+
+    /// Anomalies detected in accepted snapshots
+    pub anomalies: Vec<AnomalyInfo>,
+
+    /// Column stats of the whole batch
+    pub stats: BatchStats,
+
+    /// Time spent processing the batch
+    pub latency: Duration,
+}
+
+impl BatchResult {
+    /// Snapshots in the batch
+    pub fn len(&self) -> usize {
+        self.accepted.len() + self.rejected.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Per-car pipeline counters
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CarPipelineStats {
+    pub processed: u64,
+    pub rejected: u64,
+    pub anomalies: u64,
+    pub last_timestamp: Option<DateTime<Utc>>,
+}
+
+/// Validation and detection shared by every worker
+struct BatchWorker {
+    processor: Arc<TelemetryProcessor>,
+    detector: Arc<AnomalyDetector>,
+    car_stats: DashMap<CarId, CarPipelineStats>,
+}
+
+impl BatchWorker {
+    fn run(&self, shard: usize, batch: &mut SnapshotBatch) -> BatchResult {
+        let start = Instant::now();
+        let stats = batch.stats();
+        let outcomes = self.processor.process_batch(batch);
+
+        let mut accepted = Vec::with_capacity(batch.len());
+        let mut rejected = Vec::new();
+        let mut anomalies = Vec::new();
+        for (snapshot, outcome) in batch.take_rows().into_iter().zip(outcomes) {
+            match outcome {
+                Ok(()) => {
+                    // Detect before locking the stats shard so other workers
+                    // are not held up by detection
+                    let detected = self.detector.detect(&snapshot);
+                    let mut car = self.car_stats.entry(snapshot.car_id).or_default();
+                    car.processed += 1;
+                    car.anomalies += detected.len() as u64;
+                    car.last_timestamp = Some(snapshot.timestamp);
+                    drop(car);
+                    anomalies.extend(detected);
+                    accepted.push(snapshot);
+                }
+                Err(e) => {
+                    self.car_stats.entry(snapshot.car_id).or_default().rejected += 1;
+                    rejected.push((snapshot.car_id, e));
+                }
+            }
+        }
+
+        BatchResult {
+            shard,
+            accepted,
+            rejected,
+            anomalies,
+            stats,
+            latency: start.elapsed(),
+        }
+    }
+}
+
+/// Multi-threaded telemetry pipeline sharded by car
+///
+/// `submit` routes each snapshot to the worker owning its car; workers
+/// process micro-batches of up to `batch_size` snapshots, or whatever
+/// arrived within `max_batch_delay`, and publish a `BatchResult` per batch.
+/// Results queue up until read, so consumers should keep draining `results`.
+pub struct ParallelPipeline {
+    config: PipelineConfig,
+    worker: Arc<BatchWorker>,
+    senders: Vec<Sender<TelemetrySnapshot>>,
+    handles: Vec<JoinHandle<()>>,
+    results: Receiver<BatchResult>,
+}
+
+impl ParallelPipeline {
+    /// Start a pipeline with its own processor and anomaly detector
+    pub fn new(config: TelemetryConfig, pipeline: PipelineConfig) -> Self {
+        Self::with_components(
+            Arc::new(TelemetryProcessor::new(config.clone())),
+            Arc::new(AnomalyDetector::new(config)),
+            pipeline,
+        )
+    }
+
+    /// Start a pipeline sharing a processor and detector, e.g. with an engine
+    pub fn with_components(
+        processor: Arc<TelemetryProcessor>,
+        detector: Arc<AnomalyDetector>,
+        config: PipelineConfig,
+    ) -> Self {
+        let config = PipelineConfig {
+            shards: config.shards.max(1),
+            batch_size: config.batch_size.max(1),
+            ..config
+        };
+        let worker = Arc::new(BatchWorker {
+            processor,
+            detector,
+            car_stats: DashMap::new(),
+        });
+        let (result_tx, results) = channel::unbounded();
+
+        let mut senders = Vec::with_capacity(config.shards);
+        let mut handles = Vec::with_capacity(config.shards);
+        for shard in 0..config.shards {
+            let (tx, rx) = channel::bounded(config.queue_capacity);
+            let worker = Arc::clone(&worker);
+            let result_tx = result_tx.clone();
+            let (batch_size, delay) = (config.batch_size, config.max_batch_delay);
+            let handle = std::thread::Builder::new()
+                .name(format!("telemetry-shard-{}", shard))
+                .spawn(move || run_shard(shard, &worker, &rx, &result_tx, batch_size, delay))
+                .expect("failed to spawn telemetry worker");
+            senders.push(tx);
+            handles.push(handle);
+        }
+
+        ParallelPipeline {
+            config,
+            worker,
+            senders,
+            handles,
+            results,
+        }
+    }
+
+    pub fn config(&self) -> &PipelineConfig {
+        &self.config
+    }
+
+    /// Worker that owns `car_id`
+    pub fn shard_for(&self, car_id: CarId) -> usize {
+        car_id.0 as usize % self.config.shards
+    }
+
+    /// Queue a snapshot, waiting while its shard's queue is full
+    pub fn submit(&self, snapshot: TelemetrySnapshot) -> Result<(), PipelineError> {
+        let shard = self.shard_for(snapshot.car_id);
+        self.senders[shard].send(snapshot).map_err(|_| PipelineError::Closed)
+    }
+
+    /// Queue a snapshot without waiting
+    pub fn try_submit(&self, snapshot: TelemetrySnapshot) -> Result<(), PipelineError> {
+        let shard = self.shard_for(snapshot.car_id);
+        self.senders[shard].try_send(snapshot).map_err(|e| match e {
+            TrySendError::Full(_) => PipelineError::Full(shard),
+            TrySendError::Disconnected(_) => PipelineError::Closed,
+        })
+    }
+
+    /// Processed batches, in completion order
+    pub fn results(&self) -> &Receiver<BatchResult> {
+        &self.results
+    }
+
+    /// Snapshots waiting in each shard's queue
+    pub fn queue_depths(&self) -> Vec<usize> {
+        self.senders.iter().map(|tx| tx.len()).collect()
+    }
+
+    /// Counters of one car
+    pub fn car_stats(&self, car_id: CarId) -> Option<CarPipelineStats> {
+        self.worker.car_stats.get(&car_id).map(|stats| *stats)
+    }
+
+    /// Process a recorded run on the calling thread pool, bypassing the queues
+    ///
+    /// Snapshots are grouped by car and each car's snapshots are processed in
+    /// order, in batches of `batch_size`, with cars spread over rayon's pool.
+    pub fn process_all(&self, snapshots: Vec<TelemetrySnapshot>) -> Vec<BatchResult> {
+        let mut by_car: HashMap<CarId, Vec<TelemetrySnapshot>> = HashMap::new();
+        for snapshot in snapshots {
+            by_car.entry(snapshot.car_id).or_default().push(snapshot);
+        }
+
+        let batch_size = self.config.batch_size;
+        by_car
+            .into_par_iter()
+            .flat_map_iter(|(car_id, snapshots)| {
+                let shard = self.shard_for(car_id);
+                let mut results = Vec::with_capacity(snapshots.len().div_ceil(batch_size));
+                let mut batch = SnapshotBatch::with_capacity(batch_size);
+                for snapshot in snapshots {
+                    batch.push(snapshot);
+                    if batch.len() == batch_size {
+                        results.push(self.worker.run(shard, &mut batch));
+                    }
+                }
+                if !batch.is_empty() {
+                    results.push(self.worker.run(shard, &mut batch));
+                }
+                results
+            })
+            .collect()
+    }
+
+    /// Stop accepting snapshots, finish queued work and return the results
+    /// not yet read
+    pub fn shutdown(mut self) -> Vec<BatchResult> {
+        self.stop();
+        self.results.try_iter().collect()
+    }
+
+    fn stop(&mut self) {
+        self.senders.clear();
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for ParallelPipeline {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn run_shard(
+    shard: usize,
+    worker: &BatchWorker,
+    rx: &Receiver<TelemetrySnapshot>,
+    results: &Sender<BatchResult>,
+    batch_size: usize,
+    delay: Duration,
+) {
+    let mut batch = SnapshotBatch::with_capacity(batch_size);
+    while let Ok(first) = rx.recv() {
+        batch.push(first);
+        let deadline = Instant::now() + delay;
+        let mut closed = false;
+        while batch.len() < batch_size {
+            match rx.recv_deadline(deadline) {
+                Ok(snapshot) => batch.push(snapshot),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => {
+                    closed = true;
+                    break;
+                }
+            }
+        }
+
+        // Nobody reading results is not a reason to stop processing
+        let _ = results.send(worker.run(shard, &mut batch));
+        if closed {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::tests::create_test_snapshot;
+    use f1_nexus_core::SessionId;
+
+    fn grid(cars: u8, samples: usize) -> Vec<TelemetrySnapshot> {
+        let session_id = SessionId::new();
+        let start = Utc::now();
+        let template = create_test_snapshot();
+        (0..samples)
+            .flat_map(|i| {
+                let template = template.clone();
+                (1..=cars).map(move |car| {
+                    let mut snapshot = template.clone();
+                    snapshot.session_id = session_id;
+                    snapshot.car_id = CarId::new(car).unwrap();
+                    snapshot.timestamp = start + chrono::Duration::milliseconds(i as i64);
+                    snapshot.motion.speed = 200.0 + i as f32 * 0.01;
+                    snapshot
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_columnar_validation_matches_rows() {
+        let mut snapshots = grid(1, 5);
+        snapshots[1].motion.speed = 450.0;
+        snapshots[2].tires.rear_left.surface_temp = 250.0;
+        snapshots[3].inputs.brake = -0.1;
+        snapshots[4].inputs.throttle = f32::NAN;
+        let batch = SnapshotBatch::from_snapshots(snapshots.clone());
+        assert_eq!(batch.validate_columns(), vec![true, false, false, false, false]);
+
+        // Column-wise and row-wise processing agree, errors included
+        for enable_simd in [true, false] {
+            let processor = TelemetryProcessor::new(TelemetryConfig {
+                enable_simd,
+                ..TelemetryConfig::default()
+            });
+            let outcomes = processor.process_batch(&batch);
+            assert!(outcomes[0].is_ok());
+            let err = outcomes[1].as_ref().unwrap_err().to_string();
+            assert!(err.contains("Invalid speed: 450"), "{}", err);
+            assert!(outcomes[4].is_err());
+            assert_eq!(processor.stats().total_processed, 1);
+            assert_eq!(processor.stats().total_errors, 4);
+        }
+    }
+
+    #[test]
+    fn test_batch_stats() {
+        let batch = SnapshotBatch::from_snapshots(grid(1, 3));
+        let stats = batch.stats();
+        assert_eq!(stats.speed.min, 200.0);
+        assert_eq!(stats.speed.max, 200.02);
+        assert!((stats.speed.mean - 200.01).abs() < 1e-3);
+        assert_eq!(stats.throttle, ColumnStats::of(batch.throttle()));
+        assert_eq!(ColumnStats::of(&[]), ColumnStats::default());
+    }
+
+    #[test]
+    fn test_pipeline_shards_by_car_in_order() {
+        let pipeline = ParallelPipeline::new(
+            TelemetryConfig::default(),
+            PipelineConfig {
+                shards: 4,
+                batch_size: 16,
+                ..PipelineConfig::default()
+            },
+        );
+        let snapshots = grid(20, 50);
+        let mut invalid = snapshots[0].clone();
+        invalid.motion.speed = -1.0;
+        for snapshot in snapshots {
+            pipeline.submit(snapshot).unwrap();
+        }
+        pipeline.submit(invalid).unwrap();
+
+        let results = pipeline.shutdown();
+        assert_eq!(results.iter().map(BatchResult::len).sum::<usize>(), 1001);
+        assert_eq!(results.iter().map(|r| r.rejected.len()).sum::<usize>(), 1);
+
+        let mut last_seen: HashMap<CarId, DateTime<Utc>> = HashMap::new();
+        for result in &results {
+            assert!(result.len() <= 16);
+            for snapshot in &result.accepted {
+                assert_eq!(result.shard, snapshot.car_id.0 as usize % 4);
+                if let Some(previous) = last_seen.insert(snapshot.car_id, snapshot.timestamp) {
+                    assert!(previous < snapshot.timestamp, "car {} out of order", snapshot.car_id.0);
+                }
+            }
+        }
+        assert_eq!(last_seen.len(), 20);
+    }
+
+    #[test]
+    fn test_process_all_and_car_stats() {
+        let pipeline = ParallelPipeline::new(
+            TelemetryConfig::default(),
+            PipelineConfig {
+                shards: 2,
+                batch_size: 32,
+                ..PipelineConfig::default()
+            },
+        );
+        let results = pipeline.process_all(grid(19, 100));
+        assert_eq!(results.iter().map(BatchResult::len).sum::<usize>(), 1900);
+        // 100 snapshots per car in batches of 32
+        assert_eq!(results.len(), 19 * 4);
+
+        let car = pipeline.car_stats(CarId::new(7).unwrap()).unwrap();
+        assert_eq!(car.processed, 100);
+        assert_eq!(car.rejected, 0);
+        assert!(car.last_timestamp.is_some());
+        assert!(pipeline.car_stats(CarId::new(20).unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_keeps_up_with_full_grid() {
+        // One second of 20 cars at 1 kHz is 20,000 snapshots; processing
+        // them must take less than the second they cover, even unoptimised
+        let snapshots = grid(20, 1000);
+        let pipeline = ParallelPipeline::new(TelemetryConfig::default(), PipelineConfig::default());
+        let started = std::time::Instant::now();
+        let results = pipeline.process_all(snapshots);
+        let elapsed = started.elapsed();
+
+        assert_eq!(results.iter().map(BatchResult::len).sum::<usize>(), 20_000);
+        let rate = 20_000.0 / elapsed.as_secs_f64();
+        assert!(rate > 20_000.0, "{:.0} snapshots/s", rate);
+    }
+}
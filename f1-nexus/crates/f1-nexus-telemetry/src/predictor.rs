@@ -3,11 +3,13 @@
 //! Predicts lap times using real-time telemetry, track characteristics,
 //! tire degradation models, and physics calculations.
 
+use crate::{Channel, TelemetryBuffer};
 use f1_nexus_core::{
-    Circuit, TelemetrySnapshot, TireCompound, TireCharacteristics,
+    CarId, Circuit, SessionId, TelemetrySnapshot, TireCompound, TireCharacteristics,
     WeatherCondition, FuelConsumptionModel,
 };
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 /// Lap time predictor using physics-based models
 #[derive(Debug, Clone)]
@@ -179,6 +181,43 @@ impl LapTimePredictor {
         }
     }
 
+    /// Predict a car's lap time from the shared telemetry buffer
+    ///
+    /// Uses the newest buffered snapshot with tire and engine temperatures
+    /// averaged over the last `window`, so single noisy samples do not swing
+    /// the prediction. Tire age comes from the snapshot.
+    pub fn predict_from_buffer(
+        &self,
+        buffer: &TelemetryBuffer,
+        session_id: SessionId,
+        car_id: CarId,
+        window: Duration,
+    ) -> Option<LapTimePrediction> {
+        let mut snapshot = buffer.latest(session_id, car_id)?;
+        let mean = |channel: Channel| {
+            let samples = buffer.last(session_id, car_id, channel, window);
+            (!samples.is_empty()).then(|| samples.iter().map(|s| s.value).sum::<f32>() / samples.len() as f32)
+        };
+
+        let tires = &mut snapshot.tires;
+        for (channel, sensor) in [
+            (Channel::TireTempFrontLeft, &mut tires.front_left),
+            (Channel::TireTempFrontRight, &mut tires.front_right),
+            (Channel::TireTempRearLeft, &mut tires.rear_left),
+            (Channel::TireTempRearRight, &mut tires.rear_right),
+        ] {
+            if let Some(temp) = mean(channel) {
+                sensor.surface_temp = temp;
+            }
+        }
+        if let Some(temp) = mean(Channel::EngineTemp) {
+            snapshot.power_unit.engine_temp = temp;
+        }
+
+        let tire_age = snapshot.tires.age_laps;
+        Some(self.predict(&snapshot, tire_age))
+    }
+
     /// Calculate tire degradation penalty
     fn calculate_tire_degradation(
         &self,
@@ -378,7 +417,7 @@ impl LapTimePredictor {
             confidence *= 0.9;
         }
 
-        confidence.max(0.5).min(1.0)
+        confidence.clamp(0.5, 1.0)
     }
 
     /// Calculate average tire temperature
@@ -389,6 +428,11 @@ impl LapTimePredictor {
             + snapshot.tires.rear_right.surface_temp) / 4.0
     }
 
+    /// Get the fuel consumption model used for fuel-load estimates
+    pub fn fuel_model(&self) -> &FuelConsumptionModel {
+        &self.fuel_model
+    }
+
     /// Update weather conditions
     pub fn update_weather(&mut self, weather: WeatherCondition, track_temp: f32, air_temp: f32) {
         self.weather = weather;
@@ -665,4 +709,48 @@ mod tests {
         assert!(fresh_prediction.confidence < normal_prediction.confidence);
         assert!(very_worn_prediction.confidence < normal_prediction.confidence);
     }
+
+    #[test]
+    fn test_predict_from_buffer_smooths_temperatures() {
+        let predictor = LapTimePredictor::new(
+            create_test_circuit(),
+            FuelConsumptionModel::default_model(),
+            WeatherCondition::Dry,
+            30.0,
+            25.0,
+        );
+        let buffer = TelemetryBuffer::default();
+        let mut snapshot = create_test_snapshot(TireCompound::C3, 80.0, 95.0);
+        snapshot.tires.age_laps = 5;
+        let (session_id, car_id) = (snapshot.session_id, snapshot.car_id);
+        assert!(predictor.predict_from_buffer(&buffer, session_id, car_id, Duration::from_secs(1)).is_none());
+
+        let start = snapshot.timestamp;
+        for i in 0..10 {
+            snapshot.timestamp = start + chrono::Duration::milliseconds(i * 100);
+            buffer.push(&snapshot);
+        }
+
+        // One cold reading barely moves the prediction
+        let mut glitch = snapshot.clone();
+        glitch.timestamp = start + chrono::Duration::milliseconds(1_000);
+        for sensor in [
+            &mut glitch.tires.front_left,
+            &mut glitch.tires.front_right,
+            &mut glitch.tires.rear_left,
+            &mut glitch.tires.rear_right,
+        ] {
+            sensor.surface_temp = 60.0;
+        }
+        buffer.push(&glitch);
+
+        let steady = predictor.predict(&snapshot, 5);
+        let smoothed = predictor
+            .predict_from_buffer(&buffer, session_id, car_id, Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(smoothed.factors.tire_age, 5);
+        // Rears run 5 °C hotter in the test snapshot
+        assert!((smoothed.factors.avg_tire_temp - (10.0 * 97.5 + 60.0) / 11.0).abs() < 0.01);
+        assert!(smoothed.predicted_time - steady.predicted_time < predictor.predict(&glitch, 5).predicted_time - steady.predicted_time);
+    }
 }
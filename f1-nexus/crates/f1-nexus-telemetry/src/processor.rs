@@ -1,10 +1,12 @@
 //! Telemetry data processing and validation
 
-use crate::{TelemetryConfig, TelemetryError};
-use f1_nexus_core::TelemetrySnapshot;
-use parking_lot::RwLock;
+use crate::{
+    AnomalyAlert, RuleEngine, RulePack, Severity, SnapshotBatch, TelemetryBuffer, TelemetryConfig, TelemetryError,
+};
+use f1_nexus_core::{CarId, SessionId, TelemetrySnapshot};
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -34,7 +36,10 @@ impl TelemetryProcessor {
         let start = std::time::Instant::now();
 
         // Validate telemetry data
-        self.validate(snapshot)?;
+        if let Err(e) = self.validate(snapshot) {
+            self.stats.total_errors.fetch_add(1, Ordering::Relaxed);
+            return Err(e);
+        }
 
         // Normalize data if needed
         // (In production, this would apply calibrations, unit conversions, etc.)
@@ -47,10 +52,41 @@ impl TelemetryProcessor {
         Ok(())
     }
 
+    /// Process a micro-batch, returning the outcome of each snapshot in order
+    ///
+    /// With `enable_simd` the batch is validated column by column and only
+    /// rejected rows are re-checked to build their error; otherwise every
+    /// snapshot is validated on its own.
+    pub fn process_batch(&self, batch: &SnapshotBatch) -> Vec<Result<(), TelemetryError>> {
+        let start = std::time::Instant::now();
+
+        let outcomes: Vec<Result<(), TelemetryError>> = if self.config.enable_simd {
+            batch
+                .validate_columns()
+                .into_iter()
+                .zip(batch.snapshots())
+                .map(|(valid, snapshot)| if valid { Ok(()) } else { self.validate(snapshot) })
+                .collect()
+        } else {
+            batch.snapshots().iter().map(|snapshot| self.validate(snapshot)).collect()
+        };
+
+        let errors = outcomes.iter().filter(|outcome| outcome.is_err()).count() as u64;
+        let latency_us = start.elapsed().as_micros() as u64;
+        self.stats.total_processed.fetch_add(outcomes.len() as u64 - errors, Ordering::Relaxed);
+        self.stats.total_errors.fetch_add(errors, Ordering::Relaxed);
+        self.stats.total_latency_us.fetch_add(latency_us, Ordering::Relaxed);
+
+        outcomes
+    }
+
     /// Validate telemetry snapshot
+    ///
+    /// Range checks use `contains`, so NaN fails them like any other
+    /// out-of-range value.
     fn validate(&self, snapshot: &TelemetrySnapshot) -> Result<(), TelemetryError> {
         // Validate speed is within reasonable bounds
-        if snapshot.motion.speed < 0.0 || snapshot.motion.speed > 400.0 {
+        if !(0.0..=400.0).contains(&snapshot.motion.speed) {
             return Err(TelemetryError::InvalidData(
                 format!("Invalid speed: {}", snapshot.motion.speed)
             ));
@@ -65,7 +101,7 @@ impl TelemetryProcessor {
         ];
 
         for temp in tire_temps {
-            if temp < -50.0 || temp > 200.0 {
+            if !(-50.0..=200.0).contains(&temp) {
                 return Err(TelemetryError::InvalidData(
                     format!("Invalid tire temperature: {}", temp)
                 ));
@@ -73,13 +109,13 @@ impl TelemetryProcessor {
         }
 
         // Validate throttle/brake inputs
-        if snapshot.inputs.throttle < 0.0 || snapshot.inputs.throttle > 1.0 {
+        if !(0.0..=1.0).contains(&snapshot.inputs.throttle) {
             return Err(TelemetryError::InvalidData(
                 format!("Invalid throttle: {}", snapshot.inputs.throttle)
             ));
         }
 
-        if snapshot.inputs.brake < 0.0 || snapshot.inputs.brake > 1.0 {
+        if !(0.0..=1.0).contains(&snapshot.inputs.brake) {
             return Err(TelemetryError::InvalidData(
                 format!("Invalid brake: {}", snapshot.inputs.brake)
             ));
@@ -88,6 +124,11 @@ impl TelemetryProcessor {
         Ok(())
     }
 
+    /// Get the processor configuration
+    pub fn config(&self) -> &TelemetryConfig {
+        &self.config
+    }
+
     /// Get processing statistics
     pub fn stats(&self) -> ProcessingStats {
         let total = self.stats.total_processed.load(Ordering::Relaxed);
@@ -97,11 +138,7 @@ impl TelemetryProcessor {
         ProcessingStats {
             total_processed: total,
             total_errors: errors,
-            average_latency_us: if total > 0 {
-                total_latency / total
-            } else {
-                0
-            },
+            average_latency_us: total_latency.checked_div(total).unwrap_or(0),
         }
     }
 }
@@ -125,6 +162,16 @@ pub enum AnomalySeverity {
 /// Detailed anomaly information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnomalyInfo {
+    /// Car the anomaly was detected on
+    pub car_id: CarId,
+
+    /// Session the anomaly belongs to
+    pub session_id: SessionId,
+
+    /// Track segment whose baseline was used, if any
+    #[serde(default)]
+    pub segment: Option<u16>,
+
     /// Field name where anomaly was detected
     pub field: String,
 
@@ -141,381 +188,108 @@ pub struct AnomalyInfo {
     pub timestamp: DateTime<Utc>,
 }
 
-/// Statistical data for a single metric
-#[derive(Debug, Clone)]
-struct MetricStats {
-    values: VecDeque<f32>,
-    mean: f32,
-    std_dev: f32,
-    window_size: usize,
-}
-
-impl MetricStats {
-    fn new(window_size: usize) -> Self {
-        MetricStats {
-            values: VecDeque::with_capacity(window_size),
-            mean: 0.0,
-            std_dev: 0.0,
-            window_size,
-        }
-    }
-
-    fn push(&mut self, value: f32) {
-        if self.values.len() >= self.window_size {
-            self.values.pop_front();
+impl From<Severity> for AnomalySeverity {
+    fn from(severity: Severity) -> Self {
+        match severity {
+            Severity::Low => AnomalySeverity::Low,
+            Severity::Medium => AnomalySeverity::Medium,
+            Severity::High | Severity::Critical => AnomalySeverity::High,
         }
-        self.values.push_back(value);
-        self.update_stats();
-    }
-
-    fn update_stats(&mut self) {
-        if self.values.is_empty() {
-            return;
-        }
-
-        // Calculate mean
-        self.mean = self.values.iter().sum::<f32>() / self.values.len() as f32;
-
-        // Calculate standard deviation
-        let variance: f32 = self.values.iter()
-            .map(|&x| {
-                let diff = x - self.mean;
-                diff * diff
-            })
-            .sum::<f32>() / self.values.len() as f32;
-
-        self.std_dev = variance.sqrt();
     }
+}
 
-    fn z_score(&self, value: f32) -> f32 {
-        if self.std_dev == 0.0 {
-            return 0.0;
+impl From<AnomalyAlert> for AnomalyInfo {
+    fn from(alert: AnomalyAlert) -> Self {
+        AnomalyInfo {
+            car_id: alert.car_id,
+            session_id: alert.session_id,
+            segment: alert.segment,
+            field: alert.field,
+            expected_range: alert.expected_range.unwrap_or((alert.actual_value, alert.actual_value)),
+            actual_value: alert.actual_value,
+            severity: alert.severity.into(),
+            timestamp: alert.timestamp,
         }
-        (value - self.mean) / self.std_dev
-    }
-
-    fn has_enough_data(&self) -> bool {
-        self.values.len() >= (self.window_size / 2)
     }
 }
 
 /// Anomaly detector with statistical analysis
+///
+/// Runs the `statistical` rule pack: hard physical limits plus z-score
+/// deviation from rolling baselines kept per car and session, and optionally
+/// per track segment, so cars at different pace (or in the pits) do not skew
+/// each other. Idle baselines are evicted when new ones are created, and the
+/// total is capped at `TelemetryConfig::max_baselines`.
 pub struct AnomalyDetector {
-    config: TelemetryConfig,
-    speed_stats: RwLock<MetricStats>,
-    tire_temp_stats: RwLock<MetricStats>,
-    brake_temp_stats: RwLock<MetricStats>,
-    rpm_stats: RwLock<MetricStats>,
+    engine: RuleEngine,
 }
 
 impl AnomalyDetector {
     /// Create a new anomaly detector
     pub fn new(config: TelemetryConfig) -> Self {
-        let window_size = config.buffer_size.min(100); // Use config buffer size, cap at 100
+        Self::with_rules(config, RulePack::statistical())
+    }
 
+    /// Create a detector running a custom rule pack
+    pub fn with_rules(config: TelemetryConfig, pack: RulePack) -> Self {
         AnomalyDetector {
-            config,
-            speed_stats: RwLock::new(MetricStats::new(window_size)),
-            tire_temp_stats: RwLock::new(MetricStats::new(window_size)),
-            brake_temp_stats: RwLock::new(MetricStats::new(window_size)),
-            rpm_stats: RwLock::new(MetricStats::new(window_size)),
+            engine: RuleEngine::new(config, pack),
         }
     }
 
-    /// Detect anomalies in telemetry snapshot
-    pub fn detect(&self, snapshot: &TelemetrySnapshot) -> Vec<AnomalyInfo> {
-        if !self.config.enable_anomaly_detection {
-            return Vec::new();
-        }
-
-        let mut anomalies = Vec::new();
-        let timestamp = snapshot.timestamp;
-
-        // 1. Speed anomalies
-        anomalies.extend(self.detect_speed_anomalies(snapshot, timestamp));
-
-        // 2. Tire temperature anomalies
-        anomalies.extend(self.detect_tire_temp_anomalies(snapshot, timestamp));
-
-        // 3. Brake temperature anomalies
-        anomalies.extend(self.detect_brake_temp_anomalies(snapshot, timestamp));
-
-        // 4. Throttle/brake conflict
-        if let Some(anomaly) = self.detect_throttle_brake_conflict(snapshot, timestamp) {
-            anomalies.push(anomaly);
-        }
-
-        // 5. ERS battery anomalies
-        if let Some(anomaly) = self.detect_ers_anomalies(snapshot, timestamp) {
-            anomalies.push(anomaly);
-        }
-
-        // 6. RPM anomalies
-        if let Some(anomaly) = self.detect_rpm_anomalies(snapshot, timestamp) {
-            anomalies.push(anomaly);
-        }
-
-        // Update statistics with current values
-        self.update_statistics(snapshot);
-
-        anomalies
-    }
-
-    fn detect_speed_anomalies(&self, snapshot: &TelemetrySnapshot, timestamp: DateTime<Utc>) -> Vec<AnomalyInfo> {
-        let mut anomalies = Vec::new();
-        let speed = snapshot.motion.speed;
-
-        // Hard limits: unrealistic values
-        if speed < 0.0 {
-            anomalies.push(AnomalyInfo {
-                field: "speed".to_string(),
-                expected_range: (0.0, 380.0),
-                actual_value: speed,
-                severity: AnomalySeverity::High,
-                timestamp,
-            });
-        } else if speed > 380.0 {
-            anomalies.push(AnomalyInfo {
-                field: "speed".to_string(),
-                expected_range: (0.0, 380.0),
-                actual_value: speed,
-                severity: AnomalySeverity::High,
-                timestamp,
-            });
-        } else {
-            // Z-score based detection
-            let stats = self.speed_stats.read();
-            if stats.has_enough_data() {
-                let z = stats.z_score(speed);
-
-                if z.abs() > 3.0 {
-                    // More than 3 standard deviations
-                    anomalies.push(AnomalyInfo {
-                        field: "speed".to_string(),
-                        expected_range: (stats.mean - 3.0 * stats.std_dev, stats.mean + 3.0 * stats.std_dev),
-                        actual_value: speed,
-                        severity: AnomalySeverity::Medium,
-                        timestamp,
-                    });
-                } else if z.abs() > 2.0 {
-                    // More than 2 standard deviations
-                    anomalies.push(AnomalyInfo {
-                        field: "speed".to_string(),
-                        expected_range: (stats.mean - 2.0 * stats.std_dev, stats.mean + 2.0 * stats.std_dev),
-                        actual_value: speed,
-                        severity: AnomalySeverity::Low,
-                        timestamp,
-                    });
-                }
-            }
-        }
-
-        anomalies
+    /// Read each car's previous snapshot from a shared buffer
+    /// (see `RuleEngine::with_history`)
+    pub fn with_history(mut self, buffer: Arc<TelemetryBuffer>) -> Self {
+        self.engine = self.engine.with_history(buffer);
+        self
     }
 
-    fn detect_tire_temp_anomalies(&self, snapshot: &TelemetrySnapshot, timestamp: DateTime<Utc>) -> Vec<AnomalyInfo> {
-        let mut anomalies = Vec::new();
-
-        let tire_temps = [
-            ("tire_temp_front_left", snapshot.tires.front_left.surface_temp),
-            ("tire_temp_front_right", snapshot.tires.front_right.surface_temp),
-            ("tire_temp_rear_left", snapshot.tires.rear_left.surface_temp),
-            ("tire_temp_rear_right", snapshot.tires.rear_right.surface_temp),
-        ];
-
-        for (field, temp) in tire_temps {
-            if temp > 120.0 {
-                // Critical high temperature
-                anomalies.push(AnomalyInfo {
-                    field: field.to_string(),
-                    expected_range: (40.0, 120.0),
-                    actual_value: temp,
-                    severity: AnomalySeverity::High,
-                    timestamp,
-                });
-            } else if temp < 40.0 {
-                // Abnormally low temperature
-                anomalies.push(AnomalyInfo {
-                    field: field.to_string(),
-                    expected_range: (40.0, 120.0),
-                    actual_value: temp,
-                    severity: AnomalySeverity::Medium,
-                    timestamp,
-                });
-            } else {
-                // Statistical detection
-                let stats = self.tire_temp_stats.read();
-                if stats.has_enough_data() {
-                    let z = stats.z_score(temp);
-
-                    if z.abs() > 2.5 {
-                        anomalies.push(AnomalyInfo {
-                            field: field.to_string(),
-                            expected_range: (stats.mean - 2.5 * stats.std_dev, stats.mean + 2.5 * stats.std_dev),
-                            actual_value: temp,
-                            severity: AnomalySeverity::Low,
-                            timestamp,
-                        });
-                    }
-                }
-            }
-        }
-
-        anomalies
+    /// Detect anomalies in telemetry snapshot against the car's lap baseline
+    pub fn detect(&self, snapshot: &TelemetrySnapshot) -> Vec<AnomalyInfo> {
+        self.engine.evaluate(snapshot).into_iter().map(AnomalyInfo::from).collect()
     }
 
-    fn detect_brake_temp_anomalies(&self, snapshot: &TelemetrySnapshot, timestamp: DateTime<Utc>) -> Vec<AnomalyInfo> {
-        let mut anomalies = Vec::new();
-
-        let brake_temps = [
-            ("brake_temp_front_left", snapshot.tires.front_left.brake_temp),
-            ("brake_temp_front_right", snapshot.tires.front_right.brake_temp),
-            ("brake_temp_rear_left", snapshot.tires.rear_left.brake_temp),
-            ("brake_temp_rear_right", snapshot.tires.rear_right.brake_temp),
-        ];
-
-        for (field, temp) in brake_temps {
-            if temp > 1200.0 {
-                // Critical brake temperature
-                anomalies.push(AnomalyInfo {
-                    field: field.to_string(),
-                    expected_range: (0.0, 1200.0),
-                    actual_value: temp,
-                    severity: AnomalySeverity::High,
-                    timestamp,
-                });
-            } else {
-                // Statistical detection
-                let stats = self.brake_temp_stats.read();
-                if stats.has_enough_data() {
-                    let z = stats.z_score(temp);
-
-                    if z.abs() > 3.0 {
-                        anomalies.push(AnomalyInfo {
-                            field: field.to_string(),
-                            expected_range: (stats.mean - 3.0 * stats.std_dev, stats.mean + 3.0 * stats.std_dev),
-                            actual_value: temp,
-                            severity: AnomalySeverity::Medium,
-                            timestamp,
-                        });
-                    }
-                }
-            }
-        }
-
-        anomalies
+    /// Detect anomalies against the car's baseline for one track segment
+    ///
+    /// Speed and temperatures vary a lot around a lap; comparing against the
+    /// same segment on previous laps gives much tighter baselines.
+    pub fn detect_in_segment(&self, snapshot: &TelemetrySnapshot, segment: u16) -> Vec<AnomalyInfo> {
+        self.engine
+            .evaluate_in_segment(snapshot, segment)
+            .into_iter()
+            .map(AnomalyInfo::from)
+            .collect()
     }
 
-    fn detect_throttle_brake_conflict(&self, snapshot: &TelemetrySnapshot, timestamp: DateTime<Utc>) -> Option<AnomalyInfo> {
-        // Check for simultaneous high throttle and brake (> 0.5 each)
-        if snapshot.inputs.throttle > 0.5 && snapshot.inputs.brake > 0.5 {
-            Some(AnomalyInfo {
-                field: "throttle_brake_conflict".to_string(),
-                expected_range: (0.0, 0.5),
-                actual_value: snapshot.inputs.throttle.min(snapshot.inputs.brake),
-                severity: AnomalySeverity::High,
-                timestamp,
-            })
-        } else {
-            None
-        }
+    /// Number of baselines currently held
+    pub fn baseline_count(&self) -> usize {
+        self.engine.baseline_count()
     }
 
-    fn detect_ers_anomalies(&self, snapshot: &TelemetrySnapshot, timestamp: DateTime<Utc>) -> Option<AnomalyInfo> {
-        let battery = snapshot.power_unit.ers_battery;
-
-        // Check for battery level outside valid range
-        if battery > 1.0 {
-            Some(AnomalyInfo {
-                field: "ers_battery".to_string(),
-                expected_range: (0.0, 1.0),
-                actual_value: battery,
-                severity: AnomalySeverity::High,
-                timestamp,
-            })
-        } else if battery < 0.0 {
-            Some(AnomalyInfo {
-                field: "ers_battery".to_string(),
-                expected_range: (0.0, 1.0),
-                actual_value: battery,
-                severity: AnomalySeverity::High,
-                timestamp,
-            })
-        } else {
-            None
-        }
+    /// Drop baselines not updated within `max_idle`; returns the number removed
+    pub fn evict_idle(&self, max_idle: Duration) -> usize {
+        self.engine.evict_idle(max_idle)
     }
 
-    fn detect_rpm_anomalies(&self, snapshot: &TelemetrySnapshot, timestamp: DateTime<Utc>) -> Option<AnomalyInfo> {
-        let rpm = snapshot.power_unit.rpm as f32;
-
-        // Hard limits for F1 engines
-        if rpm > 15000.0 {
-            Some(AnomalyInfo {
-                field: "rpm".to_string(),
-                expected_range: (0.0, 15000.0),
-                actual_value: rpm,
-                severity: AnomalySeverity::High,
-                timestamp,
-            })
-        } else {
-            // Statistical detection
-            let stats = self.rpm_stats.read();
-            if stats.has_enough_data() {
-                let z = stats.z_score(rpm);
-
-                if z.abs() > 3.0 {
-                    Some(AnomalyInfo {
-                        field: "rpm".to_string(),
-                        expected_range: (stats.mean - 3.0 * stats.std_dev, stats.mean + 3.0 * stats.std_dev),
-                        actual_value: rpm,
-                        severity: AnomalySeverity::Medium,
-                        timestamp,
-                    })
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        }
+    /// Drop every baseline of a session; returns the number removed
+    pub fn evict_session(&self, session_id: SessionId) -> usize {
+        self.engine.evict_session(session_id)
     }
 
-    fn update_statistics(&self, snapshot: &TelemetrySnapshot) {
-        // Update moving averages for statistical detection
-        self.speed_stats.write().push(snapshot.motion.speed);
-
-        // Average tire temperature
-        let avg_tire_temp = (
-            snapshot.tires.front_left.surface_temp +
-            snapshot.tires.front_right.surface_temp +
-            snapshot.tires.rear_left.surface_temp +
-            snapshot.tires.rear_right.surface_temp
-        ) / 4.0;
-        self.tire_temp_stats.write().push(avg_tire_temp);
-
-        // Average brake temperature
-        let avg_brake_temp = (
-            snapshot.tires.front_left.brake_temp +
-            snapshot.tires.front_right.brake_temp +
-            snapshot.tires.rear_left.brake_temp +
-            snapshot.tires.rear_right.brake_temp
-        ) / 4.0;
-        self.brake_temp_stats.write().push(avg_brake_temp);
-
-        // RPM
-        self.rpm_stats.write().push(snapshot.power_unit.rpm as f32);
+    /// Drop every baseline of one car in a session; returns the number removed
+    pub fn evict_car(&self, session_id: SessionId, car_id: CarId) -> usize {
+        self.engine.evict_car(session_id, car_id)
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use f1_nexus_core::*;
     use f1_nexus_core::telemetry::ErsMode;
     use chrono::Utc;
 
-    fn create_test_snapshot() -> TelemetrySnapshot {
+    pub(crate) fn create_test_snapshot() -> TelemetrySnapshot {
         TelemetrySnapshot {
             session_id: SessionId::new(),
             car_id: CarId::new(1).unwrap(),
@@ -628,6 +402,17 @@ mod tests {
         snapshot.motion.speed = 500.0; // Invalid
 
         assert!(processor.process(&snapshot).is_err());
+
+        // NaN fails the range checks too
+        snapshot.motion.speed = f32::NAN;
+        assert!(processor.process(&snapshot).is_err());
+        snapshot.motion.speed = 200.0;
+        snapshot.inputs.brake = f32::NAN;
+        assert!(processor.process(&snapshot).is_err());
+
+        let stats = processor.stats();
+        assert_eq!(stats.total_processed, 0);
+        assert_eq!(stats.total_errors, 3);
     }
 
     // ===== Anomaly Detection Tests =====
@@ -789,14 +574,17 @@ mod tests {
         });
 
         // Build up history with consistent speed around 250 km/h
+        let session_id = SessionId::new();
         for i in 0..15 {
             let mut snapshot = create_test_snapshot();
+            snapshot.session_id = session_id;
             snapshot.motion.speed = 250.0 + (i as f32 * 2.0); // 250-278 km/h
             detector.detect(&snapshot);
         }
 
         // Now test with outlier
         let mut outlier = create_test_snapshot();
+        outlier.session_id = session_id;
         outlier.motion.speed = 350.0; // Way outside normal range
 
         let anomalies = detector.detect(&outlier);
@@ -806,43 +594,6 @@ mod tests {
         assert!(!speed_anomalies.is_empty(), "Should detect statistical speed anomaly");
     }
 
-    #[test]
-    fn test_metric_stats_z_score() {
-        let mut stats = MetricStats::new(10);
-
-        // Add values: 10, 20, 30, 40, 50 (mean = 30, std_dev ≈ 14.14)
-        for val in [10.0, 20.0, 30.0, 40.0, 50.0] {
-            stats.push(val);
-        }
-
-        // Test z-score calculation
-        assert!(stats.has_enough_data());
-        assert!((stats.mean - 30.0).abs() < 0.01);
-
-        // Value of 30 (mean) should have z-score near 0
-        let z = stats.z_score(30.0);
-        assert!(z.abs() < 0.01);
-
-        // Value far from mean should have high z-score
-        let z_outlier = stats.z_score(100.0);
-        assert!(z_outlier > 2.0);
-    }
-
-    #[test]
-    fn test_metric_stats_window_size() {
-        let mut stats = MetricStats::new(5);
-
-        // Add more values than window size
-        for i in 0..10 {
-            stats.push(i as f32);
-        }
-
-        // Should only keep last 5 values: 5, 6, 7, 8, 9
-        assert_eq!(stats.values.len(), 5);
-        assert_eq!(*stats.values.front().unwrap(), 5.0);
-        assert_eq!(*stats.values.back().unwrap(), 9.0);
-    }
-
     #[test]
     fn test_multiple_anomalies() {
         let detector = AnomalyDetector::new(TelemetryConfig::default());
@@ -880,6 +631,101 @@ mod tests {
         assert!(anomalies.is_empty(), "Anomaly detection should be disabled");
     }
 
+    #[test]
+    fn test_baselines_are_per_car() {
+        let detector = AnomalyDetector::new(TelemetryConfig {
+            buffer_size: 20,
+            ..TelemetryConfig::default()
+        });
+        let session_id = SessionId::new();
+
+        // Car 1 lapping at racing speed
+        for i in 0..15 {
+            let mut snapshot = create_test_snapshot();
+            snapshot.session_id = session_id;
+            snapshot.motion.speed = 250.0 + (i as f32 * 2.0);
+            detector.detect(&snapshot);
+        }
+
+        // Car 2 driving down the pit lane is not compared against car 1
+        let mut pit_lane = create_test_snapshot();
+        pit_lane.session_id = session_id;
+        pit_lane.car_id = CarId::new(2).unwrap();
+        pit_lane.motion.speed = 80.0;
+        assert!(detector.detect(&pit_lane).iter().all(|a| a.field != "speed"));
+        assert_eq!(detector.baseline_count(), 2);
+
+        // The same speed on car 1 is anomalous and tagged with the car
+        let mut slow = create_test_snapshot();
+        slow.session_id = session_id;
+        slow.motion.speed = 80.0;
+        let anomaly = detector
+            .detect(&slow)
+            .into_iter()
+            .find(|a| a.field == "speed")
+            .unwrap();
+        assert_eq!(anomaly.car_id, CarId::new(1).unwrap());
+        assert_eq!(anomaly.session_id, session_id);
+        assert_eq!(anomaly.segment, None);
+    }
+
+    #[test]
+    fn test_segment_baselines() {
+        let detector = AnomalyDetector::new(TelemetryConfig {
+            buffer_size: 20,
+            ..TelemetryConfig::default()
+        });
+        let session_id = SessionId::new();
+        let at = |speed: f32| {
+            let mut snapshot = create_test_snapshot();
+            snapshot.session_id = session_id;
+            snapshot.motion.speed = speed;
+            snapshot
+        };
+
+        // Segment 0 is a straight, segment 1 a hairpin
+        for i in 0..15 {
+            detector.detect_in_segment(&at(300.0 + i as f32), 0);
+            detector.detect_in_segment(&at(70.0 + i as f32), 1);
+        }
+
+        assert!(detector.detect_in_segment(&at(75.0), 1).is_empty());
+        let anomalies = detector.detect_in_segment(&at(75.0), 0);
+        assert_eq!(anomalies[0].segment, Some(0));
+        assert_eq!(detector.baseline_count(), 2);
+    }
+
+    #[test]
+    fn test_baseline_eviction() {
+        let detector = AnomalyDetector::new(TelemetryConfig {
+            max_baselines: 3,
+            ..TelemetryConfig::default()
+        });
+        let old_session = SessionId::new();
+        let new_session = SessionId::new();
+
+        for car in 1..=2 {
+            let mut snapshot = create_test_snapshot();
+            snapshot.session_id = old_session;
+            snapshot.car_id = CarId::new(car).unwrap();
+            detector.detect(&snapshot);
+        }
+        assert_eq!(detector.evict_car(old_session, CarId::new(2).unwrap()), 1);
+        assert_eq!(detector.evict_session(old_session), 1);
+        assert_eq!(detector.baseline_count(), 0);
+
+        // Capacity keeps the most recently seen baselines
+        for car in 1..=5 {
+            let mut snapshot = create_test_snapshot();
+            snapshot.session_id = new_session;
+            snapshot.car_id = CarId::new(car).unwrap();
+            detector.detect(&snapshot);
+        }
+        assert_eq!(detector.baseline_count(), 3);
+
+        assert_eq!(detector.evict_idle(Duration::ZERO), 3);
+    }
+
     #[test]
     fn test_severity_ordering() {
         assert!(AnomalySeverity::Low < AnomalySeverity::Medium);
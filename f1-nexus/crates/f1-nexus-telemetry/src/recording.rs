@@ -0,0 +1,989 @@
+//! On-disk telemetry recording and replay
+//!
+//! `TelemetryRecorder` writes snapshots to a compact file: each car's stream
+//! is cut into chunks, every chunk is stored column by column (integers as
+//! zigzag varint deltas, floats XOR-ed with the previous value) and
+//! zstd-compressed. An index of chunks by session, car, lap range and time
+//! range is written at the end, so `RecordingReader` only decompresses the
+//! chunks a query touches. `ReplayPlayer` feeds a recording back at real
+//! time, N× speed, unpaced, or step by step.
+//!
+//! File layout:
+//!
+//! ```text
+//! "F1NXREC1" | chunk* | index (zstd JSON) | index offset (u64 LE) | "F1NXIDX1"
+//! chunk = length (u32 LE) | zstd(columns)
+//! ```
+
+use crate::{Delivery, DeliveryReceiver, TelemetryEvent};
+use chrono::{DateTime, TimeZone, Utc};
+use f1_nexus_core::telemetry::ErsMode;
+use f1_nexus_core::{
+    AeroData, BrakeData, CarId, DriverInputs, DrsStatus, FuelData, LapNumber, MotionData, Position,
+    PowerUnitData, SessionId, TelemetrySnapshot, TireCompound, TireData, TireSensor,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::future::Future;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::time::Duration;
+use tracing::warn;
+
+const FILE_MAGIC: &[u8; 8] = b"F1NXREC1";
+const INDEX_MAGIC: &[u8; 8] = b"F1NXIDX1";
+/// Largest decompressed index accepted when opening a recording
+const MAX_INDEX_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Slowest accepted `ReplaySpeed::Scaled` factor
+pub const MIN_REPLAY_SCALE: f64 = 1.0e-3;
+/// Fastest accepted `ReplaySpeed::Scaled` factor
+pub const MAX_REPLAY_SCALE: f64 = 1.0e6;
+
+/// Applies `$m` to the path of every `f32` field of a snapshot
+macro_rules! float_fields {
+    ($m:ident) => {
+        $m!(
+            motion.speed, motion.acceleration, motion.lateral_g, motion.longitudinal_g,
+            motion.vertical_g, motion.yaw_rate, motion.pitch, motion.roll,
+            tires.front_left.surface_temp, tires.front_left.inner_temp, tires.front_left.brake_temp,
+            tires.front_left.pressure, tires.front_left.wear, tires.front_left.damage,
+            tires.front_right.surface_temp, tires.front_right.inner_temp, tires.front_right.brake_temp,
+            tires.front_right.pressure, tires.front_right.wear, tires.front_right.damage,
+            tires.rear_left.surface_temp, tires.rear_left.inner_temp, tires.rear_left.brake_temp,
+            tires.rear_left.pressure, tires.rear_left.wear, tires.rear_left.damage,
+            tires.rear_right.surface_temp, tires.rear_right.inner_temp, tires.rear_right.brake_temp,
+            tires.rear_right.pressure, tires.rear_right.wear, tires.rear_right.damage,
+            power_unit.throttle, power_unit.ers_battery, power_unit.mgu_k_deployment,
+            power_unit.mgu_h_recovery, power_unit.engine_temp, power_unit.oil_temp,
+            power_unit.oil_pressure,
+            aero.front_wing_angle, aero.rear_wing_angle, aero.downforce, aero.drag_coefficient,
+            brakes.bias, brakes.pressure, brakes.front_temp, brakes.rear_temp,
+            inputs.steering, inputs.throttle, inputs.brake, inputs.clutch,
+            fuel.remaining, fuel.consumption_rate, fuel.temperature, fuel.pressure
+        )
+    };
+}
+
+macro_rules! count_fields {
+    ($($($field:ident).+),*) => { [$(stringify!($($field).+)),*].len() };
+}
+
+const FLOAT_COLUMNS: usize = float_fields!(count_fields);
+
+fn float_values(snapshot: &TelemetrySnapshot) -> [f32; FLOAT_COLUMNS] {
+    macro_rules! get {
+        ($($($field:ident).+),*) => { [$(snapshot.$($field).+),*] };
+    }
+    float_fields!(get)
+}
+
+fn set_float_values(snapshot: &mut TelemetrySnapshot, values: &[f32]) {
+    let mut values = values.iter().copied();
+    macro_rules! set {
+        ($($($field:ident).+),*) => {{ $(snapshot.$($field).+ = values.next().unwrap_or_default();)* }};
+    }
+    float_fields!(set);
+}
+
+/// Integer and enum fields, widened to `i64`
+const INT_COLUMNS: usize = 9;
+
+/// `TelemetryRecorder::record` rejects timestamps outside the nanosecond range
+fn int_values(snapshot: &TelemetrySnapshot) -> [i64; INT_COLUMNS] {
+    [
+        snapshot.timestamp.timestamp_nanos_opt().unwrap_or_default(),
+        snapshot.lap.0 as i64,
+        snapshot.position.0 as i64,
+        snapshot.tires.age_laps as i64,
+        snapshot.power_unit.rpm as i64,
+        snapshot.inputs.gear as i64,
+        snapshot.tires.compound as i64,
+        snapshot.power_unit.ers_mode as i64,
+        snapshot.drs as i64,
+    ]
+}
+
+fn snapshot_from_columns(
+    session_id: SessionId,
+    car_id: CarId,
+    ints: &[i64],
+    floats: &[f32],
+) -> Result<TelemetrySnapshot, RecordingError> {
+    const COMPOUNDS: [TireCompound; 8] = [
+        TireCompound::C0,
+        TireCompound::C1,
+        TireCompound::C2,
+        TireCompound::C3,
+        TireCompound::C4,
+        TireCompound::C5,
+        TireCompound::Intermediate,
+        TireCompound::Wet,
+    ];
+    const ERS_MODES: [ErsMode; 6] = [
+        ErsMode::None,
+        ErsMode::Low,
+        ErsMode::Medium,
+        ErsMode::High,
+        ErsMode::Hotlap,
+        ErsMode::Overtake,
+    ];
+    const DRS: [DrsStatus; 3] = [DrsStatus::Unavailable, DrsStatus::Available, DrsStatus::Activated];
+
+    let code = |table_len: usize, value: i64, name: &str| {
+        usize::try_from(value)
+            .ok()
+            .filter(|index| *index < table_len)
+            .ok_or_else(|| RecordingError::Format(format!("invalid {} code {}", name, value)))
+    };
+    let timestamp = Utc.timestamp_nanos(ints[0]);
+    let sensor = || TireSensor {
+        surface_temp: 0.0,
+        inner_temp: 0.0,
+        brake_temp: 0.0,
+        pressure: 0.0,
+        wear: 0.0,
+        damage: 0.0,
+    };
+
+    let mut snapshot = TelemetrySnapshot {
+        session_id,
+        car_id,
+        timestamp,
+        lap: LapNumber(ints[1] as u16),
+        position: Position(ints[2] as u8),
+        motion: MotionData {
+            speed: 0.0,
+            acceleration: 0.0,
+            lateral_g: 0.0,
+            longitudinal_g: 0.0,
+            vertical_g: 0.0,
+            yaw_rate: 0.0,
+            pitch: 0.0,
+            roll: 0.0,
+        },
+        tires: TireData {
+            front_left: sensor(),
+            front_right: sensor(),
+            rear_left: sensor(),
+            rear_right: sensor(),
+            compound: COMPOUNDS[code(COMPOUNDS.len(), ints[6], "compound")?],
+            age_laps: ints[3] as u16,
+        },
+        power_unit: PowerUnitData {
+            rpm: ints[4] as u16,
+            throttle: 0.0,
+            ers_mode: ERS_MODES[code(ERS_MODES.len(), ints[7], "ERS mode")?],
+            ers_battery: 0.0,
+            mgu_k_deployment: 0.0,
+            mgu_h_recovery: 0.0,
+            engine_temp: 0.0,
+            oil_temp: 0.0,
+            oil_pressure: 0.0,
+        },
+        aero: AeroData {
+            front_wing_angle: 0.0,
+            rear_wing_angle: 0.0,
+            downforce: 0.0,
+            drag_coefficient: 0.0,
+        },
+        brakes: BrakeData {
+            bias: 0.0,
+            pressure: 0.0,
+            front_temp: 0.0,
+            rear_temp: 0.0,
+        },
+        inputs: DriverInputs {
+            steering: 0.0,
+            throttle: 0.0,
+            brake: 0.0,
+            clutch: 0.0,
+            gear: ints[5] as i8,
+        },
+        fuel: FuelData {
+            remaining: 0.0,
+            consumption_rate: 0.0,
+            temperature: 0.0,
+            pressure: 0.0,
+        },
+        drs: DRS[code(DRS.len(), ints[8], "DRS")?],
+    };
+    set_float_values(&mut snapshot, floats);
+    Ok(snapshot)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(input: &mut &[u8]) -> Result<u64, RecordingError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = input
+            .split_first()
+            .ok_or_else(|| RecordingError::Format("truncated varint".to_string()))?;
+        *input = rest;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(RecordingError::Format("varint too long".to_string()))
+}
+
+/// Largest decoded chunk of `samples` rows: a row count, worst-case varints
+/// and raw floats
+fn max_chunk_bytes(samples: u32) -> u64 {
+    10 + samples as u64 * (INT_COLUMNS * 10 + FLOAT_COLUMNS * 4) as u64
+}
+
+/// Decompress `data`, failing once the output exceeds `limit` bytes
+fn decompress(data: &[u8], limit: u64) -> Result<Vec<u8>, RecordingError> {
+    let mut out = Vec::new();
+    zstd::stream::read::Decoder::new(data)?.take(limit + 1).read_to_end(&mut out)?;
+    if out.len() as u64 > limit {
+        return Err(RecordingError::Format(format!("data decompresses to more than {} bytes", limit)));
+    }
+    Ok(out)
+}
+
+/// Encode a chunk of one car's snapshots column by column
+fn encode_chunk(snapshots: &[TelemetrySnapshot]) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_varint(&mut out, snapshots.len() as u64);
+
+    let ints: Vec<[i64; INT_COLUMNS]> = snapshots.iter().map(int_values).collect();
+    for column in 0..INT_COLUMNS {
+        let mut previous = 0i64;
+        for row in &ints {
+            let delta = row[column].wrapping_sub(previous);
+            write_varint(&mut out, ((delta << 1) ^ (delta >> 63)) as u64);
+            previous = row[column];
+        }
+    }
+
+    let floats: Vec<[f32; FLOAT_COLUMNS]> = snapshots.iter().map(float_values).collect();
+    for column in 0..FLOAT_COLUMNS {
+        let mut previous = 0u32;
+        for row in &floats {
+            let bits = row[column].to_bits();
+            out.extend_from_slice(&(bits ^ previous).to_le_bytes());
+            previous = bits;
+        }
+    }
+    out
+}
+
+fn decode_chunk(
+    session_id: SessionId,
+    car_id: CarId,
+    mut input: &[u8],
+) -> Result<Vec<TelemetrySnapshot>, RecordingError> {
+    let rows = read_varint(&mut input)?;
+
+    // Every row needs at least one byte per integer column plus its floats
+    let count = usize::try_from(rows)
+        .ok()
+        .filter(|count| {
+            count
+                .checked_mul(INT_COLUMNS + FLOAT_COLUMNS * 4)
+                .is_some_and(|min_len| min_len <= input.len())
+        })
+        .ok_or_else(|| RecordingError::Format(format!("chunk too short for {} rows", rows)))?;
+
+    let mut ints = vec![[0i64; INT_COLUMNS]; count];
+    for column in 0..INT_COLUMNS {
+        let mut previous = 0i64;
+        for row in ints.iter_mut() {
+            let zigzag = read_varint(&mut input)?;
+            let delta = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+            previous = previous.wrapping_add(delta);
+            row[column] = previous;
+        }
+    }
+
+    if input.len() != count * FLOAT_COLUMNS * 4 {
+        return Err(RecordingError::Format("float columns have the wrong length".to_string()));
+    }
+    let mut floats = vec![[0f32; FLOAT_COLUMNS]; count];
+    for column in 0..FLOAT_COLUMNS {
+        let mut previous = 0u32;
+        for row in floats.iter_mut() {
+            let (bytes, rest) = input.split_at(4);
+            input = rest;
+            previous ^= u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            row[column] = f32::from_bits(previous);
+        }
+    }
+
+    ints.iter()
+        .zip(&floats)
+        .map(|(ints, floats)| snapshot_from_columns(session_id, car_id, ints, floats))
+        .collect()
+}
+
+/// Index entry for one chunk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkIndex {
+    pub session_id: SessionId,
+    pub car_id: CarId,
+    pub first_lap: LapNumber,
+    pub last_lap: LapNumber,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub samples: u32,
+
+    /// File offset of the chunk's length prefix
+    pub offset: u64,
+
+    /// Compressed length in bytes
+    pub length: u32,
+}
+
+/// Index of a recording
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecordingIndex {
+    pub chunks: Vec<ChunkIndex>,
+}
+
+impl RecordingIndex {
+    /// Total number of recorded snapshots
+    pub fn samples(&self) -> u64 {
+        self.chunks.iter().map(|chunk| chunk.samples as u64).sum()
+    }
+
+    /// Cars present in the recording
+    pub fn cars(&self) -> Vec<(SessionId, CarId)> {
+        let mut cars: Vec<(SessionId, CarId)> = Vec::new();
+        for chunk in &self.chunks {
+            if !cars.contains(&(chunk.session_id, chunk.car_id)) {
+                cars.push((chunk.session_id, chunk.car_id));
+            }
+        }
+        cars
+    }
+
+    /// First and last timestamps
+    pub fn time_range(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let start = self.chunks.iter().map(|chunk| chunk.start).min()?;
+        let end = self.chunks.iter().map(|chunk| chunk.end).max()?;
+        Some((start, end))
+    }
+}
+
+/// Recorder configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecorderConfig {
+    /// Snapshots per car per chunk
+    pub chunk_size: usize,
+
+    /// zstd compression level
+    pub compression_level: i32,
+}
+
+impl Default for RecorderConfig {
+    fn default() -> Self {
+        RecorderConfig {
+            chunk_size: 1_000,
+            compression_level: 3,
+        }
+    }
+}
+
+/// Writes telemetry to a recording file
+pub struct TelemetryRecorder {
+    config: RecorderConfig,
+    writer: BufWriter<File>,
+    offset: u64,
+    pending: HashMap<(SessionId, CarId), Vec<TelemetrySnapshot>>,
+    index: RecordingIndex,
+}
+
+impl TelemetryRecorder {
+    /// Create (or truncate) a recording at `path`
+    pub fn create(path: impl AsRef<Path>, config: RecorderConfig) -> Result<Self, RecordingError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(FILE_MAGIC)?;
+        Ok(TelemetryRecorder {
+            config,
+            writer,
+            offset: FILE_MAGIC.len() as u64,
+            pending: HashMap::new(),
+            index: RecordingIndex::default(),
+        })
+    }
+
+    /// Record a snapshot
+    ///
+    /// Timestamps are stored as nanoseconds since the epoch, so snapshots
+    /// outside 1677-2262 are rejected.
+    pub fn record(&mut self, snapshot: &TelemetrySnapshot) -> Result<(), RecordingError> {
+        if snapshot.timestamp.timestamp_nanos_opt().is_none() {
+            return Err(RecordingError::Format(format!(
+                "timestamp {} is outside the recordable range",
+                snapshot.timestamp
+            )));
+        }
+        let key = (snapshot.session_id, snapshot.car_id);
+        let pending = self.pending.entry(key).or_default();
+        pending.push(snapshot.clone());
+        if pending.len() >= self.config.chunk_size.max(1) {
+            let chunk = std::mem::take(pending);
+            self.write_chunk(chunk)?;
+        }
+        Ok(())
+    }
+
+    /// Record snapshots from an engine's event stream until the stream
+    /// ends or the engine is dropped, then finish the recording
+    ///
+    /// Subscribe with `SubscriberConfig::lossless` so nothing is missed;
+    /// gaps from lossy subscriptions are logged.
+    pub async fn record_events(
+        mut self,
+        mut events: DeliveryReceiver<TelemetryEvent>,
+    ) -> Result<RecordingIndex, RecordingError> {
+        while let Some(delivery) = events.recv().await {
+            match delivery {
+                Delivery::Message(TelemetryEvent::Snapshot(snapshot)) => self.record(&snapshot)?,
+                Delivery::Message(TelemetryEvent::StreamEnd { .. }) => break,
+                Delivery::Message(_) => {}
+                Delivery::Gap { missed } => {
+                    warn!("Recorder lagged, {} telemetry events were not recorded", missed);
+                }
+            }
+        }
+        self.finish()
+    }
+
+    /// Flush pending chunks and write the index
+    pub fn finish(mut self) -> Result<RecordingIndex, RecordingError> {
+        let mut pending: Vec<Vec<TelemetrySnapshot>> =
+            self.pending.drain().map(|(_, chunk)| chunk).filter(|chunk| !chunk.is_empty()).collect();
+        pending.sort_by_key(|chunk| chunk[0].timestamp);
+        for chunk in pending {
+            self.write_chunk(chunk)?;
+        }
+
+        let index_offset = self.offset;
+        let json = serde_json::to_vec(&self.index).map_err(|e| RecordingError::Format(e.to_string()))?;
+        let compressed = zstd::encode_all(json.as_slice(), self.config.compression_level)?;
+        self.writer.write_all(&compressed)?;
+        self.writer.write_all(&index_offset.to_le_bytes())?;
+        self.writer.write_all(INDEX_MAGIC)?;
+        self.writer.flush()?;
+        Ok(self.index)
+    }
+
+    fn write_chunk(&mut self, mut chunk: Vec<TelemetrySnapshot>) -> Result<(), RecordingError> {
+        chunk.sort_by_key(|snapshot| snapshot.timestamp);
+        let (first, last) = (&chunk[0], &chunk[chunk.len() - 1]);
+        let compressed = zstd::encode_all(encode_chunk(&chunk).as_slice(), self.config.compression_level)?;
+        let length = u32::try_from(compressed.len())
+            .map_err(|_| RecordingError::Format("chunk too large".to_string()))?;
+
+        self.index.chunks.push(ChunkIndex {
+            session_id: first.session_id,
+            car_id: first.car_id,
+            first_lap: chunk.iter().map(|s| s.lap).min().unwrap_or(first.lap),
+            last_lap: chunk.iter().map(|s| s.lap).max().unwrap_or(last.lap),
+            start: first.timestamp,
+            end: last.timestamp,
+            samples: chunk.len() as u32,
+            offset: self.offset,
+            length,
+        });
+
+        self.writer.write_all(&length.to_le_bytes())?;
+        self.writer.write_all(&compressed)?;
+        self.offset += 4 + compressed.len() as u64;
+        Ok(())
+    }
+}
+
+/// Selection of recorded snapshots
+#[derive(Debug, Clone, Default)]
+pub struct RecordingQuery {
+    pub session_id: Option<SessionId>,
+    pub car_ids: Option<Vec<CarId>>,
+    pub laps: Option<RangeInclusive<u16>>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl RecordingQuery {
+    fn matches_chunk(&self, chunk: &ChunkIndex) -> bool {
+        self.session_id.map_or(true, |session| session == chunk.session_id)
+            && self.car_ids.as_ref().map_or(true, |cars| cars.contains(&chunk.car_id))
+            && self
+                .laps
+                .as_ref()
+                .map_or(true, |laps| chunk.first_lap.0 <= *laps.end() && chunk.last_lap.0 >= *laps.start())
+            && self.from.map_or(true, |from| chunk.end >= from)
+            && self.to.map_or(true, |to| chunk.start <= to)
+    }
+
+    fn matches(&self, snapshot: &TelemetrySnapshot) -> bool {
+        self.laps.as_ref().map_or(true, |laps| laps.contains(&snapshot.lap.0))
+            && self.from.map_or(true, |from| snapshot.timestamp >= from)
+            && self.to.map_or(true, |to| snapshot.timestamp <= to)
+    }
+}
+
+/// Reads a recording through its index
+pub struct RecordingReader {
+    file: File,
+    index: RecordingIndex,
+    /// Start of the index; chunks must end before it
+    data_end: u64,
+}
+
+impl RecordingReader {
+    /// Open a recording and load its index
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, RecordingError> {
+        let mut file = File::open(path)?;
+        let mut magic = [0u8; 8];
+        file.read_exact(&mut magic)?;
+        if &magic != FILE_MAGIC {
+            return Err(RecordingError::Format("not a telemetry recording".to_string()));
+        }
+
+        let end = file.seek(SeekFrom::End(-16))?;
+        let mut trailer = [0u8; 16];
+        file.read_exact(&mut trailer)?;
+        if &trailer[8..] != INDEX_MAGIC {
+            return Err(RecordingError::Format("missing index; recording was not finished".to_string()));
+        }
+        let index_offset = u64::from_le_bytes(trailer[..8].try_into().expect("8 bytes"));
+        if index_offset > end {
+            return Err(RecordingError::Format("index offset out of range".to_string()));
+        }
+
+        file.seek(SeekFrom::Start(index_offset))?;
+        let mut compressed = vec![0u8; (end - index_offset) as usize];
+        file.read_exact(&mut compressed)?;
+        let json = decompress(&compressed, MAX_INDEX_BYTES)?;
+        let index = serde_json::from_slice(&json).map_err(|e| RecordingError::Format(e.to_string()))?;
+
+        Ok(RecordingReader {
+            file,
+            index,
+            data_end: index_offset,
+        })
+    }
+
+    /// Recording index
+    pub fn index(&self) -> &RecordingIndex {
+        &self.index
+    }
+
+    /// Decode one chunk
+    pub fn read_chunk(&mut self, chunk: &ChunkIndex) -> Result<Vec<TelemetrySnapshot>, RecordingError> {
+        let in_bounds = chunk.offset >= FILE_MAGIC.len() as u64
+            && chunk
+                .offset
+                .checked_add(4 + chunk.length as u64)
+                .is_some_and(|chunk_end| chunk_end <= self.data_end);
+        if !in_bounds {
+            return Err(RecordingError::Format(format!("chunk at {} lies outside the data", chunk.offset)));
+        }
+
+        self.file.seek(SeekFrom::Start(chunk.offset))?;
+        let mut length = [0u8; 4];
+        self.file.read_exact(&mut length)?;
+        if u32::from_le_bytes(length) != chunk.length {
+            return Err(RecordingError::Format(format!("chunk at {} does not match the index", chunk.offset)));
+        }
+        let mut compressed = vec![0u8; chunk.length as usize];
+        self.file.read_exact(&mut compressed)?;
+        let limit = max_chunk_bytes(chunk.samples);
+        decode_chunk(chunk.session_id, chunk.car_id, &decompress(&compressed, limit)?)
+    }
+
+    /// Snapshots matching `query`, in timestamp order
+    pub fn query(&mut self, query: &RecordingQuery) -> Result<Vec<TelemetrySnapshot>, RecordingError> {
+        let chunks: Vec<ChunkIndex> =
+            self.index.chunks.iter().filter(|chunk| query.matches_chunk(chunk)).cloned().collect();
+        let mut snapshots = Vec::new();
+        for chunk in &chunks {
+            snapshots.extend(self.read_chunk(chunk)?.into_iter().filter(|s| query.matches(s)));
+        }
+        snapshots.sort_by_key(|s| s.timestamp);
+        Ok(snapshots)
+    }
+
+    /// Every snapshot, in timestamp order
+    pub fn read_all(&mut self) -> Result<Vec<TelemetrySnapshot>, RecordingError> {
+        self.query(&RecordingQuery::default())
+    }
+}
+
+/// Replay pacing
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ReplaySpeed {
+    /// Original timing
+    RealTime,
+
+    /// Original timing sped up (or slowed down) by a factor
+    Scaled(f64),
+
+    /// No delays between snapshots
+    Unpaced,
+}
+
+impl ReplaySpeed {
+    /// Pacing sped up by `factor`; `None` unless it lies within
+    /// `MIN_REPLAY_SCALE..=MAX_REPLAY_SCALE`
+    pub fn scaled(factor: f64) -> Option<Self> {
+        (MIN_REPLAY_SCALE..=MAX_REPLAY_SCALE)
+            .contains(&factor)
+            .then_some(ReplaySpeed::Scaled(factor))
+    }
+
+    /// Wall-clock delay at which data `offset` into the replay is due, or
+    /// `None` when unpaced
+    ///
+    /// `Scaled` factors outside `MIN_REPLAY_SCALE..=MAX_REPLAY_SCALE` replay
+    /// unpaced, as do delays too long to represent.
+    pub fn wall_delay(&self, offset: Duration) -> Option<Duration> {
+        let scale = match *self {
+            ReplaySpeed::RealTime => 1.0,
+            ReplaySpeed::Scaled(factor) if (MIN_REPLAY_SCALE..=MAX_REPLAY_SCALE).contains(&factor) => factor,
+            ReplaySpeed::Scaled(_) | ReplaySpeed::Unpaced => return None,
+        };
+        Duration::try_from_secs_f64(offset.as_secs_f64() / scale).ok()
+    }
+}
+
+/// Replays recorded snapshots
+pub struct ReplayPlayer {
+    snapshots: Vec<TelemetrySnapshot>,
+    position: usize,
+    speed: ReplaySpeed,
+}
+
+impl ReplayPlayer {
+    /// Player over snapshots (sorted by timestamp)
+    pub fn new(mut snapshots: Vec<TelemetrySnapshot>, speed: ReplaySpeed) -> Self {
+        snapshots.sort_by_key(|s| s.timestamp);
+        ReplayPlayer {
+            snapshots,
+            position: 0,
+            speed,
+        }
+    }
+
+    /// Player over the snapshots of a recording matching `query`
+    pub fn from_recording(
+        reader: &mut RecordingReader,
+        query: &RecordingQuery,
+        speed: ReplaySpeed,
+    ) -> Result<Self, RecordingError> {
+        Ok(Self::new(reader.query(query)?, speed))
+    }
+
+    /// Change the pacing
+    pub fn set_speed(&mut self, speed: ReplaySpeed) {
+        self.speed = speed;
+    }
+
+    /// Index of the next snapshot
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Snapshots not yet played
+    pub fn remaining(&self) -> usize {
+        self.snapshots.len() - self.position
+    }
+
+    /// Timestamp of the next snapshot
+    pub fn current_time(&self) -> Option<DateTime<Utc>> {
+        self.snapshots.get(self.position).map(|s| s.timestamp)
+    }
+
+    /// Move to the first snapshot at or after `timestamp`
+    pub fn seek(&mut self, timestamp: DateTime<Utc>) {
+        self.position = self.snapshots.partition_point(|s| s.timestamp < timestamp);
+    }
+
+    /// Restart from the beginning
+    pub fn rewind(&mut self) {
+        self.position = 0;
+    }
+
+    /// Return the next snapshot without pacing
+    pub fn step(&mut self) -> Option<TelemetrySnapshot> {
+        let snapshot = self.snapshots.get(self.position)?.clone();
+        self.position += 1;
+        Some(snapshot)
+    }
+
+    /// Return all snapshots within the next `duration` of recorded time
+    pub fn step_by(&mut self, duration: Duration) -> Vec<TelemetrySnapshot> {
+        let Some(start) = self.current_time() else {
+            return Vec::new();
+        };
+        let end = chrono::Duration::from_std(duration)
+            .ok()
+            .and_then(|duration| start.checked_add_signed(duration));
+        let stop = match end {
+            Some(end) => self.snapshots.partition_point(|s| s.timestamp < end).max(self.position),
+            None => self.snapshots.len(),
+        };
+        let stepped = self.snapshots[self.position..stop].to_vec();
+        self.position = stop;
+        stepped
+    }
+
+    /// Play the remaining snapshots into `deliver`, paced by the replay
+    /// speed; returns the number delivered
+    ///
+    /// For example `player.play(|s| engine.process(s))` or
+    /// `player.play(|s| async { let _ = server.broadcast_telemetry(s); })`.
+    pub async fn play<F, Fut>(&mut self, mut deliver: F) -> usize
+    where
+        F: FnMut(TelemetrySnapshot) -> Fut,
+        Fut: Future,
+    {
+        let Some(data_start) = self.current_time() else {
+            return 0;
+        };
+        let wall_start = tokio::time::Instant::now();
+        let mut delivered = 0;
+
+        while let Some(snapshot) = self.snapshots.get(self.position).cloned() {
+            let offset = (snapshot.timestamp - data_start).to_std().unwrap_or_default();
+            if let Some(deadline) = self.speed.wall_delay(offset).and_then(|delay| wall_start.checked_add(delay)) {
+                tokio::time::sleep_until(deadline).await;
+            }
+
+            self.position += 1;
+            deliver(snapshot).await;
+            delivered += 1;
+        }
+        delivered
+    }
+}
+
+/// Recording errors
+#[derive(Debug, thiserror::Error)]
+pub enum RecordingError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid recording: {0}")]
+    Format(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::tests::create_test_snapshot;
+
+    /// Two cars, two laps of 5 s each at 50 Hz
+    fn session() -> Vec<TelemetrySnapshot> {
+        let base = create_test_snapshot();
+        let mut snapshots = Vec::new();
+        for car in [1, 16] {
+            for i in 0..500 {
+                let mut snapshot = base.clone();
+                snapshot.car_id = CarId::new(car).unwrap();
+                snapshot.timestamp = base.timestamp + chrono::Duration::milliseconds(i * 20);
+                snapshot.lap = LapNumber(1 + (i / 250) as u16);
+                snapshot.motion.speed = 200.0 + 80.0 * (i as f32 * 0.05).sin();
+                snapshot.inputs.gear = (3 + i % 5) as i8;
+                snapshot.drs = if i % 100 < 30 { DrsStatus::Activated } else { DrsStatus::Available };
+                snapshots.push(snapshot);
+            }
+        }
+        snapshots
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("f1-nexus-{}-{}.rec", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_round_trip_and_index() {
+        let path = temp_path("round-trip");
+        let snapshots = session();
+        let mut recorder = TelemetryRecorder::create(&path, RecorderConfig { chunk_size: 100, ..Default::default() }).unwrap();
+        for snapshot in &snapshots {
+            recorder.record(snapshot).unwrap();
+        }
+        let index = recorder.finish().unwrap();
+        assert_eq!(index.samples(), 1000);
+        assert_eq!(index.chunks.len(), 10);
+        assert_eq!(index.cars().len(), 2);
+
+        // Columnar delta encoding beats plain JSON by a wide margin
+        let size = std::fs::metadata(&path).unwrap().len();
+        let json = serde_json::to_vec(&snapshots).unwrap().len() as u64;
+        assert!(size * 20 < json, "recording {} bytes vs JSON {} bytes", size, json);
+
+        let mut reader = RecordingReader::open(&path).unwrap();
+        let restored = reader.read_all().unwrap();
+        assert_eq!(restored.len(), snapshots.len());
+        let original = snapshots.iter().find(|s| s.car_id.0 == 16 && s.lap.0 == 2).unwrap();
+        let copy = restored
+            .iter()
+            .find(|s| s.car_id.0 == 16 && s.timestamp == original.timestamp)
+            .unwrap();
+        assert_eq!(serde_json::to_value(copy).unwrap(), serde_json::to_value(original).unwrap());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_decode_rejects_oversized_row_count() {
+        let session_id = SessionId::new();
+        let car_id = CarId::new(1).unwrap();
+
+        for rows in [u64::MAX, 1 << 40, 3] {
+            let mut chunk = Vec::new();
+            write_varint(&mut chunk, rows);
+            chunk.extend_from_slice(&[0u8; 16]);
+            assert!(matches!(
+                decode_chunk(session_id, car_id, &chunk),
+                Err(RecordingError::Format(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_query_by_car_lap_and_time() {
+        let path = temp_path("query");
+        let snapshots = session();
+        let mut recorder = TelemetryRecorder::create(&path, RecorderConfig::default()).unwrap();
+        for snapshot in &snapshots {
+            recorder.record(snapshot).unwrap();
+        }
+        recorder.finish().unwrap();
+
+        let mut reader = RecordingReader::open(&path).unwrap();
+        let lap_two = reader
+            .query(&RecordingQuery {
+                car_ids: Some(vec![CarId::new(1).unwrap()]),
+                laps: Some(2..=2),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(lap_two.len(), 250);
+        assert!(lap_two.iter().all(|s| s.car_id.0 == 1 && s.lap.0 == 2));
+
+        let start = snapshots[0].timestamp;
+        let first_second = reader
+            .query(&RecordingQuery {
+                to: Some(start + chrono::Duration::milliseconds(999)),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(first_second.len(), 100);
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(RecordingReader::open(&path).is_err());
+    }
+
+    #[test]
+    fn test_player_stepping() {
+        let mut player = ReplayPlayer::new(session(), ReplaySpeed::Unpaced);
+        assert_eq!(player.remaining(), 1000);
+
+        let first = player.step().unwrap();
+        assert_eq!(player.step().unwrap().timestamp, first.timestamp);
+
+        // 100 ms of recorded time covers 5 samples per car
+        let stepped = player.step_by(Duration::from_millis(100));
+        assert_eq!(stepped.len(), 10);
+
+        player.seek(first.timestamp + chrono::Duration::seconds(9));
+        assert_eq!(player.remaining(), 100);
+        player.rewind();
+        assert_eq!(player.position(), 0);
+
+        // A step past the representable time range drains the player
+        assert_eq!(player.step_by(Duration::MAX).len(), 1000);
+        assert_eq!(player.remaining(), 0);
+    }
+
+    #[test]
+    fn test_replay_speed_bounds() {
+        assert!(ReplaySpeed::scaled(0.0).is_none());
+        assert!(ReplaySpeed::scaled(f64::MIN_POSITIVE).is_none());
+        assert!(ReplaySpeed::scaled(f64::NAN).is_none());
+        assert!(ReplaySpeed::scaled(f64::INFINITY).is_none());
+
+        let offset = Duration::from_secs(10);
+        assert_eq!(ReplaySpeed::scaled(2.0).unwrap().wall_delay(offset), Some(Duration::from_secs(5)));
+        assert_eq!(ReplaySpeed::RealTime.wall_delay(offset), Some(offset));
+        assert_eq!(ReplaySpeed::Scaled(1e-300).wall_delay(offset), None);
+        assert_eq!(ReplaySpeed::scaled(MIN_REPLAY_SCALE).unwrap().wall_delay(Duration::MAX), None);
+    }
+
+    #[test]
+    fn test_reader_rejects_corrupt_chunks() {
+        let path = temp_path("corrupt");
+        let mut recorder = TelemetryRecorder::create(&path, RecorderConfig::default()).unwrap();
+        let mut future = create_test_snapshot();
+        future.timestamp = DateTime::<Utc>::MAX_UTC;
+        assert!(matches!(recorder.record(&future), Err(RecordingError::Format(_))));
+        for snapshot in &session()[..10] {
+            recorder.record(snapshot).unwrap();
+        }
+        let index = recorder.finish().unwrap();
+
+        let mut reader = RecordingReader::open(&path).unwrap();
+        let mut chunk = index.chunks[0].clone();
+        chunk.length = u32::MAX;
+        assert!(matches!(reader.read_chunk(&chunk), Err(RecordingError::Format(_))));
+        chunk = index.chunks[0].clone();
+        chunk.offset = u64::MAX - 2;
+        assert!(matches!(reader.read_chunk(&chunk), Err(RecordingError::Format(_))));
+
+        // A chunk claiming fewer rows than it holds decompresses past its bound
+        chunk = index.chunks[0].clone();
+        chunk.samples = 0;
+        assert!(matches!(reader.read_chunk(&chunk), Err(RecordingError::Format(_))));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_player_pacing() {
+        // 1 s of recorded data at 50x plays in about 20 ms
+        let snapshots: Vec<TelemetrySnapshot> = session().into_iter().filter(|s| s.car_id.0 == 1).take(51).collect();
+        let mut player = ReplayPlayer::new(snapshots, ReplaySpeed::scaled(50.0).unwrap());
+        let started = std::time::Instant::now();
+        let mut received = Vec::new();
+        let delivered = player.play(|s| {
+            received.push(s.timestamp);
+            async {}
+        }).await;
+
+        assert_eq!(delivered, 51);
+        assert!(received.windows(2).all(|w| w[0] <= w[1]));
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(19), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(500), "{:?}", elapsed);
+    }
+
+    #[tokio::test]
+    async fn test_replay_into_engine() {
+        use crate::{TelemetryConfig, TelemetryEngine};
+
+        let engine = TelemetryEngine::new(TelemetryConfig::default());
+        let mut player = ReplayPlayer::new(session(), ReplaySpeed::Unpaced);
+        player.play(|s| engine.process(s)).await;
+
+        let stats = engine.stats();
+        assert_eq!(stats.total_processed, 1000);
+        assert_eq!(engine.buffer().cars().len(), 2);
+    }
+}
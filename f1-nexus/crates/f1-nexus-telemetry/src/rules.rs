@@ -0,0 +1,1246 @@
+//! Declarative alert rules
+//!
+//! Alert definitions are loaded from YAML or TOML rule packs, so race
+//! engineers can tune thresholds without recompiling. A rule combines a
+//! condition over telemetry channels (thresholds, ranges, rate of change,
+//! cross-channel comparisons and z-score deviations from a rolling baseline)
+//! with debounce (`for_ms`), hysteresis (`clear`) and re-alert (`repeat_ms`)
+//! timing, and maps to an `AnomalyType` and `Severity` with a recommended
+//! action.
+//!
+//! Rule state and baselines are kept per car and session (and optionally per
+//! track segment). Timing uses snapshot timestamps, so replays behave like
+//! live sessions.
+
+use crate::{AnomalyAlert, AnomalyType, RollingStats, Severity, TelemetryBuffer, TelemetryConfig, TelemetryError};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use f1_nexus_core::{CarId, SessionId, TelemetrySnapshot};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Longest filter expression `Condition::parse` accepts (bytes)
+pub const MAX_EXPRESSION_LENGTH: usize = 4096;
+
+/// Deepest nesting of `!`/`not` and parentheses `Condition::parse` accepts
+pub const MAX_EXPRESSION_DEPTH: usize = 64;
+
+/// Telemetry value a rule condition can read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    Speed,
+    Acceleration,
+    LateralG,
+    LongitudinalG,
+    YawRate,
+    Rpm,
+    Throttle,
+    Brake,
+    /// Smaller of throttle and brake (both pedals pressed)
+    PedalOverlap,
+    Steering,
+    ErsBattery,
+    EngineTemp,
+    OilTemp,
+    OilPressure,
+    TireTempFrontLeft,
+    TireTempFrontRight,
+    TireTempRearLeft,
+    TireTempRearRight,
+    TireTempMax,
+    TireTempMin,
+    TireTempAvg,
+    BrakeTempFrontLeft,
+    BrakeTempFrontRight,
+    BrakeTempRearLeft,
+    BrakeTempRearRight,
+    BrakeTempMax,
+    BrakeTempAvg,
+    TireWearMax,
+    TireDamageMax,
+    FuelRemaining,
+    /// Laps of fuel left at the current consumption rate
+    FuelLaps,
+}
+
+impl Channel {
+    /// Every channel, in declaration order
+    pub const ALL: [Channel; 31] = [
+        Channel::Speed,
+        Channel::Acceleration,
+        Channel::LateralG,
+        Channel::LongitudinalG,
+        Channel::YawRate,
+        Channel::Rpm,
+        Channel::Throttle,
+        Channel::Brake,
+        Channel::PedalOverlap,
+        Channel::Steering,
+        Channel::ErsBattery,
+        Channel::EngineTemp,
+        Channel::OilTemp,
+        Channel::OilPressure,
+        Channel::TireTempFrontLeft,
+        Channel::TireTempFrontRight,
+        Channel::TireTempRearLeft,
+        Channel::TireTempRearRight,
+        Channel::TireTempMax,
+        Channel::TireTempMin,
+        Channel::TireTempAvg,
+        Channel::BrakeTempFrontLeft,
+        Channel::BrakeTempFrontRight,
+        Channel::BrakeTempRearLeft,
+        Channel::BrakeTempRearRight,
+        Channel::BrakeTempMax,
+        Channel::BrakeTempAvg,
+        Channel::TireWearMax,
+        Channel::TireDamageMax,
+        Channel::FuelRemaining,
+        Channel::FuelLaps,
+    ];
+
+    /// Read the channel from a snapshot
+    pub fn value(&self, snapshot: &TelemetrySnapshot) -> f32 {
+        let tires = &snapshot.tires;
+        let wheels = [&tires.front_left, &tires.front_right, &tires.rear_left, &tires.rear_right];
+        let max = |f: fn(&f1_nexus_core::TireSensor) -> f32| wheels.iter().map(|w| f(w)).fold(f32::MIN, f32::max);
+        let min = |f: fn(&f1_nexus_core::TireSensor) -> f32| wheels.iter().map(|w| f(w)).fold(f32::MAX, f32::min);
+        let avg = |f: fn(&f1_nexus_core::TireSensor) -> f32| wheels.iter().map(|w| f(w)).sum::<f32>() / 4.0;
+
+        match self {
+            Channel::Speed => snapshot.motion.speed,
+            Channel::Acceleration => snapshot.motion.acceleration,
+            Channel::LateralG => snapshot.motion.lateral_g,
+            Channel::LongitudinalG => snapshot.motion.longitudinal_g,
+            Channel::YawRate => snapshot.motion.yaw_rate,
+            Channel::Rpm => snapshot.power_unit.rpm as f32,
+            Channel::Throttle => snapshot.inputs.throttle,
+            Channel::Brake => snapshot.inputs.brake,
+            Channel::PedalOverlap => snapshot.inputs.throttle.min(snapshot.inputs.brake),
+            Channel::Steering => snapshot.inputs.steering,
+            Channel::ErsBattery => snapshot.power_unit.ers_battery,
+            Channel::EngineTemp => snapshot.power_unit.engine_temp,
+            Channel::OilTemp => snapshot.power_unit.oil_temp,
+            Channel::OilPressure => snapshot.power_unit.oil_pressure,
+            Channel::TireTempFrontLeft => tires.front_left.surface_temp,
+            Channel::TireTempFrontRight => tires.front_right.surface_temp,
+            Channel::TireTempRearLeft => tires.rear_left.surface_temp,
+            Channel::TireTempRearRight => tires.rear_right.surface_temp,
+            Channel::TireTempMax => max(|w| w.surface_temp),
+            Channel::TireTempMin => min(|w| w.surface_temp),
+            Channel::TireTempAvg => avg(|w| w.surface_temp),
+            Channel::BrakeTempFrontLeft => tires.front_left.brake_temp,
+            Channel::BrakeTempFrontRight => tires.front_right.brake_temp,
+            Channel::BrakeTempRearLeft => tires.rear_left.brake_temp,
+            Channel::BrakeTempRearRight => tires.rear_right.brake_temp,
+            Channel::BrakeTempMax => max(|w| w.brake_temp),
+            Channel::BrakeTempAvg => avg(|w| w.brake_temp),
+            Channel::TireWearMax => max(|w| w.wear),
+            Channel::TireDamageMax => max(|w| w.damage),
+            Channel::FuelRemaining => snapshot.fuel.remaining,
+            Channel::FuelLaps => snapshot.estimated_fuel_laps(),
+        }
+    }
+
+    /// Channel name as used in rule files
+    pub fn name(&self) -> &'static str {
+        match self {
+            Channel::Speed => "speed",
+            Channel::Acceleration => "acceleration",
+            Channel::LateralG => "lateral_g",
+            Channel::LongitudinalG => "longitudinal_g",
+            Channel::YawRate => "yaw_rate",
+            Channel::Rpm => "rpm",
+            Channel::Throttle => "throttle",
+            Channel::Brake => "brake",
+            Channel::PedalOverlap => "pedal_overlap",
+            Channel::Steering => "steering",
+            Channel::ErsBattery => "ers_battery",
+            Channel::EngineTemp => "engine_temp",
+            Channel::OilTemp => "oil_temp",
+            Channel::OilPressure => "oil_pressure",
+            Channel::TireTempFrontLeft => "tire_temp_front_left",
+            Channel::TireTempFrontRight => "tire_temp_front_right",
+            Channel::TireTempRearLeft => "tire_temp_rear_left",
+            Channel::TireTempRearRight => "tire_temp_rear_right",
+            Channel::TireTempMax => "tire_temp_max",
+            Channel::TireTempMin => "tire_temp_min",
+            Channel::TireTempAvg => "tire_temp_avg",
+            Channel::BrakeTempFrontLeft => "brake_temp_front_left",
+            Channel::BrakeTempFrontRight => "brake_temp_front_right",
+            Channel::BrakeTempRearLeft => "brake_temp_rear_left",
+            Channel::BrakeTempRearRight => "brake_temp_rear_right",
+            Channel::BrakeTempMax => "brake_temp_max",
+            Channel::BrakeTempAvg => "brake_temp_avg",
+            Channel::TireWearMax => "tire_wear_max",
+            Channel::TireDamageMax => "tire_damage_max",
+            Channel::FuelRemaining => "fuel_remaining",
+            Channel::FuelLaps => "fuel_laps",
+        }
+    }
+
+    /// Channel with the given rule-file name
+    pub fn from_name(name: &str) -> Option<Channel> {
+        Channel::ALL.iter().copied().find(|channel| channel.name() == name)
+    }
+}
+
+/// Comparison operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Comparison {
+    #[serde(rename = ">")]
+    Gt,
+    #[serde(rename = ">=")]
+    Ge,
+    #[serde(rename = "<")]
+    Lt,
+    #[serde(rename = "<=")]
+    Le,
+}
+
+impl Comparison {
+    /// Apply the comparison
+    pub fn test(&self, left: f32, right: f32) -> bool {
+        match self {
+            Comparison::Gt => left > right,
+            Comparison::Ge => left >= right,
+            Comparison::Lt => left < right,
+            Comparison::Le => left <= right,
+        }
+    }
+
+    /// Range of values for which the comparison against `limit` is false
+    fn expected_range(&self, limit: f32) -> (f32, f32) {
+        match self {
+            Comparison::Gt | Comparison::Ge => (f32::MIN, limit),
+            Comparison::Lt | Comparison::Le => (limit, f32::MAX),
+        }
+    }
+}
+
+/// Condition over one or more channels
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    /// Channel compared with a fixed value
+    Threshold {
+        channel: Channel,
+        op: Comparison,
+        value: f32,
+    },
+
+    /// Channel outside `[min, max]`
+    Outside { channel: Channel, min: f32, max: f32 },
+
+    /// Change per second since the car's previous snapshot
+    RateOfChange {
+        channel: Channel,
+        op: Comparison,
+        per_second: f32,
+    },
+
+    /// Channel compared with another channel plus an offset
+    Compare {
+        channel: Channel,
+        op: Comparison,
+        other: Channel,
+        #[serde(default)]
+        offset: f32,
+    },
+
+    /// Channel more than `z_score` standard deviations from a rolling baseline
+    ///
+    /// The baseline is `baseline` (default: the channel itself) over the last
+    /// `window` samples (default from `TelemetryConfig::buffer_size`); it needs
+    /// half a window of data before it can fire.
+    Deviation {
+        channel: Channel,
+        #[serde(default)]
+        baseline: Option<Channel>,
+        z_score: f32,
+        #[serde(default)]
+        window: Option<usize>,
+    },
+
+    /// Every condition holds
+    All(Vec<Condition>),
+
+    /// At least one condition holds
+    Any(Vec<Condition>),
+
+    /// The condition does not hold
+    Not(Box<Condition>),
+}
+
+/// Channel reading that made a condition hold
+#[derive(Debug, Clone, PartialEq)]
+struct Evidence {
+    field: &'static str,
+    value: f32,
+    expected_range: (f32, f32),
+}
+
+/// Data a condition is evaluated against
+struct EvalContext<'a> {
+    snapshot: &'a TelemetrySnapshot,
+    previous: Option<&'a TelemetrySnapshot>,
+    baselines: &'a [(BaselineChannel, RollingStats)],
+    default_window: usize,
+}
+
+impl EvalContext<'_> {
+    fn baseline(&self, channel: Channel, window: Option<usize>) -> Option<&RollingStats> {
+        let key = BaselineChannel {
+            channel,
+            window: window.unwrap_or(self.default_window),
+        };
+        self.baselines.iter().find(|(k, _)| *k == key).map(|(_, stats)| stats)
+    }
+}
+
+impl Condition {
+    /// Whether the condition holds
+    fn is_met(&self, ctx: &EvalContext) -> bool {
+        match self {
+            Condition::All(conditions) => conditions.iter().all(|c| c.is_met(ctx)),
+            Condition::Any(conditions) => conditions.iter().any(|c| c.is_met(ctx)),
+            Condition::Not(condition) => !condition.is_met(ctx),
+            leaf => leaf.leaf_evidence(ctx).is_some(),
+        }
+    }
+
+    /// Reading to report for a condition that holds: the first channel
+    /// condition that is met, ignoring negated branches
+    fn evidence(&self, ctx: &EvalContext) -> Option<Evidence> {
+        match self {
+            Condition::All(conditions) | Condition::Any(conditions) => conditions
+                .iter()
+                .filter(|c| c.is_met(ctx))
+                .find_map(|c| c.evidence(ctx)),
+            Condition::Not(_) => None,
+            leaf => leaf.leaf_evidence(ctx),
+        }
+    }
+
+    fn leaf_evidence(&self, ctx: &EvalContext) -> Option<Evidence> {
+        let snapshot = ctx.snapshot;
+        match *self {
+            Condition::Threshold { channel, op, value } => {
+                let actual = channel.value(snapshot);
+                op.test(actual, value).then(|| Evidence {
+                    field: channel.name(),
+                    value: actual,
+                    expected_range: op.expected_range(value),
+                })
+            }
+            Condition::Outside { channel, min, max } => {
+                let actual = channel.value(snapshot);
+                (actual < min || actual > max).then(|| Evidence {
+                    field: channel.name(),
+                    value: actual,
+                    expected_range: (min, max),
+                })
+            }
+            Condition::RateOfChange { channel, op, per_second } => {
+                let previous = ctx.previous?;
+                let dt = (snapshot.timestamp - previous.timestamp).num_microseconds()? as f32 / 1e6;
+                if dt <= 0.0 {
+                    return None;
+                }
+                let rate = (channel.value(snapshot) - channel.value(previous)) / dt;
+                op.test(rate, per_second).then(|| Evidence {
+                    field: channel.name(),
+                    value: rate,
+                    expected_range: op.expected_range(per_second),
+                })
+            }
+            Condition::Compare { channel, op, other, offset } => {
+                let actual = channel.value(snapshot);
+                let limit = other.value(snapshot) + offset;
+                op.test(actual, limit).then(|| Evidence {
+                    field: channel.name(),
+                    value: actual,
+                    expected_range: op.expected_range(limit),
+                })
+            }
+            Condition::Deviation { channel, baseline, z_score, window } => {
+                let stats = ctx.baseline(baseline.unwrap_or(channel), window)?;
+                if stats.len() < stats.window_size() / 2 {
+                    return None;
+                }
+                let actual = channel.value(snapshot);
+                let (mean, spread) = (stats.mean(), z_score * stats.std_dev());
+                (stats.z_score(actual).abs() > z_score).then(|| Evidence {
+                    field: channel.name(),
+                    value: actual,
+                    expected_range: (mean - spread, mean + spread),
+                })
+            }
+            Condition::All(_) | Condition::Any(_) | Condition::Not(_) => None,
+        }
+    }
+
+    /// Rolling baselines referenced by this condition
+    fn baselines(&self, default_window: usize, out: &mut Vec<BaselineChannel>) {
+        match self {
+            Condition::Deviation { channel, baseline, window, .. } => {
+                let key = BaselineChannel {
+                    channel: baseline.unwrap_or(*channel),
+                    window: window.unwrap_or(default_window),
+                };
+                if !out.contains(&key) {
+                    out.push(key);
+                }
+            }
+            Condition::All(conditions) | Condition::Any(conditions) => {
+                conditions.iter().for_each(|c| c.baselines(default_window, out))
+            }
+            Condition::Not(condition) => condition.baselines(default_window, out),
+            _ => {}
+        }
+    }
+}
+
+impl Condition {
+    /// Parse a filter expression such as `speed > 300 && tire_temp_max >= 100`
+    ///
+    /// Channels are compared with `>`, `>=`, `<` or `<=` against a number or
+    /// another channel plus an optional offset (`brake_temp_max > tire_temp_max + 200`),
+    /// and combined with `&&`/`and`, `||`/`or`, `!`/`not` and parentheses.
+    /// Expressions come from clients, so their length and nesting are capped
+    /// at `MAX_EXPRESSION_LENGTH` and `MAX_EXPRESSION_DEPTH`.
+    pub fn parse(expression: &str) -> Result<Condition, TelemetryError> {
+        if expression.len() > MAX_EXPRESSION_LENGTH {
+            return Err(TelemetryError::InvalidRule(format!(
+                "filter expression longer than {} bytes",
+                MAX_EXPRESSION_LENGTH
+            )));
+        }
+        let tokens = tokenize(expression)?;
+        let mut parser = ExprParser { tokens: &tokens, pos: 0, depth: 0 };
+        let condition = parser.or()?;
+        match parser.peek() {
+            None => Ok(condition),
+            Some(token) => Err(parser.error(&format!("unexpected {:?}", token))),
+        }
+    }
+
+    /// Whether the condition holds for one snapshot on its own
+    ///
+    /// Rate-of-change and deviation conditions need history and never hold.
+    pub fn holds(&self, snapshot: &TelemetrySnapshot) -> bool {
+        self.is_met(&EvalContext {
+            snapshot,
+            previous: None,
+            baselines: &[],
+            default_window: 0,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f32),
+    Op(Comparison),
+    And,
+    Or,
+    Not,
+    Plus,
+    Minus,
+    LParen,
+    RParen,
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, TelemetryError> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        let token = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut ident = String::new();
+                while let Some(&c) = chars.peek().filter(|c| c.is_ascii_alphanumeric() || **c == '_') {
+                    ident.push(c);
+                    chars.next();
+                }
+                match ident.as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    _ => Token::Ident(ident),
+                }
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut number = String::new();
+                while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+                    number.push(c);
+                    chars.next();
+                }
+                let value = number
+                    .parse()
+                    .map_err(|_| TelemetryError::InvalidRule(format!("invalid number '{}'", number)))?;
+                Token::Number(value)
+            }
+            _ => {
+                chars.next();
+                match c {
+                    '>' if chars.next_if_eq(&'=').is_some() => Token::Op(Comparison::Ge),
+                    '>' => Token::Op(Comparison::Gt),
+                    '<' if chars.next_if_eq(&'=').is_some() => Token::Op(Comparison::Le),
+                    '<' => Token::Op(Comparison::Lt),
+                    '&' if chars.next_if_eq(&'&').is_some() => Token::And,
+                    '|' if chars.next_if_eq(&'|').is_some() => Token::Or,
+                    '!' => Token::Not,
+                    '+' => Token::Plus,
+                    '-' => Token::Minus,
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    _ => {
+                        return Err(TelemetryError::InvalidRule(format!(
+                            "unexpected '{}' in '{}'",
+                            c, expression
+                        )))
+                    }
+                }
+            }
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Recursive-descent parser for `Condition::parse`
+struct ExprParser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+
+    /// Current `!` and parenthesis nesting
+    depth: usize,
+}
+
+impl ExprParser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        let found = self.peek() == Some(token);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn error(&self, message: &str) -> TelemetryError {
+        TelemetryError::InvalidRule(format!("filter expression: {} at token {}", message, self.pos + 1))
+    }
+
+    fn or(&mut self) -> Result<Condition, TelemetryError> {
+        let mut terms = vec![self.and()?];
+        while self.eat(&Token::Or) {
+            terms.push(self.and()?);
+        }
+        Ok(if terms.len() == 1 { terms.remove(0) } else { Condition::Any(terms) })
+    }
+
+    fn and(&mut self) -> Result<Condition, TelemetryError> {
+        let mut terms = vec![self.unary()?];
+        while self.eat(&Token::And) {
+            terms.push(self.unary()?);
+        }
+        Ok(if terms.len() == 1 { terms.remove(0) } else { Condition::All(terms) })
+    }
+
+    fn unary(&mut self) -> Result<Condition, TelemetryError> {
+        if self.eat(&Token::Not) {
+            return Ok(Condition::Not(Box::new(self.nested(Self::unary)?)));
+        }
+        if self.eat(&Token::LParen) {
+            let condition = self.nested(Self::or)?;
+            if !self.eat(&Token::RParen) {
+                return Err(self.error("expected ')'"));
+            }
+            return Ok(condition);
+        }
+        self.comparison()
+    }
+
+    /// Parse one nesting level deeper, failing beyond `MAX_EXPRESSION_DEPTH`
+    fn nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<Condition, TelemetryError>,
+    ) -> Result<Condition, TelemetryError> {
+        if self.depth >= MAX_EXPRESSION_DEPTH {
+            return Err(self.error("expression nested too deeply"));
+        }
+        self.depth += 1;
+        let condition = parse(self);
+        self.depth -= 1;
+        condition
+    }
+
+    fn comparison(&mut self) -> Result<Condition, TelemetryError> {
+        let channel = self.channel()?;
+        let op = match self.next() {
+            Some(Token::Op(op)) => *op,
+            _ => return Err(self.error("expected >, >=, < or <=")),
+        };
+        if let Some(Token::Ident(_)) = self.peek() {
+            let other = self.channel()?;
+            let offset = if self.eat(&Token::Plus) {
+                self.number()?
+            } else if self.eat(&Token::Minus) {
+                -self.number()?
+            } else {
+                0.0
+            };
+            return Ok(Condition::Compare { channel, op, other, offset });
+        }
+        let value = if self.eat(&Token::Minus) { -self.number()? } else { self.number()? };
+        Ok(Condition::Threshold { channel, op, value })
+    }
+
+    fn channel(&mut self) -> Result<Channel, TelemetryError> {
+        match self.next() {
+            Some(Token::Ident(name)) => {
+                let name = name.clone();
+                Channel::from_name(&name).ok_or_else(|| self.error(&format!("unknown channel '{}'", name)))
+            }
+            _ => Err(self.error("expected a channel name")),
+        }
+    }
+
+    fn number(&mut self) -> Result<f32, TelemetryError> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(*value),
+            _ => Err(self.error("expected a number")),
+        }
+    }
+}
+
+fn default_confidence() -> f32 {
+    1.0
+}
+
+/// One alert definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRule {
+    /// Unique rule identifier
+    pub id: String,
+
+    /// Alert text; `{value}` and `{field}` are replaced with the reading
+    pub description: String,
+
+    pub anomaly_type: AnomalyType,
+    pub severity: Severity,
+
+    #[serde(default = "default_confidence")]
+    pub confidence: f32,
+
+    #[serde(default)]
+    pub recommended_action: Option<String>,
+
+    /// Condition that raises the alert
+    pub condition: Condition,
+
+    /// Condition that clears an active alert (hysteresis); defaults to the
+    /// raising condition no longer holding
+    #[serde(default)]
+    pub clear: Option<Condition>,
+
+    /// How long the condition must hold before alerting (ms)
+    #[serde(default)]
+    pub for_ms: u64,
+
+    /// Re-alert interval while active (ms); `None` alerts once per activation
+    /// and `0` alerts on every snapshot
+    #[serde(default)]
+    pub repeat_ms: Option<u64>,
+
+    /// Reported field name, overriding the channel name
+    #[serde(default)]
+    pub field: Option<String>,
+
+    /// Reported expected range, overriding the one derived from the condition
+    #[serde(default)]
+    pub expected_range: Option<(f32, f32)>,
+}
+
+/// Named collection of alert rules
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RulePack {
+    pub name: String,
+
+    #[serde(default)]
+    pub description: String,
+
+    pub rules: Vec<AlertRule>,
+}
+
+impl RulePack {
+    /// Parse a rule pack from YAML
+    ///
+    /// Conditions are written as single-key maps (`threshold: {...}`), as in
+    /// TOML. serde_yaml only accepts `!tag` syntax for enums, so the document
+    /// is read as a generic value first.
+    pub fn from_yaml(source: &str) -> Result<Self, TelemetryError> {
+        let value: serde_json::Value =
+            serde_yaml::from_str(source).map_err(|e| TelemetryError::InvalidRule(e.to_string()))?;
+        let pack: RulePack =
+            serde_json::from_value(value).map_err(|e| TelemetryError::InvalidRule(e.to_string()))?;
+        pack.validate()?;
+        Ok(pack)
+    }
+
+    /// Parse a rule pack from TOML
+    pub fn from_toml(source: &str) -> Result<Self, TelemetryError> {
+        let pack: RulePack =
+            toml::from_str(source).map_err(|e| TelemetryError::InvalidRule(e.to_string()))?;
+        pack.validate()?;
+        Ok(pack)
+    }
+
+    /// Load a rule pack from a `.yaml`, `.yml` or `.toml` file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, TelemetryError> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| TelemetryError::InvalidRule(format!("{}: {}", path.display(), e)))?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("yaml") | Some("yml") => Self::from_yaml(&source),
+            Some("toml") => Self::from_toml(&source),
+            _ => Err(TelemetryError::InvalidRule(format!(
+                "{}: expected a .yaml, .yml or .toml rule pack",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Rules equivalent to the original `LegacyAnomalyDetector` checks
+    pub fn legacy() -> Self {
+        Self::from_yaml(include_str!("../rules/legacy.yaml")).expect("built-in legacy rule pack")
+    }
+
+    /// Hard limits and z-score baselines of the original `AnomalyDetector`
+    pub fn statistical() -> Self {
+        Self::from_yaml(include_str!("../rules/statistical.yaml")).expect("built-in statistical rule pack")
+    }
+
+    /// Append another pack's rules; rules with the same id are replaced
+    pub fn merge(mut self, other: RulePack) -> Self {
+        for rule in other.rules {
+            match self.rules.iter_mut().find(|r| r.id == rule.id) {
+                Some(existing) => *existing = rule,
+                None => self.rules.push(rule),
+            }
+        }
+        self
+    }
+
+    fn validate(&self) -> Result<(), TelemetryError> {
+        for (i, rule) in self.rules.iter().enumerate() {
+            if rule.id.is_empty() {
+                return Err(TelemetryError::InvalidRule(format!("rule {} has no id", i)));
+            }
+            if self.rules[..i].iter().any(|r| r.id == rule.id) {
+                return Err(TelemetryError::InvalidRule(format!("duplicate rule id '{}'", rule.id)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Key identifying one set of baselines and rule states
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BaselineKey {
+    pub session_id: SessionId,
+    pub car_id: CarId,
+
+    /// Track segment, or `None` for a whole-lap baseline
+    pub segment: Option<u16>,
+}
+
+/// Channel and window of one rolling baseline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BaselineChannel {
+    channel: Channel,
+    window: usize,
+}
+
+/// Debounce and hysteresis state of one rule
+#[derive(Debug, Clone, Default)]
+struct RuleState {
+    active: bool,
+    pending_since: Option<DateTime<Utc>>,
+    last_alert: Option<DateTime<Utc>>,
+}
+
+/// Baselines, rule states and previous snapshot for one key
+///
+/// `previous` is only kept when the engine has no shared history.
+#[derive(Debug, Clone)]
+struct CarState {
+    baselines: Vec<(BaselineChannel, RollingStats)>,
+    rules: Vec<RuleState>,
+    previous: Option<TelemetrySnapshot>,
+    last_seen: Instant,
+}
+
+/// Evaluates a rule pack against telemetry from many cars
+pub struct RuleEngine {
+    config: TelemetryConfig,
+    pack: RulePack,
+    baselines: Vec<BaselineChannel>,
+    default_window: usize,
+    states: DashMap<BaselineKey, CarState>,
+    history: Option<Arc<TelemetryBuffer>>,
+}
+
+impl RuleEngine {
+    /// Create an engine for a rule pack
+    pub fn new(config: TelemetryConfig, pack: RulePack) -> Self {
+        let default_window = config.buffer_size.min(100); // Use config buffer size, cap at 100
+        let mut baselines = Vec::new();
+        for rule in &pack.rules {
+            rule.condition.baselines(default_window, &mut baselines);
+            if let Some(clear) = &rule.clear {
+                clear.baselines(default_window, &mut baselines);
+            }
+        }
+
+        RuleEngine {
+            config,
+            pack,
+            baselines,
+            default_window,
+            states: DashMap::new(),
+            history: None,
+        }
+    }
+
+    /// Read each car's previous snapshot from a shared buffer instead of
+    /// keeping a copy per baseline
+    ///
+    /// Snapshots must be pushed to the buffer before they are evaluated, as
+    /// `TelemetryEngine::process` does.
+    pub fn with_history(mut self, buffer: Arc<TelemetryBuffer>) -> Self {
+        self.history = Some(buffer);
+        self
+    }
+
+    /// Loaded rule pack
+    pub fn pack(&self) -> &RulePack {
+        &self.pack
+    }
+
+    /// Evaluate every rule against a snapshot using the car's lap baselines
+    pub fn evaluate(&self, snapshot: &TelemetrySnapshot) -> Vec<AnomalyAlert> {
+        self.evaluate_with_key(snapshot, None)
+    }
+
+    /// Evaluate every rule using the car's baselines for one track segment
+    pub fn evaluate_in_segment(&self, snapshot: &TelemetrySnapshot, segment: u16) -> Vec<AnomalyAlert> {
+        self.evaluate_with_key(snapshot, Some(segment))
+    }
+
+    fn evaluate_with_key(&self, snapshot: &TelemetrySnapshot, segment: Option<u16>) -> Vec<AnomalyAlert> {
+        if !self.config.enable_anomaly_detection {
+            return Vec::new();
+        }
+
+        let key = BaselineKey {
+            session_id: snapshot.session_id,
+            car_id: snapshot.car_id,
+            segment,
+        };
+
+        // Evict before taking the entry: DashMap shard locks are not reentrant
+        if !self.states.contains_key(&key) {
+            self.evict_idle(self.config.baseline_idle_timeout);
+            self.enforce_capacity(self.config.max_baselines.saturating_sub(1));
+        }
+        let mut state = self.states.entry(key).or_insert_with(|| CarState {
+            baselines: self
+                .baselines
+                .iter()
+                .map(|b| (*b, RollingStats::new(b.window)))
+                .collect(),
+            rules: vec![RuleState::default(); self.pack.rules.len()],
+            previous: None,
+            last_seen: Instant::now(),
+        });
+        let state = &mut *state;
+
+        let buffered = self
+            .history
+            .as_ref()
+            .and_then(|buffer| buffer.before(snapshot.session_id, snapshot.car_id, snapshot.timestamp));
+        let ctx = EvalContext {
+            snapshot,
+            previous: if self.history.is_some() { buffered.as_ref() } else { state.previous.as_ref() },
+            baselines: &state.baselines,
+            default_window: self.default_window,
+        };
+        let now = snapshot.timestamp;
+        let mut alerts = Vec::new();
+
+        for (rule, rule_state) in self.pack.rules.iter().zip(state.rules.iter_mut()) {
+            let met = rule.condition.is_met(&ctx);
+
+            if rule_state.active {
+                let cleared = match &rule.clear {
+                    Some(clear) => clear.is_met(&ctx),
+                    None => !met,
+                };
+                if cleared {
+                    *rule_state = RuleState::default();
+                    continue;
+                }
+                let repeat_due = match (rule.repeat_ms, rule_state.last_alert) {
+                    (Some(repeat), Some(last)) => elapsed_ms(last, now) >= repeat,
+                    _ => false,
+                };
+                if met && repeat_due {
+                    rule_state.last_alert = Some(now);
+                    alerts.push(Self::alert(rule, &key, &ctx));
+                }
+            } else if met {
+                let since = *rule_state.pending_since.get_or_insert(now);
+                if elapsed_ms(since, now) >= rule.for_ms {
+                    rule_state.active = true;
+                    rule_state.last_alert = Some(now);
+                    alerts.push(Self::alert(rule, &key, &ctx));
+                }
+            } else {
+                rule_state.pending_since = None;
+            }
+        }
+
+        // Update baselines with current values
+        for (baseline, stats) in state.baselines.iter_mut() {
+            stats.push(baseline.channel.value(snapshot));
+        }
+        if self.history.is_none() {
+            state.previous = Some(snapshot.clone());
+        }
+        state.last_seen = Instant::now();
+
+        alerts
+    }
+
+    fn alert(rule: &AlertRule, key: &BaselineKey, ctx: &EvalContext) -> AnomalyAlert {
+        let evidence = rule.condition.evidence(ctx);
+        let field = rule
+            .field
+            .clone()
+            .or_else(|| evidence.as_ref().map(|e| e.field.to_string()))
+            .unwrap_or_else(|| rule.id.clone());
+        let actual_value = evidence.as_ref().map_or(0.0, |e| e.value);
+        let description = rule
+            .description
+            .replace("{value}", &format!("{:.1}", actual_value))
+            .replace("{field}", &field);
+
+        AnomalyAlert {
+            rule_id: rule.id.clone(),
+            car_id: key.car_id,
+            session_id: key.session_id,
+            segment: key.segment,
+            anomaly_type: rule.anomaly_type,
+            severity: rule.severity,
+            description,
+            confidence: rule.confidence,
+            recommended_action: rule.recommended_action.clone(),
+            field,
+            actual_value,
+            expected_range: rule.expected_range.or(evidence.map(|e| e.expected_range)),
+            timestamp: ctx.snapshot.timestamp,
+        }
+    }
+
+    /// Number of baselines currently held
+    pub fn baseline_count(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Drop baselines not updated within `max_idle`; returns the number removed
+    pub fn evict_idle(&self, max_idle: Duration) -> usize {
+        self.evict_where(|_, state| state.last_seen.elapsed() > max_idle)
+    }
+
+    /// Drop every baseline of a session; returns the number removed
+    pub fn evict_session(&self, session_id: SessionId) -> usize {
+        self.evict_where(|key, _| key.session_id == session_id)
+    }
+
+    /// Drop every baseline of one car in a session; returns the number removed
+    pub fn evict_car(&self, session_id: SessionId, car_id: CarId) -> usize {
+        self.evict_where(|key, _| key.session_id == session_id && key.car_id == car_id)
+    }
+
+    /// Drop matching baselines; counted during the sweep, since other
+    /// threads may insert baselines concurrently
+    fn evict_where(&self, mut evict: impl FnMut(&BaselineKey, &CarState) -> bool) -> usize {
+        let mut removed = 0;
+        self.states.retain(|key, state| {
+            let drop = evict(key, state);
+            removed += usize::from(drop);
+            !drop
+        });
+        removed
+    }
+
+    /// Evict least recently seen baselines until at most `capacity` remain
+    fn enforce_capacity(&self, capacity: usize) {
+        let excess = self.states.len().saturating_sub(capacity);
+        if excess == 0 {
+            return;
+        }
+
+        let mut by_age: Vec<(BaselineKey, Instant)> = self
+            .states
+            .iter()
+            .map(|entry| (*entry.key(), entry.value().last_seen))
+            .collect();
+        by_age.sort_by_key(|(_, last_seen)| *last_seen);
+        for (key, _) in by_age.into_iter().take(excess) {
+            self.states.remove(&key);
+        }
+    }
+}
+
+fn elapsed_ms(since: DateTime<Utc>, now: DateTime<Utc>) -> u64 {
+    (now - since).num_milliseconds().max(0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::tests::create_test_snapshot;
+    use chrono::Duration as ChronoDuration;
+
+    const OIL_PACK_YAML: &str = r#"
+name: oil
+rules:
+  - id: oil_overheat
+    description: "Oil at {value} C"
+    anomaly_type: PowerUnitIssue
+    severity: High
+    recommended_action: Lift and coast
+    for_ms: 500
+    condition:
+      threshold: { channel: oil_temp, op: ">", value: 150.0 }
+    clear:
+      threshold: { channel: oil_temp, op: "<", value: 145.0 }
+"#;
+
+    const BRAKE_PACK_TOML: &str = r#"
+name = "brakes"
+
+[[rules]]
+id = "front_brake_imbalance"
+description = "Front-left brake {value} C hotter than front-right"
+anomaly_type = "BrakeFailure"
+severity = "Medium"
+repeat_ms = 0
+
+[rules.condition.compare]
+channel = "brake_temp_front_left"
+op = ">"
+other = "brake_temp_front_right"
+offset = 150.0
+
+[[rules]]
+id = "pressure_drop"
+description = "Oil pressure falling fast"
+anomaly_type = "PowerUnitIssue"
+severity = "Critical"
+
+[rules.condition.rate_of_change]
+channel = "oil_pressure"
+op = "<"
+per_second = -2.0
+"#;
+
+    fn at(session_id: SessionId, start: DateTime<Utc>, ms: i64) -> TelemetrySnapshot {
+        let mut snapshot = create_test_snapshot();
+        snapshot.session_id = session_id;
+        snapshot.timestamp = start + ChronoDuration::milliseconds(ms);
+        snapshot
+    }
+
+    #[test]
+    fn test_debounce_and_hysteresis() {
+        let engine = RuleEngine::new(TelemetryConfig::default(), RulePack::from_yaml(OIL_PACK_YAML).unwrap());
+        let session_id = SessionId::new();
+        let start = Utc::now();
+        let oil = |ms: i64, temp: f32| {
+            let mut snapshot = at(session_id, start, ms);
+            snapshot.power_unit.oil_temp = temp;
+            snapshot
+        };
+
+        // Must stay above 150 for 500 ms before alerting
+        assert!(engine.evaluate(&oil(0, 152.0)).is_empty());
+        assert!(engine.evaluate(&oil(300, 153.0)).is_empty());
+        let alerts = engine.evaluate(&oil(600, 154.0));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].rule_id, "oil_overheat");
+        assert_eq!(alerts[0].field, "oil_temp");
+        assert_eq!(alerts[0].description, "Oil at 154.0 C");
+        assert_eq!(alerts[0].anomaly_type, AnomalyType::PowerUnitIssue);
+        assert_eq!(alerts[0].recommended_action.as_deref(), Some("Lift and coast"));
+
+        // Alerts once per activation, and stays active inside the hysteresis band
+        assert!(engine.evaluate(&oil(700, 155.0)).is_empty());
+        assert!(engine.evaluate(&oil(800, 147.0)).is_empty());
+        assert!(engine.evaluate(&oil(900, 151.0)).is_empty());
+
+        // Clears below 145 and must be debounced again
+        assert!(engine.evaluate(&oil(1000, 140.0)).is_empty());
+        assert!(engine.evaluate(&oil(1100, 151.0)).is_empty());
+        assert_eq!(engine.evaluate(&oil(1700, 151.0)).len(), 1);
+    }
+
+    #[test]
+    fn test_toml_cross_channel_and_rate_rules() {
+        let engine = RuleEngine::new(TelemetryConfig::default(), RulePack::from_toml(BRAKE_PACK_TOML).unwrap());
+        let session_id = SessionId::new();
+        let start = Utc::now();
+
+        let mut hot_left = at(session_id, start, 0);
+        hot_left.tires.front_left.brake_temp = 600.0;
+        let alerts = engine.evaluate(&hot_left);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].rule_id, "front_brake_imbalance");
+        assert_eq!(alerts[0].expected_range, Some((f32::MIN, 500.0)));
+
+        // Oil pressure drops 1.5 bar in 100 ms
+        let mut falling = at(session_id, start, 100);
+        falling.power_unit.oil_pressure -= 1.5;
+        let alerts = engine.evaluate(&falling);
+        let drop = alerts.iter().find(|a| a.rule_id == "pressure_drop").unwrap();
+        assert_eq!(drop.severity, Severity::Critical);
+        assert!((drop.actual_value + 15.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_rate_rules_read_shared_history() {
+        let buffer = Arc::new(TelemetryBuffer::default());
+        let pack = RulePack::from_toml(BRAKE_PACK_TOML).unwrap();
+        let engine = RuleEngine::new(TelemetryConfig::default(), pack).with_history(buffer.clone());
+        let session_id = SessionId::new();
+        let start = Utc::now();
+
+        let steady = at(session_id, start, 0);
+        buffer.push(&steady);
+        assert!(engine.evaluate(&steady).is_empty());
+
+        // Segment baselines still see the car's previous snapshot
+        let mut falling = at(session_id, start, 100);
+        falling.power_unit.oil_pressure -= 1.5;
+        buffer.push(&falling);
+        let alerts = engine.evaluate_in_segment(&falling, 3);
+        assert!(alerts.iter().any(|a| a.rule_id == "pressure_drop"));
+    }
+
+    #[test]
+    fn test_builtin_packs_and_merge() {
+        let legacy = RulePack::legacy();
+        let statistical = RulePack::statistical();
+        assert_eq!(legacy.rules.len(), 3);
+        assert!(statistical.rules.len() > 20);
+
+        let tuned = RulePack::from_yaml(
+            r#"
+name: tuned
+rules:
+  - id: fuel_shortage
+    description: "Fuel for {value} laps"
+    anomaly_type: FuelShortage
+    severity: High
+    condition:
+      threshold: { channel: fuel_laps, op: "<", value: 3.0 }
+"#,
+        )
+        .unwrap();
+        let merged = legacy.merge(tuned);
+        assert_eq!(merged.rules.len(), 3);
+        let fuel = merged.rules.iter().find(|r| r.id == "fuel_shortage").unwrap();
+        assert_eq!(fuel.severity, Severity::High);
+    }
+
+    #[test]
+    fn test_invalid_packs_rejected() {
+        let duplicate = r#"
+name: dup
+rules:
+  - { id: a, description: x, anomaly_type: TireDamage, severity: Low, condition: { threshold: { channel: speed, op: ">", value: 1.0 } } }
+  - { id: a, description: x, anomaly_type: TireDamage, severity: Low, condition: { threshold: { channel: speed, op: ">", value: 2.0 } } }
+"#;
+        assert!(matches!(RulePack::from_yaml(duplicate), Err(TelemetryError::InvalidRule(_))));
+
+        let unknown_channel = r#"
+name: bad
+rules:
+  - { id: a, description: x, anomaly_type: TireDamage, severity: Low, condition: { threshold: { channel: warp_drive, op: ">", value: 1.0 } } }
+"#;
+        assert!(RulePack::from_yaml(unknown_channel).is_err());
+        assert!(RulePack::from_file("rules.json").is_err());
+    }
+
+    #[test]
+    fn test_parse_filter_expressions() {
+        let mut snapshot = create_test_snapshot();
+        snapshot.motion.speed = 310.0;
+
+        let fast = Condition::parse("speed > 300").unwrap();
+        assert!(matches!(fast, Condition::Threshold { channel: Channel::Speed, op: Comparison::Gt, value } if value == 300.0));
+        assert!(fast.holds(&snapshot));
+
+        let combined = Condition::parse("speed>=300 and (throttle < 0.5 || !(tire_temp_max <= 90))").unwrap();
+        assert!(combined.holds(&snapshot));
+        let cross = Condition::parse("brake_temp_max > tire_temp_max + 500 or steering < -0.5").unwrap();
+        assert!(!cross.holds(&snapshot));
+        snapshot.motion.speed = 250.0;
+        assert!(!combined.holds(&snapshot));
+
+        for bad in ["speed >", "speed == 3", "warp_speed > 1", "(speed > 1", "speed > 1 throttle"] {
+            assert!(
+                matches!(Condition::parse(bad), Err(TelemetryError::InvalidRule(_))),
+                "{} should not parse",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_limits_nesting_and_length() {
+        let nots = |n: usize| "!".repeat(n) + "speed > 1";
+        let parens = |n: usize| "(".repeat(n) + "speed > 1" + &")".repeat(n);
+
+        assert!(Condition::parse(&nots(MAX_EXPRESSION_DEPTH)).is_ok());
+        assert!(Condition::parse(&parens(MAX_EXPRESSION_DEPTH)).is_ok());
+        assert!(matches!(Condition::parse(&nots(MAX_EXPRESSION_DEPTH + 1)), Err(TelemetryError::InvalidRule(_))));
+        assert!(matches!(Condition::parse(&parens(MAX_EXPRESSION_DEPTH + 1)), Err(TelemetryError::InvalidRule(_))));
+        assert!(Condition::parse(&"(!".repeat(1_000)).is_err());
+
+        // Input that used to overflow the stack is rejected up front
+        assert!(matches!(Condition::parse(&nots(200_000)), Err(TelemetryError::InvalidRule(_))));
+        let long = vec!["speed > 1"; 500].join(" or ");
+        assert!(long.len() > MAX_EXPRESSION_LENGTH);
+        assert!(Condition::parse(&long).is_err());
+    }
+}
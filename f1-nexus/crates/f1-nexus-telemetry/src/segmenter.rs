@@ -0,0 +1,671 @@
+//! Lap and sector detection from the raw telemetry stream
+//!
+//! `LapSegmenter` derives lap boundaries, sector splits and pit stops from
+//! timestamps, speed and (when available) lap distance, instead of trusting
+//! the upstream `lap` field. Sector boundaries come from the circuit's sector
+//! lengths. Without a distance source, distance is integrated from speed and
+//! re-anchored at each lap boundary it detects.
+//!
+//! Pit stops are taken from an explicit pit-lane flag when the source has
+//! one; otherwise a car that slows to pit-lane speed and then stands still
+//! for `min_stop_duration` is treated as pitting. In that case a lap finished
+//! at pit-lane speed is held back until it is known whether it was an in-lap.
+
+use crate::TelemetryError;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use f1_nexus_core::{CarId, Circuit, LapNumber, OpenF1CarData, Sector, SessionId, TelemetrySnapshot};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Segmenter configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmenterConfig {
+    /// Lap number given to the first lap of each car
+    pub starting_lap: u16,
+
+    /// Pit lane speed limit (km/h)
+    pub pit_speed_limit: f32,
+
+    /// Tolerance above the pit limit still counted as pit-lane speed (km/h)
+    pub pit_speed_tolerance: f32,
+
+    /// Below this speed the car counts as stationary (km/h)
+    pub stationary_speed: f32,
+
+    /// Minimum stationary time treated as a pit stop
+    pub min_stop_duration: Duration,
+}
+
+impl Default for SegmenterConfig {
+    fn default() -> Self {
+        SegmenterConfig {
+            starting_lap: 1,
+            pit_speed_limit: 80.0,
+            pit_speed_tolerance: 5.0,
+            stationary_speed: 2.0,
+            min_stop_duration: Duration::from_millis(1500),
+        }
+    }
+}
+
+/// Optional track position inputs for one snapshot
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrackSample {
+    /// Distance from the start/finish line (m)
+    pub lap_distance: Option<f32>,
+
+    /// Whether the car is in the pit lane
+    pub in_pit_lane: Option<bool>,
+}
+
+/// Lap, sector and pit events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum LapEvent {
+    /// Sector finished
+    SectorCompleted {
+        car_id: CarId,
+        lap: LapNumber,
+        sector: Sector,
+        /// Sector time (s), `None` if the sector start was not observed
+        time: Option<f32>,
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Lap finished
+    LapCompleted {
+        car_id: CarId,
+        lap: LapNumber,
+        /// Lap time (s), `None` if the lap start was not observed
+        lap_time: Option<f32>,
+        sector_times: Vec<Option<f32>>,
+        /// The car entered the pit lane during this lap
+        in_lap: bool,
+        /// The car left the pit lane during this lap
+        out_lap: bool,
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Car entered the pit lane
+    PitIn {
+        car_id: CarId,
+        lap: LapNumber,
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Car left the pit lane
+    PitOut {
+        car_id: CarId,
+        lap: LapNumber,
+        /// Time spent in the pit lane (s)
+        pit_lane_time: f32,
+        /// Time spent stationary (s)
+        stationary_time: f32,
+        timestamp: DateTime<Utc>,
+    },
+}
+
+/// Where a car currently is on the lap
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LapPosition {
+    pub lap: LapNumber,
+    pub sector: Sector,
+    /// Distance from the start/finish line (m)
+    pub lap_distance: f32,
+    pub in_pit_lane: bool,
+}
+
+/// Samples faster than this (km/h) are dropped as corrupt
+const MAX_PLAUSIBLE_SPEED: f32 = 500.0;
+
+const SECTORS: [Sector; 3] = [Sector::Sector1, Sector::Sector2, Sector::Sector3];
+
+#[derive(Debug, Clone)]
+struct CarSegmentState {
+    lap: u16,
+    lap_distance: f32,
+    sector: usize,
+    last_time: DateTime<Utc>,
+    last_speed: f32,
+    /// Start of the current lap, if it was observed
+    lap_start: Option<DateTime<Utc>>,
+    /// Start of the current sector, if it was observed
+    sector_start: Option<DateTime<Utc>>,
+    sector_times: Vec<Option<f32>>,
+    in_lap: bool,
+    out_lap: bool,
+    in_pit: bool,
+    pit_entry: Option<DateTime<Utc>>,
+    /// When and on which lap the car slowed to pit-lane speed
+    slow_since: Option<(DateTime<Utc>, u16)>,
+    /// Lap completed at pit-lane speed, waiting for a possible pit stop
+    held_lap: Option<LapEvent>,
+    stationary_since: Option<DateTime<Utc>>,
+    stationary_time: f32,
+}
+
+fn seconds(from: DateTime<Utc>, to: DateTime<Utc>) -> f32 {
+    (to - from).num_microseconds().unwrap_or(0) as f32 / 1.0e6
+}
+
+/// Derives laps, sectors and pit stops per car
+pub struct LapSegmenter {
+    config: SegmenterConfig,
+    lap_length: f32,
+    /// Distance from the line at which each sector ends
+    sector_ends: [f32; 3],
+    states: DashMap<(SessionId, CarId), CarSegmentState>,
+}
+
+impl LapSegmenter {
+    /// Segmenter for a circuit; its length must be positive
+    pub fn new(circuit: &Circuit, config: SegmenterConfig) -> Result<Self, TelemetryError> {
+        if !(circuit.length.is_finite() && circuit.length > 0.0) {
+            return Err(TelemetryError::InvalidData(format!(
+                "circuit {} has no usable length ({} m)",
+                circuit.name, circuit.length
+            )));
+        }
+        let layout = circuit.sector_layout();
+        let total: f32 = layout.iter().map(|s| s.length).sum();
+        // Scale sector lengths so they add up to the lap length
+        let scale = if total > 0.0 { circuit.length / total } else { 1.0 };
+
+        let mut sector_ends = [circuit.length / 3.0, 2.0 * circuit.length / 3.0, circuit.length];
+        if layout.len() == 3 && total > 0.0 {
+            let mut end = 0.0;
+            for (i, sector) in layout.iter().enumerate() {
+                end += sector.length * scale;
+                sector_ends[i] = end;
+            }
+        }
+        sector_ends[2] = circuit.length;
+
+        Ok(LapSegmenter {
+            config,
+            lap_length: circuit.length,
+            sector_ends,
+            states: DashMap::new(),
+        })
+    }
+
+    /// Process a snapshot, integrating distance from speed
+    pub fn observe(&self, snapshot: &TelemetrySnapshot) -> Vec<LapEvent> {
+        self.observe_with(snapshot, TrackSample::default())
+    }
+
+    /// Process a snapshot and overwrite its `lap` with the derived lap
+    pub fn relabel(&self, snapshot: &mut TelemetrySnapshot, track: TrackSample) -> Vec<LapEvent> {
+        let events = self.observe_with(snapshot, track);
+        if let Some(position) = self.position(snapshot.session_id, snapshot.car_id) {
+            snapshot.lap = position.lap;
+        }
+        events
+    }
+
+    /// Convert an OpenF1 car data sample, taking its lap from the segmenter
+    ///
+    /// `TelemetrySnapshot::from_openf1` knows neither the session nor the
+    /// lap, so the snapshot is given `session_id` and relabelled here.
+    pub fn relabel_openf1(
+        &self,
+        session_id: SessionId,
+        car_data: OpenF1CarData,
+    ) -> Result<(TelemetrySnapshot, Vec<LapEvent>), TelemetryError> {
+        let (session_key, driver_number) = (car_data.session_key, car_data.driver_number);
+        let mut snapshot = TelemetrySnapshot::from_openf1(session_key, driver_number, car_data, None)
+            .map_err(|e| TelemetryError::InvalidData(e.to_string()))?;
+        snapshot.session_id = session_id;
+        let events = self.relabel(&mut snapshot, TrackSample::default());
+        Ok((snapshot, events))
+    }
+
+    /// Process a snapshot with optional distance and pit-lane inputs
+    ///
+    /// Samples with a non-finite or implausible speed or lap distance are
+    /// ignored.
+    pub fn observe_with(&self, snapshot: &TelemetrySnapshot, track: TrackSample) -> Vec<LapEvent> {
+        let key = (snapshot.session_id, snapshot.car_id);
+        let now = snapshot.timestamp;
+        let speed = snapshot.motion.speed.max(0.0);
+        let mut events = Vec::new();
+        let plausible_distance = track
+            .lap_distance
+            .map_or(true, |d| d.is_finite() && d.abs() <= self.lap_length);
+        if !(speed <= MAX_PLAUSIBLE_SPEED && plausible_distance) {
+            return events;
+        }
+
+        let Some(mut state) = self.states.get_mut(&key) else {
+            // First sample: the lap is only timed from the line if we know
+            // the car starts there
+            let lap_distance = track.lap_distance.unwrap_or(0.0).rem_euclid(self.lap_length);
+            let sector = self.sector_at(lap_distance);
+            let at_line = track.lap_distance.map_or(true, |d| d < 1.0);
+            let in_pit = track.in_pit_lane.unwrap_or(false);
+            self.states.insert(
+                key,
+                CarSegmentState {
+                    lap: self.config.starting_lap,
+                    lap_distance,
+                    sector,
+                    last_time: now,
+                    last_speed: speed,
+                    lap_start: at_line.then_some(now),
+                    sector_start: (at_line && sector == 0).then_some(now),
+                    sector_times: vec![None; 3],
+                    in_lap: false,
+                    out_lap: false,
+                    in_pit,
+                    pit_entry: in_pit.then_some(now),
+                    slow_since: None,
+                    held_lap: None,
+                    stationary_since: None,
+                    stationary_time: 0.0,
+                },
+            );
+            return events;
+        };
+
+        if now <= state.last_time {
+            return events;
+        }
+        let dt = seconds(state.last_time, now);
+
+        // Distance travelled since the previous sample, and whether the
+        // line was crossed
+        let previous = state.lap_distance;
+        let (travelled, next) = match track.lap_distance {
+            Some(distance) => {
+                let wrapped = distance < previous - self.lap_length / 2.0;
+                let travelled = if wrapped {
+                    distance + self.lap_length - previous
+                } else {
+                    distance - previous
+                };
+                (travelled.max(0.0), distance)
+            }
+            None => {
+                let travelled = 0.5 * (state.last_speed + speed) / 3.6 * dt;
+                (travelled, previous + travelled)
+            }
+        };
+        // A long gap between samples cannot complete more than one lap
+        let travelled = travelled.min(self.lap_length);
+
+        // Sector and lap boundaries between the two samples, with crossing
+        // times interpolated by distance
+        let mut covered = 0.0;
+        let mut position = previous;
+        while travelled > covered {
+            let boundary = self.sector_ends[state.sector];
+            let to_boundary = boundary - position;
+            if covered + to_boundary > travelled {
+                break;
+            }
+            covered += to_boundary;
+            let crossing = state.last_time
+                + chrono::Duration::microseconds((dt * covered / travelled * 1.0e6) as i64);
+            let hold = track.in_pit_lane.is_none() && state.slow_since.is_some() && !state.in_pit;
+            self.complete_sector(&mut state, snapshot.car_id, crossing, hold, &mut events);
+            position = if state.sector == 0 { 0.0 } else { boundary };
+        }
+
+        state.lap_distance = if track.lap_distance.is_some() {
+            next.rem_euclid(self.lap_length)
+        } else {
+            position + (travelled - covered)
+        };
+
+        self.update_pit_state(&mut state, snapshot.car_id, now, speed, track.in_pit_lane, &mut events);
+        state.last_time = now;
+        state.last_speed = speed;
+        events
+    }
+
+    /// Current lap position of a car
+    pub fn position(&self, session_id: SessionId, car_id: CarId) -> Option<LapPosition> {
+        self.states.get(&(session_id, car_id)).map(|state| LapPosition {
+            lap: LapNumber(state.lap),
+            sector: SECTORS[state.sector],
+            lap_distance: state.lap_distance,
+            in_pit_lane: state.in_pit,
+        })
+    }
+
+    /// Drop every car of a session; returns the number removed
+    pub fn evict_session(&self, session_id: SessionId) -> usize {
+        let mut removed = 0;
+        self.states.retain(|(session, _), _| {
+            let evict = *session == session_id;
+            removed += usize::from(evict);
+            !evict
+        });
+        removed
+    }
+
+    fn sector_at(&self, distance: f32) -> usize {
+        self.sector_ends.iter().position(|end| distance < *end).unwrap_or(2)
+    }
+
+    fn complete_sector(
+        &self,
+        state: &mut CarSegmentState,
+        car_id: CarId,
+        at: DateTime<Utc>,
+        hold: bool,
+        events: &mut Vec<LapEvent>,
+    ) {
+        let lap = LapNumber(state.lap);
+        let time = state.sector_start.map(|start| seconds(start, at));
+        state.sector_times[state.sector] = time;
+        events.push(LapEvent::SectorCompleted {
+            car_id,
+            lap,
+            sector: SECTORS[state.sector],
+            time,
+            timestamp: at,
+        });
+        state.sector_start = Some(at);
+
+        if state.sector < 2 {
+            state.sector += 1;
+            return;
+        }
+
+        let completed = LapEvent::LapCompleted {
+            car_id,
+            lap,
+            lap_time: state.lap_start.map(|start| seconds(start, at)),
+            sector_times: std::mem::replace(&mut state.sector_times, vec![None; 3]),
+            in_lap: state.in_lap,
+            out_lap: state.out_lap,
+            timestamp: at,
+        };
+        if hold {
+            state.held_lap = Some(completed);
+        } else {
+            events.push(completed);
+        }
+        state.lap += 1;
+        state.sector = 0;
+        state.lap_start = Some(at);
+        // A car still in the pit lane starts its out-lap
+        state.in_lap = false;
+        state.out_lap = state.in_pit;
+    }
+
+    fn update_pit_state(
+        &self,
+        state: &mut CarSegmentState,
+        car_id: CarId,
+        now: DateTime<Utc>,
+        speed: f32,
+        in_pit_lane: Option<bool>,
+        events: &mut Vec<LapEvent>,
+    ) {
+        let stationary = speed < self.config.stationary_speed;
+        match (stationary, state.stationary_since) {
+            (true, None) => state.stationary_since = Some(state.last_time),
+            (false, Some(since)) => {
+                if state.in_pit {
+                    state.stationary_time += seconds(since, state.last_time);
+                }
+                state.stationary_since = None;
+            }
+            _ => {}
+        }
+
+        let in_pit = match in_pit_lane {
+            Some(flag) => flag,
+            None => {
+                let pit_speed = speed <= self.config.pit_speed_limit + self.config.pit_speed_tolerance;
+                if !pit_speed {
+                    state.slow_since = None;
+                    // Just a slow lap end, not a pit stop
+                    events.extend(state.held_lap.take());
+                } else if state.slow_since.is_none() {
+                    state.slow_since = Some((state.last_time, state.lap));
+                }
+                let stopped_long_enough = state.stationary_since.is_some_and(|since| {
+                    seconds(since, now) >= self.config.min_stop_duration.as_secs_f32()
+                });
+                if state.in_pit {
+                    pit_speed
+                } else {
+                    stopped_long_enough
+                }
+            }
+        };
+
+        if in_pit && !state.in_pit {
+            let (entry, entry_lap) = match (in_pit_lane, state.slow_since) {
+                (None, Some(slow_since)) => slow_since,
+                _ => (now, state.lap),
+            };
+            state.in_pit = true;
+            state.pit_entry = Some(entry);
+            state.stationary_time = 0.0;
+            events.push(LapEvent::PitIn {
+                car_id,
+                lap: LapNumber(entry_lap),
+                timestamp: entry,
+            });
+            match state.held_lap.take() {
+                Some(LapEvent::LapCompleted { car_id, lap, lap_time, sector_times, out_lap, timestamp, .. }) => {
+                    events.push(LapEvent::LapCompleted {
+                        car_id,
+                        lap,
+                        lap_time,
+                        sector_times,
+                        in_lap: true,
+                        out_lap,
+                        timestamp,
+                    });
+                    // The line was crossed in the pit lane
+                    state.out_lap = true;
+                }
+                _ => state.in_lap = true,
+            }
+        } else if !in_pit && state.in_pit {
+            if let Some(since) = state.stationary_since.take() {
+                state.stationary_time += seconds(since, now);
+            }
+            state.in_pit = false;
+            state.out_lap = true;
+            events.push(LapEvent::PitOut {
+                car_id,
+                lap: LapNumber(state.lap),
+                pit_lane_time: state.pit_entry.take().map_or(0.0, |entry| seconds(entry, now)),
+                stationary_time: state.stationary_time,
+                timestamp: now,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::tests::create_test_snapshot;
+
+    fn circuit() -> Circuit {
+        let mut circuit = Circuit::monza();
+        circuit.length = 5000.0;
+        circuit.sectors.clear();
+        circuit
+    }
+
+    /// Drive at constant speed, returning all events; 10 Hz samples
+    fn drive(
+        segmenter: &LapSegmenter,
+        snapshot: &mut TelemetrySnapshot,
+        speeds: impl IntoIterator<Item = f32>,
+    ) -> Vec<LapEvent> {
+        let mut events = Vec::new();
+        for speed in speeds {
+            snapshot.timestamp += chrono::Duration::milliseconds(100);
+            snapshot.motion.speed = speed;
+            events.extend(segmenter.observe(snapshot));
+        }
+        events
+    }
+
+    fn laps(events: &[LapEvent]) -> Vec<(u16, Option<f32>, bool, bool)> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                LapEvent::LapCompleted { lap, lap_time, in_lap, out_lap, .. } => {
+                    Some((lap.0, *lap_time, *in_lap, *out_lap))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_laps_and_sectors_from_speed() {
+        let segmenter = LapSegmenter::new(&circuit(), SegmenterConfig::default()).unwrap();
+        let mut snapshot = create_test_snapshot();
+        snapshot.motion.speed = 180.0;
+        segmenter.observe(&snapshot);
+
+        // 180 km/h = 50 m/s: 100 s per 5 km lap, 33.3 s per sector
+        let events = drive(&segmenter, &mut snapshot, std::iter::repeat_n(180.0, 2_100));
+        let laps = laps(&events);
+        assert_eq!(laps.len(), 2);
+        assert_eq!(laps[0].0, 1);
+        assert!((laps[0].1.unwrap() - 100.0).abs() < 0.01);
+        assert!((laps[1].1.unwrap() - 100.0).abs() < 0.01);
+
+        let sector_times: Vec<f32> = events
+            .iter()
+            .filter_map(|event| match event {
+                LapEvent::SectorCompleted { time, .. } => *time,
+                _ => None,
+            })
+            .collect();
+        assert_eq!(sector_times.len(), 6);
+        assert!(sector_times.iter().all(|t| (t - 33.333).abs() < 0.01));
+
+        let position = segmenter.position(snapshot.session_id, snapshot.car_id).unwrap();
+        assert_eq!(position.lap, LapNumber(3));
+        assert_eq!(position.sector, Sector::Sector1);
+        assert!((position.lap_distance - 500.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_distance_input_and_partial_first_lap() {
+        let segmenter = LapSegmenter::new(&circuit(), SegmenterConfig::default()).unwrap();
+        let mut snapshot = create_test_snapshot();
+        let mut events = Vec::new();
+
+        // Join mid-lap at 4 km, 50 m per sample
+        for i in 0..=100 {
+            snapshot.timestamp += chrono::Duration::milliseconds(1000);
+            let track = TrackSample {
+                lap_distance: Some((4000.0 + 50.0 * i as f32) % 5000.0),
+                in_pit_lane: None,
+            };
+            events.extend(segmenter.relabel(&mut snapshot, track));
+        }
+
+        let laps = laps(&events);
+        assert_eq!(laps.len(), 1);
+        // First lap started before we joined, so it has no time
+        assert_eq!(laps[0].1, None);
+        assert_eq!(snapshot.lap, LapNumber(2));
+    }
+
+    #[test]
+    fn test_pit_stop_detection() {
+        let segmenter = LapSegmenter::new(&circuit(), SegmenterConfig::default()).unwrap();
+        let mut snapshot = create_test_snapshot();
+        segmenter.observe(&snapshot);
+
+        // Enter the pit lane at the end of lap 1, cross the line in the pit
+        // lane, stop, and rejoin
+        let mut events = drive(&segmenter, &mut snapshot, std::iter::repeat_n(200.0, 880));
+        events.extend(drive(&segmenter, &mut snapshot, std::iter::repeat_n(80.0, 60)));
+        events.extend(drive(&segmenter, &mut snapshot, std::iter::repeat_n(0.0, 25)));
+        events.extend(drive(&segmenter, &mut snapshot, std::iter::repeat_n(80.0, 50)));
+        events.extend(drive(&segmenter, &mut snapshot, std::iter::repeat_n(250.0, 1_500)));
+
+        let pit_in = events.iter().find(|e| matches!(e, LapEvent::PitIn { .. })).unwrap();
+        let pit_out = events.iter().find(|e| matches!(e, LapEvent::PitOut { .. })).unwrap();
+        if let LapEvent::PitOut { pit_lane_time, stationary_time, .. } = pit_out {
+            assert!((pit_lane_time - 13.6).abs() < 0.3, "{}", pit_lane_time);
+            assert!((stationary_time - 2.5).abs() < 0.3, "{}", stationary_time);
+        }
+        assert!(matches!(pit_in, LapEvent::PitIn { lap: LapNumber(1), .. }));
+
+        let laps = laps(&events);
+        assert_eq!(laps.len(), 3);
+        assert_eq!(laps[0].0, 1);
+        assert!(laps[0].2, "lap 1 should be an in-lap");
+        assert!(!laps[1].2);
+        assert!(laps[1].3, "lap 2 should be an out-lap");
+        assert!(!laps[2].2 && !laps[2].3);
+    }
+
+    #[test]
+    fn test_rejects_corrupt_inputs() {
+        let mut empty = circuit();
+        empty.length = 0.0;
+        assert!(LapSegmenter::new(&empty, SegmenterConfig::default()).is_err());
+
+        let segmenter = LapSegmenter::new(&circuit(), SegmenterConfig::default()).unwrap();
+        let mut snapshot = create_test_snapshot();
+        snapshot.motion.speed = 180.0;
+        segmenter.observe(&snapshot);
+
+        for speed in [f32::NAN, f32::INFINITY, f32::MAX] {
+            snapshot.timestamp += chrono::Duration::milliseconds(100);
+            snapshot.motion.speed = speed;
+            assert!(segmenter.observe(&snapshot).is_empty());
+        }
+        for distance in [f32::NAN, f32::INFINITY, 1.0e12] {
+            snapshot.timestamp += chrono::Duration::milliseconds(100);
+            let track = TrackSample { lap_distance: Some(distance), in_pit_lane: None };
+            assert!(segmenter.observe_with(&snapshot, track).is_empty());
+        }
+        let position = segmenter.position(snapshot.session_id, snapshot.car_id).unwrap();
+        assert!(position.lap_distance.is_finite());
+
+        // A day-long gap completes at most one lap
+        snapshot.timestamp += chrono::Duration::days(1);
+        snapshot.motion.speed = 300.0;
+        assert_eq!(laps(&segmenter.observe(&snapshot)).len(), 1);
+    }
+
+    #[test]
+    fn test_relabel_openf1() {
+        let segmenter = LapSegmenter::new(&circuit(), SegmenterConfig::default()).unwrap();
+        let session_id = SessionId::new();
+        let start = create_test_snapshot().timestamp;
+        let mut events = Vec::new();
+        let mut last = None;
+        // 180 km/h for 150 s crosses the line once
+        for i in 0..=1_500 {
+            let car_data = OpenF1CarData {
+                session_key: 9_158,
+                driver_number: 16,
+                date: (start + chrono::Duration::milliseconds(100 * i)).to_rfc3339(),
+                speed: Some(180.0),
+                rpm: None,
+                n_gear: None,
+                throttle: None,
+                brake: None,
+                drs: None,
+            };
+            let (snapshot, lap_events) = segmenter.relabel_openf1(session_id, car_data).unwrap();
+            events.extend(lap_events);
+            last = Some(snapshot);
+        }
+        let last = last.unwrap();
+        assert_eq!(last.session_id, session_id);
+        assert_eq!(last.lap, LapNumber(2));
+        assert_eq!(laps(&events).len(), 1);
+    }
+}
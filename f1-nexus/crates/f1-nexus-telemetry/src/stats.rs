@@ -0,0 +1,487 @@
+//! Incremental rolling statistics for telemetry channels
+//!
+//! All accumulators update in O(1) per sample (the rolling median is
+//! O(log n) search plus a small memmove), so they can run per car and per
+//! channel at full telemetry rate. Internal sums use f64 to keep windowed
+//! add/remove updates stable over long sessions.
+
+use std::collections::VecDeque;
+
+/// Unbounded running mean and variance (Welford's algorithm)
+#[derive(Debug, Clone, Default)]
+pub struct RunningStats {
+    count: u64,
+    mean: f64,
+    m2: f64,
+    min: f32,
+    max: f32,
+}
+
+impl RunningStats {
+    /// Create empty running statistics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a sample (non-finite values are ignored)
+    pub fn push(&mut self, value: f32) {
+        if !value.is_finite() {
+            return;
+        }
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+
+        self.count += 1;
+        let x = value as f64;
+        let delta = x - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    /// Number of samples seen
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Mean of all samples
+    pub fn mean(&self) -> f32 {
+        self.mean as f32
+    }
+
+    /// Population variance of all samples
+    pub fn variance(&self) -> f32 {
+        if self.count == 0 {
+            0.0
+        } else {
+            (self.m2 / self.count as f64) as f32
+        }
+    }
+
+    /// Population standard deviation of all samples
+    pub fn std_dev(&self) -> f32 {
+        self.variance().sqrt()
+    }
+
+    /// Smallest sample, or `None` if empty
+    pub fn min(&self) -> Option<f32> {
+        (self.count > 0).then_some(self.min)
+    }
+
+    /// Largest sample, or `None` if empty
+    pub fn max(&self) -> Option<f32> {
+        (self.count > 0).then_some(self.max)
+    }
+}
+
+/// Mean and variance over a sliding window of the last `window_size` samples
+///
+/// Welford updates are applied for each sample entering and leaving the
+/// window, so a push costs O(1) regardless of window size.
+#[derive(Debug, Clone)]
+pub struct RollingStats {
+    values: VecDeque<f32>,
+    window_size: usize,
+    mean: f64,
+    m2: f64,
+}
+
+impl RollingStats {
+    /// Create rolling statistics over `window_size` samples (at least 1)
+    pub fn new(window_size: usize) -> Self {
+        let window_size = window_size.max(1);
+        RollingStats {
+            values: VecDeque::with_capacity(window_size),
+            window_size,
+            mean: 0.0,
+            m2: 0.0,
+        }
+    }
+
+    /// Add a sample, dropping the oldest once the window is full (non-finite
+    /// values are ignored)
+    pub fn push(&mut self, value: f32) {
+        if !value.is_finite() {
+            return;
+        }
+        if self.values.len() >= self.window_size {
+            if let Some(old) = self.values.pop_front() {
+                self.remove(old as f64);
+            }
+        }
+        self.values.push_back(value);
+        self.add(value as f64);
+    }
+
+    fn add(&mut self, x: f64) {
+        let n = self.values.len() as f64;
+        let delta = x - self.mean;
+        self.mean += delta / n;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    fn remove(&mut self, x: f64) {
+        let n = self.values.len() as f64;
+        if n == 0.0 {
+            self.mean = 0.0;
+            self.m2 = 0.0;
+            return;
+        }
+        let delta = x - self.mean;
+        self.mean -= delta / n;
+        self.m2 = (self.m2 - delta * (x - self.mean)).max(0.0);
+    }
+
+    /// Number of samples in the window
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether the window holds no samples
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Window capacity
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    /// Samples in the window, oldest first
+    pub fn values(&self) -> &VecDeque<f32> {
+        &self.values
+    }
+
+    /// Mean of the window
+    pub fn mean(&self) -> f32 {
+        self.mean as f32
+    }
+
+    /// Population variance of the window
+    pub fn variance(&self) -> f32 {
+        if self.values.is_empty() {
+            0.0
+        } else {
+            (self.m2 / self.values.len() as f64) as f32
+        }
+    }
+
+    /// Population standard deviation of the window
+    pub fn std_dev(&self) -> f32 {
+        self.variance().sqrt()
+    }
+
+    /// Standard score of `value` against the window (0.0 with no spread)
+    pub fn z_score(&self, value: f32) -> f32 {
+        let std_dev = self.std_dev();
+        if std_dev == 0.0 {
+            return 0.0;
+        }
+        (value - self.mean()) / std_dev
+    }
+}
+
+/// Exponentially weighted moving average and variance
+#[derive(Debug, Clone)]
+pub struct Ewma {
+    alpha: f32,
+    mean: Option<f32>,
+    variance: f32,
+}
+
+impl Ewma {
+    /// Create an EWMA with smoothing factor `alpha` (0.0-1.0, higher reacts faster)
+    pub fn new(alpha: f32) -> Self {
+        Ewma {
+            alpha: alpha.clamp(f32::EPSILON, 1.0),
+            mean: None,
+            variance: 0.0,
+        }
+    }
+
+    /// Create an EWMA whose weights have the centre of mass of an `n`-sample window
+    pub fn with_span(n: usize) -> Self {
+        Self::new(2.0 / (n.max(1) as f32 + 1.0))
+    }
+
+    /// Add a sample (non-finite values are ignored)
+    pub fn push(&mut self, value: f32) {
+        if !value.is_finite() {
+            return;
+        }
+        match self.mean {
+            None => self.mean = Some(value),
+            Some(mean) => {
+                let delta = value - mean;
+                let increment = self.alpha * delta;
+                self.mean = Some(mean + increment);
+                self.variance = (1.0 - self.alpha) * (self.variance + delta * increment);
+            }
+        }
+    }
+
+    /// Smoothed value, or `None` before the first sample
+    pub fn mean(&self) -> Option<f32> {
+        self.mean
+    }
+
+    /// Exponentially weighted variance
+    pub fn variance(&self) -> f32 {
+        self.variance
+    }
+
+    /// Exponentially weighted standard deviation
+    pub fn std_dev(&self) -> f32 {
+        self.variance.sqrt()
+    }
+}
+
+/// Scale factor making the MAD a consistent estimator of a normal std dev
+pub const MAD_NORMAL_SCALE: f32 = 1.4826;
+
+/// Median and median absolute deviation (MAD) over a sliding window
+///
+/// Robust to the spikes and dropouts common in raw sensor channels. Samples
+/// are kept sorted alongside arrival order; the median is a lookup and the
+/// MAD is computed on demand in O(n).
+#[derive(Debug, Clone)]
+pub struct RollingMedian {
+    arrivals: VecDeque<f32>,
+    sorted: Vec<f32>,
+    window_size: usize,
+}
+
+impl RollingMedian {
+    /// Create a rolling median over `window_size` samples (at least 1)
+    pub fn new(window_size: usize) -> Self {
+        let window_size = window_size.max(1);
+        RollingMedian {
+            arrivals: VecDeque::with_capacity(window_size),
+            sorted: Vec::with_capacity(window_size),
+            window_size,
+        }
+    }
+
+    /// Add a sample, dropping the oldest once the window is full (NaN is ignored)
+    pub fn push(&mut self, value: f32) {
+        if value.is_nan() {
+            return;
+        }
+        if self.arrivals.len() >= self.window_size {
+            if let Some(old) = self.arrivals.pop_front() {
+                let index = self.sorted.partition_point(|&v| v < old);
+                self.sorted.remove(index);
+            }
+        }
+        self.arrivals.push_back(value);
+        let index = self.sorted.partition_point(|&v| v < value);
+        self.sorted.insert(index, value);
+    }
+
+    /// Number of samples in the window
+    pub fn len(&self) -> usize {
+        self.sorted.len()
+    }
+
+    /// Whether the window holds no samples
+    pub fn is_empty(&self) -> bool {
+        self.sorted.is_empty()
+    }
+
+    /// Median of the window, or `None` if empty
+    pub fn median(&self) -> Option<f32> {
+        median_of_sorted(&self.sorted)
+    }
+
+    /// Median absolute deviation from the median, or `None` if empty
+    pub fn mad(&self) -> Option<f32> {
+        let median = self.median()?;
+        let n = self.sorted.len();
+
+        // Deviations below and above the median are each already sorted, so
+        // merge the two runs instead of sorting all deviations.
+        let split = self.sorted.partition_point(|&v| v < median);
+        let mut below = self.sorted[..split].iter().rev().map(|&v| median - v).peekable();
+        let mut above = self.sorted[split..].iter().map(|&v| v - median).peekable();
+        let mut deviations = Vec::with_capacity(n);
+        while deviations.len() < n {
+            let next = match (below.peek(), above.peek()) {
+                (Some(&b), Some(&a)) if b <= a => below.next(),
+                (Some(_), Some(_)) | (None, Some(_)) => above.next(),
+                (Some(_), None) => below.next(),
+                (None, None) => None,
+            };
+            match next {
+                Some(deviation) => deviations.push(deviation),
+                None => break,
+            }
+        }
+
+        median_of_sorted(&deviations)
+    }
+
+    /// Robust standard score: (value - median) / (1.4826 × MAD)
+    ///
+    /// Returns 0.0 when the window is empty or has no spread.
+    pub fn robust_z_score(&self, value: f32) -> f32 {
+        match (self.median(), self.mad()) {
+            (Some(median), Some(mad)) if mad > 0.0 => (value - median) / (MAD_NORMAL_SCALE * mad),
+            _ => 0.0,
+        }
+    }
+}
+
+fn median_of_sorted(sorted: &[f32]) -> Option<f32> {
+    let n = sorted.len();
+    if n == 0 {
+        None
+    } else if n % 2 == 1 {
+        Some(sorted[n / 2])
+    } else {
+        Some(0.5 * (sorted[n / 2 - 1] + sorted[n / 2]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naive_mean_std(values: &[f32]) -> (f32, f32) {
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        let variance = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / values.len() as f32;
+        (mean, variance.sqrt())
+    }
+
+    #[test]
+    fn test_rolling_stats_z_score() {
+        let mut stats = RollingStats::new(10);
+
+        // Add values: 10, 20, 30, 40, 50 (mean = 30, std_dev ≈ 14.14)
+        for val in [10.0, 20.0, 30.0, 40.0, 50.0] {
+            stats.push(val);
+        }
+
+        assert!((stats.mean() - 30.0).abs() < 0.01);
+        assert!((stats.std_dev() - 14.142).abs() < 0.01);
+        assert!(stats.z_score(30.0).abs() < 0.01);
+        assert!(stats.z_score(100.0) > 2.0);
+    }
+
+    #[test]
+    fn test_rolling_stats_window() {
+        let mut stats = RollingStats::new(5);
+
+        // Add more values than window size
+        for i in 0..10 {
+            stats.push(i as f32);
+        }
+
+        // Should only keep last 5 values: 5, 6, 7, 8, 9
+        assert_eq!(stats.len(), 5);
+        assert_eq!(*stats.values().front().unwrap(), 5.0);
+        assert_eq!(*stats.values().back().unwrap(), 9.0);
+        assert!((stats.mean() - 7.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_rolling_stats_match_recomputation() {
+        let mut stats = RollingStats::new(50);
+        let samples: Vec<f32> = (0..10_000)
+            .map(|i| 11_000.0 + 500.0 * ((i as f32) * 0.37).sin())
+            .collect();
+
+        for (i, &value) in samples.iter().enumerate() {
+            stats.push(value);
+            if i % 997 == 0 || i == samples.len() - 1 {
+                let window = &samples[(i + 1).saturating_sub(50)..=i];
+                let (mean, std_dev) = naive_mean_std(window);
+                assert!((stats.mean() - mean).abs() < 0.01);
+                assert!((stats.std_dev() - std_dev).abs() < 0.05);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rolling_stats_ignore_non_finite() {
+        let mut stats = RollingStats::new(20);
+        stats.push(f32::NAN);
+        stats.push(f32::INFINITY);
+        for i in 0..50 {
+            stats.push(100.0 + (i % 5) as f32);
+        }
+        stats.push(f32::NEG_INFINITY);
+
+        assert_eq!(stats.len(), 20);
+        assert!((stats.mean() - 102.0).abs() < 1e-3);
+        assert!(stats.std_dev().is_finite() && stats.std_dev() > 0.0);
+        assert!(stats.z_score(200.0) > 3.0);
+    }
+
+    #[test]
+    fn test_running_stats() {
+        let mut stats = RunningStats::new();
+        assert_eq!(stats.min(), None);
+
+        for val in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+            stats.push(val);
+        }
+        assert_eq!(stats.count(), 8);
+        assert!((stats.mean() - 5.0).abs() < 1e-6);
+        assert!((stats.std_dev() - 2.0).abs() < 1e-6);
+        assert_eq!(stats.min(), Some(2.0));
+        assert_eq!(stats.max(), Some(9.0));
+    }
+
+    #[test]
+    fn test_ewma_tracks_level_shift() {
+        let mut ewma = Ewma::with_span(9);
+        assert_eq!(ewma.mean(), None);
+
+        for _ in 0..50 {
+            ewma.push(100.0);
+        }
+        assert!((ewma.mean().unwrap() - 100.0).abs() < 1e-3);
+        assert!(ewma.std_dev() < 1e-3);
+
+        for _ in 0..50 {
+            ewma.push(120.0);
+        }
+        assert!((ewma.mean().unwrap() - 120.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_rolling_median_and_mad() {
+        let mut median = RollingMedian::new(5);
+        assert_eq!(median.median(), None);
+
+        for val in [1.0, 2.0, 3.0, 4.0, 100.0] {
+            median.push(val);
+        }
+        assert_eq!(median.median(), Some(3.0));
+        // Deviations: 2, 1, 0, 1, 97 -> MAD = 1
+        assert_eq!(median.mad(), Some(1.0));
+
+        // Window slides: 2, 3, 4, 100, 5
+        median.push(5.0);
+        assert_eq!(median.len(), 5);
+        assert_eq!(median.median(), Some(4.0));
+
+        // A single spike barely moves the median, but scores as an outlier
+        assert!(median.robust_z_score(100.0) > 10.0);
+        assert!(median.robust_z_score(4.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_rolling_median_even_window() {
+        let mut median = RollingMedian::new(4);
+        for val in [4.0, 1.0, 3.0, 2.0] {
+            median.push(val);
+        }
+        assert_eq!(median.median(), Some(2.5));
+        assert_eq!(median.mad(), Some(1.0));
+    }
+}
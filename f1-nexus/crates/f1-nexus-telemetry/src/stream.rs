@@ -3,48 +3,88 @@
 //! Provides WebSocket server for broadcasting telemetry data to multiple clients
 //! with automatic reconnection, filtering, and low-latency delivery.
 
+use crate::{
+    bearer_token, AccessScope, AlertError, AlertEvent, AlertId, AlertRouter, AlertSink, AuthConfig, Channel, Condition,
+    Conflatable, ConnectionLimit, ConnectionPermit, Delivery, DeliveryHub, DeliveryPolicy, Frame, StreamEncoder,
+    StreamEncoding, SubscriberConfig, SubscriberMetrics, TelemetryBuffer, TelemetryError, TlsConfig,
+};
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
-        State,
+        Query, State,
     },
-    response::Response,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     routing::get,
     Router,
 };
-use f1_nexus_core::{CarId, SessionId, TelemetrySnapshot};
+use axum_server::tls_rustls::RustlsConfig;
+use chrono::{DateTime, Utc};
+use f1_nexus_core::{SessionId, TelemetrySnapshot};
 use futures::stream::StreamExt;
 use futures::SinkExt;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::sync::broadcast;
+use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
 /// WebSocket streaming server for telemetry data
 #[derive(Clone)]
 pub struct TelemetryStreamServer {
-    /// Broadcast channel for telemetry events
-    tx: broadcast::Sender<StreamMessage>,
+    /// Per-client queues for telemetry events
+    hub: DeliveryHub<StreamMessage>,
 
     /// Server configuration
     config: StreamConfig,
+
+    /// Router that client acknowledgements are forwarded to
+    alert_router: Option<Arc<AlertRouter>>,
+
+    /// History served to clients requesting a backfill
+    buffer: Option<Arc<TelemetryBuffer>>,
+
+    /// Open connections, limited to `config.max_clients`
+    connections: ConnectionLimit,
 }
 
 /// Stream configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StreamConfig {
-    /// Maximum number of connected clients
+    /// Maximum number of connected clients; further upgrades get a 503
     pub max_clients: usize,
 
-    /// Channel buffer size
+    /// Queue size per client, and the largest a client may ask for
     pub channel_buffer_size: usize,
 
-    /// Enable compression for messages
+    /// Delivery policy of clients that do not pick one with `?delivery=`
+    ///
+    /// Clients never get `Lossless`; it falls back to `DropOldest`.
+    #[serde(default)]
+    pub delivery: DeliveryPolicy,
+
+    /// Encoding of clients that do not pick one with `?encoding=`
+    #[serde(default)]
+    pub encoding: StreamEncoding,
+
+    /// Compress messages of clients that do not choose with `?compress=`
+    ///
+    /// Application-level raw DEFLATE (see `codec`), not the WebSocket
+    /// permessage-deflate extension.
     pub enable_compression: bool,
 
     /// Heartbeat interval (seconds)
     pub heartbeat_interval_secs: u64,
+
+    /// Client authentication and team scoping
+    #[serde(default)]
+    pub auth: AuthConfig,
+
+    /// Serve over TLS from `serve`
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
 }
 
 impl Default for StreamConfig {
@@ -52,14 +92,19 @@ impl Default for StreamConfig {
         StreamConfig {
             max_clients: 1000,
             channel_buffer_size: 10_000,
+            delivery: DeliveryPolicy::DropOldest,
+            encoding: StreamEncoding::Json,
             enable_compression: false,
             heartbeat_interval_secs: 30,
+            auth: AuthConfig::default(),
+            tls: None,
         }
     }
 }
 
 /// Messages sent over the WebSocket stream
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::large_enum_variant)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum StreamMessage {
     /// Telemetry snapshot
@@ -70,6 +115,15 @@ pub enum StreamMessage {
         snapshot: TelemetrySnapshot,
     },
 
+    /// Telemetry projected onto the channels a client subscribed to
+    TelemetryFields {
+        session_id: String,
+        car_id: u8,
+        timestamp: String,
+        lap: u16,
+        values: BTreeMap<String, f32>,
+    },
+
     /// Session started
     SessionStart {
         session_id: String,
@@ -82,6 +136,14 @@ pub enum StreamMessage {
         timestamp: String,
     },
 
+    /// Alert raised or acknowledged
+    Alert {
+        session_id: String,
+        car_id: u8,
+        #[serde(flatten)]
+        event: AlertEvent,
+    },
+
     /// Server heartbeat
     Heartbeat {
         timestamp: String,
@@ -93,10 +155,27 @@ pub enum StreamMessage {
         code: String,
         message: String,
     },
+
+    /// Messages dropped for this client since the previous one
+    Gap {
+        missed: u64,
+    },
+}
+
+impl Conflatable for StreamMessage {
+    type Key = (String, u8);
+
+    /// Telemetry conflates per session and car; everything else is kept
+    fn conflation_key(&self) -> Option<Self::Key> {
+        match self {
+            StreamMessage::Telemetry { session_id, car_id, .. } => Some((session_id.clone(), *car_id)),
+            _ => None,
+        }
+    }
 }
 
 /// Client subscription filter
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SubscriptionFilter {
     /// Filter by session ID
     pub session_id: Option<String>,
@@ -106,16 +185,20 @@ pub struct SubscriptionFilter {
 
     /// Only send anomalies
     pub anomalies_only: bool,
-}
 
-impl Default for SubscriptionFilter {
-    fn default() -> Self {
-        SubscriptionFilter {
-            session_id: None,
-            car_ids: None,
-            anomalies_only: false,
-        }
-    }
+    /// Only send these channels, as `telemetry_fields` messages
+    #[serde(default)]
+    pub channels: Option<Vec<Channel>>,
+
+    /// Highest telemetry rate per car (Hz); faster telemetry, live or
+    /// backfilled, is downsampled
+    #[serde(default)]
+    pub max_rate_hz: Option<f32>,
+
+    /// Only send telemetry matching this expression, e.g. `"speed > 300"`
+    /// (see `Condition::parse`)
+    #[serde(default)]
+    pub expression: Option<String>,
 }
 
 /// Client request messages
@@ -130,41 +213,188 @@ pub enum ClientRequest {
     /// Unsubscribe
     Unsubscribe,
 
+    /// Acknowledge an alert
+    Acknowledge {
+        alert_id: AlertId,
+        by: String,
+        #[serde(default)]
+        note: Option<String>,
+    },
+
+    /// Replay the last `seconds` of buffered telemetry matching the
+    /// current filter to this client
+    Backfill {
+        seconds: f64,
+    },
+
     /// Ping (client heartbeat)
     Ping {
         timestamp: String,
     },
 }
 
+/// Query parameters of the WebSocket upgrade request
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ConnectParams {
+    /// Delivery policy for this client
+    delivery: Option<DeliveryPolicy>,
+
+    /// Queue size for this client
+    capacity: Option<usize>,
+
+    /// Message encoding for this client
+    encoding: Option<StreamEncoding>,
+
+    /// Compress this client's messages
+    compress: Option<bool>,
+
+    /// Access token, for clients that cannot send an `Authorization` header
+    token: Option<String>,
+}
+
 /// WebSocket connection state
+#[derive(Default)]
 struct ConnectionState {
     filter: SubscriptionFilter,
-    car_count: usize,
+
+    /// Compiled `filter.expression`
+    condition: Option<Condition>,
+
+    /// Timestamp of the last telemetry sent per session and car
+    last_sent: HashMap<(String, u8), DateTime<Utc>>,
+
+    /// Same for backfill, which replays history behind the live clock
+    last_backfilled: HashMap<(String, u8), DateTime<Utc>>,
+
+    /// Cars this client is allowed to see, whatever it subscribes to
+    scope: AccessScope,
+}
+
+impl ConnectionState {
+    fn new(filter: SubscriptionFilter) -> Result<Self, TelemetryError> {
+        let condition = filter.expression.as_deref().map(Condition::parse).transpose()?;
+        Ok(ConnectionState {
+            filter,
+            condition,
+            last_sent: HashMap::new(),
+            last_backfilled: HashMap::new(),
+            scope: AccessScope::unrestricted(),
+        })
+    }
+
+    fn scoped(scope: AccessScope) -> Self {
+        ConnectionState {
+            scope,
+            ..ConnectionState::default()
+        }
+    }
+
+    /// Replace the subscription, keeping the access scope
+    fn resubscribe(&mut self, filter: SubscriptionFilter) -> Result<(), TelemetryError> {
+        let subscription = ConnectionState::new(filter)?;
+        *self = ConnectionState {
+            scope: std::mem::take(&mut self.scope),
+            ..subscription
+        };
+        Ok(())
+    }
+
+    /// Apply the subscription to an outgoing message: routing filter,
+    /// expression, downsampling and channel projection
+    ///
+    /// Backfill is downsampled on a clock of its own.
+    fn prepare(&mut self, msg: StreamMessage, backfill: bool) -> Option<StreamMessage> {
+        if message_car(&msg).is_some_and(|car_id| !self.scope.allows(car_id)) {
+            return None;
+        }
+        if !should_send_message(&msg, &self.filter) {
+            return None;
+        }
+        let StreamMessage::Telemetry { session_id, car_id, snapshot } = msg else {
+            return Some(msg);
+        };
+
+        if self.condition.as_ref().is_some_and(|c| !c.holds(&snapshot)) {
+            return None;
+        }
+
+        if let Some(rate) = self.filter.max_rate_hz.filter(|rate| *rate > 0.0) {
+            let interval = chrono::Duration::microseconds((1e6 / rate as f64) as i64);
+            let key = (session_id.clone(), car_id);
+            let clock = if backfill { &mut self.last_backfilled } else { &mut self.last_sent };
+            match clock.get(&key) {
+                // Late live snapshots pass without moving the clock
+                Some(last) if !backfill && snapshot.timestamp < *last => {}
+                Some(last) if (snapshot.timestamp - *last).abs() < interval => return None,
+                _ => {
+                    clock.insert(key, snapshot.timestamp);
+                }
+            }
+        }
+
+        Some(match &self.filter.channels {
+            Some(channels) => StreamMessage::TelemetryFields {
+                session_id,
+                car_id,
+                timestamp: snapshot.timestamp.to_rfc3339(),
+                lap: snapshot.lap.0,
+                values: channels
+                    .iter()
+                    .map(|channel| (channel.name().to_string(), channel.value(&snapshot)))
+                    .collect(),
+            },
+            None => StreamMessage::Telemetry { session_id, car_id, snapshot },
+        })
+    }
 }
 
 impl TelemetryStreamServer {
     /// Create new streaming server
     pub fn new(config: StreamConfig) -> Self {
-        let (tx, _) = broadcast::channel(config.channel_buffer_size);
-
         TelemetryStreamServer {
-            tx,
+            hub: DeliveryHub::new(),
+            alert_router: None,
+            buffer: None,
+            connections: ConnectionLimit::new(config.max_clients),
             config,
         }
     }
 
+    /// Serve backfill requests from `buffer`
+    pub fn with_buffer(mut self, buffer: Arc<TelemetryBuffer>) -> Self {
+        self.buffer = Some(buffer);
+        self
+    }
+
+    /// Forward client alert acknowledgements to `router`
+    pub fn with_alert_router(mut self, router: Arc<AlertRouter>) -> Self {
+        self.alert_router = Some(router);
+        self
+    }
+
+    /// Alert sink that broadcasts alert events to connected clients
+    pub fn alert_sink(&self) -> WebSocketAlertSink {
+        WebSocketAlertSink {
+            hub: self.hub.clone(),
+        }
+    }
+
     /// Broadcast telemetry snapshot
+    ///
+    /// Never waits: full lossless client queues drop their oldest message.
     pub fn broadcast_telemetry(&self, snapshot: TelemetrySnapshot) -> Result<(), StreamError> {
-        let msg = StreamMessage::Telemetry {
-            session_id: snapshot.session_id.0.to_string(),
-            car_id: snapshot.car_id.0,
-            snapshot,
-        };
-
-        self.tx
-            .send(msg)
-            .map_err(|_| StreamError::BroadcastError("No active subscribers".to_string()))?;
+        if self.hub.send(telemetry_message(snapshot)) == 0 {
+            return Err(StreamError::BroadcastError("No active subscribers".to_string()));
+        }
+        Ok(())
+    }
 
+    /// Broadcast telemetry snapshot, waiting while a lossless client's
+    /// queue is full
+    pub async fn publish_telemetry(&self, snapshot: TelemetrySnapshot) -> Result<(), StreamError> {
+        if self.hub.publish(telemetry_message(snapshot)).await == 0 {
+            return Err(StreamError::BroadcastError("No active subscribers".to_string()));
+        }
         Ok(())
     }
 
@@ -175,7 +405,7 @@ impl TelemetryStreamServer {
             timestamp: chrono::Utc::now().to_rfc3339(),
         };
 
-        let _ = self.tx.send(msg);
+        self.hub.send(msg);
     }
 
     /// Broadcast session end event
@@ -185,12 +415,49 @@ impl TelemetryStreamServer {
             timestamp: chrono::Utc::now().to_rfc3339(),
         };
 
-        let _ = self.tx.send(msg);
+        self.hub.send(msg);
+    }
+
+    /// Get the server configuration
+    pub fn config(&self) -> &StreamConfig {
+        &self.config
     }
 
     /// Get subscriber count
     pub fn subscriber_count(&self) -> usize {
-        self.tx.receiver_count()
+        self.hub.subscriber_count()
+    }
+
+    /// Delivery counters per connected client
+    pub fn delivery_metrics(&self) -> Vec<SubscriberMetrics> {
+        self.hub.metrics()
+    }
+
+    /// Queue settings for a WebSocket client
+    ///
+    /// A lossless client would stall `publish_telemetry` for everyone else, so
+    /// lossless delivery is left to server-side subscribers such as the
+    /// recorder, and a client's queue is capped at `channel_buffer_size`.
+    fn client_config(&self, params: &ConnectParams) -> SubscriberConfig {
+        let policy = match params.delivery.unwrap_or(self.config.delivery) {
+            DeliveryPolicy::Lossless => DeliveryPolicy::DropOldest,
+            policy => policy,
+        };
+        let max_capacity = self.config.channel_buffer_size.max(1);
+        let capacity = params.capacity.unwrap_or(max_capacity).clamp(1, max_capacity);
+        SubscriberConfig::new("websocket", policy, capacity)
+    }
+
+    fn client_encoder(&self, params: &ConnectParams) -> StreamEncoder {
+        StreamEncoder::new(
+            params.encoding.unwrap_or(self.config.encoding),
+            params.compress.unwrap_or(self.config.enable_compression),
+        )
+    }
+
+    /// Number of open WebSocket connections
+    pub fn connection_count(&self) -> usize {
+        self.connections.open()
     }
 
     /// Create Axum router for WebSocket endpoint
@@ -199,53 +466,121 @@ impl TelemetryStreamServer {
             .route("/ws/telemetry", get(websocket_handler))
             .with_state(Arc::new(self))
     }
+
+    /// Serve the WebSocket endpoint on `addr`, over TLS when `config.tls` is set
+    pub async fn serve(self, addr: SocketAddr) -> Result<(), StreamError> {
+        let tls = self.config.tls.clone();
+        let app = self.router();
+        match tls {
+            Some(tls) => {
+                let config = tls.server_config().map_err(|e| StreamError::ConnectionError(e.to_string()))?;
+                info!("Telemetry stream listening on wss://{}", addr);
+                axum_server::bind_rustls(addr, RustlsConfig::from_config(config))
+                    .serve(app.into_make_service())
+                    .await
+            }
+            None => {
+                let listener = tokio::net::TcpListener::bind(addr)
+                    .await
+                    .map_err(|e| StreamError::ConnectionError(e.to_string()))?;
+                info!("Telemetry stream listening on ws://{}", addr);
+                axum::serve(listener, app).await
+            }
+        }
+        .map_err(|e| StreamError::ConnectionError(e.to_string()))
+    }
 }
 
 /// WebSocket handler
 async fn websocket_handler(
     ws: WebSocketUpgrade,
+    headers: HeaderMap,
+    Query(params): Query<ConnectParams>,
     State(server): State<Arc<TelemetryStreamServer>>,
 ) -> Response {
-    ws.on_upgrade(move |socket| handle_socket(socket, server))
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(bearer_token)
+        .or(params.token.as_deref());
+    let scope = match server.config.auth.authenticate(token) {
+        Ok(scope) => scope,
+        Err(e) => {
+            warn!("Rejected WebSocket connection: {}", e);
+            return (StatusCode::UNAUTHORIZED, e.to_string()).into_response();
+        }
+    };
+    let Some(permit) = server.connections.try_acquire() else {
+        warn!("Rejected WebSocket connection: {} clients connected", server.connections.max());
+        return (StatusCode::SERVICE_UNAVAILABLE, "Too many clients").into_response();
+    };
+
+    let delivery = server.client_config(&params);
+    let encoder = server.client_encoder(&params);
+    ws.on_upgrade(move |socket| handle_socket(socket, server, delivery, encoder, scope, permit))
 }
 
 /// Handle individual WebSocket connection
-async fn handle_socket(socket: WebSocket, server: Arc<TelemetryStreamServer>) {
-    info!("New WebSocket connection established");
+async fn handle_socket(
+    socket: WebSocket,
+    server: Arc<TelemetryStreamServer>,
+    delivery: SubscriberConfig,
+    mut encoder: StreamEncoder,
+    scope: AccessScope,
+    _permit: ConnectionPermit,
+) {
+    info!(
+        "New WebSocket connection established (team {}, {:?} delivery, {} encoding)",
+        scope.team.as_deref().unwrap_or("-"),
+        delivery.policy,
+        encoder.encoding().name()
+    );
 
     let (mut sender, mut receiver) = socket.split();
-    let mut rx = server.tx.subscribe();
-    let state = Arc::new(RwLock::new(ConnectionState {
-        filter: SubscriptionFilter::default(),
-        car_count: 0,
-    }));
+    let mut rx = server.hub.subscribe(delivery);
+    let state = Arc::new(RwLock::new(ConnectionState::scoped(scope)));
 
-    // Spawn task to receive messages from broadcast channel and send to client
+    // Spawn task to receive messages from the client's queue and send them
+    let (direct_tx, mut direct_rx) = mpsc::unbounded_channel::<Direct>();
     let state_read = Arc::clone(&state);
     let mut send_task = tokio::spawn(async move {
-        while let Ok(msg) = rx.recv().await {
-            // Apply filter
-            let filter = state_read.read().filter.clone();
-            if !should_send_message(&msg, &filter) {
+        loop {
+            // Queued messages, plus replies meant for this client only
+            let (msg, backfill) = tokio::select! {
+                msg = rx.recv() => match msg {
+                    Some(Delivery::Message(msg)) => (msg, false),
+                    Some(Delivery::Gap { missed }) => (StreamMessage::Gap { missed }, false),
+                    None => break,
+                },
+                Some(direct) = direct_rx.recv() => match direct {
+                    Direct::Reply(msg) => (msg, false),
+                    Direct::Backfill(msg) => (msg, true),
+                },
+            };
+
+            // Apply this client's subscription
+            let Some(msg) = state_read.write().prepare(msg, backfill) else {
                 continue;
-            }
+            };
 
-            // Serialize and send
-            match serde_json::to_string(&msg) {
-                Ok(json) => {
-                    if sender.send(Message::Text(json)).await.is_err() {
-                        break;
-                    }
-                }
+            // Encode and send
+            let frame = match encoder.encode(&msg) {
+                Ok(Frame::Text(text)) => Message::Text(text),
+                Ok(Frame::Binary(bytes)) => Message::Binary(bytes),
                 Err(e) => {
-                    error!("Failed to serialize message: {}", e);
+                    error!("Failed to encode message: {}", e);
+                    continue;
                 }
+            };
+            if sender.send(frame).await.is_err() {
+                break;
             }
         }
     });
 
     // Spawn task to receive messages from client
-    let tx_clone = server.tx.clone();
+    let alert_router = server.alert_router.clone();
+    let buffer = server.buffer.clone();
     let state_write = Arc::clone(&state);
     let mut recv_task = tokio::spawn(async move {
         while let Some(Ok(msg)) = receiver.next().await {
@@ -253,11 +588,55 @@ async fn handle_socket(socket: WebSocket, server: Arc<TelemetryStreamServer>) {
                 match serde_json::from_str::<ClientRequest>(&text) {
                     Ok(ClientRequest::Subscribe { filter }) => {
                         debug!("Client subscribed with filter: {:?}", filter);
-                        state_write.write().filter = filter;
+                        let subscribed = state_write.write().resubscribe(filter);
+                        if let Err(e) = subscribed {
+                            // Keep the previous subscription
+                            let _ = direct_tx.send(Direct::Reply(StreamMessage::Error {
+                                code: "invalid_filter".to_string(),
+                                message: e.to_string(),
+                            }));
+                        }
                     }
                     Ok(ClientRequest::Unsubscribe) => {
                         debug!("Client unsubscribed");
-                        state_write.write().filter = SubscriptionFilter::default();
+                        let _ = state_write.write().resubscribe(SubscriptionFilter::default());
+                    }
+                    Ok(ClientRequest::Acknowledge { alert_id, by, note }) => {
+                        match &alert_router {
+                            Some(router) => {
+                                let scope = state_write.read().scope.clone();
+                                if router.get(alert_id).is_some_and(|alert| !scope.allows(alert.anomaly.car_id.0)) {
+                                    let _ = direct_tx.send(Direct::Reply(StreamMessage::Error {
+                                        code: "forbidden".to_string(),
+                                        message: format!("Alert {} is outside this client's access", alert_id),
+                                    }));
+                                } else if let Err(e) = router.acknowledge(alert_id, by, note) {
+                                    warn!("Failed to acknowledge alert: {}", e);
+                                }
+                            }
+                            None => warn!("Alert acknowledgement received without an alert router"),
+                        }
+                    }
+                    Ok(ClientRequest::Backfill { seconds }) => {
+                        let Some(buffer) = &buffer else {
+                            warn!("Backfill requested but no telemetry buffer is attached");
+                            continue;
+                        };
+                        let Some(window) = backfill_window(seconds, buffer.config().retention) else {
+                            let _ = direct_tx.send(Direct::Reply(StreamMessage::Error {
+                                code: "invalid_backfill".to_string(),
+                                message: format!("Invalid backfill window: {} seconds", seconds),
+                            }));
+                            continue;
+                        };
+                        let filter = state_write.read().filter.clone();
+                        let snapshots = backfill_messages(buffer, &filter, window);
+                        debug!("Backfilling {} snapshots", snapshots.len());
+                        for msg in snapshots {
+                            if direct_tx.send(Direct::Backfill(msg)).is_err() {
+                                break;
+                            }
+                        }
                     }
                     Ok(ClientRequest::Ping { timestamp }) => {
                         debug!("Received ping from client at {}", timestamp);
@@ -269,7 +648,7 @@ async fn handle_socket(socket: WebSocket, server: Arc<TelemetryStreamServer>) {
                                 .unwrap()
                                 .as_millis() as u64,
                         };
-                        let _ = tx_clone.send(pong);
+                        let _ = direct_tx.send(Direct::Reply(pong));
                     }
                     Err(e) => {
                         warn!("Failed to parse client request: {}", e);
@@ -292,13 +671,85 @@ async fn handle_socket(socket: WebSocket, server: Arc<TelemetryStreamServer>) {
     info!("WebSocket connection closed");
 }
 
+/// Messages for one client that bypass the shared queue
+enum Direct {
+    /// Reply to a client request
+    Reply(StreamMessage),
+
+    /// Buffered history requested by the client
+    Backfill(StreamMessage),
+}
+
+pub(crate) fn telemetry_message(snapshot: TelemetrySnapshot) -> StreamMessage {
+    StreamMessage::Telemetry {
+        session_id: snapshot.session_id.0.to_string(),
+        car_id: snapshot.car_id.0,
+        snapshot,
+    }
+}
+
+/// Backfill window for a client request, capped at the buffer's retention
+///
+/// Returns `None` for negative or non-finite requests.
+fn backfill_window(seconds: f64, retention: std::time::Duration) -> Option<std::time::Duration> {
+    if !seconds.is_finite() || seconds < 0.0 {
+        return None;
+    }
+    std::time::Duration::try_from_secs_f64(seconds.min(retention.as_secs_f64())).ok()
+}
+
+/// Buffered telemetry matching `filter` from the last `window`
+fn backfill_messages(
+    buffer: &TelemetryBuffer,
+    filter: &SubscriptionFilter,
+    window: std::time::Duration,
+) -> Vec<StreamMessage> {
+    if filter.anomalies_only {
+        return Vec::new();
+    }
+
+    buffer
+        .backfill(window, |session_id, car_id| {
+            filter.session_id.as_ref().map_or(true, |s| *s == session_id.0.to_string())
+                && filter.car_ids.as_ref().map_or(true, |cars| cars.contains(&car_id.0))
+        })
+        .into_iter()
+        .map(|snapshot| StreamMessage::Telemetry {
+            session_id: snapshot.session_id.0.to_string(),
+            car_id: snapshot.car_id.0,
+            snapshot,
+        })
+        .collect()
+}
+
+/// Car a message is about, if any
+fn message_car(msg: &StreamMessage) -> Option<u8> {
+    match msg {
+        StreamMessage::Telemetry { car_id, .. }
+        | StreamMessage::TelemetryFields { car_id, .. }
+        | StreamMessage::Alert { car_id, .. } => Some(*car_id),
+        _ => None,
+    }
+}
+
 /// Check if message should be sent based on filter
 fn should_send_message(msg: &StreamMessage, filter: &SubscriptionFilter) -> bool {
     match msg {
+        StreamMessage::Telemetry { .. } | StreamMessage::TelemetryFields { .. } if filter.anomalies_only => false,
         StreamMessage::Telemetry {
             session_id,
             car_id,
             ..
+        }
+        | StreamMessage::TelemetryFields {
+            session_id,
+            car_id,
+            ..
+        }
+        | StreamMessage::Alert {
+            session_id,
+            car_id,
+            ..
         } => {
             // Check session filter
             if let Some(ref filter_session) = filter.session_id {
@@ -325,13 +776,39 @@ fn should_send_message(msg: &StreamMessage, filter: &SubscriptionFilter) -> bool
                 true
             }
         }
-        StreamMessage::Heartbeat { .. } | StreamMessage::Error { .. } => {
-            // Always send heartbeats and errors
+        StreamMessage::Heartbeat { .. } | StreamMessage::Error { .. } | StreamMessage::Gap { .. } => {
+            // Always send heartbeats, errors and gap notices
             true
         }
     }
 }
 
+/// Alert sink broadcasting to WebSocket clients
+///
+/// Created with [`TelemetryStreamServer::alert_sink`].
+pub struct WebSocketAlertSink {
+    hub: DeliveryHub<StreamMessage>,
+}
+
+impl AlertSink for WebSocketAlertSink {
+    fn name(&self) -> &str {
+        "websocket"
+    }
+
+    fn deliver(&self, event: &AlertEvent) -> Result<(), AlertError> {
+        let anomaly = &event.alert().anomaly;
+        let msg = StreamMessage::Alert {
+            session_id: anomaly.session_id.0.to_string(),
+            car_id: anomaly.car_id.0,
+            event: event.clone(),
+        };
+
+        // No connected clients is not a delivery failure
+        self.hub.send(msg);
+        Ok(())
+    }
+}
+
 /// Streaming errors
 #[derive(Debug, thiserror::Error)]
 pub enum StreamError {
@@ -349,7 +826,7 @@ pub enum StreamError {
 mod tests {
     use super::*;
     use f1_nexus_core::{
-        AeroData, BrakeData, DriverInputs, DrsStatus, FuelData, LapNumber, MotionData,
+        AeroData, BrakeData, CarId, DriverInputs, DrsStatus, FuelData, LapNumber, MotionData,
         Position, PowerUnitData, TireCompound, TireData, TireSensor,
     };
     use f1_nexus_core::telemetry::ErsMode;
@@ -518,9 +995,341 @@ mod tests {
             session_id: Some("test".to_string()),
             car_ids: Some(vec![1]),
             anomalies_only: true,
+            ..Default::default()
         };
 
         // Heartbeat should always be sent regardless of filter
         assert!(should_send_message(&msg, &filter));
     }
+
+    #[test]
+    fn test_backfill_messages() {
+        let buffer = TelemetryBuffer::default();
+        let mut snapshot = create_test_snapshot();
+        let start = snapshot.timestamp;
+        for car in 1..=2 {
+            snapshot.car_id = CarId::new(car).unwrap();
+            for i in 0..50 {
+                snapshot.timestamp = start + chrono::Duration::milliseconds(i * 100);
+                buffer.push(&snapshot);
+            }
+        }
+
+        let filter = SubscriptionFilter {
+            car_ids: Some(vec![2]),
+            ..Default::default()
+        };
+        let messages = backfill_messages(&buffer, &filter, std::time::Duration::from_secs(1));
+        assert_eq!(messages.len(), 11);
+        assert!(messages.iter().all(|msg| should_send_message(msg, &filter)));
+
+        let all = backfill_messages(&buffer, &SubscriptionFilter::default(), std::time::Duration::from_secs(1));
+        assert_eq!(all.len(), 22);
+    }
+
+    #[test]
+    fn test_backfill_window_validation() {
+        let retention = std::time::Duration::from_secs(60);
+        assert_eq!(backfill_window(5.0, retention), Some(std::time::Duration::from_secs(5)));
+        assert_eq!(backfill_window(1e30, retention), Some(retention));
+        assert_eq!(backfill_window(f64::INFINITY, retention), None);
+        assert_eq!(backfill_window(f64::NAN, retention), None);
+        assert_eq!(backfill_window(-1.0, retention), None);
+    }
+
+    #[test]
+    fn test_alert_sink_broadcasts_alerts() {
+        use crate::{AlertRouter, AlertRouterConfig, AnomalyInfo, AnomalySeverity, SinkConfig};
+
+        let server = TelemetryStreamServer::new(StreamConfig::default());
+        let mut rx = server.hub.subscribe(SubscriberConfig::default());
+        let router = AlertRouter::new(AlertRouterConfig::default())
+            .with_sink(server.alert_sink(), SinkConfig::default());
+
+        let snapshot = create_test_snapshot();
+        let alert = router
+            .route(AnomalyInfo {
+                car_id: snapshot.car_id,
+                session_id: snapshot.session_id,
+                segment: None,
+                field: "engine_temp".to_string(),
+                expected_range: (90.0, 110.0),
+                actual_value: 130.0,
+                severity: AnomalySeverity::High,
+                timestamp: snapshot.timestamp,
+            })
+            .unwrap();
+
+        let Some(Delivery::Message(msg)) = rx.try_recv() else {
+            panic!("alert was not delivered");
+        };
+        let json = serde_json::to_string(&msg).unwrap();
+        match serde_json::from_str::<StreamMessage>(&json).unwrap() {
+            StreamMessage::Alert { car_id, event: AlertEvent::Raised(raised), .. } => {
+                assert_eq!(car_id, 1);
+                assert_eq!(raised.id, alert.id);
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        // Alerts pass an anomalies-only filter, telemetry does not
+        let filter = SubscriptionFilter {
+            anomalies_only: true,
+            car_ids: Some(vec![1]),
+            ..Default::default()
+        };
+        assert!(should_send_message(&msg, &filter));
+        let telemetry = StreamMessage::Telemetry {
+            session_id: snapshot.session_id.0.to_string(),
+            car_id: 1,
+            snapshot,
+        };
+        assert!(!should_send_message(&telemetry, &filter));
+    }
+
+    #[test]
+    fn test_client_delivery_policies() {
+        let server = TelemetryStreamServer::new(StreamConfig {
+            channel_buffer_size: 2,
+            ..StreamConfig::default()
+        });
+        let mut dashboard = server.hub.subscribe(server.client_config(&ConnectParams {
+            delivery: Some(DeliveryPolicy::Conflate),
+            ..ConnectParams::default()
+        }));
+        let mut default = server.hub.subscribe(server.client_config(&ConnectParams::default()));
+
+        let snapshot = create_test_snapshot();
+        for speed in [200.0, 210.0, 220.0] {
+            let mut snapshot = snapshot.clone();
+            snapshot.motion.speed = speed;
+            server.broadcast_telemetry(snapshot).unwrap();
+        }
+
+        match dashboard.try_recv() {
+            Some(Delivery::Message(StreamMessage::Telemetry { snapshot, .. })) => assert_eq!(snapshot.motion.speed, 220.0),
+            other => panic!("unexpected delivery: {:?}", other),
+        }
+        assert!(dashboard.try_recv().is_none());
+
+        // Drop-oldest clients are told how much they missed
+        let Some(Delivery::Gap { missed }) = default.try_recv() else {
+            panic!("expected a gap");
+        };
+        assert_eq!(missed, 1);
+        let gap = serde_json::to_value(StreamMessage::Gap { missed }).unwrap();
+        assert_eq!(gap, serde_json::json!({"type": "gap", "missed": 1}));
+
+        let metrics = server.delivery_metrics();
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics[0].conflated, 2);
+        assert_eq!(metrics[1].dropped, 1);
+    }
+
+    #[test]
+    fn test_client_delivery_limits() {
+        let server = TelemetryStreamServer::new(StreamConfig {
+            channel_buffer_size: 64,
+            delivery: DeliveryPolicy::Lossless,
+            ..StreamConfig::default()
+        });
+
+        let lossless = server.client_config(&ConnectParams {
+            delivery: Some(DeliveryPolicy::Lossless),
+            capacity: Some(usize::MAX),
+            ..ConnectParams::default()
+        });
+        assert_eq!(lossless.policy, DeliveryPolicy::DropOldest);
+        assert_eq!(lossless.capacity, 64);
+
+        let default = server.client_config(&ConnectParams::default());
+        assert_eq!(default.policy, DeliveryPolicy::DropOldest);
+
+        let small = server.client_config(&ConnectParams {
+            delivery: Some(DeliveryPolicy::Conflate),
+            capacity: Some(0),
+            ..ConnectParams::default()
+        });
+        assert_eq!(small.policy, DeliveryPolicy::Conflate);
+        assert_eq!(small.capacity, 1);
+    }
+
+    #[test]
+    fn test_subscription_projection_and_expression() {
+        let mut state = ConnectionState::new(SubscriptionFilter {
+            channels: Some(vec![Channel::Speed, Channel::TireTempMax]),
+            expression: Some("speed > 300".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut snapshot = create_test_snapshot();
+        assert!(state.prepare(telemetry_message(snapshot.clone()), false).is_none());
+
+        snapshot.motion.speed = 320.0;
+        let full = serde_json::to_string(&telemetry_message(snapshot.clone())).unwrap();
+        let msg = state.prepare(telemetry_message(snapshot), false).unwrap();
+        let StreamMessage::TelemetryFields { values, lap, .. } = &msg else {
+            panic!("expected projected telemetry, got {:?}", msg);
+        };
+        assert_eq!(*lap, 10);
+        assert_eq!(values.len(), 2);
+        assert_eq!(values["speed"], 320.0);
+        assert_eq!(values["tire_temp_max"], 100.0);
+
+        // Projection is what saves the bandwidth
+        let projected = serde_json::to_string(&msg).unwrap();
+        assert!(projected.len() * 4 < full.len(), "{} vs {}", projected.len(), full.len());
+
+        // Non-telemetry messages are untouched
+        let heartbeat = StreamMessage::Heartbeat {
+            timestamp: Utc::now().to_rfc3339(),
+            server_time_ms: 0,
+        };
+        assert!(state.prepare(heartbeat, false).is_some());
+
+        let request = r#"{"type":"subscribe","filter":{"anomalies_only":false,"expression":"speed >> 3"}}"#;
+        let ClientRequest::Subscribe { filter } = serde_json::from_str(request).unwrap() else {
+            panic!("expected a subscribe request");
+        };
+        assert!(ConnectionState::new(filter).is_err());
+    }
+
+    #[test]
+    fn test_subscription_downsampling() {
+        let mut state = ConnectionState::new(SubscriptionFilter {
+            max_rate_hz: Some(10.0),
+            ..Default::default()
+        })
+        .unwrap();
+
+        // 1 kHz for one second, two cars
+        let start = Utc::now();
+        let mut sent = HashMap::new();
+        for car in [1, 2] {
+            let mut snapshot = create_test_snapshot();
+            snapshot.car_id = CarId::new(car).unwrap();
+            for ms in 0..1000 {
+                snapshot.timestamp = start + chrono::Duration::milliseconds(ms);
+                if state.prepare(telemetry_message(snapshot.clone()), false).is_some() {
+                    *sent.entry(car).or_insert(0) += 1;
+                }
+            }
+
+            // A late live snapshot still goes through
+            snapshot.timestamp = start - chrono::Duration::seconds(5);
+            assert!(state.prepare(telemetry_message(snapshot.clone()), false).is_some());
+
+            // Backfill of the same second is downsampled on its own clock
+            let mut backfilled = 0;
+            for ms in 0..1000 {
+                snapshot.timestamp = start + chrono::Duration::milliseconds(ms);
+                if state.prepare(telemetry_message(snapshot.clone()), true).is_some() {
+                    backfilled += 1;
+                }
+            }
+            assert_eq!(backfilled, 10);
+        }
+        assert_eq!(sent[&1], 10);
+        assert_eq!(sent[&2], 10);
+    }
+
+    #[test]
+    fn test_access_scope_limits_cars() {
+        let mut state = ConnectionState::scoped(AccessScope {
+            team: Some("mclaren".to_string()),
+            car_ids: Some(vec![4]),
+        });
+        let mut snapshot = create_test_snapshot();
+        assert!(state.prepare(telemetry_message(snapshot.clone()), false).is_none());
+        snapshot.car_id = CarId::new(4).unwrap();
+        assert!(state.prepare(telemetry_message(snapshot.clone()), false).is_some());
+
+        // Subscribing to other cars cannot widen the scope
+        state
+            .resubscribe(SubscriptionFilter {
+                car_ids: Some(vec![1, 4]),
+                channels: Some(vec![Channel::Speed]),
+                ..Default::default()
+            })
+            .unwrap();
+        snapshot.car_id = CarId::new(1).unwrap();
+        assert!(state.prepare(telemetry_message(snapshot.clone()), false).is_none());
+        snapshot.car_id = CarId::new(4).unwrap();
+        assert!(matches!(
+            state.prepare(telemetry_message(snapshot), false),
+            Some(StreamMessage::TelemetryFields { car_id: 4, .. })
+        ));
+
+        assert!(state.resubscribe(SubscriptionFilter::default()).is_ok());
+        assert_eq!(state.scope.car_ids, Some(vec![4]));
+    }
+
+    #[tokio::test]
+    async fn test_stream_client_negotiates_encoding() {
+        let server = TelemetryStreamServer::new(StreamConfig::default());
+        let hub = server.hub.clone();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/ws/telemetry", listener.local_addr().unwrap());
+        let server_handle = tokio::spawn(async move { axum::serve(listener, server.router()).await });
+
+        let mut client = crate::StreamClient::connect(&url, StreamEncoding::Delta, true).await.unwrap();
+        while hub.subscriber_count() == 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+
+        let mut snapshot = create_test_snapshot();
+        for speed in [250.0, 251.0, 252.0] {
+            snapshot.motion.speed = speed;
+            hub.send(telemetry_message(snapshot.clone()));
+        }
+        for speed in [250.0, 251.0, 252.0] {
+            match client.next().await.unwrap().unwrap() {
+                StreamMessage::Telemetry { snapshot, .. } => assert_eq!(snapshot.motion.speed, speed),
+                other => panic!("unexpected message: {:?}", other),
+            }
+        }
+
+        server_handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_stream_auth_and_client_limit() {
+        let server = TelemetryStreamServer::new(StreamConfig {
+            max_clients: 1,
+            auth: AuthConfig {
+                api_keys: [("k-mcl".to_string(), "mclaren".to_string())].into_iter().collect(),
+                teams: [("mclaren".to_string(), vec![4, 81])].into_iter().collect(),
+                ..AuthConfig::default()
+            },
+            ..StreamConfig::default()
+        });
+        let hub = server.hub.clone();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/ws/telemetry", listener.local_addr().unwrap());
+        let server_handle = tokio::spawn(async move { axum::serve(listener, server.router()).await });
+
+        let err = crate::StreamClient::connect(&url, StreamEncoding::Json, false).await.err().unwrap();
+        assert!(err.to_string().contains("401"), "{}", err);
+
+        let scoped_url = format!("{}?token=k-mcl", url);
+        let mut client = crate::StreamClient::connect(&scoped_url, StreamEncoding::Json, false).await.unwrap();
+        let err = crate::StreamClient::connect(&scoped_url, StreamEncoding::Json, false).await.err().unwrap();
+        assert!(err.to_string().contains("503"), "{}", err);
+        while hub.subscriber_count() == 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+
+        let mut snapshot = create_test_snapshot();
+        for car in [1, 4] {
+            snapshot.car_id = CarId::new(car).unwrap();
+            hub.send(telemetry_message(snapshot.clone()));
+        }
+        match client.next().await.unwrap().unwrap() {
+            StreamMessage::Telemetry { car_id, .. } => assert_eq!(car_id, 4),
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        server_handle.abort();
+    }
 }
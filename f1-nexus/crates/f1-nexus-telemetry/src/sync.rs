@@ -0,0 +1,768 @@
+//! Multi-source telemetry synchronization
+//!
+//! Feeds (the game UDP stream, OpenF1, replayed files) stamp snapshots with
+//! their own clocks and deliver them with different delays. The
+//! `TelemetrySynchronizer` maps every source onto the local clock and
+//! releases snapshots in timestamp order:
+//!
+//! - the clock offset of each source is the smallest `arrival - timestamp`
+//!   seen over a sliding window, i.e. clock skew plus the minimum transport
+//!   delay; snapshots further than `max_clock_offset` from the local clock
+//!   are rejected so they cannot drag the minimum or the watermark
+//! - a reorder buffer holds snapshots until the watermark passes them; the
+//!   watermark is the lowest `latest timestamp - max_delay` over the active
+//!   sources
+//! - snapshots older than the last released one are late and handled by the
+//!   configured `LatePolicy`
+//!
+//! The `Resampler` then puts every car on a common time grid so cross-car
+//! values (gaps, relative speeds) are read at the same instant.
+
+use chrono::{DateTime, Utc};
+use f1_nexus_core::{CarId, TelemetrySnapshot, TireSensor};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, VecDeque};
+use std::time::Duration;
+
+/// What to do with a snapshot older than the last released one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LatePolicy {
+    /// Discard it
+    #[default]
+    Drop,
+    /// Release it at once, out of order, flagged late
+    Emit,
+    /// Move it to the last released timestamp and release it, flagged late
+    Clamp,
+}
+
+/// Synchronizer configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConfig {
+    /// How long a snapshot waits for older ones from other sources
+    pub max_delay: Duration,
+
+    pub late_policy: LatePolicy,
+
+    /// Arrivals the clock offset minimum is taken over, per source
+    pub offset_window: usize,
+
+    /// Estimate clock offsets; otherwise only fixed offsets apply
+    pub estimate_offsets: bool,
+
+    /// Sources silent for this long no longer hold the watermark back
+    pub idle_timeout: Duration,
+
+    /// Buffered snapshots beyond this are released early, oldest first
+    pub max_buffered: usize,
+
+    /// Largest `arrival - timestamp` accepted while estimating offsets;
+    /// replays of older captures need a fixed offset instead
+    pub max_clock_offset: Duration,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        SyncConfig {
+            max_delay: Duration::from_millis(200),
+            late_policy: LatePolicy::Drop,
+            offset_window: 256,
+            estimate_offsets: true,
+            idle_timeout: Duration::from_secs(2),
+            max_buffered: 100_000,
+            max_clock_offset: Duration::from_secs(24 * 3600),
+        }
+    }
+}
+
+/// A snapshot released by the synchronizer
+#[derive(Debug, Clone)]
+pub struct SyncedSnapshot {
+    pub source: String,
+
+    /// Snapshot with its timestamp on the local clock
+    pub snapshot: TelemetrySnapshot,
+
+    /// Timestamp as stamped by the source
+    pub source_timestamp: DateTime<Utc>,
+
+    /// Released out of order or clamped
+    pub late: bool,
+}
+
+/// Clock and watermark state of one source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceStatus {
+    pub source: String,
+
+    /// Seconds added to the source's timestamps
+    pub offset_secs: f64,
+
+    /// Whether the offset is fixed rather than estimated
+    pub fixed_offset: bool,
+    pub received: u64,
+    pub last_arrival: Option<DateTime<Utc>>,
+
+    /// Latest timestamp on the local clock
+    pub latest: Option<DateTime<Utc>>,
+}
+
+/// Synchronizer counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncStats {
+    pub received: u64,
+    pub released: u64,
+    pub late_dropped: u64,
+    pub late_emitted: u64,
+    pub late_clamped: u64,
+
+    /// Released before the watermark because the buffer was full
+    pub forced: u64,
+
+    /// Dropped for an implausible or unrepresentable timestamp
+    pub rejected: u64,
+    pub buffered: usize,
+}
+
+#[derive(Debug, Default)]
+struct SourceClock {
+    fixed: Option<chrono::Duration>,
+    window: VecDeque<i64>,
+    received: u64,
+    last_arrival: Option<DateTime<Utc>>,
+    latest: Option<DateTime<Utc>>,
+}
+
+impl SourceClock {
+    /// Fixed offset, else the windowed minimum
+    fn offset(&self) -> chrono::Duration {
+        self.fixed
+            .or_else(|| self.window.iter().min().map(|m| chrono::Duration::microseconds(*m)))
+            .unwrap_or_else(chrono::Duration::zero)
+    }
+}
+
+struct Pending {
+    at: DateTime<Utc>,
+    seq: u64,
+    synced: SyncedSnapshot,
+}
+
+impl PartialEq for Pending {
+    fn eq(&self, other: &Self) -> bool {
+        (self.at, self.seq) == (other.at, other.seq)
+    }
+}
+
+impl Eq for Pending {}
+
+impl PartialOrd for Pending {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Pending {
+    // Reversed so the heap pops the oldest first
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.at, other.seq).cmp(&(self.at, self.seq))
+    }
+}
+
+fn to_chrono(duration: Duration) -> chrono::Duration {
+    chrono::Duration::from_std(duration).unwrap_or(chrono::Duration::MAX)
+}
+
+/// Clock reconciliation and reorder buffer over several sources
+pub struct TelemetrySynchronizer {
+    config: SyncConfig,
+    sources: HashMap<String, SourceClock>,
+    pending: BinaryHeap<Pending>,
+    seq: u64,
+    last_released: Option<DateTime<Utc>>,
+    stats: SyncStats,
+}
+
+impl TelemetrySynchronizer {
+    pub fn new(config: SyncConfig) -> Self {
+        TelemetrySynchronizer {
+            config,
+            sources: HashMap::new(),
+            pending: BinaryHeap::new(),
+            seq: 0,
+            last_released: None,
+            stats: SyncStats::default(),
+        }
+    }
+
+    /// Use a known offset for a source instead of estimating it
+    pub fn with_offset(mut self, source: impl Into<String>, offset: chrono::Duration) -> Self {
+        self.sources.entry(source.into()).or_default().fixed = Some(offset);
+        self
+    }
+
+    /// Get configuration
+    pub fn config(&self) -> &SyncConfig {
+        &self.config
+    }
+
+    /// Accept a snapshot from `source` received at `received_at` (local
+    /// clock); returns the snapshots the watermark released, in order
+    pub fn push(
+        &mut self,
+        source: &str,
+        mut snapshot: TelemetrySnapshot,
+        received_at: DateTime<Utc>,
+    ) -> Vec<SyncedSnapshot> {
+        self.stats.received += 1;
+        let clock = self.sources.entry(source.to_string()).or_default();
+        clock.received += 1;
+        clock.last_arrival = Some(clock.last_arrival.map_or(received_at, |last| last.max(received_at)));
+        if clock.fixed.is_none() && self.config.estimate_offsets {
+            let max_offset = to_chrono(self.config.max_clock_offset);
+            let sample = received_at - snapshot.timestamp;
+            if sample.abs() > max_offset {
+                self.stats.rejected += 1;
+                return Vec::new();
+            }
+            clock.window.push_back(sample.num_microseconds().unwrap_or(i64::MAX));
+            while clock.window.len() > self.config.offset_window.max(1) {
+                clock.window.pop_front();
+            }
+        }
+
+        let source_timestamp = snapshot.timestamp;
+        let Some(at) = source_timestamp.checked_add_signed(clock.offset()) else {
+            self.stats.rejected += 1;
+            return Vec::new();
+        };
+        clock.latest = Some(clock.latest.map_or(at, |latest| latest.max(at)));
+        snapshot.timestamp = at;
+        let mut synced = SyncedSnapshot {
+            source: source.to_string(),
+            snapshot,
+            source_timestamp,
+            late: false,
+        };
+
+        let mut released = Vec::new();
+        if let Some(last) = self.last_released.filter(|last| at < *last) {
+            synced.late = true;
+            match self.config.late_policy {
+                LatePolicy::Drop => self.stats.late_dropped += 1,
+                LatePolicy::Emit => {
+                    self.stats.late_emitted += 1;
+                    self.stats.released += 1;
+                    released.push(synced);
+                }
+                LatePolicy::Clamp => {
+                    self.stats.late_clamped += 1;
+                    synced.snapshot.timestamp = last;
+                    self.enqueue(last, synced);
+                }
+            }
+        } else {
+            self.enqueue(at, synced);
+        }
+
+        while self.pending.len() > self.config.max_buffered {
+            self.stats.forced += 1;
+            released.extend(self.release_one());
+        }
+        if let Some(watermark) = self.watermark() {
+            while self.pending.peek().is_some_and(|p| p.at <= watermark) {
+                released.extend(self.release_one());
+            }
+        }
+        self.stats.buffered = self.pending.len();
+        released
+    }
+
+    /// Release everything still buffered
+    pub fn flush(&mut self) -> Vec<SyncedSnapshot> {
+        let mut released = Vec::with_capacity(self.pending.len());
+        while !self.pending.is_empty() {
+            released.extend(self.release_one());
+        }
+        self.stats.buffered = 0;
+        released
+    }
+
+    /// Local time up to which every active source has been released
+    pub fn watermark(&self) -> Option<DateTime<Utc>> {
+        let newest_arrival = self.sources.values().filter_map(|s| s.last_arrival).max()?;
+        let idle = to_chrono(self.config.idle_timeout);
+        self.sources
+            .values()
+            .filter(|s| s.last_arrival.is_some_and(|arrival| newest_arrival - arrival <= idle))
+            .filter_map(|s| s.latest)
+            .min()
+            .map(|latest| {
+                latest
+                    .checked_sub_signed(to_chrono(self.config.max_delay))
+                    .unwrap_or(DateTime::<Utc>::MIN_UTC)
+            })
+    }
+
+    /// Clock state per source, sorted by name
+    pub fn sources(&self) -> Vec<SourceStatus> {
+        let mut sources: Vec<SourceStatus> = self
+            .sources
+            .iter()
+            .map(|(name, clock)| SourceStatus {
+                source: name.clone(),
+                offset_secs: clock.offset().num_microseconds().unwrap_or(0) as f64 / 1.0e6,
+                fixed_offset: clock.fixed.is_some(),
+                received: clock.received,
+                last_arrival: clock.last_arrival,
+                latest: clock.latest,
+            })
+            .collect();
+        sources.sort_by(|a, b| a.source.cmp(&b.source));
+        sources
+    }
+
+    /// Get counters
+    pub fn stats(&self) -> SyncStats {
+        self.stats
+    }
+
+    fn enqueue(&mut self, at: DateTime<Utc>, synced: SyncedSnapshot) {
+        self.seq += 1;
+        self.pending.push(Pending { at, seq: self.seq, synced });
+    }
+
+    fn release_one(&mut self) -> Option<SyncedSnapshot> {
+        let pending = self.pending.pop()?;
+        self.last_released = Some(self.last_released.map_or(pending.at, |last| last.max(pending.at)));
+        self.stats.released += 1;
+        Some(pending.synced)
+    }
+}
+
+impl Default for TelemetrySynchronizer {
+    fn default() -> Self {
+        Self::new(SyncConfig::default())
+    }
+}
+
+/// Every car's state at one grid instant
+#[derive(Debug, Clone)]
+pub struct ResampledFrame {
+    pub timestamp: DateTime<Utc>,
+
+    /// One snapshot per car, sorted by car id
+    pub cars: Vec<TelemetrySnapshot>,
+}
+
+impl ResampledFrame {
+    pub fn car(&self, car_id: CarId) -> Option<&TelemetrySnapshot> {
+        self.cars.iter().find(|s| s.car_id == car_id)
+    }
+}
+
+/// Puts ordered snapshots of all cars on a common time grid
+///
+/// Continuous channels are interpolated linearly between the samples around
+/// each grid instant; lap, position, gear, DRS and tyres come from the
+/// nearer sample. A car whose samples are further than `max_gap` apart is
+/// left out of the frames in between rather than interpolated across.
+pub struct Resampler {
+    interval: chrono::Duration,
+    max_gap: chrono::Duration,
+    cars: BTreeMap<u8, VecDeque<TelemetrySnapshot>>,
+    next_tick: Option<DateTime<Utc>>,
+    latest: Option<DateTime<Utc>>,
+}
+
+impl Resampler {
+    pub fn new(interval: Duration, max_gap: Duration) -> Self {
+        Resampler {
+            interval: to_chrono(interval.max(Duration::from_micros(1))),
+            max_gap: to_chrono(max_gap),
+            cars: BTreeMap::new(),
+            next_tick: None,
+            latest: None,
+        }
+    }
+
+    /// Add a snapshot (in timestamp order); returns the frames now complete
+    pub fn push(&mut self, snapshot: TelemetrySnapshot) -> Vec<ResampledFrame> {
+        let at = snapshot.timestamp;
+        if self.next_tick.is_none() {
+            self.next_tick = Some(self.align(at));
+        }
+        self.latest = Some(self.latest.map_or(at, |latest| latest.max(at)));
+        let samples = self.cars.entry(snapshot.car_id.0).or_default();
+        if samples.back().map_or(true, |last| last.timestamp <= at) {
+            samples.push_back(snapshot);
+        }
+
+        let mut frames = Vec::new();
+        while let Some(tick) = self.next_tick {
+            // Complete once a later sample has arrived and every car has
+            // reached the tick or fallen too far behind to interpolate into it
+            let latest = self.latest.unwrap_or(tick);
+            let ready = self.cars.values().all(|samples| {
+                samples.back().map_or(true, |last| last.timestamp >= tick || latest - tick > self.max_gap)
+            });
+            if !ready || latest <= tick {
+                break;
+            }
+            frames.extend(self.frame(tick));
+            self.advance(tick);
+        }
+        frames
+    }
+
+    /// Emit the frames up to the last sample
+    pub fn flush(&mut self) -> Vec<ResampledFrame> {
+        let mut frames = Vec::new();
+        while let (Some(tick), Some(latest)) = (self.next_tick, self.latest) {
+            if tick > latest {
+                break;
+            }
+            frames.extend(self.frame(tick));
+            self.advance(tick);
+        }
+        frames
+    }
+
+    /// First grid instant at or after `at`, on a grid anchored at the epoch
+    fn align(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let step = self.interval.num_microseconds().unwrap_or(1).max(1);
+        let micros = at.timestamp_micros();
+        let aligned = micros.div_euclid(step) * step;
+        let aligned = if aligned < micros { aligned + step } else { aligned };
+        DateTime::from_timestamp_micros(aligned).unwrap_or(at)
+    }
+
+    fn advance(&mut self, tick: DateTime<Utc>) {
+        // Keep the last sample at or before the tick for the next interval
+        for samples in self.cars.values_mut() {
+            while samples.len() > 1 && samples[1].timestamp <= tick {
+                samples.pop_front();
+            }
+        }
+        self.next_tick = Some(tick + self.interval);
+    }
+
+    fn frame(&self, tick: DateTime<Utc>) -> Option<ResampledFrame> {
+        let cars: Vec<TelemetrySnapshot> = self
+            .cars
+            .values()
+            .filter_map(|samples| {
+                let after = samples.iter().position(|s| s.timestamp >= tick)?;
+                let b = &samples[after];
+                if b.timestamp == tick {
+                    return Some(b.clone());
+                }
+                let a = samples.get(after.checked_sub(1)?)?;
+                if b.timestamp - a.timestamp > self.max_gap {
+                    return None;
+                }
+                let span = (b.timestamp - a.timestamp).num_microseconds()? as f32;
+                let t = (tick - a.timestamp).num_microseconds()? as f32 / span;
+                Some(interpolate(a, b, t, tick))
+            })
+            .collect();
+        (!cars.is_empty()).then_some(ResampledFrame { timestamp: tick, cars })
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn lerp_sensor(a: &TireSensor, b: &TireSensor, t: f32) -> TireSensor {
+    TireSensor {
+        surface_temp: lerp(a.surface_temp, b.surface_temp, t),
+        inner_temp: lerp(a.inner_temp, b.inner_temp, t),
+        brake_temp: lerp(a.brake_temp, b.brake_temp, t),
+        pressure: lerp(a.pressure, b.pressure, t),
+        wear: lerp(a.wear, b.wear, t),
+        damage: lerp(a.damage, b.damage, t),
+    }
+}
+
+/// Snapshot between `a` and `b` at fraction `t`
+pub fn interpolate(a: &TelemetrySnapshot, b: &TelemetrySnapshot, t: f32, timestamp: DateTime<Utc>) -> TelemetrySnapshot {
+    let mut out = if t < 0.5 { a.clone() } else { b.clone() };
+    out.timestamp = timestamp;
+
+    let (ma, mb) = (&a.motion, &b.motion);
+    out.motion.speed = lerp(ma.speed, mb.speed, t);
+    out.motion.acceleration = lerp(ma.acceleration, mb.acceleration, t);
+    out.motion.lateral_g = lerp(ma.lateral_g, mb.lateral_g, t);
+    out.motion.longitudinal_g = lerp(ma.longitudinal_g, mb.longitudinal_g, t);
+    out.motion.vertical_g = lerp(ma.vertical_g, mb.vertical_g, t);
+    out.motion.yaw_rate = lerp(ma.yaw_rate, mb.yaw_rate, t);
+    out.motion.pitch = lerp(ma.pitch, mb.pitch, t);
+    out.motion.roll = lerp(ma.roll, mb.roll, t);
+
+    out.tires.front_left = lerp_sensor(&a.tires.front_left, &b.tires.front_left, t);
+    out.tires.front_right = lerp_sensor(&a.tires.front_right, &b.tires.front_right, t);
+    out.tires.rear_left = lerp_sensor(&a.tires.rear_left, &b.tires.rear_left, t);
+    out.tires.rear_right = lerp_sensor(&a.tires.rear_right, &b.tires.rear_right, t);
+
+    let (pa, pb) = (&a.power_unit, &b.power_unit);
+    out.power_unit.rpm = lerp(pa.rpm as f32, pb.rpm as f32, t).round() as u16;
+    out.power_unit.throttle = lerp(pa.throttle, pb.throttle, t);
+    out.power_unit.ers_battery = lerp(pa.ers_battery, pb.ers_battery, t);
+    out.power_unit.mgu_k_deployment = lerp(pa.mgu_k_deployment, pb.mgu_k_deployment, t);
+    out.power_unit.mgu_h_recovery = lerp(pa.mgu_h_recovery, pb.mgu_h_recovery, t);
+    out.power_unit.engine_temp = lerp(pa.engine_temp, pb.engine_temp, t);
+    out.power_unit.oil_temp = lerp(pa.oil_temp, pb.oil_temp, t);
+    out.power_unit.oil_pressure = lerp(pa.oil_pressure, pb.oil_pressure, t);
+
+    out.brakes.pressure = lerp(a.brakes.pressure, b.brakes.pressure, t);
+    out.brakes.front_temp = lerp(a.brakes.front_temp, b.brakes.front_temp, t);
+    out.brakes.rear_temp = lerp(a.brakes.rear_temp, b.brakes.rear_temp, t);
+
+    out.inputs.steering = lerp(a.inputs.steering, b.inputs.steering, t);
+    out.inputs.throttle = lerp(a.inputs.throttle, b.inputs.throttle, t);
+    out.inputs.brake = lerp(a.inputs.brake, b.inputs.brake, t);
+    out.inputs.clutch = lerp(a.inputs.clutch, b.inputs.clutch, t);
+
+    out.fuel.remaining = lerp(a.fuel.remaining, b.fuel.remaining, t);
+    out.fuel.consumption_rate = lerp(a.fuel.consumption_rate, b.fuel.consumption_rate, t);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::tests::create_test_snapshot;
+
+    fn at(ms: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(1_784_034_000_000 + ms).unwrap()
+    }
+
+    fn snapshot(car: u8, ms: i64, speed: f32) -> TelemetrySnapshot {
+        let mut snapshot = create_test_snapshot();
+        snapshot.car_id = CarId(car);
+        snapshot.timestamp = at(ms);
+        snapshot.motion.speed = speed;
+        snapshot
+    }
+
+    #[test]
+    fn test_reorders_across_sources_with_offsets() {
+        let mut sync = TelemetrySynchronizer::new(SyncConfig {
+            max_delay: Duration::from_millis(100),
+            ..SyncConfig::default()
+        });
+
+        // Source "udp" runs 5 s behind the local clock with 10 ms latency,
+        // "openf1" is on time with 50 ms latency
+        let mut released = Vec::new();
+        let arrivals = [
+            ("udp", -5000, 10),
+            ("openf1", 0, 50),
+            ("udp", -4980, 30),
+            ("openf1", 20, 70),
+            ("udp", -4990, 40), // out of order within the source
+            ("udp", -4900, 110),
+            ("openf1", 100, 150),
+            ("udp", -4700, 310),
+            ("openf1", 300, 350),
+        ];
+        for (i, (source, stamp, arrival)) in arrivals.into_iter().enumerate() {
+            released.extend(sync.push(source, snapshot(1, stamp, i as f32), at(arrival)));
+        }
+        released.extend(sync.flush());
+
+        let sources = sync.sources();
+        assert_eq!(sources[0].source, "openf1");
+        assert!((sources[0].offset_secs - 0.05).abs() < 1e-9);
+        assert!((sources[1].offset_secs - 5.01).abs() < 1e-9);
+        assert_eq!(released.len(), 9);
+        assert!(released.windows(2).all(|w| w[0].snapshot.timestamp <= w[1].snapshot.timestamp));
+        assert!(released.iter().all(|s| !s.late));
+        let udp = released.iter().find(|s| s.source == "udp").unwrap();
+        assert_eq!(udp.source_timestamp, at(-5000));
+        assert_eq!(udp.snapshot.timestamp, at(10));
+        assert_eq!(sync.stats().released, 9);
+    }
+
+    #[test]
+    fn test_late_policies() {
+        for (policy, expected) in [(LatePolicy::Drop, 2), (LatePolicy::Emit, 3), (LatePolicy::Clamp, 3)] {
+            let mut sync = TelemetrySynchronizer::new(SyncConfig {
+                max_delay: Duration::ZERO,
+                late_policy: policy,
+                estimate_offsets: false,
+                ..SyncConfig::default()
+            });
+            let mut released = Vec::new();
+            released.extend(sync.push("a", snapshot(1, 0, 1.0), at(0)));
+            released.extend(sync.push("a", snapshot(1, 100, 2.0), at(100)));
+            released.extend(sync.push("a", snapshot(1, 50, 3.0), at(150)));
+            released.extend(sync.flush());
+
+            assert_eq!(released.len(), expected, "{:?}", policy);
+            let stats = sync.stats();
+            match policy {
+                LatePolicy::Drop => assert_eq!(stats.late_dropped, 1),
+                LatePolicy::Emit => {
+                    assert_eq!(stats.late_emitted, 1);
+                    assert_eq!(released[2].snapshot.timestamp, at(50));
+                    assert!(released[2].late);
+                }
+                LatePolicy::Clamp => {
+                    assert_eq!(stats.late_clamped, 1);
+                    assert_eq!(released[2].snapshot.timestamp, at(100));
+                    assert_eq!(released[2].source_timestamp, at(50));
+                    assert!(released[2].late);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_idle_source_does_not_stall_and_buffer_is_bounded() {
+        let mut sync = TelemetrySynchronizer::new(SyncConfig {
+            max_delay: Duration::from_millis(100),
+            estimate_offsets: false,
+            idle_timeout: Duration::from_secs(1),
+            ..SyncConfig::default()
+        });
+        assert!(sync.push("slow", snapshot(2, 0, 1.0), at(0)).is_empty());
+        let mut released = 0;
+        for i in 1..=20 {
+            released += sync.push("fast", snapshot(1, i * 100, 1.0), at(i * 100)).len();
+        }
+        // "slow" went idle after a second, so "fast" alone drives the watermark
+        assert_eq!(released, 20);
+        assert_eq!(sync.stats().buffered, 1);
+        assert_eq!(sync.stats().forced, 0);
+
+        let mut sync = TelemetrySynchronizer::new(SyncConfig {
+            max_delay: Duration::from_secs(60),
+            estimate_offsets: false,
+            max_buffered: 5,
+            ..SyncConfig::default()
+        });
+        for i in 0..8 {
+            sync.push("a", snapshot(1, i * 10, 1.0), at(i * 10));
+        }
+        assert_eq!(sync.stats().buffered, 5);
+        assert_eq!(sync.stats().forced, 3);
+    }
+
+    #[test]
+    fn test_rejects_implausible_and_unrepresentable_times() {
+        // A far-future snapshot neither moves the offset nor stalls the rest
+        let mut sync = TelemetrySynchronizer::new(SyncConfig {
+            max_delay: Duration::MAX,
+            ..SyncConfig::default()
+        });
+        assert!(sync.push("a", snapshot(1, 0, 1.0), at(20)).is_empty());
+        let mut future = snapshot(1, 0, 1.0);
+        future.timestamp = DateTime::<Utc>::MAX_UTC;
+        assert!(sync.push("a", future, at(30)).is_empty());
+        assert!(sync.push("a", snapshot(1, 100, 1.0), at(120)).is_empty());
+        assert_eq!(sync.watermark(), Some(DateTime::<Utc>::MIN_UTC));
+        assert!((sync.sources()[0].offset_secs - 0.02).abs() < 1e-9);
+        assert_eq!(sync.stats().rejected, 1);
+        assert_eq!(sync.flush().len(), 2);
+
+        // A fixed offset that overflows the timestamp drops the snapshot
+        let mut sync = TelemetrySynchronizer::default().with_offset("a", chrono::Duration::MAX);
+        assert!(sync.push("a", snapshot(1, 0, 1.0), at(0)).is_empty());
+        assert_eq!(sync.stats().rejected, 1);
+        assert!(sync.flush().is_empty());
+    }
+
+    #[test]
+    fn test_resampler_aligns_cars() {
+        let mut resampler = Resampler::new(Duration::from_millis(100), Duration::from_millis(500));
+        let mut frames = Vec::new();
+        // Car 1 at 60 ms, car 2 at 80 ms, offset start times
+        let mut samples = Vec::new();
+        for i in 0..10 {
+            samples.push(snapshot(1, 30 + i * 60, 100.0 + (30 + i * 60) as f32 / 10.0));
+            samples.push(snapshot(2, 10 + i * 80, 200.0));
+        }
+        samples.sort_by_key(|s| s.timestamp);
+        for sample in samples {
+            frames.extend(resampler.push(sample));
+        }
+        frames.extend(resampler.flush());
+
+        assert_eq!(frames[0].timestamp, at(100));
+        assert!(frames.windows(2).all(|w| w[1].timestamp - w[0].timestamp == chrono::Duration::milliseconds(100)));
+        let frame = frames.iter().find(|f| f.timestamp == at(300)).unwrap();
+        assert_eq!(frame.cars.len(), 2);
+        assert!((frame.car(CarId(1)).unwrap().motion.speed - 130.0).abs() < 1e-3);
+        assert_eq!(frame.car(CarId(2)).unwrap().motion.speed, 200.0);
+        assert_eq!(frame.car(CarId(1)).unwrap().timestamp, at(300));
+
+        // Cars stop being interpolated across gaps longer than max_gap
+        let mut resampler = Resampler::new(Duration::from_millis(100), Duration::from_millis(150));
+        let mut frames = Vec::new();
+        for sample in [snapshot(1, 0, 1.0), snapshot(2, 0, 1.0), snapshot(2, 100, 1.0), snapshot(2, 200, 1.0), snapshot(1, 400, 1.0), snapshot(2, 400, 1.0)] {
+            frames.extend(resampler.push(sample));
+        }
+        frames.extend(resampler.flush());
+        let at_200 = frames.iter().find(|f| f.timestamp == at(200)).unwrap();
+        assert_eq!(at_200.cars.len(), 1);
+        assert_eq!(frames.last().unwrap().cars.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_engine_ingest_releases_in_order() {
+        let engine = crate::TelemetryEngine::new(crate::TelemetryConfig::default()).with_synchronizer(
+            TelemetrySynchronizer::new(SyncConfig {
+                max_delay: Duration::from_millis(100),
+                estimate_offsets: false,
+                ..SyncConfig::default()
+            }),
+        );
+        let mut rx = engine.subscribe();
+        assert_eq!(engine.ingest("a", snapshot(1, 50, 200.0), at(50)).await.unwrap(), 0);
+        assert_eq!(engine.ingest("a", snapshot(1, 0, 190.0), at(60)).await.unwrap(), 0);
+        assert_eq!(engine.ingest("a", snapshot(1, 200, 210.0), at(200)).await.unwrap(), 2);
+        assert_eq!(engine.flush_sync().await.unwrap(), 1);
+
+        let mut speeds = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            if let crate::TelemetryEvent::Snapshot(snapshot) = event {
+                speeds.push(snapshot.motion.speed);
+            }
+        }
+        assert_eq!(speeds, vec![190.0, 200.0, 210.0]);
+        assert_eq!(engine.sync_stats().unwrap().released, 3);
+    }
+
+    #[tokio::test]
+    async fn test_engine_emits_resampled_frames() {
+        let engine = crate::TelemetryEngine::new(crate::TelemetryConfig::default())
+            .with_synchronizer(TelemetrySynchronizer::new(SyncConfig {
+                max_delay: Duration::from_millis(100),
+                estimate_offsets: false,
+                ..SyncConfig::default()
+            }))
+            .with_resampler(Resampler::new(Duration::from_millis(100), Duration::from_millis(500)));
+        let mut rx = engine.subscribe();
+        for i in 0..5 {
+            engine.ingest("a", snapshot(1, 30 + i * 60, 100.0), at(30 + i * 60)).await.unwrap();
+            engine.ingest("b", snapshot(2, 10 + i * 80, 200.0), at(10 + i * 80)).await.unwrap();
+        }
+        engine.flush_sync().await.unwrap();
+
+        let mut frames = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            if let crate::TelemetryEvent::Frame(frame) = event {
+                frames.push(frame);
+            }
+        }
+        let stamps: Vec<_> = frames.iter().map(|f| f.timestamp).collect();
+        assert_eq!(stamps, vec![at(100), at(200), at(300)]);
+        assert!(frames[..2].iter().all(|f| f.cars.len() == 2));
+        // Car 1's last sample is at 270 ms
+        assert_eq!(frames[2].cars.len(), 1);
+    }
+}
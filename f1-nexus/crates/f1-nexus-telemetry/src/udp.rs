@@ -0,0 +1,1477 @@
+//! F1 game UDP telemetry ingestion
+//!
+//! The official F1 game broadcasts little-endian binary packets over UDP
+//! (port 20777 by default). This module decodes the motion, session, lap
+//! data, car telemetry and car status packets of the 2023 and 2024 formats,
+//! assembles them into one `TelemetrySnapshot` per active car whenever a car
+//! telemetry packet arrives, and keeps the `RaceState` positions and weather
+//! current from the lap data and session packets.
+//!
+//! Car ids are the game's car index plus one. Captures are read and written
+//! as pcap files, so traffic recorded with tcpdump or Wireshark can be
+//! replayed through `GameTelemetryIngest::replay` as well.
+
+use crate::recording::ReplaySpeed;
+use crate::TelemetryEngine;
+use chrono::{DateTime, TimeZone, Utc};
+use f1_nexus_core::{
+    AeroData, BrakeData, CarId, CarPosition, DriverInputs, DrsStatus, ErsMode, FlagStatus, FuelData,
+    LapNumber, MotionData, Position, PowerUnitData, RaceState, SessionId, SessionType, TelemetrySnapshot,
+    TireCompound, TireData, TireSensor, TrackCondition, WeatherCondition, WeatherForecast, WeatherPrediction,
+};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::net::UdpSocket;
+
+/// Default port the game broadcasts on
+pub const GAME_UDP_PORT: u16 = 20777;
+
+/// Cars in every per-car packet array
+pub const GAME_MAX_CARS: usize = 22;
+
+const HEADER_SIZE: usize = 29;
+const MOTION_SIZE: usize = 60;
+const TELEMETRY_SIZE: usize = 60;
+const STATUS_SIZE: usize = 55;
+
+/// Packet ids of the decoded packet types
+pub mod packet_id {
+    pub const MOTION: u8 = 0;
+    pub const SESSION: u8 = 1;
+    pub const LAP_DATA: u8 = 2;
+    pub const CAR_TELEMETRY: u8 = 6;
+    pub const CAR_STATUS: u8 = 7;
+}
+
+/// Header shared by every packet
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PacketHeader {
+    /// Packet format year (2023, 2024)
+    pub packet_format: u16,
+    pub game_year: u8,
+    pub packet_id: u8,
+    pub session_uid: u64,
+
+    /// Seconds since the session started
+    pub session_time: f32,
+    pub frame_identifier: u32,
+    pub player_car_index: u8,
+}
+
+/// Motion of one car
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CarMotion {
+    /// World velocity (m/s)
+    pub velocity: [f32; 3],
+    pub g_force_lateral: f32,
+    pub g_force_longitudinal: f32,
+    pub g_force_vertical: f32,
+
+    /// Orientation (radians)
+    pub yaw: f32,
+    pub pitch: f32,
+    pub roll: f32,
+}
+
+/// Lap data of one car
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CarLapData {
+    pub last_lap_time_ms: u32,
+    pub current_lap_time_ms: u32,
+    pub delta_to_car_in_front_ms: u32,
+    pub delta_to_race_leader_ms: u32,
+
+    /// Distance from the start/finish line (m), negative before the first crossing
+    pub lap_distance: f32,
+    pub total_distance: f32,
+    pub car_position: u8,
+    pub current_lap_num: u8,
+
+    /// 0 = none, 1 = pitting, 2 = in pit area
+    pub pit_status: u8,
+    pub num_pit_stops: u8,
+
+    /// 0 = sector 1, 1 = sector 2, 2 = sector 3
+    pub sector: u8,
+
+    /// 0 = in garage, 1 = flying lap, 2 = in lap, 3 = out lap, 4 = on track
+    pub driver_status: u8,
+
+    /// 0 = invalid, 1 = inactive, 2 = active, 3 = finished, 4 = DNF,
+    /// 5 = disqualified, 6 = not classified, 7 = retired
+    pub result_status: u8,
+}
+
+/// Telemetry of one car
+///
+/// Corner arrays are in the game's order: rear left, rear right, front left,
+/// front right.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CarTelemetryData {
+    /// Speed (km/h)
+    pub speed: u16,
+    pub throttle: f32,
+    pub steer: f32,
+    pub brake: f32,
+
+    /// Clutch (0-100)
+    pub clutch: u8,
+    pub gear: i8,
+    pub engine_rpm: u16,
+    pub drs: u8,
+    pub brakes_temperature: [u16; 4],
+    pub tyres_surface_temperature: [u8; 4],
+    pub tyres_inner_temperature: [u8; 4],
+    pub engine_temperature: u16,
+
+    /// Tyre pressures (PSI)
+    pub tyres_pressure: [f32; 4],
+}
+
+/// Status of one car
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CarStatusData {
+    /// Front brake bias (percent)
+    pub front_brake_bias: u8,
+
+    /// Fuel in tank (kg)
+    pub fuel_in_tank: f32,
+    pub drs_allowed: u8,
+    pub actual_tyre_compound: u8,
+    pub tyres_age_laps: u8,
+
+    /// MGU-K power output (W)
+    pub engine_power_mguk: f32,
+
+    /// ERS store energy (J)
+    pub ers_store_energy: f32,
+
+    /// 0 = none, 1 = medium, 2 = hotlap, 3 = overtake
+    pub ers_deploy_mode: u8,
+    pub ers_harvested_this_lap_mguh: f32,
+}
+
+/// One weather forecast sample
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GameForecastSample {
+    pub session_type: u8,
+
+    /// Minutes ahead
+    pub time_offset: u8,
+    pub weather: u8,
+
+    /// Rain probability (percent)
+    pub rain_percentage: u8,
+}
+
+/// Session packet fields
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameSessionData {
+    /// 0 = clear, 1 = light cloud, 2 = overcast, 3 = light rain, 4 = heavy rain, 5 = storm
+    pub weather: u8,
+    pub track_temperature: i8,
+    pub air_temperature: i8,
+    pub total_laps: u8,
+
+    /// Track length (m)
+    pub track_length: u16,
+    pub session_type: u8,
+    pub track_id: i8,
+
+    /// 0 = none, 1 = full safety car, 2 = virtual safety car, 3 = formation lap
+    pub safety_car_status: u8,
+    pub forecast: Vec<GameForecastSample>,
+}
+
+/// Decoded packet body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GamePacketBody {
+    Motion(Vec<CarMotion>),
+    Session(GameSessionData),
+    LapData(Vec<CarLapData>),
+    CarTelemetry(Vec<CarTelemetryData>),
+    CarStatus(Vec<CarStatusData>),
+
+    /// A packet type this module does not decode
+    Other,
+}
+
+/// Decoded packet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GamePacket {
+    pub header: PacketHeader,
+    pub body: GamePacketBody,
+}
+
+/// UDP ingestion errors
+#[derive(Debug, thiserror::Error)]
+pub enum GameUdpError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Unsupported packet format {0}")]
+    UnsupportedFormat(u16),
+
+    #[error("Packet {packet_id} truncated at {len} bytes")]
+    Truncated { packet_id: u8, len: usize },
+
+    #[error("Invalid capture: {0}")]
+    Capture(String),
+
+    #[error("Session time {0} s out of range")]
+    InvalidSessionTime(f32),
+}
+
+/// Little-endian cursor over a packet
+struct PacketReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+    packet_id: u8,
+}
+
+impl<'a> PacketReader<'a> {
+    fn new(data: &'a [u8], packet_id: u8) -> Self {
+        PacketReader { data, offset: 0, packet_id }
+    }
+
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], GameUdpError> {
+        let bytes = self
+            .data
+            .get(self.offset..self.offset + N)
+            .and_then(|slice| slice.try_into().ok())
+            .ok_or(GameUdpError::Truncated {
+                packet_id: self.packet_id,
+                len: self.data.len(),
+            })?;
+        self.offset += N;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, n: usize) {
+        self.offset += n;
+    }
+
+    fn seek(&mut self, offset: usize) {
+        self.offset = offset;
+    }
+
+    fn u8(&mut self) -> Result<u8, GameUdpError> {
+        Ok(self.bytes::<1>()?[0])
+    }
+
+    fn i8(&mut self) -> Result<i8, GameUdpError> {
+        Ok(self.bytes::<1>()?[0] as i8)
+    }
+
+    fn u16(&mut self) -> Result<u16, GameUdpError> {
+        self.bytes().map(u16::from_le_bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, GameUdpError> {
+        self.bytes().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64, GameUdpError> {
+        self.bytes().map(u64::from_le_bytes)
+    }
+
+    fn f32(&mut self) -> Result<f32, GameUdpError> {
+        self.bytes().map(f32::from_le_bytes)
+    }
+
+    fn array<T: Default + Copy, const N: usize>(
+        &mut self,
+        mut read: impl FnMut(&mut Self) -> Result<T, GameUdpError>,
+    ) -> Result<[T; N], GameUdpError> {
+        let mut values = [T::default(); N];
+        for value in &mut values {
+            *value = read(self)?;
+        }
+        Ok(values)
+    }
+}
+
+/// Longest session time accepted in a packet header (s)
+const MAX_SESSION_TIME: f32 = 1.0e6;
+
+/// Size of one car's lap data in the given format
+fn lap_data_size(format: u16) -> usize {
+    if format >= 2024 {
+        57
+    } else {
+        50
+    }
+}
+
+/// Decode one UDP datagram
+pub fn decode_packet(data: &[u8]) -> Result<GamePacket, GameUdpError> {
+    let mut reader = PacketReader::new(data, data.get(6).copied().unwrap_or(0));
+    let packet_format = reader.u16()?;
+    if !(2023..=2024).contains(&packet_format) {
+        return Err(GameUdpError::UnsupportedFormat(packet_format));
+    }
+    let game_year = reader.u8()?;
+    reader.skip(3); // game version, packet version
+    let packet_id = reader.u8()?;
+    let session_uid = reader.u64()?;
+    let session_time = reader.f32()?;
+    if !(0.0..=MAX_SESSION_TIME).contains(&session_time) {
+        return Err(GameUdpError::InvalidSessionTime(session_time));
+    }
+    let frame_identifier = reader.u32()?;
+    reader.skip(4); // overall frame identifier
+    let player_car_index = reader.u8()?;
+    reader.skip(1); // secondary player car index
+    let header = PacketHeader {
+        packet_format,
+        game_year,
+        packet_id,
+        session_uid,
+        session_time,
+        frame_identifier,
+        player_car_index,
+    };
+
+    let body = match packet_id {
+        packet_id::MOTION => GamePacketBody::Motion(cars(&mut reader, MOTION_SIZE, decode_motion)?),
+        packet_id::SESSION => GamePacketBody::Session(decode_session(&mut reader)?),
+        packet_id::LAP_DATA => GamePacketBody::LapData(cars(&mut reader, lap_data_size(packet_format), |r| {
+            decode_lap_data(r, packet_format)
+        })?),
+        packet_id::CAR_TELEMETRY => GamePacketBody::CarTelemetry(cars(&mut reader, TELEMETRY_SIZE, decode_telemetry)?),
+        packet_id::CAR_STATUS => GamePacketBody::CarStatus(cars(&mut reader, STATUS_SIZE, decode_status)?),
+        _ => GamePacketBody::Other,
+    };
+    Ok(GamePacket { header, body })
+}
+
+/// Decode the per-car array that follows the header
+fn cars<T>(
+    reader: &mut PacketReader,
+    size: usize,
+    mut decode: impl FnMut(&mut PacketReader) -> Result<T, GameUdpError>,
+) -> Result<Vec<T>, GameUdpError> {
+    (0..GAME_MAX_CARS)
+        .map(|i| {
+            reader.seek(HEADER_SIZE + i * size);
+            decode(reader)
+        })
+        .collect()
+}
+
+fn decode_motion(r: &mut PacketReader) -> Result<CarMotion, GameUdpError> {
+    r.skip(12); // world position
+    let velocity = r.array(|r| r.f32())?;
+    r.skip(12); // forward and right direction vectors
+    Ok(CarMotion {
+        velocity,
+        g_force_lateral: r.f32()?,
+        g_force_longitudinal: r.f32()?,
+        g_force_vertical: r.f32()?,
+        yaw: r.f32()?,
+        pitch: r.f32()?,
+        roll: r.f32()?,
+    })
+}
+
+fn decode_lap_data(r: &mut PacketReader, format: u16) -> Result<CarLapData, GameUdpError> {
+    let last_lap_time_ms = r.u32()?;
+    let current_lap_time_ms = r.u32()?;
+    r.skip(6); // sector 1 and 2 times
+    let (delta_to_car_in_front_ms, delta_to_race_leader_ms) = if format >= 2024 {
+        let front = r.u16()? as u32 + r.u8()? as u32 * 60_000;
+        let leader = r.u16()? as u32 + r.u8()? as u32 * 60_000;
+        (front, leader)
+    } else {
+        (r.u16()? as u32, r.u16()? as u32)
+    };
+    let lap_distance = r.f32()?;
+    let total_distance = r.f32()?;
+    r.skip(4); // safety car delta
+    let car_position = r.u8()?;
+    let current_lap_num = r.u8()?;
+    let pit_status = r.u8()?;
+    let num_pit_stops = r.u8()?;
+    let sector = r.u8()?;
+    r.skip(7); // lap invalid, penalties, warnings, unserved penalties, grid position
+    let driver_status = r.u8()?;
+    let result_status = r.u8()?;
+    Ok(CarLapData {
+        last_lap_time_ms,
+        current_lap_time_ms,
+        delta_to_car_in_front_ms,
+        delta_to_race_leader_ms,
+        lap_distance,
+        total_distance,
+        car_position,
+        current_lap_num,
+        pit_status,
+        num_pit_stops,
+        sector,
+        driver_status,
+        result_status,
+    })
+}
+
+fn decode_telemetry(r: &mut PacketReader) -> Result<CarTelemetryData, GameUdpError> {
+    let speed = r.u16()?;
+    let throttle = r.f32()?;
+    let steer = r.f32()?;
+    let brake = r.f32()?;
+    let clutch = r.u8()?;
+    let gear = r.i8()?;
+    let engine_rpm = r.u16()?;
+    let drs = r.u8()?;
+    r.skip(3); // rev lights
+    Ok(CarTelemetryData {
+        speed,
+        throttle,
+        steer,
+        brake,
+        clutch,
+        gear,
+        engine_rpm,
+        drs,
+        brakes_temperature: r.array(|r| r.u16())?,
+        tyres_surface_temperature: r.array(|r| r.u8())?,
+        tyres_inner_temperature: r.array(|r| r.u8())?,
+        engine_temperature: r.u16()?,
+        tyres_pressure: r.array(|r| r.f32())?,
+    })
+}
+
+fn decode_status(r: &mut PacketReader) -> Result<CarStatusData, GameUdpError> {
+    r.skip(3); // traction control, ABS, fuel mix
+    let front_brake_bias = r.u8()?;
+    r.skip(1); // pit limiter
+    let fuel_in_tank = r.f32()?;
+    r.skip(13); // fuel capacity and remaining laps, rpm limits, gears
+    let drs_allowed = r.u8()?;
+    r.skip(2); // DRS activation distance
+    let actual_tyre_compound = r.u8()?;
+    r.skip(1); // visual compound
+    let tyres_age_laps = r.u8()?;
+    r.skip(5); // FIA flags, ICE power
+    let engine_power_mguk = r.f32()?;
+    let ers_store_energy = r.f32()?;
+    let ers_deploy_mode = r.u8()?;
+    r.skip(4); // MGU-K harvest
+    let ers_harvested_this_lap_mguh = r.f32()?;
+    Ok(CarStatusData {
+        front_brake_bias,
+        fuel_in_tank,
+        drs_allowed,
+        actual_tyre_compound,
+        tyres_age_laps,
+        engine_power_mguk,
+        ers_store_energy,
+        ers_deploy_mode,
+        ers_harvested_this_lap_mguh,
+    })
+}
+
+fn decode_session(r: &mut PacketReader) -> Result<GameSessionData, GameUdpError> {
+    let weather = r.u8()?;
+    let track_temperature = r.i8()?;
+    let air_temperature = r.i8()?;
+    let total_laps = r.u8()?;
+    let track_length = r.u16()?;
+    let session_type = r.u8()?;
+    let track_id = r.i8()?;
+    r.skip(10); // formula, timing, pause and spectator fields
+    r.skip(1 + 21 * 5); // marshal zones
+    let safety_car_status = r.u8()?;
+    r.skip(1); // network game
+    let samples = r.u8()? as usize;
+    let forecast = (0..samples.min(64))
+        .map(|_| {
+            let [session_type, time_offset, weather, _, _, _, _, rain_percentage] = r.bytes::<8>()?;
+            Ok(GameForecastSample {
+                session_type,
+                time_offset,
+                weather,
+                rain_percentage,
+            })
+        })
+        .collect::<Result<_, GameUdpError>>()?;
+    Ok(GameSessionData {
+        weather,
+        track_temperature,
+        air_temperature,
+        total_laps,
+        track_length,
+        session_type,
+        track_id,
+        safety_car_status,
+        forecast,
+    })
+}
+
+/// Circuit id of a game track id, matching `Circuit::id` where one exists
+pub fn game_track_name(track_id: i8) -> &'static str {
+    const TRACKS: [&str; 33] = [
+        "melbourne", "paul_ricard", "shanghai", "sakhir", "catalunya", "monaco", "montreal", "silverstone",
+        "hockenheim", "hungaroring", "spa", "monza", "singapore", "suzuka", "abu_dhabi", "texas", "brazil",
+        "austria", "sochi", "mexico", "baku", "sakhir_short", "silverstone_short", "texas_short", "suzuka_short",
+        "hanoi", "zandvoort", "imola", "portimao", "jeddah", "miami", "las_vegas", "losail",
+    ];
+    usize::try_from(track_id)
+        .ok()
+        .and_then(|i| TRACKS.get(i))
+        .copied()
+        .unwrap_or("unknown")
+}
+
+fn weather_condition(weather: u8) -> WeatherCondition {
+    match weather {
+        0 => WeatherCondition::Dry,
+        1 => WeatherCondition::PartlyCloudy,
+        2 => WeatherCondition::Cloudy,
+        3 => WeatherCondition::LightRain,
+        _ => WeatherCondition::HeavyRain,
+    }
+}
+
+fn track_condition(weather: u8) -> TrackCondition {
+    match weather {
+        0..=2 => TrackCondition::Dry,
+        3 => TrackCondition::Damp,
+        4 => TrackCondition::Wet,
+        _ => TrackCondition::VeryWet,
+    }
+}
+
+fn session_type(format: u16, session_type: u8) -> SessionType {
+    match (format >= 2024, session_type) {
+        (_, 2) => SessionType::Practice2,
+        (_, 3) => SessionType::Practice3,
+        (_, 0..=4) => SessionType::Practice1,
+        (_, 5..=9) | (true, 10..=14) => SessionType::Qualifying,
+        (false, 11) | (true, 16) => SessionType::Sprint,
+        _ => SessionType::Race,
+    }
+}
+
+fn tire_compound(actual: u8) -> TireCompound {
+    match actual {
+        16 => TireCompound::C5,
+        17 => TireCompound::C4,
+        18 => TireCompound::C3,
+        19 => TireCompound::C2,
+        20 => TireCompound::C1,
+        21 => TireCompound::C0,
+        7 => TireCompound::Intermediate,
+        8 => TireCompound::Wet,
+        _ => TireCompound::C3,
+    }
+}
+
+/// Energy of a full ERS store (J)
+const ERS_STORE_CAPACITY: f32 = 4.0e6;
+
+/// Latest packets of one car
+#[derive(Debug, Clone, Default)]
+struct GameCar {
+    motion: Option<CarMotion>,
+    lap: Option<CarLapData>,
+    status: Option<CarStatusData>,
+}
+
+/// Assembles decoded packets into snapshots and race state
+pub struct GameTelemetryAssembler {
+    session_uid: Option<u64>,
+    packet_format: u16,
+    /// Wall-clock time of session time zero
+    epoch: DateTime<Utc>,
+    cars: Vec<GameCar>,
+    session: Option<GameSessionData>,
+    latest: HashMap<CarId, TelemetrySnapshot>,
+}
+
+impl GameTelemetryAssembler {
+    pub fn new() -> Self {
+        GameTelemetryAssembler {
+            session_uid: None,
+            packet_format: 0,
+            epoch: Utc::now(),
+            cars: vec![GameCar::default(); GAME_MAX_CARS],
+            session: None,
+            latest: HashMap::new(),
+        }
+    }
+
+    /// Session id derived from the game's session UID
+    pub fn session_id(&self) -> Option<SessionId> {
+        self.session_uid
+            .map(|uid| SessionId(uuid::Uuid::from_u64_pair(u64::from_be_bytes(*b"f1-nexus"), uid)))
+    }
+
+    /// Apply a packet received at `received_at`; car telemetry packets
+    /// return one snapshot per active car
+    pub fn ingest(&mut self, packet: &GamePacket, received_at: DateTime<Utc>) -> Vec<TelemetrySnapshot> {
+        let header = &packet.header;
+        let session_time = chrono::Duration::microseconds((header.session_time as f64 * 1.0e6) as i64);
+        if self.session_uid != Some(header.session_uid) {
+            let Some(epoch) = received_at.checked_sub_signed(session_time) else {
+                return Vec::new();
+            };
+            // A new session starts from a clean slate
+            *self = GameTelemetryAssembler::new();
+            self.session_uid = Some(header.session_uid);
+            self.epoch = epoch;
+        }
+        self.packet_format = header.packet_format;
+
+        match &packet.body {
+            GamePacketBody::Motion(cars) => {
+                for (car, motion) in self.cars.iter_mut().zip(cars) {
+                    car.motion = Some(*motion);
+                }
+            }
+            GamePacketBody::LapData(cars) => {
+                for (car, lap) in self.cars.iter_mut().zip(cars) {
+                    car.lap = Some(*lap);
+                }
+            }
+            GamePacketBody::CarStatus(cars) => {
+                for (car, status) in self.cars.iter_mut().zip(cars) {
+                    car.status = Some(*status);
+                }
+            }
+            GamePacketBody::Session(session) => self.session = Some(session.clone()),
+            GamePacketBody::CarTelemetry(cars) => {
+                let Some(timestamp) = self.epoch.checked_add_signed(session_time) else {
+                    return Vec::new();
+                };
+                let session_id = self.session_id().unwrap_or_default();
+                let snapshots: Vec<TelemetrySnapshot> = cars
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, telemetry)| {
+                        let car = &self.cars[i];
+                        let lap = car.lap.filter(|lap| matches!(lap.result_status, 2 | 3))?;
+                        Some(build_snapshot(session_id, i, timestamp, telemetry, &lap, car))
+                    })
+                    .collect();
+                for snapshot in &snapshots {
+                    self.latest.insert(snapshot.car_id, snapshot.clone());
+                }
+                return snapshots;
+            }
+            GamePacketBody::Other => {}
+        }
+        Vec::new()
+    }
+
+    /// Race state from the latest session and lap data packets
+    pub fn race_state(&self) -> Option<RaceState> {
+        let session_id = self.session_id()?;
+        let session = self.session.as_ref()?;
+        let positions: HashMap<CarId, CarPosition> = self
+            .cars
+            .iter()
+            .enumerate()
+            .filter_map(|(i, car)| {
+                let lap = car.lap.filter(|lap| lap.result_status >= 2)?;
+                let car_id = CarId(i as u8 + 1);
+                Some((
+                    car_id,
+                    CarPosition {
+                        car_id,
+                        position: Position(lap.car_position),
+                        lap: LapNumber(lap.current_lap_num as u16),
+                        gap_to_leader: lap.delta_to_race_leader_ms as f32 / 1000.0,
+                        gap_to_ahead: lap.delta_to_car_in_front_ms as f32 / 1000.0,
+                        last_lap_time: lap.last_lap_time_ms as f32 / 1000.0,
+                        is_in_pit: lap.pit_status != 0,
+                        is_retired: lap.result_status >= 4,
+                        retirement_reason: None,
+                    },
+                ))
+            })
+            .collect();
+        let current_lap = positions
+            .values()
+            .find(|p| p.position == Position(1))
+            .map_or(LapNumber(0), |leader| leader.lap);
+
+        Some(RaceState {
+            session_id,
+            session_type: session_type(self.packet_format, session.session_type),
+            track_id: game_track_name(session.track_id).to_string(),
+            current_lap,
+            total_laps: session.total_laps as u16,
+            flag_status: match session.safety_car_status {
+                1 => FlagStatus::SafetyCar,
+                2 => FlagStatus::VirtualSafetyCar,
+                _ => FlagStatus::Green,
+            },
+            weather: weather_condition(session.weather),
+            track_condition: track_condition(session.weather),
+            positions,
+            strategies: HashMap::new(),
+            telemetry: self.latest.clone(),
+            incidents: vec![],
+            safety_car_periods: vec![],
+        })
+    }
+
+    /// Weather from the latest session packet
+    pub fn weather(&self) -> Option<WeatherForecast> {
+        let session = self.session.as_ref()?;
+        let condition = weather_condition(session.weather);
+        // Forecast samples cover every session of the weekend; keep the current one
+        let predictions: Vec<WeatherPrediction> = session
+            .forecast
+            .iter()
+            .filter(|sample| sample.session_type == session.session_type)
+            .map(|sample| WeatherPrediction {
+                minutes_ahead: sample.time_offset as u16,
+                condition: weather_condition(sample.weather),
+                rain_probability: sample.rain_percentage as f32 / 100.0,
+                confidence: 1.0,
+            })
+            .collect();
+
+        Some(WeatherForecast {
+            overall_condition: condition,
+            air_temperature: session.air_temperature as f32,
+            track_temperature: session.track_temperature as f32,
+            humidity: 0.5,
+            pressure: None,
+            wind_speed: 0.0,
+            wind_direction: 0.0,
+            rain_probability: predictions.first().map_or(0.0, |p| p.rain_probability),
+            rainfall_intensity: condition.typical_rainfall_intensity(),
+            sector_conditions: vec![],
+            predictions,
+        })
+    }
+}
+
+impl Default for GameTelemetryAssembler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn build_snapshot(
+    session_id: SessionId,
+    index: usize,
+    timestamp: DateTime<Utc>,
+    telemetry: &CarTelemetryData,
+    lap: &CarLapData,
+    car: &GameCar,
+) -> TelemetrySnapshot {
+    let motion = car.motion.unwrap_or_default();
+    let status = car.status.unwrap_or_default();
+    // Game corner order: rear left, rear right, front left, front right
+    let tire = |i: usize| TireSensor {
+        surface_temp: telemetry.tyres_surface_temperature[i] as f32,
+        inner_temp: telemetry.tyres_inner_temperature[i] as f32,
+        brake_temp: telemetry.brakes_temperature[i] as f32,
+        pressure: telemetry.tyres_pressure[i],
+        wear: 0.0,
+        damage: 0.0,
+    };
+    let brake_temp = |a: usize, b: usize| {
+        (telemetry.brakes_temperature[a] as f32 + telemetry.brakes_temperature[b] as f32) / 2.0
+    };
+
+    TelemetrySnapshot {
+        session_id,
+        car_id: CarId(index as u8 + 1),
+        timestamp,
+        lap: LapNumber(lap.current_lap_num as u16),
+        position: Position(lap.car_position),
+        motion: MotionData {
+            speed: telemetry.speed as f32,
+            acceleration: motion.g_force_longitudinal * 9.81,
+            lateral_g: motion.g_force_lateral,
+            longitudinal_g: motion.g_force_longitudinal,
+            vertical_g: motion.g_force_vertical,
+            yaw_rate: 0.0,
+            pitch: motion.pitch.to_degrees(),
+            roll: motion.roll.to_degrees(),
+        },
+        tires: TireData {
+            front_left: tire(2),
+            front_right: tire(3),
+            rear_left: tire(0),
+            rear_right: tire(1),
+            compound: tire_compound(status.actual_tyre_compound),
+            age_laps: status.tyres_age_laps as u16,
+        },
+        power_unit: PowerUnitData {
+            rpm: telemetry.engine_rpm,
+            throttle: telemetry.throttle,
+            ers_mode: match status.ers_deploy_mode {
+                1 => ErsMode::Medium,
+                2 => ErsMode::Hotlap,
+                3 => ErsMode::Overtake,
+                _ => ErsMode::None,
+            },
+            ers_battery: (status.ers_store_energy / ERS_STORE_CAPACITY).clamp(0.0, 1.0),
+            mgu_k_deployment: status.engine_power_mguk / 1000.0,
+            mgu_h_recovery: 0.0,
+            engine_temp: telemetry.engine_temperature as f32,
+            oil_temp: 0.0,
+            oil_pressure: 0.0,
+        },
+        aero: AeroData {
+            front_wing_angle: 0.0,
+            rear_wing_angle: 0.0,
+            downforce: 0.0,
+            drag_coefficient: 0.0,
+        },
+        brakes: BrakeData {
+            bias: status.front_brake_bias as f32 / 100.0,
+            pressure: telemetry.brake,
+            front_temp: brake_temp(2, 3),
+            rear_temp: brake_temp(0, 1),
+        },
+        inputs: DriverInputs {
+            steering: telemetry.steer,
+            throttle: telemetry.throttle,
+            brake: telemetry.brake,
+            clutch: telemetry.clutch as f32 / 100.0,
+            gear: telemetry.gear,
+        },
+        fuel: FuelData {
+            remaining: status.fuel_in_tank,
+            consumption_rate: 0.0,
+            temperature: 0.0,
+            pressure: 0.0,
+        },
+        drs: if telemetry.drs == 1 {
+            DrsStatus::Activated
+        } else if status.drs_allowed == 1 {
+            DrsStatus::Available
+        } else {
+            DrsStatus::Unavailable
+        },
+    }
+}
+
+/// Ingestion counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameIngestStats {
+    pub packets: u64,
+    pub malformed: u64,
+    pub snapshots: u64,
+
+    /// Snapshots the engine rejected as invalid
+    pub rejected: u64,
+}
+
+#[derive(Default)]
+struct GameIngestStatsInner {
+    packets: AtomicU64,
+    malformed: AtomicU64,
+    snapshots: AtomicU64,
+    rejected: AtomicU64,
+}
+
+/// Feeds F1 game packets from a socket or capture into a `TelemetryEngine`
+pub struct GameTelemetryIngest {
+    engine: Arc<TelemetryEngine>,
+    assembler: Mutex<GameTelemetryAssembler>,
+    stats: GameIngestStatsInner,
+}
+
+impl GameTelemetryIngest {
+    pub fn new(engine: Arc<TelemetryEngine>) -> Self {
+        GameTelemetryIngest {
+            engine,
+            assembler: Mutex::new(GameTelemetryAssembler::new()),
+            stats: GameIngestStatsInner::default(),
+        }
+    }
+
+    /// Decode one datagram and process the snapshots it completes
+    pub async fn ingest(&self, datagram: &[u8], received_at: DateTime<Utc>) -> Result<usize, GameUdpError> {
+        self.stats.packets.fetch_add(1, Ordering::Relaxed);
+        let packet = decode_packet(datagram).map_err(|e| {
+            self.stats.malformed.fetch_add(1, Ordering::Relaxed);
+            e
+        })?;
+        let snapshots = self.assembler.lock().ingest(&packet, received_at);
+
+        let mut processed = 0;
+        for snapshot in snapshots {
+            match self.engine.process(snapshot).await {
+                Ok(()) => processed += 1,
+                Err(e) => {
+                    tracing::debug!("Rejected game snapshot: {}", e);
+                    self.stats.rejected.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+        self.stats.snapshots.fetch_add(processed as u64, Ordering::Relaxed);
+        Ok(processed)
+    }
+
+    /// Receive packets until the socket fails, optionally capturing them
+    pub async fn listen(&self, socket: UdpSocket, mut capture: Option<PcapWriter>) -> Result<(), GameUdpError> {
+        let port = socket.local_addr()?.port();
+        let mut buf = vec![0u8; 2048];
+        loop {
+            let (len, from) = socket.recv_from(&mut buf).await?;
+            let received_at = Utc::now();
+            if let Some(writer) = capture.as_mut() {
+                writer.write(received_at, from, port, &buf[..len])?;
+            }
+            if let Err(e) = self.ingest(&buf[..len], received_at).await {
+                tracing::debug!("Dropped game packet from {}: {}", from, e);
+            }
+        }
+    }
+
+    /// Replay the UDP datagrams of a pcap capture; returns the snapshots processed
+    ///
+    /// Only datagrams sent to `port` are replayed, when one is given.
+    pub async fn replay(&self, path: impl AsRef<Path>, port: Option<u16>, speed: ReplaySpeed) -> Result<usize, GameUdpError> {
+        let datagrams: Vec<CapturedDatagram> = PcapReader::open(path)?
+            .filter(|d| d.as_ref().map_or(true, |d| port.map_or(true, |port| d.dest_port == port)))
+            .collect::<Result<_, _>>()?;
+        let Some(data_start) = datagrams.first().map(|d| d.timestamp) else {
+            return Ok(0);
+        };
+        let wall_start = tokio::time::Instant::now();
+
+        let mut processed = 0;
+        for datagram in datagrams {
+            let offset = (datagram.timestamp - data_start).to_std().unwrap_or_default();
+            if let Some(deadline) = speed.wall_delay(offset).and_then(|delay| wall_start.checked_add(delay)) {
+                tokio::time::sleep_until(deadline).await;
+            }
+            match self.ingest(&datagram.payload, datagram.timestamp).await {
+                Ok(n) => processed += n,
+                Err(e) => tracing::debug!("Dropped captured game packet: {}", e),
+            }
+        }
+        Ok(processed)
+    }
+
+    /// Current race state, once a session packet has arrived
+    pub fn race_state(&self) -> Option<RaceState> {
+        self.assembler.lock().race_state()
+    }
+
+    /// Current weather, once a session packet has arrived
+    pub fn weather(&self) -> Option<WeatherForecast> {
+        self.assembler.lock().weather()
+    }
+
+    /// Ingestion counters
+    pub fn stats(&self) -> GameIngestStats {
+        GameIngestStats {
+            packets: self.stats.packets.load(Ordering::Relaxed),
+            malformed: self.stats.malformed.load(Ordering::Relaxed),
+            snapshots: self.stats.snapshots.load(Ordering::Relaxed),
+            rejected: self.stats.rejected.load(Ordering::Relaxed),
+        }
+    }
+}
+
+const PCAP_MAGIC: u32 = 0xa1b2_c3d4;
+const PCAP_MAGIC_NANOS: u32 = 0xa1b2_3c4d;
+const LINKTYPE_NULL: u32 = 0;
+const LINKTYPE_ETHERNET: u32 = 1;
+const LINKTYPE_RAW: u32 = 101;
+const LINKTYPE_LINUX_SLL: u32 = 113;
+const LINKTYPE_IPV4: u32 = 228;
+
+/// Capture time and link-layer frame of one pcap record
+type PcapRecord = (DateTime<Utc>, Vec<u8>);
+
+/// Writes datagrams to a pcap file as raw IPv4/UDP packets
+pub struct PcapWriter {
+    writer: BufWriter<File>,
+}
+
+impl PcapWriter {
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, GameUdpError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(&PCAP_MAGIC_NANOS.to_le_bytes())?;
+        writer.write_all(&2u16.to_le_bytes())?;
+        writer.write_all(&4u16.to_le_bytes())?;
+        writer.write_all(&[0; 8])?; // timezone, timestamp accuracy
+        writer.write_all(&65535u32.to_le_bytes())?;
+        writer.write_all(&LINKTYPE_RAW.to_le_bytes())?;
+        Ok(PcapWriter { writer })
+    }
+
+    /// Append one datagram received from `source` on `dest_port`
+    pub fn write(&mut self, at: DateTime<Utc>, source: SocketAddr, dest_port: u16, payload: &[u8]) -> Result<(), GameUdpError> {
+        let source_ip = match source {
+            SocketAddr::V4(addr) => *addr.ip(),
+            SocketAddr::V6(_) => Ipv4Addr::LOCALHOST,
+        };
+        let total = 28 + payload.len();
+        let mut ip = [0u8; 20];
+        ip[0] = 0x45;
+        ip[2..4].copy_from_slice(&(total as u16).to_be_bytes());
+        ip[6] = 0x40; // don't fragment
+        ip[8] = 64;
+        ip[9] = 17;
+        ip[12..16].copy_from_slice(&source_ip.octets());
+        ip[16..20].copy_from_slice(&Ipv4Addr::LOCALHOST.octets());
+        let mut sum: u32 = ip.chunks(2).map(|word| u16::from_be_bytes([word[0], word[1]]) as u32).sum();
+        while sum > 0xffff {
+            sum = (sum & 0xffff) + (sum >> 16);
+        }
+        let checksum = !(sum as u16);
+        ip[10..12].copy_from_slice(&checksum.to_be_bytes());
+
+        let mut udp = [0u8; 8];
+        udp[0..2].copy_from_slice(&source.port().to_be_bytes());
+        udp[2..4].copy_from_slice(&dest_port.to_be_bytes());
+        udp[4..6].copy_from_slice(&((8 + payload.len()) as u16).to_be_bytes());
+
+        let nanos = at.timestamp_nanos_opt().unwrap_or(0);
+        self.writer.write_all(&(nanos.div_euclid(1_000_000_000) as u32).to_le_bytes())?;
+        self.writer.write_all(&(nanos.rem_euclid(1_000_000_000) as u32).to_le_bytes())?;
+        self.writer.write_all(&(total as u32).to_le_bytes())?;
+        self.writer.write_all(&(total as u32).to_le_bytes())?;
+        self.writer.write_all(&ip)?;
+        self.writer.write_all(&udp)?;
+        self.writer.write_all(payload)?;
+        Ok(())
+    }
+
+    /// Flush buffered packets
+    pub fn flush(&mut self) -> Result<(), GameUdpError> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+impl Drop for PcapWriter {
+    fn drop(&mut self) {
+        let _ = self.writer.flush();
+    }
+}
+
+/// A UDP datagram read from a capture
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapturedDatagram {
+    pub timestamp: DateTime<Utc>,
+    pub source_port: u16,
+    pub dest_port: u16,
+    pub payload: Vec<u8>,
+}
+
+/// Reads UDP datagrams from a pcap file
+///
+/// Handles both byte orders, microsecond and nanosecond timestamps, and
+/// Ethernet, loopback, Linux cooked and raw IP link types. Packets that are
+/// not UDP are skipped.
+pub struct PcapReader {
+    reader: BufReader<File>,
+    big_endian: bool,
+    nanos: bool,
+    link_type: u32,
+}
+
+impl PcapReader {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, GameUdpError> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut header = [0u8; 24];
+        reader.read_exact(&mut header)?;
+        let magic = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let (big_endian, nanos) = match magic {
+            PCAP_MAGIC => (false, false),
+            PCAP_MAGIC_NANOS => (false, true),
+            _ if magic.swap_bytes() == PCAP_MAGIC => (true, false),
+            _ if magic.swap_bytes() == PCAP_MAGIC_NANOS => (true, true),
+            _ => return Err(GameUdpError::Capture("not a pcap file".to_string())),
+        };
+        let mut capture = PcapReader {
+            reader,
+            big_endian,
+            nanos,
+            link_type: 0,
+        };
+        capture.link_type = capture.u32_at(&header, 20) & 0x0fff_ffff;
+        Ok(capture)
+    }
+
+    fn u32_at(&self, bytes: &[u8], offset: usize) -> u32 {
+        let word = [bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]];
+        if self.big_endian {
+            u32::from_be_bytes(word)
+        } else {
+            u32::from_le_bytes(word)
+        }
+    }
+
+    /// Next record, or `None` at the end of the file
+    fn next_record(&mut self) -> Result<Option<PcapRecord>, GameUdpError> {
+        let mut header = [0u8; 16];
+        match self.reader.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+        let seconds = self.u32_at(&header, 0) as i64;
+        let fraction = self.u32_at(&header, 4) as i64;
+        let length = self.u32_at(&header, 8) as usize;
+        if length > 1 << 20 {
+            return Err(GameUdpError::Capture(format!("record of {} bytes", length)));
+        }
+        let mut data = vec![0u8; length];
+        self.reader.read_exact(&mut data)?;
+        let nanos = seconds * 1_000_000_000 + if self.nanos { fraction } else { fraction * 1000 };
+        Ok(Some((Utc.timestamp_nanos(nanos), data)))
+    }
+
+    /// UDP header and payload inside a link-layer frame
+    fn udp_payload(&self, frame: &[u8]) -> Option<(u16, u16, Vec<u8>)> {
+        let ip = match self.link_type {
+            LINKTYPE_ETHERNET => {
+                let mut offset = 12;
+                // Skip 802.1Q tags
+                while frame.get(offset..offset + 2)? == [0x81, 0x00] {
+                    offset += 4;
+                }
+                frame.get(offset + 2..)?
+            }
+            LINKTYPE_NULL => frame.get(4..)?,
+            LINKTYPE_LINUX_SLL => frame.get(16..)?,
+            LINKTYPE_RAW | LINKTYPE_IPV4 => frame,
+            _ => return None,
+        };
+        let udp = match ip.first()? >> 4 {
+            4 if *ip.get(9)? == 17 => ip.get(((ip[0] & 0x0f) as usize) * 4..)?,
+            6 if *ip.get(6)? == 17 => ip.get(40..)?,
+            _ => return None,
+        };
+        let source_port = u16::from_be_bytes([*udp.first()?, *udp.get(1)?]);
+        let dest_port = u16::from_be_bytes([*udp.get(2)?, *udp.get(3)?]);
+        let length = (u16::from_be_bytes([*udp.get(4)?, *udp.get(5)?]) as usize).min(udp.len());
+        Some((source_port, dest_port, udp.get(8..length)?.to_vec()))
+    }
+}
+
+impl Iterator for PcapReader {
+    type Item = Result<CapturedDatagram, GameUdpError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (timestamp, frame) = match self.next_record() {
+                Ok(Some(record)) => record,
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e)),
+            };
+            if let Some((source_port, dest_port, payload)) = self.udp_payload(&frame) {
+                return Some(Ok(CapturedDatagram {
+                    timestamp,
+                    source_port,
+                    dest_port,
+                    payload,
+                }));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TelemetryConfig;
+    use std::sync::OnceLock;
+
+    const SESSION_UID: u64 = 0x1234_5678_9abc_def0;
+
+    fn put(packet: &mut [u8], offset: usize, bytes: &[u8]) {
+        packet[offset..offset + bytes.len()].copy_from_slice(bytes);
+    }
+
+    fn packet(id: u8, size: usize, session_time: f32) -> Vec<u8> {
+        let mut packet = vec![0u8; size];
+        put(&mut packet, 0, &2023u16.to_le_bytes());
+        packet[2] = 23;
+        packet[6] = id;
+        put(&mut packet, 7, &SESSION_UID.to_le_bytes());
+        put(&mut packet, 15, &session_time.to_le_bytes());
+        packet
+    }
+
+    fn lap_data_packet(session_time: f32) -> Vec<u8> {
+        let mut packet = packet(packet_id::LAP_DATA, 1131, session_time);
+        for (car, position) in [(0usize, 2u8), (1, 1)] {
+            let base = HEADER_SIZE + car * 50;
+            put(&mut packet, base, &91_500u32.to_le_bytes());
+            put(&mut packet, base + 14, &(1_200u16 * car as u16).to_le_bytes()); // delta to car in front
+            put(&mut packet, base + 18, &812.5f32.to_le_bytes());
+            packet[base + 30] = position;
+            packet[base + 31] = 5;
+            packet[base + 42] = 4; // driver status: on track
+            packet[base + 43] = 2; // result status: active
+        }
+        packet
+    }
+
+    fn telemetry_packet(session_time: f32) -> Vec<u8> {
+        let mut packet = packet(packet_id::CAR_TELEMETRY, 1352, session_time);
+        let base = HEADER_SIZE;
+        put(&mut packet, base, &287u16.to_le_bytes());
+        put(&mut packet, base + 2, &0.75f32.to_le_bytes());
+        put(&mut packet, base + 10, &0.1f32.to_le_bytes());
+        packet[base + 15] = 7;
+        put(&mut packet, base + 16, &11_200u16.to_le_bytes());
+        packet[base + 18] = 1;
+        for (i, temp) in [500u16, 510, 800, 810].iter().enumerate() {
+            put(&mut packet, base + 22 + i * 2, &temp.to_le_bytes());
+        }
+        put(&mut packet, base + 30, &[95, 96, 101, 102]);
+        put(&mut packet, base + 34, &[100, 100, 105, 105]);
+        put(&mut packet, base + 38, &110u16.to_le_bytes());
+        for i in 0..4 {
+            put(&mut packet, base + 40 + i * 4, &22.5f32.to_le_bytes());
+        }
+        packet
+    }
+
+    fn status_packet(session_time: f32) -> Vec<u8> {
+        let mut packet = packet(packet_id::CAR_STATUS, 1239, session_time);
+        let base = HEADER_SIZE;
+        packet[base + 3] = 56;
+        put(&mut packet, base + 5, &42.0f32.to_le_bytes());
+        packet[base + 25] = 18;
+        packet[base + 27] = 6;
+        put(&mut packet, base + 37, &2.0e6f32.to_le_bytes());
+        packet[base + 41] = 3;
+        packet
+    }
+
+    fn session_packet() -> Vec<u8> {
+        let mut packet = packet(packet_id::SESSION, 644, 1.0);
+        let base = HEADER_SIZE;
+        packet[base] = 3;
+        packet[base + 1] = 31;
+        packet[base + 2] = 22;
+        packet[base + 3] = 44;
+        put(&mut packet, base + 4, &7004u16.to_le_bytes());
+        packet[base + 6] = 10;
+        packet[base + 7] = 10;
+        packet[base + 124] = 2;
+        packet[base + 126] = 2;
+        put(&mut packet, base + 127, &[10, 0, 3, 30, 0, 22, 0, 60]);
+        put(&mut packet, base + 135, &[10, 15, 4, 30, 0, 22, 0, 90]);
+        packet
+    }
+
+    fn session_id() -> SessionId {
+        static ID: OnceLock<SessionId> = OnceLock::new();
+        *ID.get_or_init(|| {
+            let mut assembler = GameTelemetryAssembler::new();
+            assembler.ingest(&decode_packet(&session_packet()).unwrap(), Utc::now());
+            assembler.session_id().unwrap()
+        })
+    }
+
+    #[test]
+    fn test_decode_packets() {
+        let packet = decode_packet(&telemetry_packet(12.5)).unwrap();
+        assert_eq!(packet.header.packet_format, 2023);
+        assert_eq!(packet.header.session_uid, SESSION_UID);
+        assert_eq!(packet.header.session_time, 12.5);
+        let GamePacketBody::CarTelemetry(cars) = packet.body else {
+            panic!("expected car telemetry");
+        };
+        assert_eq!(cars.len(), GAME_MAX_CARS);
+        assert_eq!(cars[0].speed, 287);
+        assert_eq!(cars[0].gear, 7);
+        assert_eq!(cars[0].brakes_temperature, [500, 510, 800, 810]);
+        assert_eq!(cars[0].tyres_pressure, [22.5; 4]);
+
+        let GamePacketBody::LapData(laps) = decode_packet(&lap_data_packet(12.5)).unwrap().body else {
+            panic!("expected lap data");
+        };
+        assert_eq!(laps[1].car_position, 1);
+        assert_eq!(laps[1].delta_to_car_in_front_ms, 1_200);
+        assert_eq!(laps[0].lap_distance, 812.5);
+        assert_eq!(laps[0].result_status, 2);
+
+        let GamePacketBody::Session(session) = decode_packet(&session_packet()).unwrap().body else {
+            panic!("expected session");
+        };
+        assert_eq!(session.track_length, 7004);
+        assert_eq!(game_track_name(session.track_id), "spa");
+        assert_eq!(session.forecast.len(), 2);
+        assert_eq!(session.forecast[1].rain_percentage, 90);
+
+        for session_time in [f32::MAX, -f32::MAX, f32::INFINITY, f32::NAN, -1.0] {
+            assert!(matches!(
+                decode_packet(&telemetry_packet(session_time)),
+                Err(GameUdpError::InvalidSessionTime(_))
+            ));
+        }
+
+        let mut old = telemetry_packet(1.0);
+        put(&mut old, 0, &2022u16.to_le_bytes());
+        assert!(matches!(decode_packet(&old), Err(GameUdpError::UnsupportedFormat(2022))));
+        assert!(matches!(
+            decode_packet(&telemetry_packet(1.0)[..400]),
+            Err(GameUdpError::Truncated { packet_id: 6, len: 400 })
+        ));
+    }
+
+    #[test]
+    fn test_assemble_snapshots_and_race_state() {
+        let start = Utc.with_ymd_and_hms(2026, 7, 26, 13, 0, 0).unwrap();
+        let mut assembler = GameTelemetryAssembler::new();
+        // Telemetry before any lap data has no active cars yet
+        assert!(assembler
+            .ingest(&decode_packet(&telemetry_packet(10.0)).unwrap(), start)
+            .is_empty());
+
+        for packet in [session_packet(), lap_data_packet(10.1), status_packet(10.1)] {
+            assert!(assembler.ingest(&decode_packet(&packet).unwrap(), start).is_empty());
+        }
+        let snapshots = assembler.ingest(
+            &decode_packet(&telemetry_packet(10.5)).unwrap(),
+            start + chrono::Duration::milliseconds(500),
+        );
+        assert_eq!(snapshots.len(), 2);
+
+        let snapshot = &snapshots[0];
+        assert_eq!(snapshot.session_id, session_id());
+        assert_eq!(snapshot.car_id, CarId(1));
+        assert_eq!(snapshot.timestamp, start + chrono::Duration::milliseconds(500));
+        assert_eq!(snapshot.lap, LapNumber(5));
+        assert_eq!(snapshot.position, Position(2));
+        assert_eq!(snapshot.motion.speed, 287.0);
+        assert_eq!(snapshot.tires.front_left.surface_temp, 101.0);
+        assert_eq!(snapshot.tires.rear_right.brake_temp, 510.0);
+        assert_eq!(snapshot.tires.compound, TireCompound::C3);
+        assert_eq!(snapshot.tires.age_laps, 6);
+        assert_eq!(snapshot.power_unit.ers_battery, 0.5);
+        assert_eq!(snapshot.power_unit.ers_mode, ErsMode::Overtake);
+        assert_eq!(snapshot.brakes.bias, 0.56);
+        assert_eq!(snapshot.fuel.remaining, 42.0);
+        assert_eq!(snapshot.drs, DrsStatus::Activated);
+
+        let state = assembler.race_state().unwrap();
+        assert_eq!(state.track_id, "spa");
+        assert_eq!(state.total_laps, 44);
+        assert_eq!(state.session_type, SessionType::Race);
+        assert_eq!(state.flag_status, FlagStatus::VirtualSafetyCar);
+        assert_eq!(state.weather, WeatherCondition::LightRain);
+        assert_eq!(state.current_lap, LapNumber(5));
+        assert_eq!(state.leader().unwrap().car_id, CarId(2));
+        assert_eq!(state.positions[&CarId(1)].gap_to_ahead, 0.0);
+        assert_eq!(state.positions[&CarId(1)].last_lap_time, 91.5);
+        assert_eq!(state.telemetry.len(), 2);
+
+        let weather = assembler.weather().unwrap();
+        assert_eq!(weather.air_temperature, 22.0);
+        assert_eq!(weather.track_temperature, 31.0);
+        assert_eq!(weather.predictions.len(), 2);
+        assert_eq!(weather.rain_probability, 0.6);
+        assert_eq!(weather.predictions[1].condition, WeatherCondition::HeavyRain);
+    }
+
+    #[test]
+    fn test_assembler_drops_unrepresentable_times() {
+        // A session starting before the earliest representable time is ignored
+        let mut assembler = GameTelemetryAssembler::new();
+        let packet = decode_packet(&lap_data_packet(10.0)).unwrap();
+        assert!(assembler.ingest(&packet, DateTime::<Utc>::MIN_UTC).is_empty());
+        assert_eq!(assembler.session_id(), None);
+
+        // Telemetry past the latest representable time is dropped
+        let end = DateTime::<Utc>::MAX_UTC - chrono::Duration::seconds(1);
+        assembler.ingest(&decode_packet(&lap_data_packet(0.0)).unwrap(), end);
+        assert!(assembler
+            .ingest(&decode_packet(&telemetry_packet(10.0)).unwrap(), end)
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_capture_replay_into_engine() {
+        let path = std::env::temp_dir().join(format!("f1-nexus-game-{}.pcap", std::process::id()));
+        let start = Utc.with_ymd_and_hms(2026, 7, 26, 13, 0, 0).unwrap();
+        let source: SocketAddr = "192.168.1.20:50000".parse().unwrap();
+        {
+            let mut writer = PcapWriter::create(&path).unwrap();
+            writer.write(start, source, GAME_UDP_PORT, &session_packet()).unwrap();
+            writer.write(start, source, GAME_UDP_PORT, &lap_data_packet(1.0)).unwrap();
+            for i in 0..5 {
+                let at = start + chrono::Duration::milliseconds(50 * i);
+                writer.write(at, source, GAME_UDP_PORT, &telemetry_packet(1.0 + 0.05 * i as f32)).unwrap();
+            }
+            // Other traffic on another port is ignored
+            writer.write(start, source, 5353, &telemetry_packet(2.0)).unwrap();
+            writer.write(start, source, GAME_UDP_PORT, &[0u8; 10]).unwrap();
+        }
+
+        let datagrams: Vec<_> = PcapReader::open(&path).unwrap().collect::<Result<_, _>>().unwrap();
+        assert_eq!(datagrams.len(), 9);
+        assert_eq!(datagrams[0].source_port, 50000);
+        assert_eq!(datagrams[0].payload, session_packet());
+        assert_eq!(datagrams[3].timestamp, start + chrono::Duration::milliseconds(50));
+
+        let engine = Arc::new(TelemetryEngine::new(TelemetryConfig::default()));
+        let mut rx = engine.subscribe();
+        let ingest = GameTelemetryIngest::new(engine.clone());
+        let processed = ingest
+            .replay(&path, Some(GAME_UDP_PORT), ReplaySpeed::Unpaced)
+            .await
+            .unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(processed, 10);
+        assert_eq!(
+            ingest.stats(),
+            GameIngestStats {
+                packets: 8,
+                malformed: 1,
+                snapshots: 10,
+                rejected: 0,
+            }
+        );
+        assert_eq!(engine.buffer().len(session_id(), CarId(2)), 5);
+        assert_eq!(ingest.race_state().unwrap().positions.len(), 2);
+        let mut snapshots = 0;
+        while let Ok(event) = rx.try_recv() {
+            if matches!(event, crate::TelemetryEvent::Snapshot(_)) {
+                snapshots += 1;
+            }
+        }
+        assert_eq!(snapshots, 10);
+    }
+
+    #[tokio::test]
+    async fn test_listen_on_socket() {
+        let engine = Arc::new(TelemetryEngine::new(TelemetryConfig::default()));
+        let ingest = Arc::new(GameTelemetryIngest::new(engine.clone()));
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        let listener = {
+            let ingest = ingest.clone();
+            tokio::spawn(async move { ingest.listen(socket, None).await })
+        };
+
+        let sender = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        sender.send_to(&lap_data_packet(1.0), addr).await.unwrap();
+        sender.send_to(&telemetry_packet(1.1), addr).await.unwrap();
+
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
+        while ingest.stats().snapshots < 2 && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        listener.abort();
+        assert_eq!(ingest.stats().snapshots, 2);
+        assert!(engine.buffer().latest(session_id(), CarId(1)).is_some());
+    }
+}
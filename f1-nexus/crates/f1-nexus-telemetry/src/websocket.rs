@@ -3,25 +3,36 @@
 //! Provides real-time telemetry data streaming to connected clients via WebSocket.
 //! Supports multiple concurrent clients with automatic heartbeat monitoring.
 
+use crate::{
+    bearer_token, AccessScope, AuthConfig, ConnectionLimit, ConnectionPermit, Delivery, DeliveryHub, SubscriberConfig,
+    SubscriberMetrics, TlsConfig,
+};
 use f1_nexus_core::TelemetrySnapshot;
 use anyhow::{Context, Result};
 use futures::{SinkExt, StreamExt};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::broadcast;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
 use tokio::time::{interval, Instant};
+use tokio_rustls::TlsAcceptor;
 use tokio_tungstenite::{
-    accept_async,
-    tungstenite::{Message, Result as WsResult},
+    accept_hdr_async,
+    tungstenite::{
+        handshake::server::{ErrorResponse, Request, Response},
+        http::{header, StatusCode},
+        Message,
+    },
     WebSocketStream,
 };
 use tracing::{debug, error, info, warn};
 
 /// WebSocket message types
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum WsMessage {
@@ -52,15 +63,13 @@ pub enum WsMessage {
 /// Client connection state
 struct ClientConnection {
     id: u64,
-    addr: SocketAddr,
     last_pong: Arc<RwLock<Instant>>,
 }
 
 impl ClientConnection {
-    fn new(id: u64, addr: SocketAddr) -> Self {
+    fn new(id: u64) -> Self {
         Self {
             id,
-            addr,
             last_pong: Arc::new(RwLock::new(Instant::now())),
         }
     }
@@ -77,10 +86,15 @@ impl ClientConnection {
 /// WebSocket telemetry streaming server
 pub struct TelemetryWebSocketServer {
     addr: SocketAddr,
-    tx: broadcast::Sender<TelemetrySnapshot>,
+    hub: DeliveryHub<TelemetrySnapshot>,
+    delivery: SubscriberConfig,
     client_counter: Arc<RwLock<u64>>,
     heartbeat_interval: Duration,
     client_timeout: Duration,
+    handshake_timeout: Duration,
+    auth: AuthConfig,
+    connections: ConnectionLimit,
+    tls: Option<TlsAcceptor>,
 }
 
 impl TelemetryWebSocketServer {
@@ -88,16 +102,19 @@ impl TelemetryWebSocketServer {
     ///
     /// # Arguments
     /// * `addr` - Socket address to bind to (e.g., "127.0.0.1:8080")
-    /// * `channel_capacity` - Broadcast channel capacity (default: 1000)
+    /// * `channel_capacity` - Queue size per client (default: 1000)
     pub fn new(addr: SocketAddr, channel_capacity: usize) -> Self {
-        let (tx, _) = broadcast::channel(channel_capacity);
-
         Self {
             addr,
-            tx,
+            hub: DeliveryHub::new(),
+            delivery: SubscriberConfig::drop_oldest("websocket", channel_capacity),
             client_counter: Arc::new(RwLock::new(0)),
             heartbeat_interval: Duration::from_secs(30),
             client_timeout: Duration::from_secs(90),
+            handshake_timeout: Duration::from_secs(10),
+            auth: AuthConfig::default(),
+            connections: ConnectionLimit::new(1000),
+            tls: None,
         }
     }
 
@@ -122,6 +139,37 @@ impl TelemetryWebSocketServer {
         self
     }
 
+    /// Set how long a client may take to finish the TLS and WebSocket handshakes
+    pub fn with_handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.handshake_timeout = timeout;
+        self
+    }
+
+    /// Set the delivery policy and queue size used for each client
+    pub fn with_delivery(mut self, delivery: SubscriberConfig) -> Self {
+        self.delivery = delivery;
+        self
+    }
+
+    /// Require clients to authenticate, and only send them their team's cars
+    pub fn with_auth(mut self, auth: AuthConfig) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Set the maximum number of connected clients (default: 1000)
+    pub fn with_max_clients(mut self, max_clients: usize) -> Self {
+        self.connections = ConnectionLimit::new(max_clients);
+        self
+    }
+
+    /// Accept only TLS connections, using a local certificate and key
+    pub fn with_tls(mut self, tls: &TlsConfig) -> Result<Self> {
+        let config = tls.server_config().context("Failed to load TLS certificate")?;
+        self.tls = Some(TlsAcceptor::from(config));
+        Ok(self)
+    }
+
     /// Start the WebSocket server
     ///
     /// This will bind to the configured address and start accepting connections.
@@ -130,15 +178,32 @@ impl TelemetryWebSocketServer {
         let listener = TcpListener::bind(&self.addr)
             .await
             .context("Failed to bind to address")?;
+        self.serve(listener).await
+    }
 
-        info!("WebSocket telemetry server listening on {}", self.addr);
+    /// Accept connections on an already bound listener
+    pub async fn serve(self: Arc<Self>, listener: TcpListener) -> Result<()> {
+        info!(
+            "WebSocket telemetry server listening on {}",
+            listener.local_addr().context("Failed to read listener address")?
+        );
 
         loop {
             match listener.accept().await {
                 Ok((stream, addr)) => {
                     let server = Arc::clone(&self);
                     tokio::spawn(async move {
-                        if let Err(e) = server.handle_connection(stream, addr).await {
+                        let result = match &server.tls {
+                            Some(acceptor) => {
+                                match tokio::time::timeout(server.handshake_timeout, acceptor.accept(stream)).await {
+                                    Ok(Ok(stream)) => server.handle_connection(stream, addr).await,
+                                    Ok(Err(e)) => Err(e).context("TLS handshake failed"),
+                                    Err(_) => Err(anyhow::anyhow!("TLS handshake timed out")),
+                                }
+                            }
+                            None => server.handle_connection(stream, addr).await,
+                        };
+                        if let Err(e) = result {
                             error!("Connection error from {}: {}", addr, e);
                         }
                     });
@@ -153,30 +218,60 @@ impl TelemetryWebSocketServer {
     /// Broadcast telemetry snapshot to all connected clients
     ///
     /// This can be called from external code to push telemetry data to the WebSocket server.
+    /// It never waits, so a full client queue drops its oldest message even
+    /// under a lossless delivery config.
     pub fn broadcast_telemetry(&self, snapshot: TelemetrySnapshot) -> Result<()> {
-        self.tx
-            .send(snapshot)
-            .context("Failed to broadcast telemetry")?;
+        self.hub.send(snapshot);
         Ok(())
     }
 
-    /// Get the broadcast sender for external use
+    /// Get the delivery hub for external use
     ///
     /// This allows external code to send telemetry snapshots directly
-    pub fn sender(&self) -> broadcast::Sender<TelemetrySnapshot> {
-        self.tx.clone()
+    pub fn sender(&self) -> DeliveryHub<TelemetrySnapshot> {
+        self.hub.clone()
     }
 
     /// Get the number of currently connected clients
     pub fn client_count(&self) -> usize {
-        self.tx.receiver_count()
+        self.hub.subscriber_count()
+    }
+
+    /// Delivery counters per connected client
+    pub fn delivery_metrics(&self) -> Vec<SubscriberMetrics> {
+        self.hub.metrics()
     }
 
     /// Handle a single WebSocket connection
-    async fn handle_connection(&self, stream: TcpStream, addr: SocketAddr) -> Result<()> {
-        let ws_stream = accept_async(stream)
+    // The handshake callback's error type is fixed by tungstenite
+    #[allow(clippy::result_large_err)]
+    async fn handle_connection<S>(&self, stream: S, addr: SocketAddr) -> Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let mut scope = AccessScope::unrestricted();
+        let mut permit: Option<ConnectionPermit> = None;
+        let handshake = accept_hdr_async(stream, |request: &Request, response: Response| {
+            scope = match self.auth.authenticate(request_token(request).as_deref()) {
+                Ok(granted) => granted,
+                Err(e) => {
+                    warn!("Rejected connection from {}: {}", addr, e);
+                    return Err(reject(StatusCode::UNAUTHORIZED, e.to_string()));
+                }
+            };
+            // Only authenticated clients count against the limit
+            permit = self.connections.try_acquire();
+            if permit.is_none() {
+                warn!("Rejected connection from {}: {} clients connected", addr, self.connections.max());
+                return Err(reject(StatusCode::SERVICE_UNAVAILABLE, "Too many clients".to_string()));
+            }
+            Ok(response)
+        });
+        let ws_stream = tokio::time::timeout(self.handshake_timeout, handshake)
             .await
+            .map_err(|_| anyhow::anyhow!("WebSocket handshake timed out"))?
             .context("WebSocket handshake failed")?;
+        let _permit = permit;
 
         let client_id = {
             let mut counter = self.client_counter.write();
@@ -184,17 +279,16 @@ impl TelemetryWebSocketServer {
             *counter
         };
 
-        info!("Client #{} connected from {}", client_id, addr);
+        info!(
+            "Client #{} connected from {} (team {})",
+            client_id,
+            addr,
+            scope.team.as_deref().unwrap_or("-")
+        );
 
-        let client = Arc::new(ClientConnection::new(client_id, addr));
+        let client = Arc::new(ClientConnection::new(client_id));
 
-        // Send welcome message
-        let welcome = WsMessage::Connected {
-            message: format!("Connected to F1 Nexus Telemetry Server (Client #{})", client_id),
-            server_time: chrono::Utc::now().to_rfc3339(),
-        };
-
-        if let Err(e) = self.handle_client(ws_stream, client).await {
+        if let Err(e) = self.handle_client(ws_stream, client, scope).await {
             warn!("Client #{} disconnected: {}", client_id, e);
         }
 
@@ -204,11 +298,15 @@ impl TelemetryWebSocketServer {
     }
 
     /// Handle client communication
-    async fn handle_client(
+    async fn handle_client<S>(
         &self,
-        ws_stream: WebSocketStream<TcpStream>,
+        ws_stream: WebSocketStream<S>,
         client: Arc<ClientConnection>,
-    ) -> Result<()> {
+        scope: AccessScope,
+    ) -> Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
         let (mut write, mut read) = ws_stream.split();
 
         // Send welcome message
@@ -220,7 +318,7 @@ impl TelemetryWebSocketServer {
         write.send(Message::Text(welcome_json)).await?;
 
         // Subscribe to telemetry broadcasts
-        let mut rx = self.tx.subscribe();
+        let mut rx = self.hub.subscribe(self.delivery.clone());
 
         // Heartbeat timer
         let mut heartbeat = interval(self.heartbeat_interval);
@@ -229,10 +327,12 @@ impl TelemetryWebSocketServer {
 
         loop {
             tokio::select! {
-                // Receive telemetry from broadcast channel
+                // Receive telemetry from this client's queue
                 telemetry = rx.recv() => {
                     match telemetry {
-                        Ok(snapshot) => {
+                        // Cars outside this client's access
+                        Some(Delivery::Message(snapshot)) if !scope.allows(snapshot.car_id.0) => {}
+                        Some(Delivery::Message(snapshot)) => {
                             let msg = WsMessage::Telemetry {
                                 timestamp: snapshot.timestamp.to_rfc3339(),
                                 data: snapshot,
@@ -251,7 +351,7 @@ impl TelemetryWebSocketServer {
                                 break;
                             }
                         }
-                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        Some(Delivery::Gap { missed: skipped }) => {
                             warn!("Client #{} lagged, skipped {} messages", client.id, skipped);
                             // Send error notification to client
                             let error_msg = WsMessage::Error {
@@ -261,8 +361,8 @@ impl TelemetryWebSocketServer {
                                 let _ = write.send(Message::Text(json)).await;
                             }
                         }
-                        Err(broadcast::error::RecvError::Closed) => {
-                            debug!("Broadcast channel closed");
+                        None => {
+                            debug!("Delivery hub closed");
                             break;
                         }
                     }
@@ -350,6 +450,29 @@ impl TelemetryWebSocketServer {
     }
 }
 
+/// Access token from an `Authorization: Bearer` header or a percent-encoded
+/// `token` query parameter
+fn request_token(request: &Request) -> Option<Cow<'_, str>> {
+    request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(bearer_token)
+        .map(Cow::Borrowed)
+        .or_else(|| {
+            form_urlencoded::parse(request.uri().query()?.as_bytes())
+                .find(|(key, _)| key == "token")
+                .map(|(_, value)| value)
+        })
+}
+
+/// Handshake rejection with a plain-text reason
+fn reject(status: StatusCode, reason: String) -> ErrorResponse {
+    let mut response = ErrorResponse::new(Some(reason));
+    *response.status_mut() = status;
+    response
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -457,6 +580,24 @@ mod tests {
         assert_eq!(server.client_count(), 0);
     }
 
+    #[test]
+    fn test_request_token() {
+        let request = |uri: &str, authorization: Option<&str>| {
+            let mut builder = Request::builder().uri(uri);
+            if let Some(value) = authorization {
+                builder = builder.header(header::AUTHORIZATION, value);
+            }
+            builder.body(()).unwrap()
+        };
+
+        let token = |uri: &str, authorization: Option<&str>| request_token(&request(uri, authorization)).map(Cow::into_owned);
+        assert_eq!(token("/?token=k-mcl", None).as_deref(), Some("k-mcl"));
+        assert_eq!(token("/?a=1&token=k-fer", None).as_deref(), Some("k-fer"));
+        assert_eq!(token("/?token=k%2Bmcl%3D%3D", None).as_deref(), Some("k+mcl=="));
+        assert_eq!(token("/", Some("Bearer k-mcl")).as_deref(), Some("k-mcl"));
+        assert_eq!(token("/", None), None);
+    }
+
     #[test]
     fn test_server_with_defaults() {
         let server = TelemetryWebSocketServer::with_defaults().unwrap();
@@ -513,8 +654,7 @@ mod tests {
 
     #[test]
     fn test_client_connection_pong_tracking() {
-        let addr = "127.0.0.1:9092".parse().unwrap();
-        let client = ClientConnection::new(1, addr);
+        let client = ClientConnection::new(1);
 
         let initial_time = client.time_since_pong();
         std::thread::sleep(Duration::from_millis(100));
@@ -583,17 +723,15 @@ mod tests {
         let mut received_telemetry = false;
 
         for _ in 0..10 {
-            if let Some(Ok(msg)) = tokio::time::timeout(
+            if let Some(Ok(Message::Text(text))) = tokio::time::timeout(
                 Duration::from_millis(500),
                 read.next()
             ).await.ok().flatten() {
-                if let Message::Text(text) = msg {
-                    if let Ok(ws_msg) = serde_json::from_str::<WsMessage>(&text) {
-                        match ws_msg {
-                            WsMessage::Connected { .. } => received_welcome = true,
-                            WsMessage::Telemetry { .. } => received_telemetry = true,
-                            _ => {}
-                        }
+                if let Ok(ws_msg) = serde_json::from_str::<WsMessage>(&text) {
+                    match ws_msg {
+                        WsMessage::Connected { .. } => received_welcome = true,
+                        WsMessage::Telemetry { .. } => received_telemetry = true,
+                        _ => {}
                     }
                 }
             }
@@ -642,23 +780,21 @@ mod tests {
         let mut received_ping = false;
 
         for _ in 0..20 {
-            if let Some(Ok(msg)) = tokio::time::timeout(
+            if let Some(Ok(Message::Text(text))) = tokio::time::timeout(
                 Duration::from_millis(100),
                 read.next()
             ).await.ok().flatten() {
-                if let Message::Text(text) = msg {
-                    if let Ok(ws_msg) = serde_json::from_str::<WsMessage>(&text) {
-                        if matches!(ws_msg, WsMessage::Ping { .. }) {
-                            received_ping = true;
-
-                            // Send pong response
-                            let pong = WsMessage::Pong {
-                                timestamp: chrono::Utc::now().to_rfc3339(),
-                            };
-                            let pong_json = serde_json::to_string(&pong).unwrap();
-                            let _ = write.send(Message::Text(pong_json)).await;
-                            break;
-                        }
+                if let Ok(ws_msg) = serde_json::from_str::<WsMessage>(&text) {
+                    if matches!(ws_msg, WsMessage::Ping { .. }) {
+                        received_ping = true;
+
+                        // Send pong response
+                        let pong = WsMessage::Pong {
+                            timestamp: chrono::Utc::now().to_rfc3339(),
+                        };
+                        let pong_json = serde_json::to_string(&pong).unwrap();
+                        let _ = write.send(Message::Text(pong_json)).await;
+                        break;
                     }
                 }
             }
@@ -670,4 +806,77 @@ mod tests {
         let _ = write.close().await;
         server_handle.abort();
     }
+
+    #[tokio::test]
+    async fn test_tls_authenticated_scoped_stream() {
+        use tokio_rustls::rustls::{self, pki_types::ServerName};
+
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert_path = std::env::temp_dir().join(format!("f1-nexus-ws-cert-{}.pem", std::process::id()));
+        let key_path = std::env::temp_dir().join(format!("f1-nexus-ws-key-{}.pem", std::process::id()));
+        std::fs::write(&cert_path, cert.cert.pem()).unwrap();
+        std::fs::write(&key_path, cert.signing_key.serialize_pem()).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = Arc::new(
+            TelemetryWebSocketServer::new(addr, 100)
+                .with_auth(AuthConfig {
+                    api_keys: [("k-mcl".to_string(), "mclaren".to_string())].into_iter().collect(),
+                    teams: [("mclaren".to_string(), vec![4, 81])].into_iter().collect(),
+                    ..AuthConfig::default()
+                })
+                .with_max_clients(1)
+                .with_tls(&TlsConfig::new(&cert_path, &key_path))
+                .unwrap(),
+        );
+        let server_clone = Arc::clone(&server);
+        let server_handle = tokio::spawn(async move { server_clone.serve(listener).await });
+
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add(cert.cert.der().clone()).unwrap();
+        let client_config = rustls::ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let connector = tokio_rustls::TlsConnector::from(Arc::new(client_config));
+        let connect = |query: &'static str| {
+            let connector = connector.clone();
+            async move {
+                let tcp = tokio::net::TcpStream::connect(addr).await.unwrap();
+                let tls = connector.connect(ServerName::try_from("localhost").unwrap(), tcp).await.unwrap();
+                tokio_tungstenite::client_async(format!("wss://localhost:{}/{}", addr.port(), query), tls).await
+            }
+        };
+
+        // An idle connection that never finishes its handshake holds no slot
+        let _idle = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let err = connect("").await.err().unwrap();
+        assert!(err.to_string().contains("401"), "{}", err);
+
+        let (ws_stream, _) = connect("?token=k-mcl").await.unwrap();
+        let err = connect("?token=k-mcl").await.err().unwrap();
+        assert!(err.to_string().contains("503"), "{}", err);
+
+        let (mut write, mut read) = ws_stream.split();
+        let mut snapshot = create_test_snapshot();
+        for car in [1, 4] {
+            snapshot.car_id = CarId::new(car).unwrap();
+            server.broadcast_telemetry(snapshot.clone()).unwrap();
+        }
+
+        let mut received = Vec::new();
+        while let Ok(Some(Ok(Message::Text(text)))) = tokio::time::timeout(Duration::from_millis(500), read.next()).await {
+            if let Ok(WsMessage::Telemetry { data, .. }) = serde_json::from_str::<WsMessage>(&text) {
+                received.push(data.car_id.0);
+            }
+        }
+        assert_eq!(received, vec![4]);
+
+        let _ = write.close().await;
+        server_handle.abort();
+        std::fs::remove_file(&cert_path).ok();
+        std::fs::remove_file(&key_path).ok();
+    }
 }
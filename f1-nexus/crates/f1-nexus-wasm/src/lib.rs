@@ -178,6 +178,7 @@ impl F1Nexus {
             track_temperature: 30.0,
             air_temperature: 25.0,
             changes: vec![],
+            sector_changes: vec![],
         };
 
         // Create simulator
@@ -529,6 +529,7 @@ pub fn to_weather_forecast(
         air_temperature: conditions.temperature,
         track_temperature: track_temp,
         humidity: conditions.humidity,
+        pressure: None,
         wind_speed: conditions.wind_speed,
         wind_direction: conditions.wind_direction,
         rain_probability: conditions.rain_probability,